@@ -24,10 +24,23 @@ async fn main() -> Result<(), RedisError> {
     tls: None,
     // whether to enable tracing
     tracing: false,
+    // an optional mocking layer that replaces the network connection
+    #[cfg(feature = "mocks")]
+    mocks: None,
+    // an optional exporter for latency, error, reconnection, backpressure, and cluster redirection metrics
+    #[cfg(feature = "metrics")]
+    metrics_exporter: None,
+    // an optional provider used to fetch fresh credentials before each connection authenticates
+    #[cfg(feature = "credential-provider")]
+    credential_provider: None,
     // the protocol version to use
     version: RespVersion::RESP2,
     // the database to automatically select after connecting or reconnecting
     database: Some(DATABASE),
+    // optional client-side caching settings
+    cache: None,
+    // optional settings to route read-only commands to replicas
+    replica: None,
     // performance tuning options
     performance: PerformanceConfig {
       // whether or not to automatically pipeline commands
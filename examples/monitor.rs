@@ -1,4 +1,4 @@
-use fred::monitor::{self, Config};
+use fred::monitor::{self, Config, Filter};
 use fred::prelude::*;
 use futures::stream::StreamExt;
 use std::time::Duration;
@@ -9,7 +9,15 @@ async fn main() -> Result<(), RedisError> {
   pretty_env_logger::init();
 
   let monitor_jh = tokio::spawn(async move {
-    let config = Config::default();
+    let config = Config {
+      // only show commands that modify keys with the `foo` prefix
+      filter: Filter {
+        commands: Some(vec!["SET".into()]),
+        key_pattern: Some("foo*".into()),
+        ..Default::default()
+      },
+      ..Default::default()
+    };
     let mut monitor_stream = monitor::run(config).await?;
 
     while let Some(command) = monitor_stream.next().await {
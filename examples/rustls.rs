@@ -0,0 +1,29 @@
+use fred::prelude::*;
+use fred::types::{TlsBackend, TlsConfig};
+
+#[tokio::main]
+async fn main() -> Result<(), RedisError> {
+  let config = RedisConfig {
+    // `rustls` must be selected explicitly when the default `enable-tls` feature is also enabled
+    tls: Some(TlsConfig {
+      backend: TlsBackend::Rustls,
+      root_store: None,
+      webpki_roots: true,
+      alpn_protocols: vec![],
+      cert_verifier: None,
+      ..TlsConfig::default()
+    }),
+    ..RedisConfig::default()
+  };
+  let client = RedisClient::new(config);
+
+  let jh = client.connect(None);
+  if let Err(error) = client.wait_for_connect().await {
+    println!("Client failed to connect with error: {:?}", error);
+  }
+
+  // do stuff...
+
+  let _ = jh.await;
+  Ok(())
+}
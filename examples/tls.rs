@@ -1,5 +1,5 @@
 use fred::prelude::*;
-use fred::types::TlsConfig;
+use fred::types::{TlsBackend, TlsConfig};
 
 
 #[tokio::main]
@@ -7,11 +7,16 @@ async fn main() -> Result<(), RedisError> {
   let config = RedisConfig {
     // or use Some(TlsConfig::default()) to use default TLS settings
     tls: Some(TlsConfig {
+      backend: TlsBackend::NativeTls,
       root_certs: None,
       min_protocol_version: None,
       max_protocol_version: None,
       disable_built_in_roots: false,
       use_sni: true,
+      // or use TlsIdentity::from_pem_files("client.crt", "client.key")? for mutual TLS
+      identity: None,
+      danger_accept_invalid_certs: false,
+      danger_accept_invalid_hostnames: false,
     }),
     ..RedisConfig::default()
   };
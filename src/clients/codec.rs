@@ -0,0 +1,156 @@
+use crate::clients::RedisClient;
+use crate::codec::{self, Codec};
+use crate::error::RedisError;
+use crate::interfaces::KeysInterface;
+use crate::types::{Expiration, MultipleKeys, RedisKey, RedisValue, SetOptions};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::mem;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A client wrapper that encodes and decodes values with a [Codec](crate::codec::Codec).
+///
+/// Encoded values are sent to the server as `RedisValue::Bytes`. Encoding and decoding run in a blocking task when the
+/// value is larger than the [blocking encode threshold](crate::globals::get_blocking_encode_threshold).
+///
+/// The wrapper is cheap to create and uses the same connections as the client from which it was created, so it can
+/// be created for each call if different values use different codecs. It also dereferences to the underlying
+/// `RedisClient`, so a `CodecClient` can be created once and used in place of the client to register a codec for the
+/// lifetime of the client. The typed `get`, `set`, and `mget` functions take precedence over the client's versions.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::codec::JsonCodec;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///   name: String,
+/// }
+///
+/// # async fn example(client: RedisClient) -> Result<(), RedisError> {
+/// let user = User { name: "alice".into() };
+/// let _ = client.with_codec(JsonCodec).set("user:1", &user, None, None).await?;
+///
+/// let user: Option<User> = client.with_codec(JsonCodec).get("user:1").await?;
+///
+/// // or register the codec once and use the wrapper as the client
+/// let client = client.with_codec(JsonCodec);
+/// let user: Option<User> = client.get("user:1").await?;
+/// let _: () = client.del("user:1").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CodecClient<C: Codec> {
+  client: RedisClient,
+  codec: Arc<C>,
+}
+
+impl<C: Codec> fmt::Debug for CodecClient<C> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("CodecClient").field("id", &self.client.inner.id).finish()
+  }
+}
+
+impl<C: Codec> Deref for CodecClient<C> {
+  type Target = RedisClient;
+
+  fn deref(&self) -> &Self::Target {
+    &self.client
+  }
+}
+
+impl<C: Codec> CodecClient<C> {
+  pub(crate) fn new(client: RedisClient, codec: C) -> Self {
+    CodecClient {
+      client,
+      codec: Arc::new(codec),
+    }
+  }
+
+  /// Read the underlying client.
+  pub fn client(&self) -> &RedisClient {
+    &self.client
+  }
+
+  /// Read the codec used by the client.
+  pub fn codec(&self) -> &C {
+    &self.codec
+  }
+
+  /// Encode a value so it can be used as an argument to any other command.
+  ///
+  /// The size of the value is estimated from its in-memory size, so only the top level of a value such as a slice or
+  /// string counts toward the blocking encode threshold.
+  pub fn encode<T>(&self, value: &T) -> Result<RedisValue, RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    codec::maybe_block(mem::size_of_val(value), || self.codec.encode(value)).map(RedisValue::Bytes)
+  }
+
+  /// Decode a value returned by any other command, returning `None` if the value is `nil`.
+  pub fn decode<T>(&self, value: RedisValue) -> Result<Option<T>, RedisError>
+  where
+    T: DeserializeOwned,
+  {
+    let bytes = match value {
+      RedisValue::Null => return Ok(None),
+      RedisValue::Bytes(b) => b,
+      RedisValue::String(s) => s.into_inner(),
+      _ => {
+        return Err(RedisError::new_parse(format!(
+          "Cannot decode {} with codec.",
+          value.kind()
+        )))
+      }
+    };
+
+    codec::maybe_block(bytes.len(), || self.codec.decode(&bytes)).map(Some)
+  }
+
+  /// Read and decode the value at `key`.
+  ///
+  /// <https://redis.io/commands/get>
+  pub async fn get<T, K>(&self, key: K) -> Result<Option<T>, RedisError>
+  where
+    T: DeserializeOwned,
+    K: Into<RedisKey>,
+  {
+    let value: RedisValue = self.client.get(key).await?;
+    self.decode(value)
+  }
+
+  /// Encode and set the value at `key`, returning whether the value was set.
+  ///
+  /// <https://redis.io/commands/set>
+  pub async fn set<T, K>(
+    &self,
+    key: K,
+    value: &T,
+    expire: Option<Expiration>,
+    options: Option<SetOptions>,
+  ) -> Result<bool, RedisError>
+  where
+    T: Serialize + ?Sized,
+    K: Into<RedisKey>,
+  {
+    let value = self.encode(value)?;
+    let result: RedisValue = self.client.set(key, value, expire, options, false).await?;
+    Ok(!result.is_null())
+  }
+
+  /// Read and decode the values at each of the provided keys.
+  ///
+  /// <https://redis.io/commands/mget>
+  pub async fn mget<T, K>(&self, keys: K) -> Result<Vec<Option<T>>, RedisError>
+  where
+    T: DeserializeOwned,
+    K: Into<MultipleKeys>,
+  {
+    let values: RedisValue = self.client.mget(keys).await?;
+    values.into_array().into_iter().map(|value| self.decode(value)).collect()
+  }
+}
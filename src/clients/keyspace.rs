@@ -0,0 +1,416 @@
+use crate::clients::RedisClient;
+use crate::error::RedisError;
+use crate::interfaces::*;
+use crate::types::KeyspaceEvent;
+use crate::utils;
+use futures::future::try_join_all;
+use futures::{Stream, StreamExt};
+use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// Configuration options for [KeyspaceNotifications].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyspaceConfig {
+  /// The flags used to configure `notify-keyspace-events` on each server, such as `KEA`. If `None` the server
+  /// configuration is not changed.
+  ///
+  /// <https://redis.io/topics/notifications#configuration>
+  ///
+  /// Default: `None`
+  pub notify_flags: Option<String>,
+  /// The databases for which to receive notifications. If empty notifications are received for all databases.
+  ///
+  /// Default: empty
+  pub databases: Vec<u8>,
+  /// Whether or not to subscribe to `__keyspace@<db>__:<key_pattern>` channels.
+  ///
+  /// Default: `true`
+  pub keyspace: bool,
+  /// Whether or not to subscribe to `__keyevent@<db>__:*` channels. If both `keyspace` and `keyevent` are enabled
+  /// each notification is received twice.
+  ///
+  /// Default: `false`
+  pub keyevent: bool,
+  /// The key pattern used with keyspace channels.
+  ///
+  /// Default: `*`
+  pub key_pattern: String,
+}
+
+impl Default for KeyspaceConfig {
+  fn default() -> Self {
+    KeyspaceConfig {
+      notify_flags: None,
+      databases: Vec::new(),
+      keyspace: true,
+      keyevent: false,
+      key_pattern: "*".into(),
+    }
+  }
+}
+
+impl KeyspaceConfig {
+  /// Read the channel patterns used with `PSUBSCRIBE`.
+  pub fn patterns(&self) -> Vec<String> {
+    let databases: Vec<String> = if self.databases.is_empty() {
+      vec!["*".into()]
+    } else {
+      self.databases.iter().map(|db| db.to_string()).collect()
+    };
+
+    let mut patterns = Vec::with_capacity(databases.len() * 2);
+    for db in databases.iter() {
+      if self.keyspace {
+        patterns.push(format!("__keyspace@{}__:{}", db, self.key_pattern));
+      }
+      if self.keyevent {
+        patterns.push(format!("__keyevent@{}__:*", db));
+      }
+    }
+    patterns
+  }
+}
+
+/// The operation that triggered a keyspace notification.
+///
+/// <https://redis.io/topics/notifications#events-generated-by-different-commands>
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum KeyspaceEventKind {
+  Set,
+  SetRange,
+  Append,
+  IncrBy,
+  IncrByFloat,
+  Del,
+  Copy,
+  Restore,
+  RenameFrom,
+  RenameTo,
+  MoveFrom,
+  MoveTo,
+  Expire,
+  Persist,
+  Expired,
+  Evicted,
+  New,
+  SortStore,
+  LPush,
+  RPush,
+  LPop,
+  RPop,
+  LInsert,
+  LSet,
+  LRem,
+  LTrim,
+  HSet,
+  HIncrBy,
+  HIncrByFloat,
+  HDel,
+  SAdd,
+  SRem,
+  SPop,
+  SInterStore,
+  SUnionStore,
+  SDiffStore,
+  ZAdd,
+  ZIncr,
+  ZRem,
+  ZRemByScore,
+  ZRemByRank,
+  ZInterStore,
+  ZUnionStore,
+  ZDiffStore,
+  XAdd,
+  XDel,
+  XTrim,
+  XSetId,
+  XGroupCreate,
+  XGroupCreateConsumer,
+  XGroupDelConsumer,
+  XGroupDestroy,
+  XGroupSetId,
+  /// An event that is not covered by another variant, such as those emitted by modules.
+  Other(String),
+}
+
+impl<'a> From<&'a str> for KeyspaceEventKind {
+  fn from(operation: &'a str) -> Self {
+    match operation {
+      "set" => KeyspaceEventKind::Set,
+      "setrange" => KeyspaceEventKind::SetRange,
+      "append" => KeyspaceEventKind::Append,
+      "incrby" => KeyspaceEventKind::IncrBy,
+      "incrbyfloat" => KeyspaceEventKind::IncrByFloat,
+      "del" => KeyspaceEventKind::Del,
+      "copy_to" => KeyspaceEventKind::Copy,
+      "restore" => KeyspaceEventKind::Restore,
+      "rename_from" => KeyspaceEventKind::RenameFrom,
+      "rename_to" => KeyspaceEventKind::RenameTo,
+      "move_from" => KeyspaceEventKind::MoveFrom,
+      "move_to" => KeyspaceEventKind::MoveTo,
+      "expire" => KeyspaceEventKind::Expire,
+      "persist" => KeyspaceEventKind::Persist,
+      "expired" => KeyspaceEventKind::Expired,
+      "evicted" => KeyspaceEventKind::Evicted,
+      "new" => KeyspaceEventKind::New,
+      "sortstore" => KeyspaceEventKind::SortStore,
+      "lpush" => KeyspaceEventKind::LPush,
+      "rpush" => KeyspaceEventKind::RPush,
+      "lpop" => KeyspaceEventKind::LPop,
+      "rpop" => KeyspaceEventKind::RPop,
+      "linsert" => KeyspaceEventKind::LInsert,
+      "lset" => KeyspaceEventKind::LSet,
+      "lrem" => KeyspaceEventKind::LRem,
+      "ltrim" => KeyspaceEventKind::LTrim,
+      "hset" => KeyspaceEventKind::HSet,
+      "hincrby" => KeyspaceEventKind::HIncrBy,
+      "hincrbyfloat" => KeyspaceEventKind::HIncrByFloat,
+      "hdel" => KeyspaceEventKind::HDel,
+      "sadd" => KeyspaceEventKind::SAdd,
+      "srem" => KeyspaceEventKind::SRem,
+      "spop" => KeyspaceEventKind::SPop,
+      "sinterstore" => KeyspaceEventKind::SInterStore,
+      "sunionstore" => KeyspaceEventKind::SUnionStore,
+      "sdiffstore" => KeyspaceEventKind::SDiffStore,
+      "zadd" => KeyspaceEventKind::ZAdd,
+      "zincr" => KeyspaceEventKind::ZIncr,
+      "zrem" => KeyspaceEventKind::ZRem,
+      "zrembyscore" => KeyspaceEventKind::ZRemByScore,
+      "zrembyrank" => KeyspaceEventKind::ZRemByRank,
+      "zinterstore" => KeyspaceEventKind::ZInterStore,
+      "zunionstore" => KeyspaceEventKind::ZUnionStore,
+      "zdiffstore" => KeyspaceEventKind::ZDiffStore,
+      "xadd" => KeyspaceEventKind::XAdd,
+      "xdel" => KeyspaceEventKind::XDel,
+      "xtrim" => KeyspaceEventKind::XTrim,
+      "xsetid" => KeyspaceEventKind::XSetId,
+      "xgroup-create" => KeyspaceEventKind::XGroupCreate,
+      "xgroup-createconsumer" => KeyspaceEventKind::XGroupCreateConsumer,
+      "xgroup-delconsumer" => KeyspaceEventKind::XGroupDelConsumer,
+      "xgroup-destroy" => KeyspaceEventKind::XGroupDestroy,
+      "xgroup-setid" => KeyspaceEventKind::XGroupSetId,
+      _ => KeyspaceEventKind::Other(operation.to_owned()),
+    }
+  }
+}
+
+/// A keyspace notification with the operation parsed into a [KeyspaceEventKind].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct KeyspaceNotification {
+  pub db: u8,
+  pub key: String,
+  pub kind: KeyspaceEventKind,
+}
+
+impl From<KeyspaceEvent> for KeyspaceNotification {
+  fn from(event: KeyspaceEvent) -> Self {
+    KeyspaceNotification {
+      kind: event.operation.as_str().into(),
+      db: event.db,
+      key: event.key,
+    }
+  }
+}
+
+/// A helper that subscribes to keyspace notifications on each server and parses them into
+/// [KeyspaceNotification](crate::clients::KeyspaceNotification) structs.
+///
+/// Notifications are only sent to subscribers on the node that modified the key, so when the client is clustered
+/// this will connect to each primary node with [split_cluster](crate::clients::RedisClient::split_cluster). Otherwise
+/// a new connection is created with [clone_new](crate::clients::RedisClient::clone_new). These clients use the
+/// reconnect policy from the provided client and subscribe again after reconnecting.
+///
+/// **Note: If nodes are added to or removed from the cluster the caller should call [quit](Self::quit) and
+/// [connect](Self::connect) again.**
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::clients::{KeyspaceConfig, KeyspaceEventKind, KeyspaceNotifications};
+/// # use futures::StreamExt;
+/// # async fn example(client: RedisClient) -> Result<(), RedisError> {
+/// let config = KeyspaceConfig {
+///   notify_flags: Some("KEA".into()),
+///   databases: vec![0],
+///   ..KeyspaceConfig::default()
+/// };
+/// let notifications = KeyspaceNotifications::new(&client, config);
+/// let mut events = notifications.on_event();
+/// let _ = notifications.connect().await?;
+///
+/// while let Some(event) = events.next().await {
+///   if event.kind == KeyspaceEventKind::Expired {
+///     println!("{} expired in db {}", event.key, event.db);
+///   }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct KeyspaceNotifications {
+  client: RedisClient,
+  config: Arc<KeyspaceConfig>,
+  clients: Arc<RwLock<Vec<RedisClient>>>,
+  tasks: Arc<RwLock<Vec<JoinHandle<()>>>>,
+  event_tx: Arc<RwLock<VecDeque<UnboundedSender<KeyspaceNotification>>>>,
+}
+
+impl fmt::Debug for KeyspaceNotifications {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("KeyspaceNotifications")
+      .field("config", &self.config)
+      .field("clients", &self.clients.read().len())
+      .finish()
+  }
+}
+
+/// Configure the server and subscribe to the keyspace channels.
+async fn subscribe(client: &RedisClient, config: &KeyspaceConfig) -> Result<(), RedisError> {
+  if let Some(ref flags) = config.notify_flags {
+    let _ = client.config_set("notify-keyspace-events", flags.as_str()).await?;
+  }
+
+  let _ = client.psubscribe(config.patterns()).await?;
+  Ok(())
+}
+
+/// Send a notification to each listener, dropping any listeners whose receiver has been dropped.
+fn emit(event_tx: &RwLock<VecDeque<UnboundedSender<KeyspaceNotification>>>, event: KeyspaceNotification) {
+  event_tx.write().retain(|tx| tx.send(event.clone()).is_ok());
+}
+
+impl KeyspaceNotifications {
+  /// Create a new helper that uses the config and reconnect policy from the provided client.
+  pub fn new(client: &RedisClient, config: KeyspaceConfig) -> Self {
+    KeyspaceNotifications {
+      client: client.clone(),
+      config: Arc::new(config),
+      clients: Arc::new(RwLock::new(Vec::new())),
+      tasks: Arc::new(RwLock::new(Vec::new())),
+      event_tx: Arc::new(RwLock::new(VecDeque::new())),
+    }
+  }
+
+  /// Read the keyspace config.
+  pub fn config(&self) -> &KeyspaceConfig {
+    &self.config
+  }
+
+  /// Read the clients used to receive notifications, with one client for each primary node in a cluster.
+  pub fn clients(&self) -> Vec<RedisClient> {
+    self.clients.read().clone()
+  }
+
+  /// Listen for keyspace notifications from all servers.
+  ///
+  /// This should be called before [connect](Self::connect) to avoid missing notifications.
+  pub fn on_event(&self) -> impl Stream<Item = KeyspaceNotification> {
+    let (tx, rx) = unbounded_channel();
+    self.event_tx.write().push_back(tx);
+
+    UnboundedReceiverStream::new(rx)
+  }
+
+  /// Connect to each server, configure `notify-keyspace-events` if necessary, and subscribe to the keyspace channels.
+  ///
+  /// If this was called before then the previous connections are closed first.
+  pub async fn connect(&self) -> Result<(), RedisError> {
+    if let Err(e) = self.quit().await {
+      warn!("{}: Error closing keyspace notification clients: {:?}", self.client.id(), e);
+    }
+
+    let clients = if utils::is_clustered(&self.client.inner().config) {
+      self.client.split_cluster().await?
+    } else {
+      vec![self.client.clone_new()]
+    };
+    let policy = self.client.client_reconnect_policy();
+    let mut tasks = Vec::with_capacity(clients.len() * 2);
+
+    for client in clients.iter() {
+      let (event_tx, mut events) = (self.event_tx.clone(), client.on_keyspace_event());
+      tasks.push(tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+          emit(&event_tx, event.into());
+        }
+      }));
+
+      let (config, mut reconnections) = (self.config.clone(), client.on_reconnect());
+      tasks.push(tokio::spawn(async move {
+        while let Some(client) = reconnections.next().await {
+          if let Err(e) = subscribe(&client, &config).await {
+            warn!("{}: Error subscribing to keyspace notifications: {:?}", client.id(), e);
+          }
+        }
+      }));
+
+      let _ = client.connect(policy.clone());
+    }
+    utils::set_locked(&self.clients, clients.clone());
+    utils::set_locked(&self.tasks, tasks);
+
+    let _ = try_join_all(clients.iter().map(|client| client.wait_for_connect())).await?;
+    let _ = try_join_all(clients.iter().map(|client| subscribe(client, &self.config))).await?;
+    Ok(())
+  }
+
+  /// Close the connection to each server.
+  ///
+  /// Streams returned by [on_event](Self::on_event) are not closed, so [connect](Self::connect) can be called again
+  /// to resume receiving notifications.
+  pub async fn quit(&self) -> Result<(), RedisError> {
+    for task in utils::set_locked(&self.tasks, Vec::new()).into_iter() {
+      task.abort();
+    }
+    let clients = utils::set_locked(&self.clients, Vec::new());
+    let _ = try_join_all(clients.iter().map(|client| client.quit())).await?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_create_keyspace_patterns() {
+    let config = KeyspaceConfig {
+      databases: vec![0, 2],
+      keyevent: true,
+      key_pattern: "foo:*".into(),
+      ..KeyspaceConfig::default()
+    };
+
+    assert_eq!(config.patterns(), vec![
+      "__keyspace@0__:foo:*",
+      "__keyevent@0__:*",
+      "__keyspace@2__:foo:*",
+      "__keyevent@2__:*"
+    ]);
+    assert_eq!(KeyspaceConfig::default().patterns(), vec!["__keyspace@*__:*"]);
+  }
+
+  #[test]
+  fn should_parse_keyspace_event_kinds() {
+    let event = KeyspaceEvent {
+      db: 1,
+      operation: "expired".into(),
+      key: "foo".into(),
+    };
+    assert_eq!(KeyspaceNotification::from(event), KeyspaceNotification {
+      db: 1,
+      key: "foo".into(),
+      kind: KeyspaceEventKind::Expired,
+    });
+
+    assert_eq!(KeyspaceEventKind::from("lpush"), KeyspaceEventKind::LPush);
+    assert_eq!(
+      KeyspaceEventKind::from("json.set"),
+      KeyspaceEventKind::Other("json.set".into())
+    );
+  }
+}
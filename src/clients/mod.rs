@@ -1,6 +1,10 @@
+mod pipeline;
 mod redis;
+mod replicas;
 mod transaction;
+pub use pipeline::Pipeline;
 pub use redis::RedisClient;
+pub use replicas::Replicas;
 pub use transaction::TransactionClient;
 
 #[cfg(feature = "sentinel-client")]
@@ -14,3 +18,21 @@ mod pubsub;
 #[cfg(feature = "subscriber-client")]
 #[cfg_attr(docsrs, doc(cfg(feature = "subscriber-client")))]
 pub use pubsub::SubscriberClient;
+
+#[cfg(feature = "stream-consumer")]
+mod stream_consumer;
+#[cfg(feature = "stream-consumer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream-consumer")))]
+pub use stream_consumer::{StreamConsumer, StreamConsumerConfig, StreamMessage};
+
+#[cfg(feature = "codec")]
+mod codec;
+#[cfg(feature = "codec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codec")))]
+pub use codec::CodecClient;
+
+#[cfg(feature = "keyspace-notifications")]
+mod keyspace;
+#[cfg(feature = "keyspace-notifications")]
+#[cfg_attr(docsrs, doc(cfg(feature = "keyspace-notifications")))]
+pub use keyspace::{KeyspaceConfig, KeyspaceEventKind, KeyspaceNotification, KeyspaceNotifications};
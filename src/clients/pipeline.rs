@@ -0,0 +1,220 @@
+use crate::error::RedisError;
+use crate::interfaces::*;
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::types::{RedisCommand, RedisCommandKind};
+use crate::protocol::utils as protocol_utils;
+use crate::types::{FromRedis, RedisValue};
+use crate::utils;
+use futures::future::join_all;
+use redis_protocol::resp3::types::Frame as Resp3Frame;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::oneshot::channel as oneshot_channel;
+
+/// A client struct that buffers commands locally and sends them to the server together in one pipeline.
+///
+/// Commands called on a `Pipeline` will return `QUEUED` immediately rather than waiting on a response from the
+/// server. Callers should use `()` or `RedisValue` as the return type for intermediate commands, and then use
+/// [all](Self::all) or [try_all](Self::try_all) to send the buffered commands and read every response in order.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # async fn example(client: &RedisClient) -> Result<(), RedisError> {
+/// let pipeline = client.pipeline();
+/// let _: () = pipeline.incr("foo").await?;
+/// let _: () = pipeline.hset("bar", ("a", 1)).await?;
+///
+/// let (foo, bar): (i64, i64) = pipeline.all().await?;
+/// assert_eq!((foo, bar), (1, 1));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The pipeline will use the same connection(s) as the client from which it was created. Commands are always
+/// pipelined when sent from a `Pipeline`, regardless of the `pipeline` flag on the client's
+/// [PerformanceConfig](crate::types::PerformanceConfig). The buffered commands are written to the socket together
+/// and flushed once.
+///
+/// Responses are converted the same way as they are outside of a pipeline. Commands that parse their response into a
+/// struct, such as `memory_stats` or `cluster_info`, cannot be used in a pipeline and will return an error.
+pub struct Pipeline {
+  /// The inner struct for the client that sends the buffered commands.
+  client: Arc<RedisClientInner>,
+  /// The inner struct that buffers commands.
+  inner: Arc<RedisClientInner>,
+}
+
+impl fmt::Debug for Pipeline {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Pipeline")
+      .field("id", &self.inner.id)
+      .field("length", &self.len())
+      .finish()
+  }
+}
+
+impl ClientLike for Pipeline {
+  #[doc(hidden)]
+  fn inner(&self) -> &Arc<RedisClientInner> {
+    &self.inner
+  }
+}
+
+impl AclInterface for Pipeline {}
+impl ClientInterface for Pipeline {}
+impl ClusterInterface for Pipeline {}
+impl PubsubInterface for Pipeline {}
+impl ConfigInterface for Pipeline {}
+impl BitmapInterface for Pipeline {}
+impl GeoInterface for Pipeline {}
+impl HashesInterface for Pipeline {}
+impl HyperloglogInterface for Pipeline {}
+impl KeysInterface for Pipeline {}
+impl LuaInterface for Pipeline {}
+impl FunctionInterface for Pipeline {}
+impl ListInterface for Pipeline {}
+impl MemoryInterface for Pipeline {}
+impl ServerInterface for Pipeline {}
+impl SlowlogInterface for Pipeline {}
+impl SetsInterface for Pipeline {}
+impl SortedSetsInterface for Pipeline {}
+impl StreamsInterface for Pipeline {}
+
+impl Pipeline {
+  /// Read the number of commands buffered in the pipeline.
+  pub fn len(&self) -> usize {
+    self.inner.command_buffer.as_ref().map(|b| b.lock().len()).unwrap_or(0)
+  }
+
+  /// Whether or not the pipeline is empty.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Clear the buffered commands without sending them to the server.
+  pub fn clear(&self) {
+    let _ = self.take_commands();
+  }
+
+  /// Send all the buffered commands to the server and convert the responses to the provided type.
+  ///
+  /// The responses are returned in the same order as the commands were buffered. If any command returns an error
+  /// then the first error is returned.
+  pub async fn all<R>(&self) -> Result<R, RedisError>
+  where
+    R: FromRedis,
+  {
+    let mut values = Vec::with_capacity(self.len());
+    for result in self.send_all().await?.into_iter() {
+      values.push(result?);
+    }
+
+    RedisValue::Array(values).convert()
+  }
+
+  /// Send all the buffered commands to the server, returning the result of each command in the same order as the
+  /// commands were buffered.
+  ///
+  /// Unlike [all](Self::all) errors returned by individual commands will not affect the results of other commands.
+  /// An error is only returned if the commands could not be sent to the server.
+  pub async fn try_all<R>(&self) -> Result<Vec<Result<R, RedisError>>, RedisError>
+  where
+    R: FromRedis,
+  {
+    Ok(
+      self
+        .send_all()
+        .await?
+        .into_iter()
+        .map(|result| result.and_then(|value| value.convert()))
+        .collect(),
+    )
+  }
+
+  fn take_commands(&self) -> VecDeque<RedisCommand> {
+    match self.inner.command_buffer {
+      Some(ref buffer) => buffer.lock().drain(..).collect(),
+      None => VecDeque::new(),
+    }
+  }
+
+  /// Send the buffered commands to the client's multiplexer together, flushing the socket once after the last
+  /// command is written.
+  async fn send_all(&self) -> Result<Vec<Result<RedisValue, RedisError>>, RedisError> {
+    let inner = &self.client;
+    let mut commands = self.take_commands();
+    let timeout = inner.perf_config.default_command_timeout() as u64;
+    _debug!(inner, "Sending {} pipelined commands.", commands.len());
+
+    let mut responses = Vec::with_capacity(commands.len());
+    for command in commands.iter_mut() {
+      let (tx, rx) = oneshot_channel();
+      command.tx = Some(tx);
+      command.pipelined = true;
+
+      let convert = ResponseKind::from(&*command);
+      responses.push(async move {
+        utils::apply_timeout(rx, timeout)
+          .await
+          .and_then(|result| result)
+          .and_then(|frame| convert.into_value(frame))
+      });
+    }
+    utils::send_commands(inner, commands)?;
+
+    Ok(join_all(responses).await)
+  }
+}
+
+/// The conversion applied to a pipelined response so that it matches the value returned outside of a pipeline.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ResponseKind {
+  Map,
+  FlatMap,
+  Raw,
+  Default,
+}
+
+impl<'a> From<&'a RedisCommand> for ResponseKind {
+  fn from(command: &'a RedisCommand) -> Self {
+    match command.kind {
+      RedisCommandKind::HGetAll => ResponseKind::Map,
+      // HRANDFIELD key count WITHVALUES
+      RedisCommandKind::HRandField if command.args.len() == 3 => ResponseKind::FlatMap,
+      RedisCommandKind::XinfoConsumers
+      | RedisCommandKind::XinfoGroups
+      | RedisCommandKind::Xrange
+      | RedisCommandKind::Xrevrange
+      | RedisCommandKind::Xread(_)
+      | RedisCommandKind::Xreadgroup(_)
+      | RedisCommandKind::Xclaim
+      | RedisCommandKind::Xautoclaim => ResponseKind::Raw,
+      _ => ResponseKind::Default,
+    }
+  }
+}
+
+impl ResponseKind {
+  fn into_value(self, frame: Resp3Frame) -> Result<RedisValue, RedisError> {
+    match self {
+      ResponseKind::Map => protocol_utils::frame_to_map(frame).map(RedisValue::Map),
+      ResponseKind::FlatMap => {
+        let frame = protocol_utils::flatten_frame(frame);
+        protocol_utils::frame_to_map(frame).map(RedisValue::Map)
+      }
+      ResponseKind::Raw => protocol_utils::frame_to_results_raw(frame),
+      ResponseKind::Default => protocol_utils::frame_to_results(frame),
+    }
+  }
+}
+
+#[doc(hidden)]
+impl<'a> From<&'a Arc<RedisClientInner>> for Pipeline {
+  fn from(inner: &'a Arc<RedisClientInner>) -> Self {
+    Pipeline {
+      client: inner.clone(),
+      inner: RedisClientInner::new_pipeline(inner),
+    }
+  }
+}
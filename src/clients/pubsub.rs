@@ -23,11 +23,17 @@ use tokio_stream::StreamExt;
 
 type ChannelSet = Arc<RwLock<BTreeSet<Str>>>;
 
-fn from_redis_client(client: RedisClient, channels: &ChannelSet, patterns: &ChannelSet) -> SubscriberClient {
+fn from_redis_client(
+  client: RedisClient,
+  channels: &ChannelSet,
+  patterns: &ChannelSet,
+  sharded_channels: &ChannelSet,
+) -> SubscriberClient {
   SubscriberClient {
     inner: client.inner,
     patterns: patterns.clone(),
     channels: channels.clone(),
+    sharded_channels: sharded_channels.clone(),
   }
 }
 
@@ -47,8 +53,10 @@ fn remove_from_channels(channels: &ChannelSet, channel: &str) {
 enum ReconnectOperation {
   Subscribe,
   PSubscribe,
+  SSubscribe,
   Unsubscribe,
   PUnsubscribe,
+  SUnsubscribe,
 }
 
 fn concurrent_op(
@@ -66,17 +74,22 @@ fn concurrent_op(
         match operation {
           ReconnectOperation::Subscribe => client.subscribe(val).await.map(|_| ()),
           ReconnectOperation::PSubscribe => client.psubscribe(val).await.map(|_| ()),
+          ReconnectOperation::SSubscribe => client.ssubscribe(val).await.map(|_| ()),
           ReconnectOperation::Unsubscribe => client.unsubscribe(val).await.map(|_| ()),
           ReconnectOperation::PUnsubscribe => client.punsubscribe(val).await.map(|_| ()),
+          ReconnectOperation::SUnsubscribe => client.sunsubscribe(val).await.map(|_| ()),
         }
       })
     })
     .collect()
 }
 
-/// A subscriber client that will manage subscription state to any pubsub channels or patterns for the caller.
+/// A subscriber client that will manage subscription state to any pubsub channels, patterns, or sharded channels for
+/// the caller.
 ///
-/// If the connection to the server closes for any reason this struct can automatically re-subscribe, etc.
+/// If the connection to the server closes for any reason this struct can automatically re-subscribe, etc. Sharded
+/// channels are re-subscribed on the node that owns their hash slot when the cluster state changes, such as after a
+/// `MOVED` error or a failover.
 ///
 /// ```rust no_run
 /// use fred::clients::SubscriberClient;
@@ -97,11 +110,13 @@ fn concurrent_op(
 ///
 /// let _ = subscriber.subscribe("foo").await?;
 /// let _ = subscriber.psubscribe("bar*").await?;
-/// // if the subscriber connection closes now for any reason the client will automatically re-subscribe to "foo" and "bar*"
+/// let _ = subscriber.ssubscribe("baz").await?;
+/// // if the subscriber connection closes now for any reason the client will automatically re-subscribe to "foo", "bar*", and "baz"
 ///
 /// // some convenience functions exist as well
 /// println!("Tracking channels: {:?}", subscriber.tracked_channels());
 /// println!("Tracking patterns: {:?}", subscriber.tracked_patterns());
+/// println!("Tracking sharded channels: {:?}", subscriber.tracked_sharded_channels());
 ///
 /// // or force a re-subscription at any time
 /// let _ = subscriber.resubscribe_all().await?;
@@ -118,6 +133,7 @@ fn concurrent_op(
 pub struct SubscriberClient {
   channels: ChannelSet,
   patterns: ChannelSet,
+  sharded_channels: ChannelSet,
   inner: Arc<RedisClientInner>,
 }
 
@@ -127,6 +143,7 @@ impl fmt::Debug for SubscriberClient {
       .field("id", &self.inner.id)
       .field("channels", &self.tracked_channels())
       .field("patterns", &self.tracked_patterns())
+      .field("sharded_channels", &self.tracked_sharded_channels())
       .finish()
   }
 }
@@ -209,6 +226,36 @@ impl PubsubInterface for SubscriberClient {
       result
     })
   }
+
+  fn ssubscribe<S>(&self, channel: S) -> AsyncResult<usize>
+  where
+    S: Into<Str>,
+  {
+    into!(channel);
+    let cached_channels = self.sharded_channels.clone();
+    async_spawn(self, |inner| async move {
+      let result = commands::pubsub::ssubscribe(&inner, channel.clone()).await;
+      if result.is_ok() {
+        add_to_channels(&cached_channels, channel);
+      }
+      result
+    })
+  }
+
+  fn sunsubscribe<S>(&self, channel: S) -> AsyncResult<usize>
+  where
+    S: Into<Str>,
+  {
+    into!(channel);
+    let cached_channels = self.sharded_channels.clone();
+    async_spawn(self, |inner| async move {
+      let result = commands::pubsub::sunsubscribe(&inner, channel.clone()).await;
+      if result.is_ok() {
+        remove_from_channels(&cached_channels, &channel);
+      }
+      result
+    })
+  }
 }
 
 impl SubscriberClient {
@@ -217,6 +264,7 @@ impl SubscriberClient {
     SubscriberClient {
       channels: Arc::new(RwLock::new(BTreeSet::new())),
       patterns: Arc::new(RwLock::new(BTreeSet::new())),
+      sharded_channels: Arc::new(RwLock::new(BTreeSet::new())),
       inner: RedisClientInner::new(config),
     }
   }
@@ -231,6 +279,7 @@ impl SubscriberClient {
       inner,
       channels: Arc::new(RwLock::new(self.channels.read().clone())),
       patterns: Arc::new(RwLock::new(self.patterns.read().clone())),
+      sharded_channels: Arc::new(RwLock::new(self.sharded_channels.read().clone())),
     }
   }
 
@@ -245,10 +294,16 @@ impl SubscriberClient {
 
     let channels = self.channels.clone();
     let patterns = self.patterns.clone();
-    UnboundedReceiverStream::new(rx).map(move |client| from_redis_client(client, &channels, &patterns))
+    let sharded_channels = self.sharded_channels.clone();
+    UnboundedReceiverStream::new(rx)
+      .map(move |client| from_redis_client(client, &channels, &patterns, &sharded_channels))
   }
 
-  /// Spawn a task that will automatically re-subscribe to any channels or channel patterns used by the client.
+  /// Spawn a task that will automatically re-subscribe to any channels, channel patterns, or sharded channels used by
+  /// the client.
+  ///
+  /// In a cluster this also runs after the cluster state changes, so sharded channels follow their hash slot to a
+  /// new node.
   pub fn manage_subscriptions(&self) -> JoinHandle<()> {
     let _self = self.clone();
     tokio::spawn(async move {
@@ -276,29 +331,40 @@ impl SubscriberClient {
     self.patterns.read().clone()
   }
 
-  /// Re-subscribe to any tracked channels and patterns concurrently.
+  /// Read the set of sharded channels that this client will manage.
+  pub fn tracked_sharded_channels(&self) -> BTreeSet<Str> {
+    self.sharded_channels.read().clone()
+  }
+
+  /// Re-subscribe to any tracked channels, patterns, and sharded channels concurrently.
   ///
   /// This can be used to sync the client's subscriptions with the server after calling `QUIT`, then `connect`, etc.
   pub async fn resubscribe_all(&self) -> Result<(), RedisError> {
     let channels = self.tracked_channels();
     let patterns = self.tracked_patterns();
+    let sharded_channels = self.tracked_sharded_channels();
 
     let mut channel_tasks = concurrent_op(self, channels, ReconnectOperation::Subscribe);
     let pattern_tasks = concurrent_op(self, patterns, ReconnectOperation::PSubscribe);
+    let sharded_channel_tasks = concurrent_op(self, sharded_channels, ReconnectOperation::SSubscribe);
     channel_tasks.extend(pattern_tasks);
+    channel_tasks.extend(sharded_channel_tasks);
 
     result_of_vec(join_all(channel_tasks).await)?;
     Ok(())
   }
 
-  /// Unsubscribe from all tracked channels and patterns, and remove them from the client cache.
+  /// Unsubscribe from all tracked channels, patterns, and sharded channels, and remove them from the client cache.
   pub async fn unsubscribe_all(&self) -> Result<(), RedisError> {
     let channels = mem::replace(&mut *self.channels.write(), BTreeSet::new());
     let patterns = mem::replace(&mut *self.patterns.write(), BTreeSet::new());
+    let sharded_channels = mem::replace(&mut *self.sharded_channels.write(), BTreeSet::new());
 
     let mut channel_tasks = concurrent_op(self, channels, ReconnectOperation::Unsubscribe);
     let pattern_tasks = concurrent_op(self, patterns, ReconnectOperation::PUnsubscribe);
+    let sharded_channel_tasks = concurrent_op(self, sharded_channels, ReconnectOperation::SUnsubscribe);
     channel_tasks.extend(pattern_tasks);
+    channel_tasks.extend(sharded_channel_tasks);
 
     result_of_vec(join_all(channel_tasks).await)?;
     Ok(())
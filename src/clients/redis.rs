@@ -1,10 +1,11 @@
+use crate::clients::{Pipeline, Replicas};
 use crate::commands;
 use crate::error::{RedisError, RedisErrorKind};
 use crate::interfaces::{
-  AclInterface, AuthInterface, ClientInterface, ClusterInterface, ConfigInterface, GeoInterface, HashesInterface,
-  HeartbeatInterface, HyperloglogInterface, KeysInterface, ListInterface, LuaInterface, MemoryInterface,
-  MetricsInterface, PubsubInterface, ServerInterface, SetsInterface, SlowlogInterface, SortedSetsInterface,
-  TransactionInterface,
+  AclInterface, AuthInterface, BitmapInterface, ClientInterface, ClusterInterface, ConfigInterface, FunctionInterface,
+  GeoInterface, HashesInterface, HeartbeatInterface, HyperloglogInterface, KeysInterface, ListInterface,
+  LuaInterface, MemoryInterface, MetricsInterface, PubsubInterface, ServerInterface, SetsInterface,
+  SlowlogInterface, SortedSetsInterface, TransactionInterface,
 };
 use crate::modules::inner::RedisClientInner;
 use crate::prelude::{ClientLike, StreamsInterface};
@@ -17,6 +18,11 @@ use std::sync::Arc;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
+#[cfg(feature = "codec")]
+use crate::clients::CodecClient;
+#[cfg(feature = "codec")]
+use crate::codec::Codec;
+
 /// The primary Redis client struct.
 #[derive(Clone)]
 pub struct RedisClient {
@@ -51,6 +57,7 @@ impl ClientInterface for RedisClient {}
 impl ClusterInterface for RedisClient {}
 impl PubsubInterface for RedisClient {}
 impl ConfigInterface for RedisClient {}
+impl BitmapInterface for RedisClient {}
 impl GeoInterface for RedisClient {}
 impl HashesInterface for RedisClient {}
 impl HyperloglogInterface for RedisClient {}
@@ -58,6 +65,7 @@ impl MetricsInterface for RedisClient {}
 impl TransactionInterface for RedisClient {}
 impl KeysInterface for RedisClient {}
 impl LuaInterface for RedisClient {}
+impl FunctionInterface for RedisClient {}
 impl ListInterface for RedisClient {}
 impl MemoryInterface for RedisClient {}
 impl AuthInterface for RedisClient {}
@@ -83,6 +91,36 @@ impl RedisClient {
     RedisClient::new(utils::read_locked(&self.inner.config))
   }
 
+  /// Create a new [Pipeline](crate::clients::Pipeline) that buffers commands and sends them to the server together.
+  ///
+  /// The pipeline will use the same connection(s) as this client.
+  pub fn pipeline(&self) -> Pipeline {
+    Pipeline::from(&self.inner)
+  }
+
+  /// Create a [Replicas](crate::clients::Replicas) client that sends read-only commands to replica nodes.
+  ///
+  /// The returned client will use the same connections as this client.
+  pub fn replicas(&self) -> Replicas {
+    Replicas::from(&self.inner)
+  }
+
+  /// Create a [CodecClient](crate::clients::CodecClient) that encodes and decodes values with the provided codec.
+  ///
+  /// The returned client will use the same connections as this client.
+  #[cfg(feature = "codec")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "codec")))]
+  pub fn with_codec<C: Codec>(&self, codec: C) -> CodecClient<C> {
+    CodecClient::new(self.clone(), codec)
+  }
+
+  /// Read the number of values read from the client-side cache instead of the server.
+  ///
+  /// Returns `0` if the client was created without a [CacheConfig](crate::types::CacheConfig).
+  pub fn cache_hits(&self) -> usize {
+    self.inner.cache.as_ref().map(|cache| cache.hits()).unwrap_or(0)
+  }
+
   /// Listen for reconnection notifications.
   ///
   /// This function can be used to receive notifications whenever the client successfully reconnects in order to select the right database again, re-subscribe to channels, etc.
@@ -0,0 +1,63 @@
+use crate::interfaces::*;
+use crate::modules::inner::RedisClientInner;
+use std::fmt;
+use std::sync::Arc;
+
+/// A client struct that sends read-only commands to replica nodes.
+///
+/// Read-only commands (`GET`, `HGETALL`, `ZRANGE`, etc) called on a `Replicas` client are sent to a replica node
+/// selected by the client's [ReplicaPolicy](crate::types::ReplicaPolicy), even if
+/// [route_by_default](crate::types::ReplicaConfig::route_by_default) is `false`. All other commands are sent to the
+/// primary node.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # async fn example(client: &RedisClient) -> Result<(), RedisError> {
+/// let _: () = client.set("foo", "bar", None, None, false).await?;
+/// let foo: Option<String> = client.replicas().get("foo").await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The client will use the same connections as the client from which it was created, and requires a
+/// [ReplicaConfig](crate::types::ReplicaConfig) on that client's `RedisConfig`. Commands are sent to the primary node
+/// when no replica is connected.
+#[derive(Clone)]
+pub struct Replicas {
+  inner: Arc<RedisClientInner>,
+}
+
+impl fmt::Debug for Replicas {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Replicas").field("id", &self.inner.id).finish()
+  }
+}
+
+impl ClientLike for Replicas {
+  #[doc(hidden)]
+  fn inner(&self) -> &Arc<RedisClientInner> {
+    &self.inner
+  }
+}
+
+impl BitmapInterface for Replicas {}
+impl GeoInterface for Replicas {}
+impl HashesInterface for Replicas {}
+impl HyperloglogInterface for Replicas {}
+impl KeysInterface for Replicas {}
+impl LuaInterface for Replicas {}
+impl FunctionInterface for Replicas {}
+impl ListInterface for Replicas {}
+impl MemoryInterface for Replicas {}
+impl SetsInterface for Replicas {}
+impl SortedSetsInterface for Replicas {}
+impl StreamsInterface for Replicas {}
+
+#[doc(hidden)]
+impl<'a> From<&'a Arc<RedisClientInner>> for Replicas {
+  fn from(inner: &'a Arc<RedisClientInner>) -> Self {
+    Replicas {
+      inner: RedisClientInner::new_replicas(inner),
+    }
+  }
+}
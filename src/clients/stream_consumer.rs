@@ -0,0 +1,563 @@
+use crate::clients::RedisClient;
+use crate::error::RedisError;
+use crate::interfaces::{ClientLike, StreamsInterface};
+use crate::types::{
+  ConnectHandle, FromRedis, MultipleIDs, ReconnectPolicy, RedisKey, RedisValue, XPendingArgs, XReadResponse,
+  XReadValue, XID,
+};
+use futures::future::FutureExt;
+use futures::stream::{Stream, StreamExt};
+use parking_lot::Mutex;
+use std::cmp;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::channel;
+use tokio::time::sleep;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// The cursor returned by `XAUTOCLAIM` when there are no more entries to scan.
+const LAST_CLAIM_CURSOR: &'static str = "0-0";
+/// The amount of time to wait before reading again after an error.
+const ERROR_DELAY_MS: u64 = 1_000;
+
+/// Configuration options for a [StreamConsumer].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamConsumerConfig {
+  /// The name of the consumer group.
+  pub group: String,
+  /// The name of this consumer within the group.
+  pub consumer: String,
+  /// The maximum number of messages processed concurrently by [run](StreamConsumer::run).
+  ///
+  /// Default: `1`
+  pub concurrency: usize,
+  /// The maximum number of messages read or claimed with each command.
+  ///
+  /// Default: `10`
+  pub batch_size: u64,
+  /// The amount of time, in milliseconds, to block waiting on new messages.
+  ///
+  /// Default: `1000`
+  pub block_ms: u64,
+  /// The minimum amount of time, in milliseconds, that a pending message must be idle before it's claimed from
+  /// another consumer. If `None` pending messages are never claimed.
+  ///
+  /// Default: `Some(60_000)`
+  pub claim_idle_ms: Option<u64>,
+  /// How often, in milliseconds, to claim idle pending messages. Messages are also claimed after each reconnection.
+  ///
+  /// Default: `30_000`
+  pub claim_interval_ms: u64,
+  /// The maximum number of deliveries before an idle pending message is moved to the dead letter stream.
+  ///
+  /// Default: `None`
+  pub max_deliveries: Option<u64>,
+  /// The stream that receives messages after `max_deliveries`. If `None` these messages are acknowledged and dropped.
+  ///
+  /// Default: `None`
+  pub dead_letter_key: Option<String>,
+}
+
+impl StreamConsumerConfig {
+  /// Create a new config with the default options for the provided group and consumer.
+  pub fn new<G, C>(group: G, consumer: C) -> Self
+  where
+    G: Into<String>,
+    C: Into<String>,
+  {
+    StreamConsumerConfig {
+      group: group.into(),
+      consumer: consumer.into(),
+      concurrency: 1,
+      batch_size: 10,
+      block_ms: 1_000,
+      claim_idle_ms: Some(60_000),
+      claim_interval_ms: 30_000,
+      max_deliveries: None,
+      dead_letter_key: None,
+    }
+  }
+}
+
+/// A message read from a stream by a [StreamConsumer].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamMessage<V> {
+  /// The ID of the message.
+  pub id: String,
+  /// The fields in the message.
+  pub fields: HashMap<String, V>,
+}
+
+/// The phase of the read loop in [messages](StreamConsumer::messages).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ReadPhase {
+  /// Reading the messages already delivered to this consumer that have not been acknowledged.
+  History,
+  /// Reading new messages.
+  New,
+}
+
+/// A consumer group worker built on `XREADGROUP`, `XACK`, `XAUTOCLAIM`, and `XPENDING`.
+///
+/// The consumer will:
+///
+/// 1. Create the consumer group, if necessary.
+/// 2. Read any messages already delivered to this consumer that have not been acknowledged.
+/// 3. Read new messages, blocking for up to `block_ms` at a time.
+/// 4. Periodically, and after each reconnection, claim pending messages that have been idle for `claim_idle_ms` from
+/// other consumers. Messages that have been delivered `max_deliveries` times are moved to the dead letter stream
+/// instead.
+///
+/// Messages are acknowledged on a separate connection, created with `clone_new` the first time a message is
+/// acknowledged, so that acknowledgements are not delayed by blocking reads. This connection is closed when the last
+/// clone of the consumer is dropped.
+///
+/// **Note: Blocking reads will block the client's connection, so callers should use a dedicated client.**
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::clients::{StreamConsumer, StreamConsumerConfig, StreamMessage};
+/// # async fn example(client: RedisClient) -> Result<(), RedisError> {
+/// let mut config = StreamConsumerConfig::new("group", "consumer1");
+/// config.concurrency = 10;
+/// config.max_deliveries = Some(5);
+/// config.dead_letter_key = Some("events:dead".into());
+///
+/// let consumer = StreamConsumer::new(&client, "events", config);
+/// consumer
+///   .run(|message: StreamMessage<String>| async move {
+///     println!("Processing {}: {:?}", message.id, message.fields);
+///     // the message is acknowledged if this returns `Ok`
+///     Ok(())
+///   })
+///   .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct StreamConsumer {
+  client: RedisClient,
+  key: RedisKey,
+  config: Arc<StreamConsumerConfig>,
+  /// Incremented by `stop`. Each stream runs until this changes from the value read when the stream was created.
+  stop_epoch: Arc<AtomicUsize>,
+  ack_client: Arc<AckClient>,
+}
+
+/// A separate connection used to acknowledge messages while the consumer's client is blocked reading new messages.
+struct AckClient {
+  client: RedisClient,
+  connection: Mutex<Option<ConnectHandle>>,
+}
+
+impl AckClient {
+  /// Connect the client, if necessary, and wait for the connection to be established.
+  async fn connect(&self, policy: Option<ReconnectPolicy>) -> Result<&RedisClient, RedisError> {
+    {
+      let mut connection = self.connection.lock();
+      if connection.is_none() {
+        *connection = Some(self.client.connect(policy));
+      }
+    }
+
+    let _ = self.client.wait_for_connect().await?;
+    Ok(&self.client)
+  }
+}
+
+impl Drop for AckClient {
+  fn drop(&mut self) {
+    let connection = match self.connection.get_mut().take() {
+      Some(connection) => connection,
+      None => return,
+    };
+
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+      let client = self.client.clone();
+      let _ = handle.spawn(async move {
+        let _ = client.quit().await;
+      });
+    } else {
+      connection.abort();
+    }
+  }
+}
+
+impl fmt::Debug for StreamConsumer {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("StreamConsumer")
+      .field("key", &self.key)
+      .field("group", &self.config.group)
+      .field("consumer", &self.config.consumer)
+      .finish()
+  }
+}
+
+fn into_messages<V>(entries: Vec<XReadValue<String, String, V>>) -> Vec<StreamMessage<V>> {
+  entries
+    .into_iter()
+    .map(|(id, fields)| StreamMessage { id, fields })
+    .collect()
+}
+
+/// Parse an `XAUTOCLAIM` response, which contains a third element with deleted IDs in Redis 7.
+fn parse_autoclaim<V>(value: RedisValue) -> Result<(String, Vec<XReadValue<String, String, V>>), RedisError>
+where
+  V: FromRedis,
+{
+  let mut values = match value {
+    RedisValue::Array(values) if values.len() >= 2 => values,
+    _ => return Err(RedisError::new_parse("Expected 2 or 3 element array response.")),
+  };
+  values.truncate(2);
+  let entries = values.pop().unwrap_or(RedisValue::Null);
+  let cursor: String = values.pop().unwrap_or(RedisValue::Null).convert()?;
+
+  Ok((cursor, entries.flatten_array_values(1).convert()?))
+}
+
+impl StreamConsumer {
+  /// Create a new consumer for the stream at `key`.
+  pub fn new<K>(client: &RedisClient, key: K, config: StreamConsumerConfig) -> Self
+  where
+    K: Into<RedisKey>,
+  {
+    StreamConsumer {
+      client: client.clone(),
+      key: key.into(),
+      config: Arc::new(config),
+      stop_epoch: Arc::new(AtomicUsize::new(0)),
+      ack_client: Arc::new(AckClient {
+        client: client.clone_new(),
+        connection: Mutex::new(None),
+      }),
+    }
+  }
+
+  /// Read the client used by the consumer.
+  pub fn client(&self) -> &RedisClient {
+    &self.client
+  }
+
+  /// Read the stream key.
+  pub fn key(&self) -> &RedisKey {
+    &self.key
+  }
+
+  /// Read the consumer config.
+  pub fn config(&self) -> &StreamConsumerConfig {
+    &self.config
+  }
+
+  /// Create the consumer group, and the stream if necessary, starting from the provided ID.
+  ///
+  /// This does nothing if the group already exists. The [messages](Self::messages) stream creates the group starting
+  /// at `$` if it doesn't exist.
+  pub async fn create_group<I>(&self, id: I) -> Result<(), RedisError>
+  where
+    I: Into<XID>,
+  {
+    let result: Result<(), RedisError> = self
+      .client
+      .xgroup_create(self.key.clone(), self.config.group.as_str(), id, true)
+      .await;
+
+    match result {
+      Err(e) if e.details().starts_with("BUSYGROUP") => Ok(()),
+      result => result,
+    }
+  }
+
+  /// Acknowledge one or more messages, returning the number of messages that were acknowledged.
+  ///
+  /// Messages are acknowledged on a separate connection so that they're not queued behind a blocking read.
+  pub async fn ack<I>(&self, ids: I) -> Result<u64, RedisError>
+  where
+    I: Into<MultipleIDs>,
+  {
+    let client = self.ack_client.connect(self.client.client_reconnect_policy()).await?;
+    client.xack(self.key.clone(), self.config.group.as_str(), ids).await
+  }
+
+  /// Stop the [messages](Self::messages) streams and [run](Self::run) calls on this consumer and its clones after the
+  /// current read finishes.
+  ///
+  /// Only the streams that were already created are stopped, so the consumer can be started again afterwards.
+  pub fn stop(&self) {
+    self.stop_epoch.fetch_add(1, Ordering::SeqCst);
+  }
+
+  fn is_stopped(&self, epoch: usize) -> bool {
+    self.stop_epoch.load(Ordering::SeqCst) != epoch
+  }
+
+  /// Move pending messages that have been delivered `max_deliveries` times to the dead letter stream.
+  async fn dead_letter(&self, min_idle_time: u64) -> Result<(), RedisError> {
+    let max_deliveries = match self.config.max_deliveries {
+      Some(max) => max,
+      None => return Ok(()),
+    };
+    let mut start = "-".to_owned();
+    loop {
+      let args = XPendingArgs {
+        idle: Some(min_idle_time),
+        start: Some(start.as_str().into()),
+        end: Some("+".into()),
+        count: Some(self.config.batch_size),
+        consumer: None,
+      };
+      let pending: Vec<(String, String, u64, u64)> = self
+        .client
+        .xpending(self.key.clone(), self.config.group.as_str(), args)
+        .await?;
+      let is_last_page = (pending.len() as u64) < self.config.batch_size;
+      let last_id = pending.last().map(|(id, _, _, _)| id.clone());
+
+      for (id, _, _, deliveries) in pending.into_iter() {
+        if deliveries >= max_deliveries {
+          let _ = self.move_to_dead_letter(id, deliveries).await?;
+        }
+      }
+
+      match last_id {
+        // continue after the last ID on this page
+        Some(id) if !is_last_page => start = format!("({}", id),
+        _ => break,
+      };
+    }
+
+    Ok(())
+  }
+
+  /// Copy the message to the dead letter stream, if configured, and acknowledge it.
+  async fn move_to_dead_letter(&self, id: String, deliveries: u64) -> Result<(), RedisError> {
+    if let Some(ref dead_letter_key) = self.config.dead_letter_key {
+      let entries: Vec<XReadValue<String, String, RedisValue>> = self
+        .client
+        .xrange_values(self.key.clone(), id.as_str(), id.as_str(), Some(1))
+        .await?;
+
+      for (_, fields) in entries.into_iter() {
+        let fields: Vec<(String, RedisValue)> = fields.into_iter().collect();
+        if fields.is_empty() {
+          continue;
+        }
+
+        let _: String = self
+          .client
+          .xadd(dead_letter_key.as_str(), false, None, "*", fields)
+          .await?;
+      }
+    }
+    let inner = &self.client.inner;
+    _debug!(inner, "Moving {} to the dead letter stream after {} deliveries.", id, deliveries);
+    let _ = self.ack(id).await?;
+
+    Ok(())
+  }
+
+  /// Claim up to `batch_size` pending messages that have been idle for at least `claim_idle_ms`.
+  ///
+  /// Messages that have been delivered `max_deliveries` times are moved to the dead letter stream first.
+  pub async fn claim_pending<V>(&self) -> Result<Vec<StreamMessage<V>>, RedisError>
+  where
+    V: FromRedis + Unpin + Send + 'static,
+  {
+    let min_idle_time = match self.config.claim_idle_ms {
+      Some(idle) => idle,
+      None => return Ok(Vec::new()),
+    };
+    let _ = self.dead_letter(min_idle_time).await?;
+
+    let mut messages = Vec::new();
+    let mut cursor = LAST_CLAIM_CURSOR.to_owned();
+    loop {
+      let response: RedisValue = self
+        .client
+        .xautoclaim(
+          self.key.clone(),
+          self.config.group.as_str(),
+          self.config.consumer.as_str(),
+          min_idle_time,
+          cursor,
+          Some(self.config.batch_size),
+          false,
+        )
+        .await?;
+      let (next_cursor, entries) = parse_autoclaim(response)?;
+
+      for (id, fields) in entries.into_iter() {
+        // entries deleted from the stream while pending have no fields and cannot be processed
+        if fields.is_empty() {
+          let _ = self.ack(id).await?;
+        } else {
+          messages.push(StreamMessage { id, fields });
+        }
+      }
+
+      if next_cursor == LAST_CLAIM_CURSOR || messages.len() as u64 >= self.config.batch_size {
+        break;
+      }
+      cursor = next_cursor;
+    }
+
+    Ok(messages)
+  }
+
+  /// Read up to `batch_size` messages after `id`, blocking if reading new messages.
+  async fn read<V>(&self, id: &str, block: Option<u64>) -> Result<Vec<StreamMessage<V>>, RedisError>
+  where
+    V: FromRedis + Unpin + Send + 'static,
+  {
+    let mut response: XReadResponse<String, String, String, V> = self
+      .client
+      .xreadgroup_map(
+        self.config.group.as_str(),
+        self.config.consumer.as_str(),
+        Some(self.config.batch_size),
+        block,
+        false,
+        self.key.clone(),
+        id,
+      )
+      .await?;
+
+    let entries = response.drain().flat_map(|(_, entries)| entries.into_iter()).collect();
+    Ok(into_messages(entries))
+  }
+
+  /// Read messages from the stream.
+  ///
+  /// Messages must be acknowledged with [ack](Self::ack) once they're processed. Messages that are not acknowledged
+  /// will be delivered again after a reconnection or claimed by another consumer after `claim_idle_ms`.
+  ///
+  /// Errors are returned on the stream, after which the consumer will wait briefly and try again. The stream ends
+  /// when [stop](Self::stop) is called or when the stream is dropped.
+  pub fn messages<V>(&self) -> impl Stream<Item = Result<StreamMessage<V>, RedisError>>
+  where
+    V: FromRedis + Unpin + Send + 'static,
+  {
+    let buffer = cmp::max(1, self.config.batch_size as usize * cmp::max(1, self.config.concurrency));
+    let (tx, rx) = channel(buffer);
+    let consumer = self.clone();
+    let epoch = self.stop_epoch.load(Ordering::SeqCst);
+
+    let _ = tokio::spawn(async move {
+      if let Err(e) = consumer.create_group(XID::Max).await {
+        let _ = tx.send(Err(e)).await;
+        return;
+      }
+
+      let mut reconnect_rx = consumer.client.on_reconnect();
+      let claim_interval = Duration::from_millis(consumer.config.claim_interval_ms);
+      let mut last_claim: Option<Instant> = None;
+      let mut phase = ReadPhase::History;
+      let mut history_id = "0".to_owned();
+
+      while !consumer.is_stopped(epoch) && !tx.is_closed() {
+        if let Some(Some(_)) = reconnect_rx.next().now_or_never() {
+          // anything delivered before the connection closed may not have been processed
+          let inner = &consumer.client.inner;
+          _debug!(inner, "Resuming stream consumer after reconnecting.");
+          last_claim = None;
+          phase = ReadPhase::History;
+          history_id = "0".to_owned();
+        }
+
+        let should_claim = last_claim.map(|last| last.elapsed() >= claim_interval).unwrap_or(true);
+        let result = if should_claim {
+          last_claim = Some(Instant::now());
+          consumer.claim_pending::<V>().await
+        } else if phase == ReadPhase::History {
+          let result = consumer.read::<V>(&history_id, None).await;
+          match result {
+            Ok(ref messages) if messages.is_empty() => phase = ReadPhase::New,
+            Ok(ref messages) => {
+              if let Some(message) = messages.last() {
+                history_id = message.id.clone();
+              }
+            }
+            Err(_) => {}
+          };
+          result
+        } else {
+          consumer.read::<V>(">", Some(consumer.config.block_ms)).await
+        };
+
+        match result {
+          Ok(messages) => {
+            for message in messages.into_iter() {
+              if let Err(_) = tx.send(Ok(message)).await {
+                return;
+              }
+            }
+          }
+          Err(e) => {
+            if let Err(_) = tx.send(Err(e)).await {
+              return;
+            }
+            sleep(Duration::from_millis(ERROR_DELAY_MS)).await;
+          }
+        }
+      }
+    });
+
+    ReceiverStream::new(rx)
+  }
+
+  /// Process messages with the provided handler, acknowledging each message when the handler returns `Ok`.
+  ///
+  /// Up to `concurrency` messages are processed at once. Messages for which the handler returns an error remain
+  /// pending and will be retried after they're claimed. Errors reading from the stream are logged and the consumer
+  /// will try again.
+  ///
+  /// This runs until [stop](Self::stop) is called, or returns the error that ended the stream, such as an error
+  /// creating the consumer group.
+  pub async fn run<V, F, Fut>(&self, handler: F) -> Result<(), RedisError>
+  where
+    V: FromRedis + Unpin + Send + 'static,
+    F: Fn(StreamMessage<V>) -> Fut,
+    Fut: Future<Output = Result<(), RedisError>>,
+  {
+    let concurrency = cmp::max(1, self.config.concurrency);
+    let epoch = self.stop_epoch.load(Ordering::SeqCst);
+    let last_error = Mutex::new(None);
+    let (handler, last_error_ref) = (&handler, &last_error);
+
+    self
+      .messages::<V>()
+      .for_each_concurrent(concurrency, |message| async move {
+        let inner = &self.client.inner;
+        let message = match message {
+          Ok(message) => message,
+          Err(e) => {
+            _warn!(inner, "Error reading from stream: {:?}", e);
+            *last_error_ref.lock() = Some(e);
+            return;
+          }
+        };
+        let id = message.id.clone();
+
+        match handler(message).await {
+          Ok(_) => {
+            if let Err(e) = self.ack(id.as_str()).await {
+              _warn!(inner, "Error acknowledging {}: {:?}", id, e);
+            }
+          }
+          Err(e) => _debug!(inner, "Handler error for {}: {:?}", id, e),
+        }
+      })
+      .await;
+
+    let last_error = last_error.lock().take();
+    match last_error {
+      // the stream only ends without a call to `stop` after an error it cannot recover from
+      Some(e) if !self.is_stopped(epoch) => Err(e),
+      _ => Ok(()),
+    }
+  }
+}
@@ -47,6 +47,7 @@ impl AclInterface for TransactionClient {}
 impl ClientInterface for TransactionClient {}
 impl PubsubInterface for TransactionClient {}
 impl ConfigInterface for TransactionClient {}
+impl BitmapInterface for TransactionClient {}
 impl GeoInterface for TransactionClient {}
 impl HashesInterface for TransactionClient {}
 impl HyperloglogInterface for TransactionClient {}
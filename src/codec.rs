@@ -0,0 +1,364 @@
+use crate::error::RedisError;
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+#[cfg(feature = "blocking-encoding")]
+use crate::globals::globals;
+#[cfg(feature = "codec-gzip")]
+use std::io::{Read, Write};
+
+/// The magic bytes at the start of a gzip stream.
+#[cfg(feature = "codec-gzip")]
+const GZIP_MAGIC: &'static [u8] = &[0x1f, 0x8b];
+/// The magic bytes at the start of a zstd frame.
+#[cfg(feature = "codec-zstd")]
+const ZSTD_MAGIC: &'static [u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// A trait used to convert values to and from the bytes stored on the server.
+///
+/// See [with_codec](crate::clients::RedisClient::with_codec) for more information.
+pub trait Codec: Send + Sync + 'static {
+  /// Encode the value as bytes.
+  fn encode<T>(&self, value: &T) -> Result<Bytes, RedisError>
+  where
+    T: Serialize + ?Sized;
+
+  /// Decode a value from the provided bytes.
+  fn decode<T>(&self, bytes: &[u8]) -> Result<T, RedisError>
+  where
+    T: DeserializeOwned;
+}
+
+/// Run the provided function in a blocking task if `size` is larger than the
+/// [blocking encode threshold](crate::globals::get_blocking_encode_threshold).
+#[cfg(feature = "blocking-encoding")]
+pub(crate) fn maybe_block<F, R>(size: usize, func: F) -> R
+where
+  F: FnOnce() -> R,
+{
+  if size >= globals().blocking_encode_threshold() {
+    trace!("Using blocking task to run codec with size {}", size);
+    tokio::task::block_in_place(func)
+  } else {
+    func()
+  }
+}
+
+#[cfg(not(feature = "blocking-encoding"))]
+pub(crate) fn maybe_block<F, R>(_: usize, func: F) -> R
+where
+  F: FnOnce() -> R,
+{
+  func()
+}
+
+/// A codec that encodes values as JSON.
+#[cfg(feature = "codec-json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codec-json")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonCodec;
+
+#[cfg(feature = "codec-json")]
+impl Codec for JsonCodec {
+  fn encode<T>(&self, value: &T) -> Result<Bytes, RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    serde_json::to_vec(value)
+      .map(|v| v.into())
+      .map_err(|e| RedisError::new_parse(format!("Error encoding JSON: {}", e)))
+  }
+
+  fn decode<T>(&self, bytes: &[u8]) -> Result<T, RedisError>
+  where
+    T: DeserializeOwned,
+  {
+    serde_json::from_slice(bytes).map_err(|e| RedisError::new_parse(format!("Error decoding JSON: {}", e)))
+  }
+}
+
+/// A codec that encodes values as [MessagePack](https://msgpack.org/).
+///
+/// Structs are encoded as maps with their field names so that fields can be added or reordered later.
+#[cfg(feature = "codec-msgpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codec-msgpack")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MsgPackCodec;
+
+#[cfg(feature = "codec-msgpack")]
+impl Codec for MsgPackCodec {
+  fn encode<T>(&self, value: &T) -> Result<Bytes, RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    rmp_serde::to_vec_named(value)
+      .map(|v| v.into())
+      .map_err(|e| RedisError::new_parse(format!("Error encoding MessagePack: {}", e)))
+  }
+
+  fn decode<T>(&self, bytes: &[u8]) -> Result<T, RedisError>
+  where
+    T: DeserializeOwned,
+  {
+    rmp_serde::from_slice(bytes).map_err(|e| RedisError::new_parse(format!("Error decoding MessagePack: {}", e)))
+  }
+}
+
+/// A codec that encodes values with [bincode](https://docs.rs/bincode).
+#[cfg(feature = "codec-bincode")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codec-bincode")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "codec-bincode")]
+impl Codec for BincodeCodec {
+  fn encode<T>(&self, value: &T) -> Result<Bytes, RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    bincode::serialize(value)
+      .map(|v| v.into())
+      .map_err(|e| RedisError::new_parse(format!("Error encoding bincode: {}", e)))
+  }
+
+  fn decode<T>(&self, bytes: &[u8]) -> Result<T, RedisError>
+  where
+    T: DeserializeOwned,
+  {
+    bincode::deserialize(bytes).map_err(|e| RedisError::new_parse(format!("Error decoding bincode: {}", e)))
+  }
+}
+
+/// The compression algorithm used by a [Compressed](crate::codec::Compressed) codec.
+#[cfg(any(feature = "codec-gzip", feature = "codec-zstd"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "codec-gzip", feature = "codec-zstd"))))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compression {
+  /// Compress values with gzip at the provided level, from 0 to 9.
+  #[cfg(feature = "codec-gzip")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "codec-gzip")))]
+  Gzip(u32),
+  /// Compress values with zstd at the provided level, from 1 to 22. A level of 0 uses the zstd default.
+  #[cfg(feature = "codec-zstd")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "codec-zstd")))]
+  Zstd(i32),
+}
+
+#[cfg(any(feature = "codec-gzip", feature = "codec-zstd"))]
+impl Compression {
+  /// Detect the compression algorithm from the magic bytes at the start of `bytes`, if any.
+  fn detect(bytes: &[u8]) -> Option<Compression> {
+    #[cfg(feature = "codec-gzip")]
+    if bytes.starts_with(GZIP_MAGIC) {
+      return Some(Compression::Gzip(0));
+    }
+    #[cfg(feature = "codec-zstd")]
+    if bytes.starts_with(ZSTD_MAGIC) {
+      return Some(Compression::Zstd(0));
+    }
+
+    None
+  }
+
+  fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, RedisError> {
+    let mut out = Vec::with_capacity(bytes.len() / 2);
+
+    match *self {
+      #[cfg(feature = "codec-gzip")]
+      Compression::Gzip(level) => {
+        let mut encoder = flate2::write::GzEncoder::new(&mut out, flate2::Compression::new(level));
+        encoder.write_all(bytes)?;
+        let _ = encoder.finish()?;
+      }
+      #[cfg(feature = "codec-zstd")]
+      Compression::Zstd(level) => zstd::stream::copy_encode(bytes, &mut out, level)?,
+    };
+
+    Ok(out)
+  }
+
+  fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, RedisError> {
+    let mut out = Vec::with_capacity(bytes.len() * 2);
+
+    match *self {
+      #[cfg(feature = "codec-gzip")]
+      Compression::Gzip(_) => {
+        let _ = flate2::read::GzDecoder::new(bytes).read_to_end(&mut out)?;
+      }
+      #[cfg(feature = "codec-zstd")]
+      Compression::Zstd(_) => zstd::stream::copy_decode(bytes, &mut out)?,
+    };
+
+    Ok(out)
+  }
+}
+
+/// A codec that compresses values from an inner codec once they reach a size threshold.
+///
+/// Values below the threshold are stored exactly as the inner codec encodes them, and compressed values are stored as
+/// plain gzip or zstd streams. When decoding, the algorithm is detected from the stream's magic bytes, so values
+/// written with a different algorithm or threshold, or written by the inner codec without compression, can still be
+/// decoded. If a value starts with the magic bytes but does not decompress it is decoded as an uncompressed value.
+///
+/// Compression and decompression run in a blocking task when the value is larger than the
+/// [blocking encode threshold](crate::globals::get_blocking_encode_threshold).
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::codec::{Compressed, Compression, MsgPackCodec};
+/// # async fn example(client: RedisClient) -> Result<(), RedisError> {
+/// // compress values larger than 1 KB with zstd
+/// let codec = Compressed::new(MsgPackCodec, Compression::Zstd(3), 1024);
+/// let client = client.with_codec(codec);
+///
+/// let _ = client.set("foo", &vec![1, 2, 3], None, None).await?;
+/// let foo: Option<Vec<u32>> = client.get("foo").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(any(feature = "codec-gzip", feature = "codec-zstd"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "codec-gzip", feature = "codec-zstd"))))]
+#[derive(Clone, Debug)]
+pub struct Compressed<C: Codec> {
+  codec: C,
+  compression: Compression,
+  threshold: usize,
+}
+
+#[cfg(any(feature = "codec-gzip", feature = "codec-zstd"))]
+impl<C: Codec> Compressed<C> {
+  /// Create a new codec that compresses values from `codec` once they are at least `threshold` bytes.
+  pub fn new(codec: C, compression: Compression, threshold: usize) -> Self {
+    Compressed {
+      codec,
+      compression,
+      threshold,
+    }
+  }
+
+  /// Read the inner codec.
+  pub fn inner(&self) -> &C {
+    &self.codec
+  }
+}
+
+#[cfg(any(feature = "codec-gzip", feature = "codec-zstd"))]
+impl<C: Codec> Codec for Compressed<C> {
+  fn encode<T>(&self, value: &T) -> Result<Bytes, RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    let encoded = self.codec.encode(value)?;
+
+    if encoded.len() < self.threshold {
+      Ok(encoded)
+    } else {
+      maybe_block(encoded.len(), || self.compression.compress(&encoded)).map(|out| out.into())
+    }
+  }
+
+  fn decode<T>(&self, bytes: &[u8]) -> Result<T, RedisError>
+  where
+    T: DeserializeOwned,
+  {
+    let decompressed = match Compression::detect(bytes) {
+      Some(compression) => maybe_block(bytes.len(), || compression.decompress(bytes)).ok(),
+      None => None,
+    };
+
+    match decompressed {
+      Some(decompressed) => self.codec.decode(&decompressed),
+      None => self.codec.decode(bytes),
+    }
+  }
+}
+
+#[cfg(all(
+  test,
+  any(feature = "codec-json", feature = "codec-msgpack", feature = "codec-bincode")
+))]
+mod tests {
+  use super::*;
+  use serde::Deserialize;
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Value {
+    name: String,
+    values: Vec<u64>,
+  }
+
+  fn value(len: usize) -> Value {
+    Value {
+      name: "foo".into(),
+      values: (0 .. len as u64).collect(),
+    }
+  }
+
+  fn round_trip<C: Codec>(codec: C, len: usize) -> Bytes {
+    let encoded = codec.encode(&value(len)).unwrap();
+    assert_eq!(codec.decode::<Value>(&encoded).unwrap(), value(len));
+    encoded
+  }
+
+  #[test]
+  #[cfg(feature = "codec-json")]
+  fn should_round_trip_json() {
+    let encoded = round_trip(JsonCodec, 3);
+    assert_eq!(encoded, r#"{"name":"foo","values":[0,1,2]}"#);
+  }
+
+  #[test]
+  #[cfg(feature = "codec-msgpack")]
+  fn should_round_trip_msgpack() {
+    round_trip(MsgPackCodec, 3);
+  }
+
+  #[test]
+  #[cfg(feature = "codec-bincode")]
+  fn should_round_trip_bincode() {
+    round_trip(BincodeCodec, 3);
+    assert!(BincodeCodec.decode::<Value>(b"foo").is_err());
+  }
+
+  #[test]
+  #[cfg(all(feature = "codec-json", feature = "codec-gzip"))]
+  fn should_compress_gzip_above_threshold() {
+    let codec = Compressed::new(JsonCodec, Compression::Gzip(6), 100);
+
+    assert_eq!(round_trip(codec.clone(), 3), JsonCodec.encode(&value(3)).unwrap());
+    let encoded = round_trip(codec, 1000);
+    assert!(encoded.starts_with(GZIP_MAGIC));
+    assert!(encoded.len() < JsonCodec.encode(&value(1000)).unwrap().len());
+  }
+
+  #[test]
+  #[cfg(all(feature = "codec-json", feature = "codec-zstd"))]
+  fn should_compress_zstd_above_threshold() {
+    let codec = Compressed::new(JsonCodec, Compression::Zstd(3), 100);
+
+    assert_eq!(round_trip(codec.clone(), 3), JsonCodec.encode(&value(3)).unwrap());
+    let encoded = round_trip(codec, 1000);
+    assert!(encoded.starts_with(ZSTD_MAGIC));
+    assert!(encoded.len() < JsonCodec.encode(&value(1000)).unwrap().len());
+  }
+
+  #[test]
+  #[cfg(all(feature = "codec-json", feature = "codec-gzip", feature = "codec-zstd"))]
+  fn should_decode_with_different_compression() {
+    let gzip = Compressed::new(JsonCodec, Compression::Gzip(6), 0);
+    let zstd = Compressed::new(JsonCodec, Compression::Zstd(3), 0);
+
+    let encoded = gzip.encode(&value(100)).unwrap();
+    assert_eq!(zstd.decode::<Value>(&encoded).unwrap(), value(100));
+  }
+
+  #[test]
+  #[cfg(all(feature = "codec-json", feature = "codec-gzip"))]
+  fn should_decode_uncompressed_values() {
+    let codec = Compressed::new(JsonCodec, Compression::Gzip(6), 0);
+
+    let encoded = JsonCodec.encode(&value(100)).unwrap();
+    assert_eq!(codec.decode::<Value>(&encoded).unwrap(), value(100));
+  }
+}
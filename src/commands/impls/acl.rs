@@ -37,6 +37,7 @@ pub async fn acl_setuser(
 }
 
 pub async fn acl_getuser(inner: &Arc<RedisClientInner>, username: Str) -> Result<Option<AclUser>, RedisError> {
+  utils::disallow_pipeline(inner, RedisCommandKind::AclGetUser)?;
   let frame =
     utils::request_response(inner, move || Ok((RedisCommandKind::AclGetUser, vec![username.into()]))).await?;
 
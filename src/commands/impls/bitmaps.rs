@@ -0,0 +1,188 @@
+use super::*;
+use crate::error::*;
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::types::*;
+use crate::protocol::utils as protocol_utils;
+use crate::types::*;
+use crate::utils;
+use std::sync::Arc;
+
+fn check_bit(bit: u8) -> Result<(), RedisError> {
+  if bit > 1 {
+    Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "Bit value must be 0 or 1.",
+    ))
+  } else {
+    Ok(())
+  }
+}
+
+pub async fn setbit<K>(inner: &Arc<RedisClientInner>, key: K, offset: u32, value: u8) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let _ = check_bit(value)?;
+  let key = key.into();
+  args_value_cmd(inner, RedisCommandKind::Setbit, vec![key.into(), offset.into(), value.into()]).await
+}
+
+pub async fn getbit<K>(inner: &Arc<RedisClientInner>, key: K, offset: u32) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+  args_value_cmd(inner, RedisCommandKind::GetBit, vec![key.into(), offset.into()]).await
+}
+
+pub async fn bitcount<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  range: Option<(i64, i64)>,
+  unit: Option<BitUnit>,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  if range.is_none() && unit.is_some() {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "BITCOUNT unit requires a range.",
+    ));
+  }
+  let key = key.into();
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(4);
+    args.push(key.into());
+
+    if let Some((start, end)) = range {
+      args.push(start.into());
+      args.push(end.into());
+    }
+    if let Some(unit) = unit {
+      args.push(unit.to_str().into());
+    }
+
+    Ok((RedisCommandKind::BitCount, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn bitpos<K>(
+  inner: &Arc<RedisClientInner>,
+  key: K,
+  bit: u8,
+  start: Option<i64>,
+  end: Option<i64>,
+  unit: Option<BitUnit>,
+) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let _ = check_bit(bit)?;
+  if (start.is_none() && end.is_some()) || (end.is_none() && unit.is_some()) {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "BITPOS end requires a start index and unit requires an end index.",
+    ));
+  }
+  let key = key.into();
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(5);
+    args.push(key.into());
+    args.push(bit.into());
+
+    if let Some(start) = start {
+      args.push(start.into());
+    }
+    if let Some(end) = end {
+      args.push(end.into());
+    }
+    if let Some(unit) = unit {
+      args.push(unit.to_str().into());
+    }
+
+    Ok((RedisCommandKind::BitPos, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn bitop<D>(
+  inner: &Arc<RedisClientInner>,
+  operation: BitOperation,
+  dest: D,
+  keys: MultipleKeys,
+) -> Result<RedisValue, RedisError>
+where
+  D: Into<RedisKey>,
+{
+  if keys.len() == 0 || (operation == BitOperation::Not && keys.len() != 1) {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "BITOP requires at least one source key, and NOT requires exactly one source key.",
+    ));
+  }
+  let dest = dest.into();
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(2 + keys.len());
+    args.push(operation.to_str().into());
+    args.push(dest.into());
+
+    for key in keys.inner().into_iter() {
+      args.push(key.into());
+    }
+    Ok((RedisCommandKind::BitOp, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_single_result(frame)
+}
+
+pub async fn bitfield<K>(inner: &Arc<RedisClientInner>, key: K, ops: BitfieldOp) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  let key = key.into();
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(1 + ops.len() * 4);
+    args.push(key.into());
+    args.extend(ops.into_args());
+
+    Ok((RedisCommandKind::BitField, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
+
+pub async fn bitfield_ro<K>(inner: &Arc<RedisClientInner>, key: K, ops: BitfieldOp) -> Result<RedisValue, RedisError>
+where
+  K: Into<RedisKey>,
+{
+  if !ops.is_read_only() {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "BITFIELD_RO only supports GET operations.",
+    ));
+  }
+  let key = key.into();
+
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(1 + ops.len() * 3);
+    args.push(key.into());
+    args.extend(ops.into_args());
+
+    Ok((RedisCommandKind::BitFieldRO, args))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
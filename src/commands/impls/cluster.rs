@@ -16,6 +16,7 @@ ok_cmd!(cluster_saveconfig, ClusterSaveConfig);
 values_cmd!(cluster_slots, ClusterSlots);
 
 pub async fn cluster_info(inner: &Arc<RedisClientInner>) -> Result<ClusterInfo, RedisError> {
+  utils::disallow_pipeline(inner, RedisCommandKind::ClusterInfo)?;
   let frame = utils::request_response(inner, || Ok((RedisCommandKind::ClusterInfo, vec![]))).await?;
   protocol_utils::parse_cluster_info(frame)
 }
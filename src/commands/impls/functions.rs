@@ -0,0 +1,218 @@
+use super::*;
+use crate::commands::lua::check_key_slot;
+use crate::error::*;
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::types::*;
+use crate::protocol::utils as protocol_utils;
+use crate::types::*;
+use crate::utils;
+use bytes::Bytes;
+use bytes_utils::Str;
+use redis_protocol::resp3::types::Frame;
+use std::convert::TryInto;
+use std::sync::Arc;
+use tokio::sync::oneshot::channel as oneshot_channel;
+
+fn fcall_args(function: Str, keys: Vec<RedisKey>, cmd_args: MultipleValues) -> Result<Vec<RedisValue>, RedisError> {
+  let mut args = Vec::with_capacity(2 + keys.len() + cmd_args.len());
+  args.push(function.into());
+  args.push(keys.len().try_into()?);
+
+  for key in keys.into_iter() {
+    args.push(key.into());
+  }
+  for arg in cmd_args.inner().into_iter() {
+    args.push(arg);
+  }
+
+  Ok(args)
+}
+
+pub async fn fcall(
+  inner: &Arc<RedisClientInner>,
+  function: Str,
+  keys: MultipleKeys,
+  args: MultipleValues,
+) -> Result<RedisValue, RedisError> {
+  let keys = keys.inner();
+  let custom_key_slot = check_key_slot(inner, &keys)?;
+
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::Fcall(custom_key_slot), fcall_args(function, keys, args)?))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
+
+pub async fn fcall_ro(
+  inner: &Arc<RedisClientInner>,
+  function: Str,
+  keys: MultipleKeys,
+  args: MultipleValues,
+) -> Result<RedisValue, RedisError> {
+  let keys = keys.inner();
+  let custom_key_slot = check_key_slot(inner, &keys)?;
+
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::FcallRO(custom_key_slot), fcall_args(function, keys, args)?))
+  })
+  .await?;
+
+  protocol_utils::frame_to_results(frame)
+}
+
+pub async fn function_load(
+  inner: &Arc<RedisClientInner>,
+  replace: bool,
+  code: Str,
+) -> Result<RedisValue, RedisError> {
+  let mut args = Vec::with_capacity(2);
+  if replace {
+    args.push(static_val!(REPLACE));
+  }
+  args.push(code.into());
+
+  args_value_cmd(inner, RedisCommandKind::FunctionLoad, args).await
+}
+
+pub async fn function_load_cluster(
+  inner: &Arc<RedisClientInner>,
+  replace: bool,
+  code: Str,
+) -> Result<RedisValue, RedisError> {
+  let _ = utils::check_clustered(inner)?;
+  let name = parse_library_name(&code)?;
+
+  let mut args = Vec::with_capacity(2);
+  if replace {
+    args.push(static_val!(REPLACE));
+  }
+  args.push(code.into());
+
+  let (tx, rx) = oneshot_channel();
+  let kind = RedisCommandKind::_FunctionLoadCluster(AllNodesResponse::new(tx));
+  let command = RedisCommand::new(kind, args, None);
+  let _ = utils::send_command(inner, command)?;
+  let _ = rx.await??;
+
+  Ok(name.into())
+}
+
+pub async fn function_list(
+  inner: &Arc<RedisClientInner>,
+  library_name: Option<Str>,
+  with_code: bool,
+) -> Result<Vec<FunctionLibrary>, RedisError> {
+  utils::disallow_pipeline(inner, RedisCommandKind::FunctionList)?;
+  let frame = utils::request_response(inner, move || {
+    let mut args = Vec::with_capacity(3);
+    if let Some(library_name) = library_name {
+      args.push(static_val!(LIBRARYNAME));
+      args.push(library_name.into());
+    }
+    if with_code {
+      args.push(static_val!(WITHCODE));
+    }
+
+    Ok((RedisCommandKind::FunctionList, args))
+  })
+  .await?;
+
+  let frame = protocol_utils::frame_map_or_set_to_nested_array(frame)?;
+  if let Frame::Array { data, .. } = frame {
+    protocol_utils::parse_function_libraries(data)
+  } else {
+    Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Expected array response.",
+    ))
+  }
+}
+
+pub async fn function_delete(inner: &Arc<RedisClientInner>, library_name: Str) -> Result<(), RedisError> {
+  one_arg_ok_cmd(inner, RedisCommandKind::FunctionDelete, library_name.into()).await
+}
+
+pub async fn function_delete_cluster(inner: &Arc<RedisClientInner>, library_name: Str) -> Result<(), RedisError> {
+  let _ = utils::check_clustered(inner)?;
+
+  let (tx, rx) = oneshot_channel();
+  let kind = RedisCommandKind::_FunctionDeleteCluster(AllNodesResponse::new(tx));
+  let command = RedisCommand::new(kind, vec![library_name.into()], None);
+  let _ = utils::send_command(inner, command)?;
+  let _ = rx.await??;
+
+  Ok(())
+}
+
+value_cmd!(function_dump, FunctionDump);
+
+pub async fn function_restore(
+  inner: &Arc<RedisClientInner>,
+  serialized: Bytes,
+  policy: Option<FunctionRestorePolicy>,
+) -> Result<(), RedisError> {
+  let mut args = Vec::with_capacity(2);
+  args.push(serialized.into());
+  if let Some(policy) = policy {
+    args.push(policy.to_str().into());
+  }
+
+  args_ok_cmd(inner, RedisCommandKind::FunctionRestore, args).await
+}
+
+pub async fn function_restore_cluster(
+  inner: &Arc<RedisClientInner>,
+  serialized: Bytes,
+  policy: Option<FunctionRestorePolicy>,
+) -> Result<(), RedisError> {
+  let _ = utils::check_clustered(inner)?;
+
+  let mut args = Vec::with_capacity(2);
+  args.push(serialized.into());
+  if let Some(policy) = policy {
+    args.push(policy.to_str().into());
+  }
+
+  let (tx, rx) = oneshot_channel();
+  let kind = RedisCommandKind::_FunctionRestoreCluster(AllNodesResponse::new(tx));
+  let command = RedisCommand::new(kind, args, None);
+  let _ = utils::send_command(inner, command)?;
+  let _ = rx.await??;
+
+  Ok(())
+}
+
+pub async fn function_flush(inner: &Arc<RedisClientInner>, r#async: bool) -> Result<(), RedisError> {
+  let arg = static_val!(if r#async { ASYNC } else { SYNC });
+  one_arg_ok_cmd(inner, RedisCommandKind::FunctionFlush, arg).await
+}
+
+pub async fn function_flush_cluster(inner: &Arc<RedisClientInner>, r#async: bool) -> Result<(), RedisError> {
+  let _ = utils::check_clustered(inner)?;
+
+  let (tx, rx) = oneshot_channel();
+  let kind = RedisCommandKind::_FunctionFlushCluster(AllNodesResponse::new(tx));
+  let arg = static_val!(if r#async { ASYNC } else { SYNC });
+  let command = RedisCommand::new(kind, vec![arg], None);
+  let _ = utils::send_command(inner, command)?;
+  let _ = rx.await??;
+
+  Ok(())
+}
+
+pub async fn function_stats(inner: &Arc<RedisClientInner>) -> Result<FunctionStats, RedisError> {
+  utils::disallow_pipeline(inner, RedisCommandKind::FunctionStats)?;
+  let frame = utils::request_response(inner, || Ok((RedisCommandKind::FunctionStats, vec![]))).await?;
+
+  let frame = protocol_utils::frame_map_or_set_to_nested_array(frame)?;
+  if let Frame::Array { data, .. } = frame {
+    protocol_utils::parse_function_stats(&data)
+  } else {
+    Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Expected array response.",
+    ))
+  }
+}
@@ -149,6 +149,7 @@ where
   K: Into<RedisKey>,
   P: Into<GeoPosition>,
 {
+  utils::disallow_pipeline(inner, RedisCommandKind::GeoRadius)?;
   let (key, position) = (key.into(), position.into());
 
   let frame = utils::request_response(inner, move || {
@@ -211,6 +212,7 @@ pub async fn georadiusbymember<K>(
 where
   K: Into<RedisKey>,
 {
+  utils::disallow_pipeline(inner, RedisCommandKind::GeoRadiusByMember)?;
   let key = key.into();
 
   let frame = utils::request_response(inner, move || {
@@ -271,6 +273,7 @@ pub async fn geosearch<K>(
 where
   K: Into<RedisKey>,
 {
+  utils::disallow_pipeline(inner, RedisCommandKind::GeoSearch)?;
   let key = key.into();
 
   let frame = utils::request_response(inner, move || {
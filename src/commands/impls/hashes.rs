@@ -1,4 +1,5 @@
 use super::*;
+use crate::modules::cache;
 use crate::modules::inner::RedisClientInner;
 use crate::protocol::types::*;
 use crate::protocol::utils as protocol_utils;
@@ -42,8 +43,21 @@ where
   K: Into<RedisKey>,
   F: Into<RedisKey>,
 {
-  let args: Vec<RedisValue> = vec![key.into().into(), field.into().into()];
-  args_value_cmd(inner, RedisCommandKind::HGet, args).await
+  let (key, field) = (key.into(), field.into());
+  if let Some(cache) = cache::read_cache(inner) {
+    if let Some(value) = cache.hget(key.as_bytes(), field.as_bytes()) {
+      return Ok(value);
+    }
+
+    let reservation = cache.reserve(vec![key.clone().into_bytes()]);
+    let args: Vec<RedisValue> = vec![key.clone().into(), field.clone().into()];
+    let value = args_value_cmd(inner, RedisCommandKind::HGet, args).await?;
+    reservation.insert_field(key.into_bytes(), field.into_bytes(), value.clone());
+    Ok(value)
+  } else {
+    let args: Vec<RedisValue> = vec![key.into(), field.into()];
+    args_value_cmd(inner, RedisCommandKind::HGet, args).await
+  }
 }
 
 pub async fn hgetall<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue, RedisError>
@@ -52,7 +66,11 @@ where
 {
   let key = key.into();
   let frame = utils::request_response(inner, move || Ok((RedisCommandKind::HGetAll, vec![key.into()]))).await?;
-  Ok(RedisValue::Map(protocol_utils::frame_to_map(frame)?))
+  if protocol_utils::is_queued(&frame) {
+    Ok(RedisValue::Queued)
+  } else {
+    Ok(RedisValue::Map(protocol_utils::frame_to_map(frame)?))
+  }
 }
 
 pub async fn hincrby<K, F>(
@@ -210,7 +228,7 @@ where
   .await?;
 
   if has_count {
-    if has_values {
+    if has_values && !protocol_utils::is_queued(&frame) {
       let frame = protocol_utils::flatten_frame(frame);
       protocol_utils::frame_to_map(frame).map(|m| RedisValue::Map(m))
     } else {
@@ -1,10 +1,12 @@
 use super::*;
 use crate::error::*;
+use crate::modules::cache;
 use crate::modules::inner::RedisClientInner;
 use crate::protocol::types::*;
 use crate::protocol::utils as protocol_utils;
 use crate::types::*;
 use crate::utils;
+use bytes::Bytes;
 use std::convert::TryInto;
 use std::sync::Arc;
 
@@ -14,7 +16,19 @@ pub async fn get<K>(inner: &Arc<RedisClientInner>, key: K) -> Result<RedisValue,
 where
   K: Into<RedisKey>,
 {
-  one_arg_values_cmd(inner, RedisCommandKind::Get, key.into().into()).await
+  let key = key.into();
+  if let Some(cache) = cache::read_cache(inner) {
+    if let Some(value) = cache.get(key.as_bytes()) {
+      return Ok(value);
+    }
+
+    let reservation = cache.reserve(vec![key.clone().into_bytes()]);
+    let value = one_arg_values_cmd(inner, RedisCommandKind::Get, key.clone().into()).await?;
+    reservation.insert(key.into_bytes(), value.clone());
+    Ok(value)
+  } else {
+    one_arg_values_cmd(inner, RedisCommandKind::Get, key.into()).await
+  }
 }
 
 pub async fn set(
@@ -298,6 +312,16 @@ pub async fn mget(inner: &Arc<RedisClientInner>, keys: MultipleKeys) -> Result<R
   let keys = keys.into();
   utils::check_empty_keys(&keys)?;
 
+  let cache = cache::read_cache(inner);
+  let cache_keys: Vec<Bytes> = match cache {
+    Some(_) => keys.clone().inner().into_iter().map(|key| key.into_bytes()).collect(),
+    None => Vec::new(),
+  };
+  if let Some(values) = cache.and_then(|cache| cache.mget(&cache_keys)) {
+    return Ok(RedisValue::Array(values));
+  }
+  let reservation = cache.map(|cache| cache.reserve(cache_keys.clone()));
+
   let frame = utils::request_response(inner, move || {
     let mut args = Vec::with_capacity(keys.len());
 
@@ -309,7 +333,13 @@ pub async fn mget(inner: &Arc<RedisClientInner>, keys: MultipleKeys) -> Result<R
   })
   .await?;
 
-  protocol_utils::frame_to_results(frame)
+  let values = protocol_utils::frame_to_results(frame)?;
+  if let (Some(reservation), RedisValue::Array(ref values)) = (reservation, &values) {
+    for (key, value) in cache_keys.into_iter().zip(values.iter()) {
+      reservation.insert(key, value.clone());
+    }
+  }
+  Ok(values)
 }
 
 pub async fn mset<V>(inner: &Arc<RedisClientInner>, values: V) -> Result<RedisValue, RedisError>
@@ -29,6 +29,7 @@ pub async fn memory_malloc_stats(inner: &Arc<RedisClientInner>) -> Result<String
 ok_cmd!(memory_purge, MemoryPurge);
 
 pub async fn memory_stats(inner: &Arc<RedisClientInner>) -> Result<MemoryStats, RedisError> {
+  utils::disallow_pipeline(inner, RedisCommandKind::MemoryStats)?;
   let response = utils::request_response(inner, || Ok((RedisCommandKind::MemoryStats, vec![]))).await?;
 
   let frame = protocol_utils::frame_map_or_set_to_nested_array(response)?;
@@ -48,6 +48,8 @@ pub static IDLE: &'static str = "IDLE";
 pub static TIME: &'static str = "TIME";
 pub static RETRYCOUNT: &'static str = "RETRYCOUNT";
 pub static JUSTID: &'static str = "JUSTID";
+pub static LIBRARYNAME: &'static str = "LIBRARYNAME";
+pub static WITHCODE: &'static str = "WITHCODE";
 
 /// Macro to generate a command function that takes no arguments and expects an OK response - returning `()` to the caller.
 macro_rules! ok_cmd(
@@ -151,9 +153,11 @@ pub async fn args_ok_cmd(
 }
 
 pub mod acl;
+pub mod bitmaps;
 pub mod client;
 pub mod cluster;
 pub mod config;
+pub mod functions;
 pub mod geo;
 pub mod hashes;
 pub mod hyperloglog;
@@ -51,6 +51,41 @@ pub async fn unsubscribe(inner: &Arc<RedisClientInner>, channel: Str) -> Result<
   }
 }
 
+pub async fn ssubscribe(inner: &Arc<RedisClientInner>, channel: Str) -> Result<usize, RedisError> {
+  // the channel is the first argument, so in a cluster the command is routed to the node that owns its hash slot
+  let results = one_arg_values_cmd(inner, RedisCommandKind::Ssubscribe, channel.into()).await?;
+
+  // last value in the array is number of channels
+  if let RedisValue::Array(mut values) = results {
+    values.pop().and_then(|c| c.as_usize()).ok_or(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid SSUBSCRIBE response.",
+    ))
+  } else {
+    Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid SSUBSCRIBE response.",
+    ))
+  }
+}
+
+pub async fn sunsubscribe(inner: &Arc<RedisClientInner>, channel: Str) -> Result<usize, RedisError> {
+  let results = one_arg_values_cmd(inner, RedisCommandKind::Sunsubscribe, channel.into()).await?;
+
+  // last value in the array is number of channels
+  if let RedisValue::Array(mut values) = results {
+    values.pop().and_then(|c| c.as_usize()).ok_or(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid SUNSUBSCRIBE response.",
+    ))
+  } else {
+    Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid SUNSUBSCRIBE response.",
+    ))
+  }
+}
+
 pub async fn publish(
   inner: &Arc<RedisClientInner>,
   channel: Str,
@@ -125,3 +160,16 @@ where
     ))
   }
 }
+
+pub async fn spublish(
+  inner: &Arc<RedisClientInner>,
+  channel: Str,
+  message: RedisValue,
+) -> Result<RedisValue, RedisError> {
+  let frame = utils::request_response(inner, move || {
+    Ok((RedisCommandKind::Spublish, vec![channel.into(), message]))
+  })
+  .await?;
+
+  protocol_utils::frame_to_single_result(frame)
+}
@@ -9,6 +9,7 @@ use redis_protocol::resp3::types::Frame;
 use std::sync::Arc;
 
 pub async fn slowlog_get(inner: &Arc<RedisClientInner>, count: Option<i64>) -> Result<Vec<SlowlogEntry>, RedisError> {
+  utils::disallow_pipeline(inner, RedisCommandKind::Slowlog)?;
   let frame = utils::request_response(inner, move || {
     let mut args = Vec::with_capacity(2);
     args.push(static_val!(GET));
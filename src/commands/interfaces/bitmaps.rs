@@ -0,0 +1,121 @@
+use crate::commands;
+use crate::interfaces::{async_spawn, AsyncResult, ClientLike};
+use crate::types::{BitOperation, BitUnit, BitfieldOp, FromRedis, MultipleKeys, RedisKey};
+
+/// Functions that implement the [bitmap](https://redis.io/docs/data-types/bitmaps/) and
+/// [BITFIELD](https://redis.io/commands/bitfield) interface.
+pub trait BitmapInterface: ClientLike + Sized {
+  /// Sets or clears the bit at `offset` in the string value stored at `key`, returning the original bit value.
+  ///
+  /// <https://redis.io/commands/setbit>
+  fn setbit<R, K>(&self, key: K, offset: u32, value: u8) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    K: Into<RedisKey>,
+  {
+    into!(key);
+    async_spawn(self, |inner| async move {
+      commands::bitmaps::setbit(&inner, key, offset, value).await?.convert()
+    })
+  }
+
+  /// Returns the bit value at `offset` in the string value stored at `key`.
+  ///
+  /// <https://redis.io/commands/getbit>
+  fn getbit<R, K>(&self, key: K, offset: u32) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    K: Into<RedisKey>,
+  {
+    into!(key);
+    async_spawn(self, |inner| async move {
+      commands::bitmaps::getbit(&inner, key, offset).await?.convert()
+    })
+  }
+
+  /// Count the number of set bits in a string, optionally limited to the inclusive `(start, end)` range.
+  ///
+  /// The range is interpreted as a byte index by default, or as a bit index with `BitUnit::Bit` (Redis >= 7.0).
+  ///
+  /// <https://redis.io/commands/bitcount>
+  fn bitcount<R, K>(&self, key: K, range: Option<(i64, i64)>, unit: Option<BitUnit>) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    K: Into<RedisKey>,
+  {
+    into!(key);
+    async_spawn(self, |inner| async move {
+      commands::bitmaps::bitcount(&inner, key, range, unit).await?.convert()
+    })
+  }
+
+  /// Return the position of the first bit set to 1 or 0 in a string.
+  ///
+  /// <https://redis.io/commands/bitpos>
+  fn bitpos<R, K>(
+    &self,
+    key: K,
+    bit: u8,
+    start: Option<i64>,
+    end: Option<i64>,
+    unit: Option<BitUnit>,
+  ) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    K: Into<RedisKey>,
+  {
+    into!(key);
+    async_spawn(self, |inner| async move {
+      commands::bitmaps::bitpos(&inner, key, bit, start, end, unit)
+        .await?
+        .convert()
+    })
+  }
+
+  /// Perform a bitwise operation between multiple keys and store the result in the destination key, returning the
+  /// size of the string stored in the destination key.
+  ///
+  /// When used with a clustered deployment all the keys must belong to the same hash slot.
+  ///
+  /// <https://redis.io/commands/bitop>
+  fn bitop<R, D, S>(&self, operation: BitOperation, dest: D, keys: S) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    D: Into<RedisKey>,
+    S: Into<MultipleKeys>,
+  {
+    into!(dest, keys);
+    async_spawn(self, |inner| async move {
+      commands::bitmaps::bitop(&inner, operation, dest, keys).await?.convert()
+    })
+  }
+
+  /// Treat the string stored at `key` as an array of integers and read or modify those integers, returning one value
+  /// for each `GET`, `SET`, or `INCRBY` operation.
+  ///
+  /// <https://redis.io/commands/bitfield>
+  fn bitfield<R, K>(&self, key: K, ops: BitfieldOp) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    K: Into<RedisKey>,
+  {
+    into!(key);
+    async_spawn(self, |inner| async move {
+      commands::bitmaps::bitfield(&inner, key, ops).await?.convert()
+    })
+  }
+
+  /// A read-only variant of [bitfield](Self::bitfield) that only supports `GET` operations.
+  ///
+  /// <https://redis.io/commands/bitfield_ro>
+  fn bitfield_ro<R, K>(&self, key: K, ops: BitfieldOp) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    K: Into<RedisKey>,
+  {
+    into!(key);
+    async_spawn(self, |inner| async move {
+      commands::bitmaps::bitfield_ro(&inner, key, ops).await?.convert()
+    })
+  }
+}
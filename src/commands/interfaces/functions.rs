@@ -0,0 +1,200 @@
+use crate::commands;
+use crate::error::RedisError;
+use crate::interfaces::{async_spawn, AsyncResult, ClientLike};
+use crate::types::{FromRedis, FunctionLibrary, FunctionRestorePolicy, FunctionStats, MultipleKeys, MultipleValues};
+use crate::utils;
+use bytes::Bytes;
+use bytes_utils::Str;
+use std::convert::TryInto;
+
+/// Functions that implement the [function](https://redis.io/docs/manual/programmability/functions-intro/) interface
+/// added in Redis 7.0.
+pub trait FunctionInterface: ClientLike + Sized {
+  /// Load a library into the server, returning the library name.
+  ///
+  /// If `replace` is true an existing library with the same name will be replaced.
+  ///
+  /// <https://redis.io/commands/function-load>
+  fn function_load<S>(&self, replace: bool, code: S) -> AsyncResult<String>
+  where
+    S: Into<Str>,
+  {
+    into!(code);
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::function_load(&inner, replace, code)
+        .await?
+        .convert()
+    })
+  }
+
+  /// A clustered variant of [function_load](Self::function_load) that loads the library on all primary nodes in a
+  /// cluster.
+  ///
+  /// The library name is read from the `#!<engine> name=<name>` header in the library code.
+  fn function_load_cluster<S>(&self, replace: bool, code: S) -> AsyncResult<String>
+  where
+    S: Into<Str>,
+  {
+    into!(code);
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::function_load_cluster(&inner, replace, code)
+        .await?
+        .convert()
+    })
+  }
+
+  /// Return information about the functions and libraries, optionally filtered by a library name pattern.
+  ///
+  /// <https://redis.io/commands/function-list>
+  fn function_list<S>(&self, library_name: Option<S>, with_code: bool) -> AsyncResult<Vec<FunctionLibrary>>
+  where
+    S: Into<Str>,
+  {
+    let library_name = library_name.map(|s| s.into());
+    async_spawn(self, |inner| async move {
+      commands::functions::function_list(&inner, library_name, with_code).await
+    })
+  }
+
+  /// Delete a library and all its functions.
+  ///
+  /// <https://redis.io/commands/function-delete>
+  fn function_delete<S>(&self, library_name: S) -> AsyncResult<()>
+  where
+    S: Into<Str>,
+  {
+    into!(library_name);
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::function_delete(&inner, library_name).await
+    })
+  }
+
+  /// A clustered variant of [function_delete](Self::function_delete) that deletes the library on all primary nodes
+  /// in a cluster.
+  fn function_delete_cluster<S>(&self, library_name: S) -> AsyncResult<()>
+  where
+    S: Into<Str>,
+  {
+    into!(library_name);
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::function_delete_cluster(&inner, library_name).await
+    })
+  }
+
+  /// Return the serialized payload of all loaded libraries.
+  ///
+  /// <https://redis.io/commands/function-dump>
+  fn function_dump<R>(&self) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+  {
+    async_spawn(self, |inner| async move {
+      commands::functions::function_dump(&inner).await?.convert()
+    })
+  }
+
+  /// Restore libraries from the serialized payload returned by [function_dump](Self::function_dump).
+  ///
+  /// <https://redis.io/commands/function-restore>
+  fn function_restore<B>(&self, serialized: B, policy: Option<FunctionRestorePolicy>) -> AsyncResult<()>
+  where
+    B: Into<Bytes>,
+  {
+    into!(serialized);
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::function_restore(&inner, serialized, policy).await
+    })
+  }
+
+  /// A clustered variant of [function_restore](Self::function_restore) that restores the libraries on all primary
+  /// nodes in a cluster.
+  fn function_restore_cluster<B>(&self, serialized: B, policy: Option<FunctionRestorePolicy>) -> AsyncResult<()>
+  where
+    B: Into<Bytes>,
+  {
+    into!(serialized);
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::function_restore_cluster(&inner, serialized, policy).await
+    })
+  }
+
+  /// Delete all the libraries.
+  ///
+  /// <https://redis.io/commands/function-flush>
+  fn function_flush(&self, r#async: bool) -> AsyncResult<()> {
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::function_flush(&inner, r#async).await
+    })
+  }
+
+  /// A clustered variant of [function_flush](Self::function_flush) that deletes all the libraries on all primary
+  /// nodes in a cluster.
+  fn function_flush_cluster(&self, r#async: bool) -> AsyncResult<()> {
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::function_flush_cluster(&inner, r#async).await
+    })
+  }
+
+  /// Return information about the function that's currently running and the available execution engines.
+  ///
+  /// <https://redis.io/commands/function-stats>
+  fn function_stats(&self) -> AsyncResult<FunctionStats> {
+    async_spawn(self, |inner| async move {
+      commands::functions::function_stats(&inner).await
+    })
+  }
+
+  /// Invoke a function.
+  ///
+  /// <https://redis.io/commands/fcall>
+  ///
+  /// **Note: Use `None` to represent an empty set of keys or args.**
+  fn fcall<R, F, K, V>(&self, function: F, keys: K, args: V) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    F: Into<Str>,
+    K: Into<MultipleKeys>,
+    V: TryInto<MultipleValues>,
+    V::Error: Into<RedisError>,
+  {
+    into!(function, keys);
+    try_into!(args);
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::fcall(&inner, function, keys, args)
+        .await?
+        .convert()
+    })
+  }
+
+  /// A read-only variant of [fcall](Self::fcall) that can only invoke functions registered with the `no-writes` flag.
+  ///
+  /// <https://redis.io/commands/fcall_ro>
+  ///
+  /// **Note: Use `None` to represent an empty set of keys or args.**
+  fn fcall_ro<R, F, K, V>(&self, function: F, keys: K, args: V) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    F: Into<Str>,
+    K: Into<MultipleKeys>,
+    V: TryInto<MultipleValues>,
+    V::Error: Into<RedisError>,
+  {
+    into!(function, keys);
+    try_into!(args);
+    async_spawn(self, |inner| async move {
+      utils::disallow_during_transaction(&inner)?;
+      commands::functions::fcall_ro(&inner, function, keys, args)
+        .await?
+        .convert()
+    })
+  }
+}
@@ -2,7 +2,9 @@ use crate::interfaces::ClientLike;
 use crate::utils;
 
 #[cfg(feature = "metrics")]
-use crate::modules::metrics::Stats;
+use crate::modules::metrics::{HistogramStats, Stats};
+#[cfg(feature = "metrics")]
+use std::collections::HashMap;
 
 /// Functions that implement the internal metrics interface, largely controlled by the `metrics` feature flag.
 pub trait MetricsInterface: ClientLike + Sized {
@@ -84,4 +86,67 @@ pub trait MetricsInterface: ClientLike + Sized {
   fn take_res_size_metrics(&self) -> Stats {
     self.inner().res_size_stats.write().take_metrics()
   }
+
+  /// Read latency histograms for each command, keyed by the command name.
+  ///
+  /// Like [read_latency_metrics](Self::read_latency_metrics) this includes the time spent waiting to be written, but
+  /// values are in microseconds.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn read_command_latency_histograms(&self) -> HashMap<String, HistogramStats> {
+    self.inner().command_histograms.read().read_latency()
+  }
+
+  /// Read and consume latency histograms for each command, resetting their values afterwards.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn take_command_latency_histograms(&self) -> HashMap<String, HistogramStats> {
+    self.inner().command_histograms.write().take_latency()
+  }
+
+  /// Read latency histograms for each server, keyed by the `host:port` server name.
+  ///
+  /// Values are in microseconds.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn read_server_latency_histograms(&self) -> HashMap<String, HistogramStats> {
+    self.inner().command_histograms.read().read_server_latency()
+  }
+
+  /// Read and consume latency histograms for each server, resetting their values afterwards.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn take_server_latency_histograms(&self) -> HashMap<String, HistogramStats> {
+    self.inner().command_histograms.write().take_server_latency()
+  }
+
+  /// Read request payload size histograms for each command, keyed by the command name.
+  ///
+  /// Sizes are estimated from the command arguments and are in bytes.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn read_command_req_size_histograms(&self) -> HashMap<String, HistogramStats> {
+    self.inner().command_histograms.read().read_req_size()
+  }
+
+  /// Read and consume request payload size histograms for each command, resetting their values afterwards.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn take_command_req_size_histograms(&self) -> HashMap<String, HistogramStats> {
+    self.inner().command_histograms.write().take_req_size()
+  }
+
+  /// Read response payload size histograms for each command, keyed by the command name.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn read_command_res_size_histograms(&self) -> HashMap<String, HistogramStats> {
+    self.inner().command_histograms.read().read_res_size()
+  }
+
+  /// Read and consume response payload size histograms for each command, resetting their values afterwards.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn take_command_res_size_histograms(&self) -> HashMap<String, HistogramStats> {
+    self.inner().command_histograms.write().take_res_size()
+  }
 }
@@ -1,7 +1,9 @@
 pub mod acl;
+pub mod bitmaps;
 pub mod client;
 pub mod cluster;
 pub mod config;
+pub mod functions;
 pub mod geo;
 pub mod hashes;
 pub mod hyperloglog;
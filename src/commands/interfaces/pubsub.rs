@@ -1,7 +1,7 @@
 use crate::commands;
 use crate::error::RedisError;
 use crate::interfaces::{async_spawn, AsyncResult, AsyncStream, ClientLike};
-use crate::types::{FromRedis, KeyspaceEvent, MultipleStrings, RedisValue};
+use crate::types::{FromRedis, KeyspaceEvent, Message, MultipleStrings, RedisValue};
 use bytes_utils::Str;
 use std::convert::TryInto;
 use tokio::sync::mpsc::unbounded_channel;
@@ -19,6 +19,18 @@ pub trait PubsubInterface: ClientLike + Sized {
     UnboundedReceiverStream::new(rx).into()
   }
 
+  /// Listen for messages on the publish-subscribe interface. **Keyspace events are not sent on this interface.**
+  ///
+  /// The [kind](crate::types::MessageKind) of each message describes whether it was received from a channel, a channel pattern, or a sharded channel.
+  ///
+  /// If the connection to the Redis server closes for any reason this function does not need to be called again. Messages will start appearing on the original stream after [subscribe](Self::subscribe) is called again.
+  fn on_pubsub_message(&self) -> AsyncStream<Message> {
+    let (tx, rx) = unbounded_channel();
+    self.inner().pubsub_message_tx.write().push_back(tx);
+
+    UnboundedReceiverStream::new(rx).into()
+  }
+
   /// Listen for keyspace and keyevent notifications on the publish subscribe interface.
   ///
   /// Callers still need to configure the server and subscribe to the relevant channels, but this interface will format the messages automatically.
@@ -104,4 +116,51 @@ pub trait PubsubInterface: ClientLike + Sized {
       commands::pubsub::publish(&inner, channel, message).await?.convert()
     })
   }
+
+  /// Subscribe to a sharded channel, returning the number of sharded channels to which the client is subscribed.
+  ///
+  /// In a cluster the command is sent to the node that owns the channel's hash slot.
+  ///
+  /// <https://redis.io/commands/ssubscribe>
+  fn ssubscribe<S>(&self, channel: S) -> AsyncResult<usize>
+  where
+    S: Into<Str>,
+  {
+    into!(channel);
+    async_spawn(self, |inner| async move {
+      commands::pubsub::ssubscribe(&inner, channel).await
+    })
+  }
+
+  /// Unsubscribe from a sharded channel, returning the number of sharded channels to which the client is subscribed.
+  ///
+  /// <https://redis.io/commands/sunsubscribe>
+  fn sunsubscribe<S>(&self, channel: S) -> AsyncResult<usize>
+  where
+    S: Into<Str>,
+  {
+    into!(channel);
+    async_spawn(self, |inner| async move {
+      commands::pubsub::sunsubscribe(&inner, channel).await
+    })
+  }
+
+  /// Publish a message on a sharded channel, returning the number of clients that received the message.
+  ///
+  /// In a cluster the message is only propagated within the shard that owns the channel's hash slot.
+  ///
+  /// <https://redis.io/commands/spublish>
+  fn spublish<R, S, V>(&self, channel: S, message: V) -> AsyncResult<R>
+  where
+    R: FromRedis + Unpin + Send,
+    S: Into<Str>,
+    V: TryInto<RedisValue>,
+    V::Error: Into<RedisError>,
+  {
+    into!(channel);
+    try_into!(message);
+    async_spawn(self, |inner| async move {
+      commands::pubsub::spublish(&inner, channel, message).await?.convert()
+    })
+  }
 }
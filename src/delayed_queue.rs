@@ -0,0 +1,625 @@
+use crate::clients::{KeyspaceConfig, KeyspaceEventKind, KeyspaceNotification, KeyspaceNotifications, RedisClient};
+use crate::error::{RedisError, RedisErrorKind};
+use crate::interfaces::{LuaInterface, SortedSetsInterface};
+use crate::types::{RedisKey, RedisValue};
+use crate::utils;
+use futures::{Stream, StreamExt};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::channel;
+use tokio::time::sleep;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// The amount of time to wait before claiming jobs again after an error.
+const ERROR_DELAY_MS: u64 = 1_000;
+/// The length of generated job IDs.
+const JOB_ID_LEN: usize = 24;
+
+/// Store the payload and schedule the job, unless the job is claimed.
+///
+/// Scheduling a dead lettered job removes it from the dead letter set and resets its attempts.
+///
+/// Keys: queue, jobs, processing, dead letters, attempts
+///
+/// Arguments: ID, payload, delay
+static SCHEDULE_SCRIPT: &'static str = lua_with_now_ms!(r#"
+if redis.call('zscore', KEYS[3], ARGV[1]) then
+  return 0
+end
+if redis.call('zrem', KEYS[4], ARGV[1]) == 1 then
+  redis.call('hdel', KEYS[5], ARGV[1])
+end
+redis.call('hset', KEYS[2], ARGV[1], ARGV[2])
+redis.call('zadd', KEYS[1], now + tonumber(ARGV[3]), ARGV[1])
+return 1
+"#);
+
+/// Move jobs whose visibility timeout has passed back to the queue, then claim due jobs.
+///
+/// The visibility deadline of each claimed job is returned as a claim token. A job's deadline changes each time it's
+/// claimed, so the token identifies one delivery of the job. Due jobs that were already claimed `max attempts` times
+/// are moved to the dead letter set instead.
+///
+/// Keys: queue, processing, jobs, attempts, dead letters
+///
+/// Arguments: count, visibility timeout, max attempts (0 for no limit)
+static CLAIM_SCRIPT: &'static str = lua_with_now_ms!(r#"
+local count = tonumber(ARGV[1])
+local visibility = tonumber(ARGV[2])
+local max_attempts = tonumber(ARGV[3])
+
+local expired = redis.call('zrangebyscore', KEYS[2], '-inf', now, 'LIMIT', 0, count)
+for _, id in ipairs(expired) do
+  redis.call('zrem', KEYS[2], id)
+  redis.call('zadd', KEYS[1], now, id)
+end
+
+local jobs = {}
+local due = redis.call('zrangebyscore', KEYS[1], '-inf', now, 'LIMIT', 0, count)
+for _, id in ipairs(due) do
+  redis.call('zrem', KEYS[1], id)
+  local payload = redis.call('hget', KEYS[3], id)
+  if payload then
+    local attempts = tonumber(redis.call('hget', KEYS[4], id) or '0')
+    if max_attempts > 0 and attempts >= max_attempts then
+      redis.call('zadd', KEYS[5], now, id)
+    else
+      local deadline = now + visibility
+      redis.call('zadd', KEYS[2], deadline, id)
+      attempts = redis.call('hincrby', KEYS[4], id, 1)
+      table.insert(jobs, id)
+      table.insert(jobs, payload)
+      table.insert(jobs, attempts)
+      table.insert(jobs, deadline)
+    end
+  end
+end
+return jobs
+"#);
+
+/// Remove a claimed job if it's still claimed with the provided claim token.
+///
+/// Keys: processing, jobs, attempts
+///
+/// Arguments: ID, claim token
+static ACK_SCRIPT: &'static str = r#"
+local deadline = redis.call('zscore', KEYS[1], ARGV[1])
+if not deadline or tonumber(deadline) ~= tonumber(ARGV[2]) then
+  return 0
+end
+redis.call('zrem', KEYS[1], ARGV[1])
+redis.call('hdel', KEYS[2], ARGV[1])
+redis.call('hdel', KEYS[3], ARGV[1])
+return 1
+"#;
+
+/// Move a claimed job back to the queue if it's still claimed with the provided claim token.
+///
+/// Keys: processing, queue
+///
+/// Arguments: ID, claim token, delay
+static NACK_SCRIPT: &'static str = lua_with_now_ms!(r#"
+local deadline = redis.call('zscore', KEYS[1], ARGV[1])
+if not deadline or tonumber(deadline) ~= tonumber(ARGV[2]) then
+  return 0
+end
+redis.call('zrem', KEYS[1], ARGV[1])
+redis.call('zadd', KEYS[2], now + tonumber(ARGV[3]), ARGV[1])
+return 1
+"#);
+
+/// Remove a job whether or not it has been claimed or dead lettered.
+///
+/// Keys: queue, processing, jobs, attempts, dead letters
+///
+/// Arguments: ID
+static CANCEL_SCRIPT: &'static str = r#"
+local removed = redis.call('zrem', KEYS[1], ARGV[1]) + redis.call('zrem', KEYS[2], ARGV[1])
+removed = removed + redis.call('zrem', KEYS[5], ARGV[1])
+redis.call('hdel', KEYS[3], ARGV[1])
+redis.call('hdel', KEYS[4], ARGV[1])
+return removed
+"#;
+
+/// Move a dead lettered job back to the queue and reset its attempts.
+///
+/// Keys: dead letters, queue, attempts
+///
+/// Arguments: ID, delay
+static RETRY_DEAD_LETTER_SCRIPT: &'static str = lua_with_now_ms!(r#"
+if redis.call('zrem', KEYS[1], ARGV[1]) == 0 then
+  return 0
+end
+redis.call('hdel', KEYS[3], ARGV[1])
+redis.call('zadd', KEYS[2], now + tonumber(ARGV[2]), ARGV[1])
+return 1
+"#);
+
+/// Configuration options for a [DelayedQueue].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelayedQueueConfig {
+  /// The amount of time, in milliseconds, that a claimed job is hidden from other consumers. If the job is not
+  /// acknowledged within this time it is moved back to the queue.
+  ///
+  /// Default: `30_000`
+  pub visibility_timeout_ms: u64,
+  /// The maximum number of jobs claimed with each call to the claim script.
+  ///
+  /// Default: `10`
+  pub batch_size: u64,
+  /// The amount of time, in milliseconds, to wait before claiming again when no jobs are due.
+  ///
+  /// Default: `1_000`
+  pub poll_interval_ms: u64,
+  /// The maximum number of times a job is claimed. Jobs that are due after being claimed `max_attempts` times are
+  /// moved to the dead letter set instead of being claimed again. If `None` jobs are retried forever.
+  ///
+  /// Default: `None`
+  pub max_attempts: Option<u64>,
+}
+
+impl Default for DelayedQueueConfig {
+  fn default() -> Self {
+    DelayedQueueConfig {
+      visibility_timeout_ms: 30_000,
+      batch_size: 10,
+      poll_interval_ms: 1_000,
+      max_attempts: None,
+    }
+  }
+}
+
+/// A job claimed from a [DelayedQueue].
+///
+/// Jobs must be acknowledged with [ack](Self::ack) once they're processed, or moved back to the queue with
+/// [nack](Self::nack). Jobs that are not acknowledged within the visibility timeout are delivered again.
+pub struct DelayedJob<C>
+where
+  C: LuaInterface + SortedSetsInterface + Clone + 'static,
+{
+  /// The job ID.
+  pub id: String,
+  /// The job payload.
+  pub payload: RedisValue,
+  /// The number of times the job has been claimed, including this time.
+  pub attempts: u64,
+  claim_token: i64,
+  queue: DelayedQueue<C>,
+}
+
+impl<C> fmt::Debug for DelayedJob<C>
+where
+  C: LuaInterface + SortedSetsInterface + Clone + 'static,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("DelayedJob")
+      .field("id", &self.id)
+      .field("payload", &self.payload)
+      .field("attempts", &self.attempts)
+      .finish()
+  }
+}
+
+impl<C> DelayedJob<C>
+where
+  C: LuaInterface + SortedSetsInterface + Clone + 'static,
+{
+  /// Remove the job from the queue, returning whether the job was still claimed by this consumer.
+  ///
+  /// This returns `false` without modifying the job if the visibility timeout expired and the job was claimed again.
+  pub async fn ack(&self) -> Result<bool, RedisError> {
+    self.queue.ack(self).await
+  }
+
+  /// Move the job back to the queue to run again after `delay`, returning whether the job was still claimed by this
+  /// consumer.
+  ///
+  /// This returns `false` without modifying the job if the visibility timeout expired and the job was claimed again.
+  pub async fn nack(&self, delay: Duration) -> Result<bool, RedisError> {
+    self.queue.nack(self, delay).await
+  }
+}
+
+/// A queue of jobs that are delivered to consumers after a delay.
+///
+/// Jobs are stored in a sorted set scored by the time they're due, and are claimed atomically with a Lua script that
+/// moves them to a second sorted set scored by their visibility deadline. All keys use the queue name as a hash tag
+/// so the queue works with clustered deployments. The server's clock is used for all timestamps.
+///
+/// Jobs are delivered at least once. A job that is not acknowledged before its visibility timeout expires is moved
+/// back to the queue and delivered again. If `max_attempts` is set, jobs that are claimed too many times are moved to
+/// a dead letter set where they can be inspected and retried with [retry_dead_letter](Self::retry_dead_letter).
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::delayed_queue::{DelayedQueue, DelayedQueueConfig};
+/// # use futures::StreamExt;
+/// # use std::time::Duration;
+/// # async fn example(client: RedisClient) -> Result<(), RedisError> {
+/// let queue = DelayedQueue::new(client, "emails", DelayedQueueConfig::default());
+/// let _ = queue.schedule("user:1", Duration::from_secs(60)).await?;
+///
+/// let mut jobs = queue.jobs();
+/// while let Some(job) = jobs.next().await {
+///   let job = job?;
+///   println!("Sending email to {:?}", job.payload);
+///   let _ = job.ack().await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DelayedQueue<C>
+where
+  C: LuaInterface + SortedSetsInterface + Clone + 'static,
+{
+  client: C,
+  name: Arc<String>,
+  config: Arc<DelayedQueueConfig>,
+}
+
+impl<C> fmt::Debug for DelayedQueue<C>
+where
+  C: LuaInterface + SortedSetsInterface + Clone + 'static,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("DelayedQueue")
+      .field("name", &self.name)
+      .field("config", &self.config)
+      .finish()
+  }
+}
+
+/// Parse the flattened `[id, payload, attempts, claim token, ...]` response from the claim script.
+fn parse_claimed(value: RedisValue) -> Result<Vec<(String, RedisValue, u64, i64)>, RedisError> {
+  let values = match value {
+    RedisValue::Null => return Ok(Vec::new()),
+    value => value.into_array(),
+  };
+  if values.len() % 4 != 0 {
+    return Err(RedisError::new_parse("Invalid delayed queue claim response."));
+  }
+
+  let mut jobs = Vec::with_capacity(values.len() / 4);
+  let mut values = values.into_iter();
+  while let (Some(id), Some(payload), Some(attempts), Some(token)) =
+    (values.next(), values.next(), values.next(), values.next())
+  {
+    jobs.push((id.convert()?, payload, attempts.convert()?, token.convert()?));
+  }
+  Ok(jobs)
+}
+
+impl<C> DelayedQueue<C>
+where
+  C: LuaInterface + SortedSetsInterface + Clone + 'static,
+{
+  /// Create a new queue with the provided name.
+  pub fn new<S>(client: C, name: S, config: DelayedQueueConfig) -> Self
+  where
+    S: Into<String>,
+  {
+    DelayedQueue {
+      client,
+      name: Arc::new(name.into()),
+      config: Arc::new(config),
+    }
+  }
+
+  /// Read the queue name.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Read the queue config.
+  pub fn config(&self) -> &DelayedQueueConfig {
+    &self.config
+  }
+
+  /// Read the key of the sorted set that contains scheduled jobs.
+  pub fn queue_key(&self) -> RedisKey {
+    format!("{{{}}}:queue", self.name).into()
+  }
+
+  /// Read the key of the sorted set that contains claimed jobs.
+  pub fn processing_key(&self) -> RedisKey {
+    format!("{{{}}}:processing", self.name).into()
+  }
+
+  fn jobs_key(&self) -> RedisKey {
+    format!("{{{}}}:jobs", self.name).into()
+  }
+
+  fn attempts_key(&self) -> RedisKey {
+    format!("{{{}}}:attempts", self.name).into()
+  }
+
+  /// Read the key of the sorted set that contains dead lettered jobs, scored by the time they were dead lettered.
+  pub fn dead_letter_key(&self) -> RedisKey {
+    format!("{{{}}}:dead", self.name).into()
+  }
+
+  /// Schedule a job to run after `delay`, returning the generated job ID.
+  pub async fn schedule<V>(&self, payload: V, delay: Duration) -> Result<String, RedisError>
+  where
+    V: Into<RedisValue>,
+  {
+    let id = utils::random_string(JOB_ID_LEN);
+    let _ = self.schedule_with_id(&id, payload, delay).await?;
+    Ok(id)
+  }
+
+  /// Schedule a job with the provided ID to run after `delay`.
+  ///
+  /// If a job with the same ID is already scheduled its payload and due time are replaced, and a dead lettered job
+  /// with the same ID is scheduled again with its attempts reset. An error is returned if the job is currently
+  /// claimed, since the consumer's `ack` would otherwise remove the new job.
+  pub async fn schedule_with_id<V>(&self, id: &str, payload: V, delay: Duration) -> Result<(), RedisError>
+  where
+    V: Into<RedisValue>,
+  {
+    let keys = vec![
+      self.queue_key(),
+      self.jobs_key(),
+      self.processing_key(),
+      self.dead_letter_key(),
+      self.attempts_key(),
+    ];
+    let args = vec![id.into(), payload.into(), (delay.as_millis() as i64).into()];
+
+    let scheduled: i64 = utils::eval_script(&self.client, SCHEDULE_SCRIPT, keys.into(), args).await?;
+    if scheduled > 0 {
+      Ok(())
+    } else {
+      Err(RedisError::new(
+        RedisErrorKind::InvalidArgument,
+        format!("Job {} is claimed and cannot be scheduled.", id),
+      ))
+    }
+  }
+
+  /// Remove a job whether or not it has been claimed or dead lettered, returning whether the job existed.
+  pub async fn cancel(&self, id: &str) -> Result<bool, RedisError> {
+    let keys = vec![
+      self.queue_key(),
+      self.processing_key(),
+      self.jobs_key(),
+      self.attempts_key(),
+      self.dead_letter_key(),
+    ];
+
+    let removed: i64 = utils::eval_script(&self.client, CANCEL_SCRIPT, keys.into(), vec![id.into()]).await?;
+    Ok(removed > 0)
+  }
+
+  /// Claim up to `count` jobs that are due.
+  ///
+  /// Claimed jobs whose visibility timeout has passed are moved back to the queue first, and due jobs that reached
+  /// `max_attempts` are moved to the dead letter set.
+  pub async fn claim(&self, count: u64) -> Result<Vec<DelayedJob<C>>, RedisError> {
+    let keys = vec![
+      self.queue_key(),
+      self.processing_key(),
+      self.jobs_key(),
+      self.attempts_key(),
+      self.dead_letter_key(),
+    ];
+    let args = vec![
+      (count as i64).into(),
+      (self.config.visibility_timeout_ms as i64).into(),
+      (self.config.max_attempts.unwrap_or(0) as i64).into(),
+    ];
+
+    let result = utils::eval_script(&self.client, CLAIM_SCRIPT, keys.into(), args).await?;
+    let jobs = parse_claimed(result)?
+      .into_iter()
+      .map(|(id, payload, attempts, claim_token)| DelayedJob {
+        id,
+        payload,
+        attempts,
+        claim_token,
+        queue: self.clone(),
+      })
+      .collect();
+
+    Ok(jobs)
+  }
+
+  /// Remove a claimed job from the queue, returning whether the job was still claimed by the caller.
+  pub async fn ack(&self, job: &DelayedJob<C>) -> Result<bool, RedisError> {
+    let keys = vec![self.processing_key(), self.jobs_key(), self.attempts_key()];
+    let args = vec![job.id.as_str().into(), job.claim_token.into()];
+
+    let removed: i64 = utils::eval_script(&self.client, ACK_SCRIPT, keys.into(), args).await?;
+    Ok(removed > 0)
+  }
+
+  /// Move a claimed job back to the queue to run again after `delay`, returning whether the job was still claimed by
+  /// the caller.
+  pub async fn nack(&self, job: &DelayedJob<C>, delay: Duration) -> Result<bool, RedisError> {
+    let keys = vec![self.processing_key(), self.queue_key()];
+    let args = vec![
+      job.id.as_str().into(),
+      job.claim_token.into(),
+      (delay.as_millis() as i64).into(),
+    ];
+
+    let moved: i64 = utils::eval_script(&self.client, NACK_SCRIPT, keys.into(), args).await?;
+    Ok(moved > 0)
+  }
+
+  /// Read the number of scheduled jobs, including jobs that are due but not claimed.
+  pub async fn len(&self) -> Result<u64, RedisError> {
+    self.client.zcard(self.queue_key()).await
+  }
+
+  /// Read the number of claimed jobs that have not been acknowledged.
+  pub async fn processing_len(&self) -> Result<u64, RedisError> {
+    self.client.zcard(self.processing_key()).await
+  }
+
+  /// Read the number of dead lettered jobs.
+  pub async fn dead_letter_len(&self) -> Result<u64, RedisError> {
+    self.client.zcard(self.dead_letter_key()).await
+  }
+
+  /// Move a dead lettered job back to the queue to run after `delay` with its attempts reset, returning whether the
+  /// job was dead lettered.
+  pub async fn retry_dead_letter(&self, id: &str, delay: Duration) -> Result<bool, RedisError> {
+    let keys = vec![self.dead_letter_key(), self.queue_key(), self.attempts_key()];
+    let args = vec![id.into(), (delay.as_millis() as i64).into()];
+
+    let moved: i64 = utils::eval_script(&self.client, RETRY_DEAD_LETTER_SCRIPT, keys.into(), args).await?;
+    Ok(moved > 0)
+  }
+
+  /// Claim jobs as they become due, polling every `poll_interval_ms` when no jobs are due.
+  ///
+  /// Errors are returned on the stream, after which the queue will wait briefly and try again. Each call returns an
+  /// independent stream that stops claiming jobs when it's dropped. Jobs buffered in a dropped stream are not
+  /// acknowledged and will be delivered again after their visibility timeout.
+  pub fn jobs(&self) -> impl Stream<Item = Result<DelayedJob<C>, RedisError>> {
+    let (tx, rx) = channel(std::cmp::max(1, self.config.batch_size as usize));
+    let queue = self.clone();
+
+    let _ = tokio::spawn(async move {
+      let poll_interval = Duration::from_millis(queue.config.poll_interval_ms);
+
+      while !tx.is_closed() {
+        match queue.claim(queue.config.batch_size).await {
+          Ok(jobs) => {
+            let is_full = jobs.len() as u64 >= queue.config.batch_size;
+
+            for job in jobs.into_iter() {
+              if let Err(_) = tx.send(Ok(job)).await {
+                return;
+              }
+            }
+            if !is_full {
+              tokio::select! {
+                _ = sleep(poll_interval) => {},
+                _ = tx.closed() => return,
+              }
+            }
+          }
+          Err(e) => {
+            if let Err(_) = tx.send(Err(e)).await {
+              return;
+            }
+            tokio::select! {
+              _ = sleep(Duration::from_millis(ERROR_DELAY_MS)) => {},
+              _ = tx.closed() => return,
+            }
+          }
+        }
+      }
+    });
+
+    ReceiverStream::new(rx)
+  }
+}
+
+/// Listens for keys that expire, for callers that schedule work with key TTLs.
+///
+/// This subscribes to keyspace notifications with [KeyspaceNotifications] and only emits `expired` events. The
+/// server must be configured to send them, either with `notify_flags` in the provided config or by including `K`
+/// and `x` in `notify-keyspace-events`.
+///
+/// Expiration events are sent when the server deletes the key, which can happen some time after the key's TTL, and
+/// are not delivered while the listener is disconnected. Use a [DelayedQueue] if each job must run.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::clients::KeyspaceConfig;
+/// # use fred::delayed_queue::ExpirationListener;
+/// # use futures::StreamExt;
+/// # async fn example(client: RedisClient) -> Result<(), RedisError> {
+/// let config = KeyspaceConfig {
+///   notify_flags: Some("Kx".into()),
+///   key_pattern: "sessions:*".into(),
+///   ..KeyspaceConfig::default()
+/// };
+/// let listener = ExpirationListener::new(&client, config);
+/// let mut expired = listener.on_expired();
+/// let _ = listener.connect().await?;
+///
+/// while let Some(event) = expired.next().await {
+///   println!("{} expired", event.key);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExpirationListener {
+  notifications: KeyspaceNotifications,
+}
+
+impl ExpirationListener {
+  /// Create a new listener for the keys matching `key_pattern` in the provided config.
+  ///
+  /// The listener always subscribes to keyspace channels, so the `keyspace` and `keyevent` fields are ignored.
+  pub fn new(client: &RedisClient, config: KeyspaceConfig) -> Self {
+    let config = KeyspaceConfig {
+      keyspace: true,
+      keyevent: false,
+      ..config
+    };
+
+    ExpirationListener {
+      notifications: KeyspaceNotifications::new(client, config),
+    }
+  }
+
+  /// Read the keyspace notifications helper used by the listener.
+  pub fn notifications(&self) -> &KeyspaceNotifications {
+    &self.notifications
+  }
+
+  /// Listen for expired keys.
+  ///
+  /// This should be called before [connect](Self::connect) to avoid missing events.
+  pub fn on_expired(&self) -> impl Stream<Item = KeyspaceNotification> {
+    self
+      .notifications
+      .on_event()
+      .filter(|event| futures::future::ready(event.kind == KeyspaceEventKind::Expired))
+  }
+
+  /// Connect to each server and subscribe to the keyspace channels.
+  pub async fn connect(&self) -> Result<(), RedisError> {
+    self.notifications.connect().await
+  }
+
+  /// Close the connection to each server.
+  pub async fn quit(&self) -> Result<(), RedisError> {
+    self.notifications.quit().await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_claimed_jobs() {
+    let value = RedisValue::Array(vec![
+      "a".into(),
+      "foo".into(),
+      1.into(),
+      1000.into(),
+      "b".into(),
+      "bar".into(),
+      2.into(),
+      2000.into(),
+    ]);
+    let jobs = parse_claimed(value).unwrap();
+
+    assert_eq!(jobs, vec![
+      ("a".to_owned(), RedisValue::from("foo"), 1, 1000),
+      ("b".to_owned(), RedisValue::from("bar"), 2, 2000)
+    ]);
+    assert!(parse_claimed(RedisValue::Array(vec!["a".into()])).is_err());
+    assert!(parse_claimed(RedisValue::Null).unwrap().is_empty());
+  }
+}
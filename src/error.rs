@@ -235,6 +235,38 @@ impl From<Resp2Frame> for RedisError {
   }
 }
 
+#[cfg(feature = "prometheus-exporter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prometheus-exporter")))]
+impl From<prometheus::Error> for RedisError {
+  fn from(e: prometheus::Error) -> Self {
+    RedisError::new(RedisErrorKind::Config, format!("Prometheus error: {:?}", e))
+  }
+}
+
+#[cfg(feature = "opentelemetry-exporter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry-exporter")))]
+impl From<opentelemetry::metrics::MetricsError> for RedisError {
+  fn from(e: opentelemetry::metrics::MetricsError) -> Self {
+    RedisError::new(RedisErrorKind::Config, format!("OpenTelemetry error: {:?}", e))
+  }
+}
+
+#[cfg(feature = "serde-value")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde-value")))]
+impl serde::ser::Error for RedisError {
+  fn custom<T: Display>(msg: T) -> Self {
+    RedisError::new_parse(msg.to_string())
+  }
+}
+
+#[cfg(feature = "serde-value")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde-value")))]
+impl serde::de::Error for RedisError {
+  fn custom<T: Display>(msg: T) -> Self {
+    RedisError::new_parse(msg.to_string())
+  }
+}
+
 #[cfg(feature = "enable-tls")]
 #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
 impl From<native_tls::Error> for RedisError {
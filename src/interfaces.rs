@@ -7,6 +7,10 @@ use crate::types::{
   ShutdownFlags,
 };
 use crate::types::{PerformanceConfig, RespVersion};
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use crate::types::TlsConfig;
+#[cfg(feature = "credential-provider")]
+use crate::modules::credentials;
 use crate::utils;
 use futures::Stream;
 pub use redis_protocol::resp3::types::Frame as Resp3Frame;
@@ -168,7 +172,6 @@ where
 }
 
 /// Run a function and wrap the result in an `AsyncResult` trait object.
-#[cfg(feature = "subscriber-client")]
 pub(crate) fn wrap_async<F, Fut, T>(func: F) -> AsyncResult<T>
 where
   Fut: Future<Output = Result<T, RedisError>> + Send + 'static,
@@ -225,6 +228,19 @@ pub trait ClientLike: Unpin + Send + Sync + Sized {
     guard.performance = config;
   }
 
+  /// Replace the [TlsConfig](crate::types::TlsConfig) used by the client.
+  ///
+  /// Existing connections are not affected. New connections, including those created when the client reconnects,
+  /// will use the new TLS settings. This can be used to rotate client certificates before they expire.
+  ///
+  /// TLS cannot be disabled with this function. If called on a client that does not use TLS the client will use TLS
+  /// the next time it connects.
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "enable-tls", feature = "enable-rustls"))))]
+  fn update_tls_config(&self, config: TlsConfig) {
+    self.inner().config.write().tls = Some(config);
+  }
+
   /// Read the state of the underlying connection(s).
   ///
   /// If running against a cluster the underlying state will reflect the state of the least healthy connection, if any.
@@ -248,7 +264,13 @@ pub trait ClientLike: Unpin + Send + Sync + Sized {
     let inner = self.inner().clone();
 
     tokio::spawn(async move {
+      #[cfg(feature = "credential-provider")]
+      let refresh_task = credentials::spawn_refresh_task(&inner);
       let result = multiplexer_commands::init(&inner, policy).await;
+      #[cfg(feature = "credential-provider")]
+      if let Some(refresh_task) = refresh_task {
+        refresh_task.abort();
+      }
       if let Err(ref e) = result {
         multiplexer_utils::emit_connect_error(&inner, e);
       }
@@ -358,12 +380,12 @@ pub trait ClientLike: Unpin + Send + Sync + Sized {
 }
 
 pub use crate::commands::interfaces::{
-  acl::AclInterface, client::ClientInterface, cluster::ClusterInterface, config::ConfigInterface, geo::GeoInterface,
-  hashes::HashesInterface, hyperloglog::HyperloglogInterface, keys::KeysInterface, lists::ListInterface,
-  lua::LuaInterface, memory::MemoryInterface, metrics::MetricsInterface, pubsub::PubsubInterface,
-  server::AuthInterface, server::HeartbeatInterface, server::ServerInterface, sets::SetsInterface,
-  slowlog::SlowlogInterface, sorted_sets::SortedSetsInterface, streams::StreamsInterface,
-  transactions::TransactionInterface,
+  acl::AclInterface, bitmaps::BitmapInterface, client::ClientInterface, cluster::ClusterInterface,
+  config::ConfigInterface, functions::FunctionInterface, geo::GeoInterface, hashes::HashesInterface,
+  hyperloglog::HyperloglogInterface, keys::KeysInterface, lists::ListInterface, lua::LuaInterface,
+  memory::MemoryInterface, metrics::MetricsInterface, pubsub::PubsubInterface, server::AuthInterface,
+  server::HeartbeatInterface, server::ServerInterface, sets::SetsInterface, slowlog::SlowlogInterface,
+  sorted_sets::SortedSetsInterface, streams::StreamsInterface, transactions::TransactionInterface,
 };
 
 #[cfg(feature = "sentinel-client")]
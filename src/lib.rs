@@ -50,15 +50,21 @@ pub extern crate bytes_utils;
 #[cfg(feature = "serde-json")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serde-json")))]
 pub extern crate serde_json;
+#[cfg(feature = "enable-tls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
+pub extern crate native_tls;
+#[cfg(feature = "enable-rustls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "enable-rustls")))]
+pub extern crate rustls;
 
 #[macro_use]
 extern crate async_trait;
 #[macro_use]
 extern crate log;
 #[cfg(feature = "enable-tls")]
-extern crate native_tls;
-#[cfg(feature = "enable-tls")]
 extern crate tokio_native_tls;
+#[cfg(feature = "enable-rustls")]
+extern crate tokio_rustls;
 #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
 extern crate tracing;
 #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
@@ -76,10 +82,26 @@ mod utils;
 
 /// Redis client implementations.
 pub mod clients;
+/// Codecs used to encode and decode values stored on the server.
+#[cfg(feature = "codec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codec")))]
+pub mod codec;
+/// A queue of delayed jobs built on sorted sets.
+#[cfg(feature = "delayed-queue")]
+#[cfg_attr(docsrs, doc(cfg(feature = "delayed-queue")))]
+pub mod delayed_queue;
 /// Error structs returned by Redis commands.
 pub mod error;
 /// Traits that implement portions of the Redis interface.
 pub mod interfaces;
+/// A distributed lock built on the [Redlock](https://redis.io/topics/distlock) algorithm.
+#[cfg(feature = "redlock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redlock")))]
+pub mod lock;
+/// Rate limiters backed by Lua scripts.
+#[cfg(feature = "rate-limit")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rate-limit")))]
+pub mod rate_limit;
 /// An interface to run the `MONITOR` command.
 #[cfg(feature = "monitor")]
 #[cfg_attr(docsrs, doc(cfg(feature = "monitor")))]
@@ -108,6 +130,11 @@ pub mod util {
 
 pub use crate::modules::{globals, pool};
 
+/// An interface for mocking Redis commands.
+#[cfg(feature = "mocks")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mocks")))]
+pub use crate::modules::mocks;
+
 /// Convenience module to import a `RedisClient`, all possible interfaces, error types, and common argument types or return value types.
 pub mod prelude {
   pub use crate::clients::RedisClient;
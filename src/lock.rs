@@ -0,0 +1,563 @@
+use crate::error::{RedisError, RedisErrorKind};
+use crate::interfaces::{KeysInterface, LuaInterface};
+use crate::types::{Expiration, RedisKey, RedisValue, SetOptions};
+use crate::utils;
+use futures::future::join_all;
+use futures::Future;
+use parking_lot::RwLock;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch::{self, Receiver as WatchReceiver, Sender as WatchSender};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, sleep_until, timeout, Instant};
+
+/// Delete the key if it still contains the lock token.
+static UNLOCK_SCRIPT: &'static str =
+  "if redis.call('get', KEYS[1]) == ARGV[1] then return redis.call('del', KEYS[1]) else return 0 end";
+/// Reset the TTL on the key if it still contains the lock token.
+static EXTEND_SCRIPT: &'static str =
+  "if redis.call('get', KEYS[1]) == ARGV[1] then return redis.call('pexpire', KEYS[1], ARGV[2]) else return 0 end";
+/// The constant clock drift, in milliseconds, added to the drift computed from the TTL.
+const BASE_CLOCK_DRIFT_MS: u64 = 2;
+/// The minimum lock TTL, in milliseconds. Shorter TTLs never leave any validity time after accounting for clock
+/// drift.
+const MIN_TTL_MS: u64 = BASE_CLOCK_DRIFT_MS + 1;
+
+/// Check that the lock TTL is long enough to acquire and renew a lock.
+fn check_ttl(ttl_ms: u64) -> Result<(), RedisError> {
+  if ttl_ms < MIN_TTL_MS {
+    Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      format!("Lock TTL must be at least {} ms.", MIN_TTL_MS),
+    ))
+  } else {
+    Ok(())
+  }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LockState {
+  Held,
+  Lost,
+  Released,
+}
+
+/// Configuration options for a [Redlock](crate::lock::Redlock).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockConfig {
+  /// The TTL of each lock, in milliseconds. This must be at least 3 ms.
+  ///
+  /// Default: 30 sec
+  pub ttl_ms: u64,
+  /// The number of times to retry acquiring a lock before giving up.
+  ///
+  /// Default: 3
+  pub retry_count: u32,
+  /// The amount of time to wait between retries, in milliseconds.
+  ///
+  /// Default: 200 ms
+  pub retry_delay_ms: u64,
+  /// The maximum amount of random jitter to add to the retry delay, in milliseconds.
+  ///
+  /// Default: 100 ms
+  pub retry_jitter_ms: u32,
+  /// The factor of the TTL used to account for clock drift between servers.
+  ///
+  /// Default: 0.01
+  pub drift_factor: f64,
+  /// The amount of time to wait for each instance to respond, in milliseconds.
+  ///
+  /// This should be small relative to the TTL so that an unavailable instance does not consume the lock validity time.
+  ///
+  /// Default: 250 ms
+  pub instance_timeout_ms: u64,
+  /// Whether or not to automatically extend the lock in a background task until it is released.
+  ///
+  /// The lock is extended after a third of the TTL has elapsed.
+  ///
+  /// Default: true
+  pub auto_renew: bool,
+}
+
+impl Default for LockConfig {
+  fn default() -> Self {
+    LockConfig {
+      ttl_ms: 30_000,
+      retry_count: 3,
+      retry_delay_ms: 200,
+      retry_jitter_ms: 100,
+      drift_factor: 0.01,
+      instance_timeout_ms: 250,
+      auto_renew: true,
+    }
+  }
+}
+
+impl LockConfig {
+  fn drift(&self, ttl_ms: u64) -> Duration {
+    Duration::from_millis((ttl_ms as f64 * self.drift_factor) as u64 + BASE_CLOCK_DRIFT_MS)
+  }
+
+  /// Compute the remaining time for which a lock is valid after `elapsed` was spent acquiring it.
+  fn validity(&self, ttl_ms: u64, elapsed: Duration) -> Option<Duration> {
+    Duration::from_millis(ttl_ms)
+      .checked_sub(elapsed)
+      .and_then(|remaining| remaining.checked_sub(self.drift(ttl_ms)))
+      .filter(|validity| *validity > Duration::from_millis(0))
+  }
+}
+
+/// A distributed lock manager that implements the [Redlock](https://redis.io/topics/distlock) algorithm.
+///
+/// The instances should be independent servers (or clusters), each of which can be a `RedisClient` or a `RedisPool`.
+/// A lock is acquired when it is set on a majority of the instances within the lock's validity time. A single instance
+/// can be used for a simpler lock without any fault tolerance.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::lock::{LockConfig, Redlock};
+/// # async fn example(clients: Vec<RedisClient>) -> Result<(), RedisError> {
+/// let redlock = Redlock::new(clients, LockConfig::default())?;
+///
+/// if let Some(lock) = redlock.lock("resource").await? {
+///   tokio::select! {
+///     _ = lock.on_lost() => println!("Lost lock on resource."),
+///     _ = tokio::time::sleep(std::time::Duration::from_secs(60)) => println!("Finished work."),
+///   };
+///
+///   // or drop the lock to release it in a background task
+///   let _ = lock.release().await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Redlock<C>
+where
+  C: KeysInterface + LuaInterface + Clone + 'static,
+{
+  instances: Arc<Vec<C>>,
+  config: Arc<LockConfig>,
+}
+
+impl<C> Clone for Redlock<C>
+where
+  C: KeysInterface + LuaInterface + Clone + 'static,
+{
+  fn clone(&self) -> Self {
+    Redlock {
+      instances: self.instances.clone(),
+      config: self.config.clone(),
+    }
+  }
+}
+
+impl<C> fmt::Debug for Redlock<C>
+where
+  C: KeysInterface + LuaInterface + Clone + 'static,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Redlock")
+      .field("instances", &self.instances.len())
+      .field("config", &self.config)
+      .finish()
+  }
+}
+
+impl<C> Redlock<C>
+where
+  C: KeysInterface + LuaInterface + Clone + 'static,
+{
+  /// Create a new lock manager with the provided instances.
+  pub fn new(instances: Vec<C>, config: LockConfig) -> Result<Self, RedisError> {
+    if instances.is_empty() {
+      return Err(RedisError::new(
+        RedisErrorKind::Config,
+        "At least one instance is required.",
+      ));
+    }
+    check_ttl(config.ttl_ms)?;
+
+    Ok(Redlock {
+      instances: Arc::new(instances),
+      config: Arc::new(config),
+    })
+  }
+
+  /// Read the lock config.
+  pub fn config(&self) -> &LockConfig {
+    &self.config
+  }
+
+  /// Read the number of instances that must agree for a lock to be acquired.
+  pub fn quorum(&self) -> usize {
+    self.instances.len() / 2 + 1
+  }
+
+  /// Acquire a lock on `key` with the configured TTL.
+  ///
+  /// Returns `None` if the lock could not be acquired after retrying. Errors from individual instances are treated as
+  /// a failure to acquire the lock on that instance, but an error is returned if every instance failed on the last
+  /// attempt.
+  pub async fn lock<K>(&self, key: K) -> Result<Option<RedisLock<C>>, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    self.lock_with_ttl(key, self.config.ttl_ms).await
+  }
+
+  /// Acquire a lock on `key` with the provided TTL, in milliseconds.
+  ///
+  /// See [lock](Self::lock) for more information.
+  pub async fn lock_with_ttl<K>(&self, key: K, ttl_ms: u64) -> Result<Option<RedisLock<C>>, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    check_ttl(ttl_ms)?;
+    let key = key.into();
+    let mut last_error = None;
+
+    for attempt in 0 .. self.config.retry_count + 1 {
+      if attempt > 0 {
+        let delay = if self.config.retry_jitter_ms > 0 {
+          utils::add_jitter(self.config.retry_delay_ms, self.config.retry_jitter_ms)
+        } else {
+          self.config.retry_delay_ms
+        };
+        sleep(Duration::from_millis(delay)).await;
+      }
+
+      let token = utils::random_string(32);
+      let start = Instant::now();
+      let (acquired, error) = self.set_token(&key, &token, ttl_ms).await;
+
+      if acquired >= self.quorum() {
+        if let Some(validity) = self.config.validity(ttl_ms, start.elapsed()) {
+          return Ok(Some(RedisLock::new(self.clone(), key, token, ttl_ms, start + validity)));
+        }
+      }
+
+      let _ = self.unlock(&key, &token).await;
+      last_error = error;
+    }
+
+    match last_error {
+      Some(error) => Err(error),
+      None => Ok(None),
+    }
+  }
+
+  /// Run the function on each instance with the configured timeout, returning the number of instances that returned
+  /// `true` and the last error if every instance failed.
+  async fn quorum_call<F, Fut>(&self, func: F) -> (usize, Option<RedisError>)
+  where
+    F: Fn(&C) -> Fut,
+    Fut: Future<Output = Result<bool, RedisError>>,
+  {
+    let instance_timeout = Duration::from_millis(self.config.instance_timeout_ms);
+    let results = join_all(self.instances.iter().map(|instance| timeout(instance_timeout, func(instance)))).await;
+
+    let mut successes = 0;
+    let mut error = None;
+    let mut errors = 0;
+    for result in results.into_iter() {
+      match result {
+        Ok(Ok(true)) => successes += 1,
+        Ok(Ok(false)) => {}
+        Ok(Err(e)) => {
+          errors += 1;
+          error = Some(e);
+        }
+        Err(_) => {
+          errors += 1;
+          error = Some(RedisError::new(RedisErrorKind::Timeout, "Timed out waiting on lock instance."));
+        }
+      };
+    }
+
+    if errors == self.instances.len() {
+      (successes, error)
+    } else {
+      (successes, None)
+    }
+  }
+
+  async fn set_token(&self, key: &RedisKey, token: &str, ttl_ms: u64) -> (usize, Option<RedisError>) {
+    self
+      .quorum_call(|instance| {
+        let set = instance.set::<RedisValue, _, _>(
+          key,
+          token,
+          Some(Expiration::PX(ttl_ms as i64)),
+          Some(SetOptions::NX),
+          false,
+        );
+        async move { set.await.map(|value| value.is_ok()) }
+      })
+      .await
+  }
+
+  async fn extend_token(&self, key: &RedisKey, token: &str, ttl_ms: u64) -> (usize, Option<RedisError>) {
+    let args: Vec<RedisValue> = vec![token.into(), (ttl_ms as i64).into()];
+    self
+      .quorum_call(|instance| {
+        let (instance, key, args) = (instance.clone(), key.clone(), args.clone());
+        async move {
+          let result: i64 = utils::eval_script(&instance, EXTEND_SCRIPT, key.into(), args).await?;
+          Ok(result > 0)
+        }
+      })
+      .await
+  }
+
+  async fn unlock(&self, key: &RedisKey, token: &str) -> (usize, Option<RedisError>) {
+    let args: Vec<RedisValue> = vec![token.into()];
+    self
+      .quorum_call(|instance| {
+        let (instance, key, args) = (instance.clone(), key.clone(), args.clone());
+        async move {
+          let result: i64 = utils::eval_script(&instance, UNLOCK_SCRIPT, key.into(), args).await?;
+          Ok(result > 0)
+        }
+      })
+      .await
+  }
+}
+
+/// A lock acquired with a [Redlock](crate::lock::Redlock).
+///
+/// The lock is released in a background task when dropped. Callers should use [release](Self::release) instead when
+/// they need to know whether the lock was still held.
+pub struct RedisLock<C>
+where
+  C: KeysInterface + LuaInterface + Clone + 'static,
+{
+  redlock: Redlock<C>,
+  key: RedisKey,
+  token: Arc<String>,
+  ttl_ms: u64,
+  expiration: Arc<RwLock<Instant>>,
+  state_tx: Arc<WatchSender<LockState>>,
+  state_rx: WatchReceiver<LockState>,
+  renew_task: Option<JoinHandle<()>>,
+  released: bool,
+}
+
+impl<C> fmt::Debug for RedisLock<C>
+where
+  C: KeysInterface + LuaInterface + Clone + 'static,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("RedisLock")
+      .field("key", &self.key)
+      .field("ttl_ms", &self.ttl_ms)
+      .field("lost", &self.is_lost())
+      .finish()
+  }
+}
+
+impl<C> RedisLock<C>
+where
+  C: KeysInterface + LuaInterface + Clone + 'static,
+{
+  fn new(redlock: Redlock<C>, key: RedisKey, token: String, ttl_ms: u64, expiration: Instant) -> Self {
+    let (state_tx, state_rx) = watch::channel(LockState::Held);
+    let mut lock = RedisLock {
+      redlock,
+      key,
+      token: Arc::new(token),
+      ttl_ms,
+      expiration: Arc::new(RwLock::new(expiration)),
+      state_tx: Arc::new(state_tx),
+      state_rx,
+      renew_task: None,
+      released: false,
+    };
+
+    if lock.redlock.config.auto_renew {
+      lock.renew_task = Some(lock.spawn_renew_task());
+    }
+    lock
+  }
+
+  fn spawn_renew_task(&self) -> JoinHandle<()> {
+    let redlock = self.redlock.clone();
+    let key = self.key.clone();
+    let token = self.token.clone();
+    let ttl_ms = self.ttl_ms;
+    let expiration = self.expiration.clone();
+    let state_tx = self.state_tx.clone();
+    let interval = Duration::from_millis(ttl_ms / 3);
+
+    tokio::spawn(async move {
+      loop {
+        sleep(interval).await;
+
+        let start = Instant::now();
+        let (extended, _) = redlock.extend_token(&key, &token, ttl_ms).await;
+        match redlock.config.validity(ttl_ms, start.elapsed()) {
+          Some(validity) if extended >= redlock.quorum() => {
+            *expiration.write() = start + validity;
+          }
+          _ => {
+            warn!("Failed to extend lock on {:?}.", key.as_str_lossy());
+            let _ = state_tx.send(LockState::Lost);
+            break;
+          }
+        }
+      }
+    })
+  }
+
+  /// Read the locked key.
+  pub fn key(&self) -> &RedisKey {
+    &self.key
+  }
+
+  /// Read the random token stored in the locked key.
+  pub fn token(&self) -> &str {
+    &self.token
+  }
+
+  /// Read the time at which the lock will expire, accounting for clock drift, unless it is extended.
+  pub fn expiration(&self) -> Instant {
+    *self.expiration.read()
+  }
+
+  /// Whether or not the lock was lost, either because it expired or could not be extended.
+  pub fn is_lost(&self) -> bool {
+    match *self.state_rx.borrow() {
+      LockState::Held => Instant::now() >= self.expiration(),
+      LockState::Lost => true,
+      LockState::Released => false,
+    }
+  }
+
+  /// Wait until the lock is lost, either because it expired or could not be extended.
+  ///
+  /// The returned future does not resolve if the lock is released.
+  pub fn on_lost(&self) -> impl Future<Output = ()> + Send + 'static {
+    let mut state_rx = self.state_rx.clone();
+    let expiration = self.expiration.clone();
+
+    async move {
+      loop {
+        let state = *state_rx.borrow();
+        match state {
+          LockState::Lost => return,
+          LockState::Released => futures::future::pending::<()>().await,
+          LockState::Held => {}
+        };
+
+        let deadline = *expiration.read();
+        if Instant::now() >= deadline {
+          return;
+        }
+
+        tokio::select! {
+          changed = state_rx.changed() => {
+            if changed.is_err() {
+              futures::future::pending::<()>().await;
+            }
+          },
+          _ = sleep_until(deadline) => {}
+        };
+      }
+    }
+  }
+
+  /// Extend the lock with the provided TTL, in milliseconds, returning whether the lock was extended.
+  pub async fn extend(&self, ttl_ms: u64) -> Result<bool, RedisError> {
+    check_ttl(ttl_ms)?;
+    let start = Instant::now();
+    let (extended, error) = self.redlock.extend_token(&self.key, &self.token, ttl_ms).await;
+    if let Some(error) = error {
+      return Err(error);
+    }
+
+    match self.redlock.config.validity(ttl_ms, start.elapsed()) {
+      Some(validity) if extended >= self.redlock.quorum() => {
+        *self.expiration.write() = start + validity;
+        Ok(true)
+      }
+      _ => Ok(false),
+    }
+  }
+
+  /// Release the lock, returning whether it was still held on a majority of the instances.
+  pub async fn release(mut self) -> Result<bool, RedisError> {
+    self.released = true;
+    if let Some(jh) = self.renew_task.take() {
+      jh.abort();
+    }
+    let _ = self.state_tx.send(LockState::Released);
+
+    let (released, error) = self.redlock.unlock(&self.key, &self.token).await;
+    match error {
+      Some(error) => Err(error),
+      None => Ok(released >= self.redlock.quorum()),
+    }
+  }
+}
+
+impl<C> Drop for RedisLock<C>
+where
+  C: KeysInterface + LuaInterface + Clone + 'static,
+{
+  fn drop(&mut self) {
+    if let Some(jh) = self.renew_task.take() {
+      jh.abort();
+    }
+    if self.released {
+      return;
+    }
+    let _ = self.state_tx.send(LockState::Released);
+
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+      let redlock = self.redlock.clone();
+      let key = self.key.clone();
+      let token = self.token.clone();
+
+      let _ = handle.spawn(async move {
+        let _ = redlock.unlock(&key, &token).await;
+      });
+    } else {
+      warn!("Cannot release lock on {:?} outside of a Tokio runtime.", self.key.as_str_lossy());
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::clients::RedisClient;
+  use crate::types::RedisConfig;
+
+  #[test]
+  fn should_compute_validity_with_clock_drift() {
+    let config = LockConfig::default();
+
+    assert_eq!(
+      config.validity(10_000, Duration::from_millis(100)),
+      Some(Duration::from_millis(9_798))
+    );
+    assert_eq!(config.validity(10_000, Duration::from_millis(9_900)), None);
+    assert_eq!(config.validity(10_000, Duration::from_millis(11_000)), None);
+  }
+
+  #[tokio::test]
+  async fn should_reject_short_ttl() {
+    let client = RedisClient::new(RedisConfig::default());
+    let config = |ttl_ms| LockConfig {
+      ttl_ms,
+      ..LockConfig::default()
+    };
+
+    assert!(Redlock::new(vec![client.clone()], config(0)).is_err());
+    assert!(Redlock::new(vec![client.clone()], config(2)).is_err());
+
+    let redlock = Redlock::new(vec![client], config(3)).unwrap();
+    let error = redlock.lock_with_ttl("foo", 0).await.unwrap_err();
+    assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+  }
+}
@@ -62,6 +62,23 @@ macro_rules! fspan (
   }
 );
 
+/// Call the metrics exporter, if configured, with the exporter and the client ID.
+#[cfg(feature = "metrics")]
+macro_rules! _export (
+  ($inner:ident, |$exporter:ident, $client:ident| $body:expr) => {
+    if let Some(ref $exporter) = $inner.exporter {
+      let $client: &str = &$inner.id;
+      $body;
+    }
+  }
+);
+
+/// Metrics exporters are only available with the `metrics` feature.
+#[cfg(not(feature = "metrics"))]
+macro_rules! _export (
+  ($inner:ident, |$exporter:ident, $client:ident| $body:expr) => {};
+);
+
 /// Async try! for `AsyncResult`. This is rarely used on its own, but rather as a part of try_into!.
 macro_rules! atry (
   ($expr:expr) => {
@@ -147,3 +164,15 @@ macro_rules! try_into (
   );
   // add to this as needed
 );
+
+/// Prefix a Lua script with code that enables effects replication and reads the server time into `now`, in
+/// milliseconds.
+#[cfg(any(feature = "rate-limit", feature = "delayed-queue"))]
+macro_rules! lua_with_now_ms (
+  ($body:literal) => (concat!(
+    "redis.replicate_commands()\n",
+    "local time = redis.call('time')\n",
+    "local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)\n",
+    $body
+  ));
+);
@@ -2,19 +2,25 @@ use crate::error::RedisError;
 use crate::modules::inner::RedisClientInner;
 use crate::multiplexer::ConnectionIDs;
 use crate::protocol::connection;
-use crate::protocol::connection::{FramedTcp, FramedTls, RedisTransport};
+use crate::protocol::connection::{FramedTcp, FramedTls, FramedUnix, RedisTransport};
 use crate::protocol::types::{ProtocolFrame, RedisCommand};
 use crate::protocol::utils as protocol_utils;
 use crate::types::Resolve;
 use redis_protocol::resp3::types::Frame as Resp3Frame;
 use std::sync::Arc;
 
-async fn create_transport(
+/// Create a new transport to the provided server, which is either a `host:port` string or the path to a Unix socket.
+pub async fn create_transport(
   inner: &Arc<RedisClientInner>,
-  host: &str,
-  port: u16,
+  server: &Arc<String>,
   tls: bool,
 ) -> Result<RedisTransport, RedisError> {
+  if let Some(path) = protocol_utils::read_unix_path(&inner.config) {
+    let transport = connection::create_authenticated_connection_unix(&path, inner).await?;
+    return Ok(RedisTransport::Unix(transport));
+  }
+
+  let (host, port) = protocol_utils::server_to_parts(server)?;
   let addr = inner.resolver.resolve(host.to_owned(), port).await?;
 
   let transport = if tls {
@@ -44,6 +50,14 @@ fn map_tls_response(
     .map_err(|(e, t)| (e, RedisTransport::Tls(t)))
 }
 
+fn map_unix_response(
+  result: Result<(ProtocolFrame, FramedUnix), (RedisError, FramedUnix)>,
+) -> Result<(Resp3Frame, RedisTransport), (RedisError, RedisTransport)> {
+  result
+    .map(|(f, t)| (f.into_resp3(), RedisTransport::Unix(t)))
+    .map_err(|(e, t)| (e, RedisTransport::Unix(t)))
+}
+
 /// A struct that allows for a backchannel to the server(s) even when the connections are blocked.
 #[derive(Default)]
 pub struct Backchannel {
@@ -102,8 +116,7 @@ impl Backchannel {
   pub async fn take_or_create_transport(
     &mut self,
     inner: &Arc<RedisClientInner>,
-    host: &str,
-    port: u16,
+    server: &Arc<String>,
     uses_tls: bool,
     use_blocked: bool,
   ) -> Result<(RedisTransport, Option<Arc<String>>, bool), RedisError> {
@@ -111,15 +124,15 @@ impl Backchannel {
       if let Some((transport, server)) = self.transport.take() {
         Ok((transport, Some(server), false))
       } else {
-        _debug!(inner, "Creating backchannel to {}:{}", host, port);
-        let transport = create_transport(inner, host, port, uses_tls).await?;
+        _debug!(inner, "Creating backchannel to {}", server);
+        let transport = create_transport(inner, server, uses_tls).await?;
         Ok((transport, None, true))
       }
     } else {
       let _ = self.transport.take();
-      _debug!(inner, "Creating backchannel to {}:{}", host, port);
+      _debug!(inner, "Creating backchannel to {}", server);
 
-      let transport = create_transport(inner, host, port, uses_tls).await?;
+      let transport = create_transport(inner, server, uses_tls).await?;
       Ok((transport, None, true))
     }
   }
@@ -137,10 +150,9 @@ impl Backchannel {
   ) -> Result<Resp3Frame, RedisError> {
     let is_resp3 = inner.is_resp3();
     let uses_tls = inner.config.read().uses_tls();
-    let (host, port) = protocol_utils::server_to_parts(server)?;
 
     let (transport, _server, try_once) = self
-      .take_or_create_transport(inner, host, port, uses_tls, use_blocked)
+      .take_or_create_transport(inner, server, uses_tls, use_blocked)
       .await?;
     let server = _server.unwrap_or(server.clone());
     let result = match transport {
@@ -150,6 +162,9 @@ impl Backchannel {
       RedisTransport::Tls(transport) => {
         map_tls_response(connection::request_response_safe(transport, &command, is_resp3).await)
       }
+      RedisTransport::Unix(transport) => {
+        map_unix_response(connection::request_response_safe(transport, &command, is_resp3).await)
+      }
     };
 
     match result {
@@ -165,7 +180,7 @@ impl Backchannel {
         } else {
           // need to avoid async recursion
           let (transport, _, _) = self
-            .take_or_create_transport(inner, host, port, uses_tls, use_blocked)
+            .take_or_create_transport(inner, &server, uses_tls, use_blocked)
             .await?;
           let result = match transport {
             RedisTransport::Tcp(transport) => {
@@ -174,6 +189,9 @@ impl Backchannel {
             RedisTransport::Tls(transport) => {
               map_tls_response(connection::request_response_safe(transport, &command, is_resp3).await)
             }
+            RedisTransport::Unix(transport) => {
+              map_unix_response(connection::request_response_safe(transport, &command, is_resp3).await)
+            }
           };
 
           match result {
@@ -0,0 +1,734 @@
+use crate::error::{RedisError, RedisErrorKind};
+use crate::modules::backchannel;
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::codec::RedisCodec;
+use crate::protocol::connection;
+use crate::protocol::connection::RedisTransport;
+use crate::protocol::types::{RedisCommand, RedisCommandKind};
+use crate::protocol::utils as protocol_utils;
+use crate::types::{CacheConfig, CustomCommand, RedisValue, TrackingMode};
+use bytes::Bytes;
+use futures::stream::StreamExt;
+use parking_lot::Mutex;
+use redis_protocol::redis_keyslot;
+use redis_protocol::resp3::types::Frame as Resp3Frame;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::task::JoinHandle;
+use tokio_util::codec::Framed;
+
+/// The channel used by the server to send invalidation messages to a redirect connection.
+pub const INVALIDATION_CHANNEL: &'static str = "__redis__:invalidate";
+const INVALIDATE_PREFIX: &'static str = "invalidate";
+const PUBSUB_MESSAGE: &'static str = "message";
+
+/// An invalidation message sent by the server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Invalidation {
+  /// The provided keys were modified.
+  Keys(Vec<Bytes>),
+  /// Every key was modified, usually via `FLUSHALL` or `FLUSHDB`.
+  All,
+}
+
+/// A value stored in the cache along with the time at which it was stored.
+struct CachedValue {
+  value: RedisValue,
+  inserted: Instant,
+}
+
+/// The cached values associated with one key.
+struct CacheEntry {
+  /// The unique ID of the entry, used to detect stale eviction records.
+  id: u64,
+  /// The value returned by `GET`, if known.
+  value: Option<CachedValue>,
+  /// The hash fields returned by `HGET`.
+  fields: HashMap<Bytes, CachedValue>,
+}
+
+/// A read that was sent to the server but has not yet been added to the cache.
+struct PendingRead {
+  keys: Vec<Bytes>,
+  invalidated: bool,
+}
+
+#[derive(Default)]
+struct CacheState {
+  entries: HashMap<Bytes, CacheEntry>,
+  /// The order in which keys were added to the cache, used to evict the oldest keys first.
+  order: VecDeque<(Bytes, u64)>,
+  pending: HashMap<u64, PendingRead>,
+  next_id: u64,
+}
+
+impl CacheState {
+  fn next_id(&mut self) -> u64 {
+    self.next_id = self.next_id.wrapping_add(1);
+    self.next_id
+  }
+
+  fn invalidate(&mut self, key: &[u8]) {
+    self.entries.remove(key);
+
+    for pending in self.pending.values_mut() {
+      if pending.keys.iter().any(|k| k == key) {
+        pending.invalidated = true;
+      }
+    }
+  }
+
+  fn clear(&mut self) {
+    self.entries.clear();
+    self.order.clear();
+
+    for pending in self.pending.values_mut() {
+      pending.invalidated = true;
+    }
+  }
+
+  fn entry(&mut self, key: Bytes, max_keys: usize) -> &mut CacheEntry {
+    if !self.entries.contains_key(&key) {
+      let id = self.next_id();
+      self.order.push_back((key.clone(), id));
+      self.entries.insert(key.clone(), CacheEntry {
+        id,
+        value: None,
+        fields: HashMap::new(),
+      });
+      self.evict(max_keys);
+    }
+
+    // the key that was just added is never evicted since it's the newest key
+    self.entries.get_mut(&key).unwrap()
+  }
+
+  fn evict(&mut self, max_keys: usize) {
+    while self.entries.len() > max_keys {
+      match self.order.pop_front() {
+        Some((key, id)) => {
+          if self.entries.get(&key).map(|e| e.id == id).unwrap_or(false) {
+            self.entries.remove(&key);
+          }
+        }
+        None => break,
+      }
+    }
+
+    // the order queue can contain records for keys that were invalidated, so compact it if it grows too large
+    if self.order.len() > max_keys.saturating_mul(2) {
+      let entries = &self.entries;
+      self
+        .order
+        .retain(|(key, id)| entries.get(key).map(|e| e.id == *id).unwrap_or(false));
+    }
+  }
+}
+
+/// A connection that receives invalidation messages on behalf of another connection when using RESP2.
+struct Redirect {
+  id: i64,
+  task: JoinHandle<()>,
+}
+
+/// A reservation for the values returned by a read command, created before the command is sent to the server.
+///
+/// Values can only be added to the cache via a reservation, and are dropped if any of the reserved keys were
+/// invalidated while the command was in flight.
+pub struct Reservation<'a> {
+  cache: &'a ClientCache,
+  id: u64,
+}
+
+impl<'a> Reservation<'a> {
+  fn is_invalidated(state: &CacheState, id: u64) -> bool {
+    state.pending.get(&id).map(|p| p.invalidated).unwrap_or(true)
+  }
+
+  /// Cache the value returned by `GET` or `MGET`.
+  pub fn insert(&self, key: Bytes, value: RedisValue) {
+    if value.is_queued() {
+      return;
+    }
+
+    let mut state = self.cache.state.lock();
+    if Self::is_invalidated(&state, self.id) {
+      return;
+    }
+
+    let entry = state.entry(key, self.cache.config.max_keys);
+    entry.value = Some(CachedValue {
+      value,
+      inserted: Instant::now(),
+    });
+  }
+
+  /// Cache the value returned by `HGET`.
+  pub fn insert_field(&self, key: Bytes, field: Bytes, value: RedisValue) {
+    if value.is_queued() {
+      return;
+    }
+
+    let mut state = self.cache.state.lock();
+    if Self::is_invalidated(&state, self.id) {
+      return;
+    }
+
+    let entry = state.entry(key, self.cache.config.max_keys);
+    entry.fields.insert(field, CachedValue {
+      value,
+      inserted: Instant::now(),
+    });
+  }
+}
+
+impl<'a> Drop for Reservation<'a> {
+  fn drop(&mut self) {
+    self.cache.state.lock().pending.remove(&self.id);
+  }
+}
+
+/// A local cache of values read from the server, kept up to date via `CLIENT TRACKING` invalidation messages.
+pub struct ClientCache {
+  config: CacheConfig,
+  state: Mutex<CacheState>,
+  /// Whether or not tracking is enabled on the current connection(s).
+  enabled: AtomicBool,
+  /// Servers whose connections can no longer receive invalidation messages.
+  broken: Mutex<BTreeSet<Arc<String>>>,
+  /// Redirect connections, by server, used with RESP2.
+  redirects: Mutex<HashMap<Arc<String>, Redirect>>,
+  /// The number of values read from the cache instead of the server.
+  hits: AtomicUsize,
+}
+
+impl ClientCache {
+  pub fn new(config: CacheConfig) -> Self {
+    ClientCache {
+      config,
+      state: Mutex::new(CacheState::default()),
+      enabled: AtomicBool::new(false),
+      broken: Mutex::new(BTreeSet::new()),
+      redirects: Mutex::new(HashMap::new()),
+      hits: AtomicUsize::new(0),
+    }
+  }
+
+  pub fn config(&self) -> &CacheConfig {
+    &self.config
+  }
+
+  /// Whether or not values can be read from or written to the cache.
+  pub fn is_enabled(&self) -> bool {
+    self.enabled.load(Ordering::Acquire) && self.broken.lock().is_empty()
+  }
+
+  /// Mark the cache as enabled after tracking was enabled on the connection to `server`.
+  pub fn set_enabled(&self, server: &Arc<String>) {
+    self.broken.lock().remove(server);
+    self.enabled.store(true, Ordering::Release);
+  }
+
+  /// Disable the cache until tracking is enabled again on the connection to `server`.
+  pub fn set_broken(&self, server: &Arc<String>) {
+    self.broken.lock().insert(server.clone());
+    self.clear();
+  }
+
+  pub fn len(&self) -> usize {
+    self.state.lock().entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Read the number of values read from the cache instead of the server.
+  pub fn hits(&self) -> usize {
+    self.hits.load(Ordering::Acquire)
+  }
+
+  fn is_expired(&self, value: &CachedValue) -> bool {
+    self.config.ttl_ms > 0 && value.inserted.elapsed() > Duration::from_millis(self.config.ttl_ms)
+  }
+
+  /// Read the value for `key` set by a previous `GET` or `MGET`.
+  pub fn get(&self, key: &[u8]) -> Option<RedisValue> {
+    let value = self.read_value(key)?;
+    self.hits.fetch_add(1, Ordering::AcqRel);
+    Some(value)
+  }
+
+  fn read_value(&self, key: &[u8]) -> Option<RedisValue> {
+    let mut state = self.state.lock();
+    let (value, expired) = match state.entries.get(key).and_then(|e| e.value.as_ref()) {
+      Some(cached) => (cached.value.clone(), self.is_expired(cached)),
+      None => return None,
+    };
+
+    if expired {
+      if let Some(entry) = state.entries.get_mut(key) {
+        entry.value = None;
+      }
+      None
+    } else {
+      Some(value)
+    }
+  }
+
+  /// Read the values for all of the provided keys, returning `None` if any of the keys are missing.
+  pub fn mget(&self, keys: &[Bytes]) -> Option<Vec<RedisValue>> {
+    let mut values = Vec::with_capacity(keys.len());
+    for key in keys.iter() {
+      values.push(self.read_value(key)?);
+    }
+
+    self.hits.fetch_add(values.len(), Ordering::AcqRel);
+    Some(values)
+  }
+
+  /// Read the value of the hash `field` stored at `key` set by a previous `HGET`.
+  pub fn hget(&self, key: &[u8], field: &[u8]) -> Option<RedisValue> {
+    let mut state = self.state.lock();
+    let (value, expired) = match state.entries.get(key).and_then(|e| e.fields.get(field)) {
+      Some(cached) => (cached.value.clone(), self.is_expired(cached)),
+      None => return None,
+    };
+
+    if expired {
+      if let Some(entry) = state.entries.get_mut(key) {
+        entry.fields.remove(field);
+      }
+      None
+    } else {
+      self.hits.fetch_add(1, Ordering::AcqRel);
+      Some(value)
+    }
+  }
+
+  /// Reserve the provided keys before sending a read command to the server.
+  pub fn reserve(&self, keys: Vec<Bytes>) -> Reservation<'_> {
+    let mut state = self.state.lock();
+    let id = state.next_id();
+    state.pending.insert(id, PendingRead {
+      keys,
+      invalidated: false,
+    });
+
+    Reservation { cache: self, id }
+  }
+
+  pub fn invalidate(&self, invalidation: Invalidation) {
+    let mut state = self.state.lock();
+
+    match invalidation {
+      Invalidation::Keys(keys) => {
+        for key in keys.iter() {
+          state.invalidate(key);
+        }
+      }
+      Invalidation::All => state.clear(),
+    }
+  }
+
+  pub fn clear(&self) {
+    self.state.lock().clear();
+  }
+
+  /// Close any redirect connections.
+  pub fn close(&self) {
+    self.enabled.store(false, Ordering::Release);
+    for (_, redirect) in self.redirects.lock().drain() {
+      redirect.task.abort();
+    }
+    self.clear();
+  }
+
+  fn redirect_id(&self, server: &Arc<String>) -> Option<i64> {
+    self.redirects.lock().get(server).map(|r| r.id)
+  }
+}
+
+/// Read the cache if it's enabled and the client is not inside a transaction.
+pub fn read_cache(inner: &Arc<RedisClientInner>) -> Option<&ClientCache> {
+  match inner.cache {
+    Some(ref cache) => {
+      if cache.is_enabled() && inner.multi_block.read().is_none() {
+        Some(cache)
+      } else {
+        None
+      }
+    }
+    None => None,
+  }
+}
+
+/// Whether or not the command should be preceded by `CLIENT CACHING YES`.
+pub fn should_opt_in(inner: &Arc<RedisClientInner>, command: &RedisCommand) -> bool {
+  let is_cached_read = match command.kind {
+    RedisCommandKind::Get | RedisCommandKind::HGet | RedisCommandKind::Mget => true,
+    _ => false,
+  };
+
+  is_cached_read
+    && read_cache(inner)
+      .map(|cache| cache.config.mode == TrackingMode::OptIn)
+      .unwrap_or(false)
+}
+
+/// Create the `CLIENT CACHING YES` command that should be sent before the provided command.
+pub fn opt_in_command(command: &RedisCommand) -> RedisCommand {
+  let hash_slot = command.extract_key().map(|key| redis_keyslot(key));
+  let kind = RedisCommandKind::_Custom(CustomCommand::new_static("CLIENT CACHING", hash_slot, false));
+
+  RedisCommand::new(kind, vec![static_val!("YES")], None)
+}
+
+/// Parse an invalidation message in either the RESP3 push format or the RESP2 pubsub format used by redirect
+/// connections.
+pub fn parse_invalidation(frame: &Resp3Frame) -> Option<Invalidation> {
+  let (kind, keys) = match frame {
+    Resp3Frame::Push { ref data, .. } if data.len() == 2 => (&data[0], &data[1]),
+    Resp3Frame::Push { ref data, .. } | Resp3Frame::Array { ref data, .. } if data.len() == 3 => {
+      if data[0].as_str() != Some(PUBSUB_MESSAGE) {
+        return None;
+      }
+      (&data[1], &data[2])
+    }
+    _ => return None,
+  };
+
+  match kind.as_str() {
+    Some(INVALIDATE_PREFIX) | Some(INVALIDATION_CHANNEL) => {}
+    _ => return None,
+  };
+
+  match keys {
+    Resp3Frame::Array { ref data, .. } => Some(Invalidation::Keys(
+      data
+        .iter()
+        .filter_map(|f| f.as_bytes().map(|b| Bytes::copy_from_slice(b)))
+        .collect(),
+    )),
+    Resp3Frame::Null => Some(Invalidation::All),
+    _ => None,
+  }
+}
+
+/// Create the `CLIENT TRACKING` arguments for the provided config.
+fn tracking_args(config: &CacheConfig, redirect: Option<i64>) -> Vec<RedisValue> {
+  let mut args: Vec<RedisValue> = vec![static_val!("ON")];
+
+  if let Some(id) = redirect {
+    args.push(static_val!("REDIRECT"));
+    args.push(id.into());
+  }
+  match config.mode {
+    TrackingMode::Default => {}
+    TrackingMode::Broadcast { ref prefixes } => {
+      args.push(static_val!("BCAST"));
+      for prefix in prefixes.iter() {
+        args.push(static_val!("PREFIX"));
+        args.push(prefix.as_str().into());
+      }
+    }
+    TrackingMode::OptIn => args.push(static_val!("OPTIN")),
+  };
+
+  args
+}
+
+/// Enable `CLIENT TRACKING` on a new connection to `server`, if the client uses a cache.
+///
+/// On RESP2 this will create a redirect connection to receive invalidation messages, if needed.
+pub async fn enable_tracking<T>(
+  inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  transport: Framed<T, RedisCodec>,
+) -> Result<Framed<T, RedisCodec>, RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  let cache = match inner.cache {
+    Some(ref cache) => cache,
+    None => return Ok(transport),
+  };
+  // the server no longer tracks keys read on previous connections
+  cache.clear();
+
+  let redirect = if inner.is_resp3() {
+    None
+  } else {
+    Some(create_redirect(inner, server).await?)
+  };
+
+  _debug!(inner, "Enabling client tracking on {}", server);
+  let command = RedisCommand::new(RedisCommandKind::ClientTracking, tracking_args(&cache.config, redirect), None);
+  let (frame, transport) = connection::request_response(transport, &command, inner.is_resp3()).await?;
+  if let Some(error) = protocol_utils::frame_to_error(&frame.into_resp3()) {
+    return Err(error);
+  }
+
+  cache.set_enabled(server);
+  Ok(transport)
+}
+
+/// Enable tracking on the provided transport.
+pub async fn enable_transport_tracking(
+  inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  transport: RedisTransport,
+) -> Result<RedisTransport, RedisError> {
+  Ok(match transport {
+    RedisTransport::Tcp(framed) => RedisTransport::Tcp(enable_tracking(inner, server, framed).await?),
+    RedisTransport::Tls(framed) => RedisTransport::Tls(enable_tracking(inner, server, framed).await?),
+    RedisTransport::Unix(framed) => RedisTransport::Unix(enable_tracking(inner, server, framed).await?),
+  })
+}
+
+/// Read or create the redirect connection to `server`, returning the connection ID.
+async fn create_redirect(inner: &Arc<RedisClientInner>, server: &Arc<String>) -> Result<i64, RedisError> {
+  let cache = match inner.cache {
+    Some(ref cache) => cache,
+    None => return Err(RedisError::new(RedisErrorKind::Unknown, "Missing client cache.")),
+  };
+  if let Some(id) = cache.redirect_id(server) {
+    return Ok(id);
+  }
+
+  let uses_tls = protocol_utils::uses_tls(inner);
+  _debug!(inner, "Creating tracking redirect connection to {}", server);
+  let transport = backchannel::create_transport(inner, server, uses_tls).await?;
+
+  let (id, task) = match transport {
+    RedisTransport::Tcp(framed) => subscribe_redirect(inner, server, framed).await?,
+    RedisTransport::Tls(framed) => subscribe_redirect(inner, server, framed).await?,
+    RedisTransport::Unix(framed) => subscribe_redirect(inner, server, framed).await?,
+  };
+  cache.redirects.lock().insert(server.clone(), Redirect { id, task });
+
+  Ok(id)
+}
+
+/// Subscribe to invalidation messages on the redirect connection and spawn a task to process them.
+async fn subscribe_redirect<T>(
+  inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  transport: Framed<T, RedisCodec>,
+) -> Result<(i64, JoinHandle<()>), RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+  let (id, transport) = connection::read_client_id(inner, transport).await.map_err(|(e, _)| e)?;
+  let id = match id {
+    Some(id) => id,
+    None => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Failed to read redirect connection ID.",
+      ))
+    }
+  };
+
+  let command = RedisCommand::new(RedisCommandKind::Subscribe, vec![INVALIDATION_CHANNEL.into()], None);
+  let (frame, mut transport) = connection::request_response(transport, &command, false).await?;
+  if let Some(error) = protocol_utils::frame_to_error(&frame.into_resp3()) {
+    return Err(error);
+  }
+
+  let (inner, server) = (inner.clone(), server.clone());
+  let task = tokio::spawn(async move {
+    while let Some(frame) = transport.next().await {
+      let frame = match frame {
+        Ok(frame) => frame.into_resp3(),
+        Err(e) => {
+          _debug!(inner, "Error reading tracking redirect connection to {}: {:?}", server, e);
+          break;
+        }
+      };
+
+      if let Some(invalidation) = parse_invalidation(&frame) {
+        _trace!(inner, "Recv invalidation message from {}: {:?}", server, invalidation);
+        if let Some(ref cache) = inner.cache {
+          cache.invalidate(invalidation);
+        }
+      }
+    }
+
+    _debug!(inner, "Tracking redirect connection to {} closed.", server);
+    if let Some(ref cache) = inner.cache {
+      cache.redirects.lock().remove(&server);
+      cache.set_broken(&server);
+    }
+  });
+
+  Ok((id, task))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::types::CacheConfig;
+
+  fn cache(max_keys: usize, ttl_ms: u64) -> ClientCache {
+    ClientCache::new(CacheConfig {
+      max_keys,
+      ttl_ms,
+      ..Default::default()
+    })
+  }
+
+  fn key(s: &'static str) -> Bytes {
+    Bytes::from_static(s.as_bytes())
+  }
+
+  fn push(data: Vec<Resp3Frame>) -> Resp3Frame {
+    Resp3Frame::Push { data, attributes: None }
+  }
+
+  fn blob(s: &'static str) -> Resp3Frame {
+    Resp3Frame::BlobString {
+      data: key(s),
+      attributes: None,
+    }
+  }
+
+  #[test]
+  fn should_cache_values_and_fields() {
+    let cache = cache(10, 0);
+    let reservation = cache.reserve(vec![key("foo"), key("bar")]);
+    reservation.insert(key("foo"), "a".into());
+    reservation.insert_field(key("bar"), key("baz"), 1.into());
+    drop(reservation);
+
+    assert_eq!(cache.get(b"foo"), Some("a".into()));
+    assert_eq!(cache.hget(b"bar", b"baz"), Some(1.into()));
+    assert_eq!(cache.hget(b"bar", b"wibble"), None);
+    assert_eq!(cache.mget(&[key("foo")]), Some(vec!["a".into()]));
+    assert_eq!(cache.mget(&[key("foo"), key("wibble")]), None);
+    assert_eq!(cache.hits(), 3);
+  }
+
+  #[test]
+  fn should_not_cache_values_invalidated_while_in_flight() {
+    let cache = cache(10, 0);
+    let reservation = cache.reserve(vec![key("foo")]);
+    cache.invalidate(Invalidation::Keys(vec![key("foo")]));
+    reservation.insert(key("foo"), "a".into());
+
+    assert_eq!(cache.get(b"foo"), None);
+  }
+
+  #[test]
+  fn should_not_cache_queued_values() {
+    let cache = cache(10, 0);
+    let reservation = cache.reserve(vec![key("foo")]);
+    reservation.insert(key("foo"), RedisValue::Queued);
+
+    assert!(cache.is_empty());
+  }
+
+  #[test]
+  fn should_invalidate_keys() {
+    let cache = cache(10, 0);
+    let reservation = cache.reserve(vec![key("foo"), key("bar")]);
+    reservation.insert(key("foo"), "a".into());
+    reservation.insert(key("bar"), "b".into());
+
+    cache.invalidate(Invalidation::Keys(vec![key("foo")]));
+    assert_eq!(cache.get(b"foo"), None);
+    assert_eq!(cache.get(b"bar"), Some("b".into()));
+    cache.invalidate(Invalidation::All);
+    assert!(cache.is_empty());
+  }
+
+  #[test]
+  fn should_evict_oldest_keys() {
+    let cache = cache(2, 0);
+    let reservation = cache.reserve(vec![key("a"), key("b"), key("c")]);
+    reservation.insert(key("a"), 1.into());
+    reservation.insert(key("b"), 2.into());
+    reservation.insert(key("c"), 3.into());
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(b"a"), None);
+    assert_eq!(cache.get(b"c"), Some(3.into()));
+  }
+
+  #[test]
+  fn should_expire_values() {
+    let cache = cache(10, 1);
+    let reservation = cache.reserve(vec![key("foo")]);
+    reservation.insert(key("foo"), "a".into());
+    std::thread::sleep(Duration::from_millis(5));
+
+    assert_eq!(cache.get(b"foo"), None);
+  }
+
+  #[test]
+  fn should_parse_resp3_invalidation() {
+    let frame = push(vec![
+      blob("invalidate"),
+      Resp3Frame::Array {
+        data: vec![blob("foo"), blob("bar")],
+        attributes: None,
+      },
+    ]);
+    assert_eq!(
+      parse_invalidation(&frame),
+      Some(Invalidation::Keys(vec![key("foo"), key("bar")]))
+    );
+
+    let frame = push(vec![blob("invalidate"), Resp3Frame::Null]);
+    assert_eq!(parse_invalidation(&frame), Some(Invalidation::All));
+  }
+
+  #[test]
+  fn should_parse_resp2_invalidation() {
+    let frame = Resp3Frame::Array {
+      data: vec![
+        blob("message"),
+        blob(INVALIDATION_CHANNEL),
+        Resp3Frame::Array {
+          data: vec![blob("foo")],
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+    assert_eq!(parse_invalidation(&frame), Some(Invalidation::Keys(vec![key("foo")])));
+  }
+
+  #[test]
+  fn should_ignore_pubsub_messages() {
+    let frame = Resp3Frame::Array {
+      data: vec![blob("message"), blob("foo"), blob("bar")],
+      attributes: None,
+    };
+    assert_eq!(parse_invalidation(&frame), None);
+  }
+
+  #[test]
+  fn should_create_tracking_args() {
+    let config = CacheConfig {
+      mode: TrackingMode::Broadcast {
+        prefixes: vec!["a:".into(), "b:".into()],
+      },
+      ..Default::default()
+    };
+    let expected: Vec<RedisValue> = vec![
+      "ON".into(),
+      "REDIRECT".into(),
+      5.into(),
+      "BCAST".into(),
+      "PREFIX".into(),
+      "a:".into(),
+      "PREFIX".into(),
+      "b:".into(),
+    ];
+    assert_eq!(tracking_args(&config, Some(5)), expected);
+  }
+}
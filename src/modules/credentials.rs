@@ -0,0 +1,205 @@
+use crate::commands;
+use crate::error::RedisError;
+use crate::modules::inner::RedisClientInner;
+use crate::multiplexer::utils as multiplexer_utils;
+use crate::types::ClientState;
+use crate::utils;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+/// The amount of time to wait before retrying a proactive re-authentication that could not run because the client
+/// was disconnected or inside a transaction.
+const RETRY_DELAY_MS: u64 = 100;
+
+/// An interface used to read credentials before the client authenticates.
+///
+/// Implementations are consulted before every `AUTH` (or `HELLO`) sent while establishing a connection, including
+/// reconnections, which makes it possible to use short-lived credentials such as IAM authentication tokens without
+/// rebuilding the client.
+///
+/// ```rust no_run
+/// use fred::prelude::*;
+/// use fred::types::CredentialProvider;
+/// use std::time::Duration;
+///
+/// #[derive(Debug)]
+/// struct TokenProvider;
+///
+/// #[async_trait::async_trait]
+/// impl CredentialProvider for TokenProvider {
+///   async fn fetch(&self) -> Result<(Option<String>, String), RedisError> {
+///     // call out to the cloud provider here
+///     Ok((Some("app".into()), "short-lived-token".into()))
+///   }
+///
+///   fn refresh_interval(&self) -> Option<Duration> {
+///     Some(Duration::from_secs(10 * 60))
+///   }
+/// }
+/// ```
+#[async_trait]
+#[cfg_attr(docsrs, doc(cfg(feature = "credential-provider")))]
+pub trait CredentialProvider: Debug + Send + Sync + 'static {
+  /// Read the optional ACL username and the password (or token) that should be used to authenticate.
+  ///
+  /// Errors returned here are treated like connection errors and will trigger the client's reconnection policy.
+  async fn fetch(&self) -> Result<(Option<String>, String), RedisError>;
+
+  /// An optional interval on which the client should fetch new credentials and re-authenticate any live
+  /// connections via `AUTH`.
+  ///
+  /// This should be shorter than the lifetime of the credentials returned by [fetch](Self::fetch). Failed refreshes
+  /// are emitted on the client's [on_error](crate::interfaces::ClientLike::on_error) stream and retried on the next
+  /// interval.
+  ///
+  /// Note: RESP2 connections in a subscribed state, such as those used by a `SubscriberClient`, reject `AUTH`. These
+  /// connections will use the new credentials the next time they reconnect.
+  ///
+  /// Default: `None`
+  fn refresh_interval(&self) -> Option<Duration> {
+    None
+  }
+}
+
+/// Read the credentials for a new connection, using the credential provider if one is configured.
+pub async fn read_credentials(
+  inner: &Arc<RedisClientInner>,
+) -> Result<(Option<String>, Option<String>), RedisError> {
+  let provider = inner.config.read().credential_provider.clone();
+
+  if let Some(provider) = provider {
+    let (username, password) = provider.fetch().await?;
+    Ok((username, Some(password)))
+  } else {
+    let guard = inner.config.read();
+    Ok((guard.username.clone(), guard.password.clone()))
+  }
+}
+
+/// Spawn a task that periodically re-authenticates the client's connections, if the credential provider has a
+/// refresh interval.
+///
+/// The caller is responsible for aborting the task when the connection is closed.
+pub fn spawn_refresh_task(inner: &Arc<RedisClientInner>) -> Option<JoinHandle<()>> {
+  let provider = inner.config.read().credential_provider.clone()?;
+  let interval = provider.refresh_interval()?;
+  let inner = inner.clone();
+
+  Some(tokio::spawn(async move {
+    let mut delay = interval;
+
+    loop {
+      sleep(delay).await;
+
+      // AUTH would be queued inside a MULTI block, and there is nothing to refresh while disconnected
+      if utils::read_client_state(&inner.state) != ClientState::Connected || utils::is_locked_some(&inner.multi_block)
+      {
+        delay = Duration::from_millis(RETRY_DELAY_MS);
+        continue;
+      }
+      delay = interval;
+
+      let result = match provider.fetch().await {
+        Ok((username, password)) => commands::server::auth(&inner, username, password.into()).await,
+        Err(e) => Err(e),
+      };
+      if let Err(e) = result {
+        _warn!(inner, "Failed to refresh credentials: {:?}", e);
+        multiplexer_utils::emit_error(&inner, &e);
+      } else {
+        _debug!(inner, "Refreshed credentials on live connections.");
+      }
+    }
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::error::RedisErrorKind;
+  use crate::types::RedisConfig;
+  use tokio::sync::mpsc::unbounded_channel;
+
+  #[derive(Debug)]
+  struct StaticProvider;
+
+  #[derive(Debug)]
+  struct FailingProvider;
+
+  #[async_trait]
+  impl CredentialProvider for FailingProvider {
+    async fn fetch(&self) -> Result<(Option<String>, String), RedisError> {
+      Err(RedisError::new(RedisErrorKind::Auth, "Token expired."))
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+      Some(Duration::from_millis(10))
+    }
+  }
+
+  #[async_trait]
+  impl CredentialProvider for StaticProvider {
+    async fn fetch(&self) -> Result<(Option<String>, String), RedisError> {
+      Ok((Some("foo".into()), "bar".into()))
+    }
+  }
+
+  fn inner(config: RedisConfig) -> Arc<RedisClientInner> {
+    RedisClientInner::new(config)
+  }
+
+  #[tokio::test]
+  async fn should_read_credentials_from_config() {
+    let config = RedisConfig {
+      username: Some("baz".into()),
+      password: Some("qux".into()),
+      ..RedisConfig::default()
+    };
+
+    let credentials = read_credentials(&inner(config)).await.unwrap();
+    assert_eq!(credentials, (Some("baz".into()), Some("qux".into())));
+  }
+
+  #[tokio::test]
+  async fn should_read_credentials_from_provider() {
+    let config = RedisConfig {
+      username: Some("baz".into()),
+      password: Some("qux".into()),
+      credential_provider: Some(Arc::new(StaticProvider)),
+      ..RedisConfig::default()
+    };
+
+    let credentials = read_credentials(&inner(config)).await.unwrap();
+    assert_eq!(credentials, (Some("foo".into()), Some("bar".into())));
+  }
+
+  #[tokio::test]
+  async fn should_not_spawn_refresh_task_without_interval() {
+    let config = RedisConfig {
+      credential_provider: Some(Arc::new(StaticProvider)),
+      ..RedisConfig::default()
+    };
+
+    assert!(spawn_refresh_task(&inner(config)).is_none());
+  }
+
+  #[tokio::test]
+  async fn should_emit_refresh_errors() {
+    let config = RedisConfig {
+      credential_provider: Some(Arc::new(FailingProvider)),
+      ..RedisConfig::default()
+    };
+    let inner = inner(config);
+    let (tx, mut rx) = unbounded_channel();
+    inner.error_tx.write().push_back(tx);
+    utils::set_client_state(&inner.state, ClientState::Connected);
+
+    let task = spawn_refresh_task(&inner).unwrap();
+    let error = rx.recv().await.unwrap();
+    task.abort();
+    assert_eq!(*error.kind(), RedisErrorKind::Auth);
+  }
+}
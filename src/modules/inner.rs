@@ -1,23 +1,25 @@
 use crate::clients::RedisClient;
 use crate::error::*;
 use crate::modules::backchannel::Backchannel;
+use crate::modules::cache::ClientCache;
+use crate::multiplexer::replicas::ReplicaRouter;
 use crate::multiplexer::SentCommand;
 use crate::protocol::types::DefaultResolver;
 use crate::protocol::types::RedisCommand;
 use crate::types::*;
 use crate::utils;
 use arc_swap::ArcSwap;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::collections::VecDeque;
 use std::sync::atomic::AtomicUsize;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::sync::oneshot::Sender as OneshotSender;
 use tokio::sync::RwLock as AsyncRwLock;
 use tokio::task::JoinHandle;
 
 #[cfg(feature = "metrics")]
-use crate::modules::metrics::MovingStats;
+use crate::modules::metrics::{CommandHistograms, MetricsExporter, MovingStats};
 
 pub type CommandSender = UnboundedSender<RedisCommand>;
 pub type CommandReceiver = UnboundedReceiver<RedisCommand>;
@@ -170,11 +172,11 @@ pub struct RedisClientInner {
   /// The RESP version used by the underlying connections.
   pub resp_version: Arc<ArcSwap<RespVersion>>,
   /// The response policy to apply when the client is in a MULTI block.
-  pub multi_block: RwLock<Option<MultiPolicy>>,
+  pub multi_block: Arc<RwLock<Option<MultiPolicy>>>,
   /// The state of the underlying connection.
-  pub state: RwLock<ClientState>,
+  pub state: Arc<RwLock<ClientState>>,
   /// The redis config used for initializing connections.
-  pub config: RwLock<RedisConfig>,
+  pub config: Arc<RwLock<RedisConfig>>,
   /// An optional reconnect policy.
   pub policy: RwLock<Option<ReconnectPolicy>>,
   /// An mpsc sender for errors to `on_error` streams.
@@ -185,6 +187,8 @@ pub struct RedisClientInner {
   pub command_rx: RwLock<Option<CommandReceiver>>,
   /// An mpsc sender for pubsub messages to `on_message` streams.
   pub message_tx: RwLock<VecDeque<UnboundedSender<(String, RedisValue)>>>,
+  /// An mpsc sender for pubsub messages to `on_pubsub_message` streams.
+  pub pubsub_message_tx: RwLock<VecDeque<UnboundedSender<Message>>>,
   /// An mpsc sender for pubsub messages to `on_keyspace_event` streams.
   pub keyspace_tx: RwLock<VecDeque<UnboundedSender<KeyspaceEvent>>>,
   /// An mpsc sender for reconnection events to `on_reconnect` streams.
@@ -197,20 +201,30 @@ pub struct RedisClientInner {
   pub reconnect_sleep_jh: RwLock<Option<JoinHandle<Result<(), ()>>>>,
   /// Command queue buffer size.
   pub cmd_buffer_len: Arc<AtomicUsize>,
+  /// The in-flight command counters for each connection.
+  pub in_flight_counters: RwLock<Vec<Weak<AtomicUsize>>>,
   /// Number of message redeliveries.
   pub redeliver_count: Arc<AtomicUsize>,
   /// Channel listening to connection closed events.
   pub connection_closed_tx: RwLock<Option<ConnectionClosedTx>>,
   /// The cached view of the cluster state, if running against a clustered deployment.
-  pub cluster_state: RwLock<Option<ClusterKeyCache>>,
+  pub cluster_state: Arc<RwLock<Option<ClusterKeyCache>>>,
   /// The DNS resolver to use when establishing new connections.
   pub resolver: DefaultResolver,
   /// A backchannel that can be used to control the multiplexer connections even while the connections are blocked.
   pub backchannel: Arc<AsyncRwLock<Backchannel>>,
   /// The server host/port resolved from the sentinel nodes, if known.
-  pub sentinel_primary: RwLock<Option<Arc<String>>>,
+  pub sentinel_primary: Arc<RwLock<Option<Arc<String>>>>,
   /// The internal representation of the performance config options from the `RedisConfig`.
   pub perf_config: Arc<InternalPerfConfig>,
+  /// A buffer for commands queued by a `Pipeline`. When present commands are buffered here instead of being sent to the multiplexer.
+  pub command_buffer: Option<Mutex<VecDeque<RedisCommand>>>,
+  /// The client-side cache, if enabled.
+  pub cache: Option<ClientCache>,
+  /// The router used to send read-only commands to replica nodes, if enabled.
+  pub replicas: Option<ReplicaRouter>,
+  /// The client that sends commands on behalf of a `Replicas` client. When present commands are sent through this client and routed to a replica, if possible.
+  pub replica_parent: Option<Arc<RedisClientInner>>,
 
   /// Command latency metrics.
   #[cfg(feature = "metrics")]
@@ -224,16 +238,84 @@ pub struct RedisClientInner {
   /// Payload size metrics tracking for responses
   #[cfg(feature = "metrics")]
   pub res_size_stats: Arc<RwLock<MovingStats>>,
+  /// Latency and payload size histograms for each command and server.
+  #[cfg(feature = "metrics")]
+  pub command_histograms: RwLock<CommandHistograms>,
+  /// The exporter that receives metrics events, if configured.
+  #[cfg(feature = "metrics")]
+  pub exporter: Option<Arc<dyn MetricsExporter>>,
 }
 
 impl RedisClientInner {
   pub fn new(config: RedisConfig) -> Arc<RedisClientInner> {
-    let backchannel = Backchannel::default();
     let id = Arc::new(format!("fred-{}", utils::random_string(10)));
+    let cache = config.cache.clone().map(ClientCache::new);
+    RedisClientInner::create(config, id, None, cache, None)
+  }
+
+  /// Create a new inner struct for a `Pipeline` that buffers commands instead of sending them to the multiplexer.
+  pub fn new_pipeline(parent: &Arc<RedisClientInner>) -> Arc<RedisClientInner> {
+    let mut config = parent.config.read().clone();
+    config.version = parent.resp_version.as_ref().load().as_ref().clone();
+
+    let buffer = Some(Mutex::new(VecDeque::new()));
+    let inner = RedisClientInner::create(config, parent.id.clone(), buffer, None, None);
+    // the pipeline never drives its own connections
+    let _ = inner.take_command_rx();
+    inner.update_cluster_state(parent.cluster_state.read().clone());
+    utils::set_client_state(&inner.state, parent.state.read().clone());
+    inner
+  }
+
+  /// Create a new inner struct for a `Replicas` client that sends commands through the parent client's connections.
+  ///
+  /// The config, connection state, cluster state, and transaction state are shared with the parent client rather than
+  /// copied, so the `Replicas` client always sees the parent's current state.
+  pub fn new_replicas(parent: &Arc<RedisClientInner>) -> Arc<RedisClientInner> {
+    let config = parent.config.read().clone();
+    let inner = RedisClientInner::create(config, parent.id.clone(), None, None, Some(parent.clone()));
+    // commands are sent to the parent client's multiplexer
+    let _ = inner.take_command_rx();
+    inner
+  }
+
+  fn create(
+    config: RedisConfig,
+    id: Arc<String>,
+    command_buffer: Option<Mutex<VecDeque<RedisCommand>>>,
+    cache: Option<ClientCache>,
+    replica_parent: Option<Arc<RedisClientInner>>,
+  ) -> Arc<RedisClientInner> {
+    let backchannel = Backchannel::default();
     let resolver = DefaultResolver::new(&id);
     let (command_tx, command_rx) = unbounded_channel();
     let version = config.version.clone();
-    let perf_config = InternalPerfConfig::from(&config);
+    let replicas = config.replica.clone().map(ReplicaRouter::new);
+    #[cfg(feature = "metrics")]
+    let exporter = config.metrics_exporter.clone();
+
+    // a `Replicas` client reads the parent client's state instead of a copy
+    let shared = match replica_parent {
+      Some(ref parent) => (
+        parent.resp_version.clone(),
+        parent.perf_config.clone(),
+        parent.config.clone(),
+        parent.state.clone(),
+        parent.multi_block.clone(),
+        parent.cluster_state.clone(),
+        parent.sentinel_primary.clone(),
+      ),
+      None => (
+        Arc::new(ArcSwap::from(Arc::new(version))),
+        Arc::new(InternalPerfConfig::from(&config)),
+        Arc::new(RwLock::new(config)),
+        Arc::new(RwLock::new(ClientState::Disconnected)),
+        Arc::new(RwLock::new(None)),
+        Arc::new(RwLock::new(None)),
+        Arc::new(RwLock::new(None)),
+      ),
+    };
+    let (resp_version, perf_config, config, state, multi_block, cluster_state, sentinel_primary) = shared;
 
     Arc::new(RedisClientInner {
       #[cfg(feature = "metrics")]
@@ -244,28 +326,38 @@ impl RedisClientInner {
       req_size_stats: Arc::new(RwLock::new(MovingStats::default())),
       #[cfg(feature = "metrics")]
       res_size_stats: Arc::new(RwLock::new(MovingStats::default())),
+      #[cfg(feature = "metrics")]
+      command_histograms: RwLock::new(CommandHistograms::default()),
+      #[cfg(feature = "metrics")]
+      exporter,
 
-      resp_version: Arc::new(ArcSwap::from(Arc::new(version))),
-      perf_config: Arc::new(perf_config),
-      config: RwLock::new(config),
+      resp_version,
+      perf_config,
+      config,
       policy: RwLock::new(None),
-      state: RwLock::new(ClientState::Disconnected),
+      state,
       error_tx: RwLock::new(VecDeque::new()),
       message_tx: RwLock::new(VecDeque::new()),
+      pubsub_message_tx: RwLock::new(VecDeque::new()),
       keyspace_tx: RwLock::new(VecDeque::new()),
       reconnect_tx: RwLock::new(VecDeque::new()),
       cluster_change_tx: RwLock::new(VecDeque::new()),
       connect_tx: RwLock::new(VecDeque::new()),
       reconnect_sleep_jh: RwLock::new(None),
       cmd_buffer_len: Arc::new(AtomicUsize::new(0)),
+      in_flight_counters: RwLock::new(Vec::new()),
       redeliver_count: Arc::new(AtomicUsize::new(0)),
       connection_closed_tx: RwLock::new(None),
-      multi_block: RwLock::new(None),
-      cluster_state: RwLock::new(None),
+      multi_block,
+      cluster_state,
       backchannel: Arc::new(AsyncRwLock::new(backchannel)),
-      sentinel_primary: RwLock::new(None),
+      sentinel_primary,
       command_rx: RwLock::new(Some(command_rx)),
       command_tx,
+      command_buffer,
+      cache,
+      replicas,
+      replica_parent,
       resolver,
       id,
     })
@@ -275,6 +367,11 @@ impl RedisClientInner {
     self.perf_config.pipeline()
   }
 
+  /// Whether or not the inner struct belongs to a `Pipeline`.
+  pub fn is_pipeline(&self) -> bool {
+    self.command_buffer.is_some()
+  }
+
   pub fn log_client_name_fn<F>(&self, level: log::Level, func: F)
   where
     F: FnOnce(&str),
@@ -284,6 +381,23 @@ impl RedisClientInner {
     }
   }
 
+  /// Read the number of commands that were sent to the server and are waiting on a response, across all connections.
+  pub fn in_flight_commands(&self) -> usize {
+    self
+      .in_flight_counters
+      .read()
+      .iter()
+      .filter_map(|counter| counter.upgrade())
+      .fold(0, |sum, counter| sum.saturating_add(utils::read_atomic(&counter)))
+  }
+
+  /// Register the in-flight command counter for a new connection, removing the counters for closed connections.
+  pub fn register_in_flight_counter(&self, counter: &Arc<AtomicUsize>) {
+    let mut counters = self.in_flight_counters.write();
+    counters.retain(|counter| counter.strong_count() > 0);
+    counters.push(Arc::downgrade(counter));
+  }
+
   pub fn client_name(&self) -> &str {
     self.id.as_str()
   }
@@ -316,6 +430,10 @@ impl RedisClientInner {
     self.command_rx.write().take()
   }
 
+  pub fn has_command_rx(&self) -> bool {
+    self.command_rx.read().is_some()
+  }
+
   pub fn store_command_rx(&self, rx: CommandReceiver) {
     let mut guard = self.command_rx.write();
     *guard = Some(rx);
@@ -1,7 +1,25 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
 
+use crate::error::RedisErrorKind;
 use std::cmp;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(any(feature = "prometheus-exporter", feature = "opentelemetry-exporter"))]
+use crate::error::RedisError;
+#[cfg(feature = "opentelemetry-exporter")]
+use opentelemetry::metrics::{Counter, Histogram as OtelHistogram, Meter, ObservableGauge, Unit};
+#[cfg(feature = "opentelemetry-exporter")]
+use opentelemetry::KeyValue;
+#[cfg(feature = "opentelemetry-exporter")]
+use parking_lot::Mutex;
+#[cfg(feature = "prometheus-exporter")]
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry};
+#[cfg(any(feature = "prometheus-exporter", feature = "opentelemetry-exporter"))]
+use std::fmt;
 
 /// Stats describing a distribution of samples.
 ///
@@ -108,3 +126,578 @@ impl<'a> From<&'a MovingStats> for Stats {
     }
   }
 }
+
+/// The number of bits used to index the linear sub-buckets within each power of two.
+const SUB_BUCKET_BITS: u32 = 5;
+/// The number of linear sub-buckets within each power of two. Quantiles are accurate to within 1/32 of the value.
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+
+fn bucket_index(value: u64) -> usize {
+  if value < SUB_BUCKETS {
+    value as usize
+  } else {
+    let shift = (63 - value.leading_zeros()) - SUB_BUCKET_BITS;
+    let sub_bucket = (value >> shift) - SUB_BUCKETS;
+    (SUB_BUCKETS + shift as u64 * SUB_BUCKETS + sub_bucket) as usize
+  }
+}
+
+fn bucket_upper_bound(index: usize) -> u64 {
+  let index = index as u64;
+  if index < SUB_BUCKETS {
+    index
+  } else {
+    let shift = (index - SUB_BUCKETS) / SUB_BUCKETS;
+    let sub_bucket = (index - SUB_BUCKETS) % SUB_BUCKETS;
+    let upper = ((SUB_BUCKETS + sub_bucket + 1) as u128) << shift;
+    cmp::min(upper - 1, u64::MAX as u128) as u64
+  }
+}
+
+/// Quantiles and summary values read from a [Histogram].
+///
+/// Latency values are in microseconds, data size values are in bytes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistogramStats {
+  pub samples: u64,
+  pub min: u64,
+  pub max: u64,
+  pub mean: f64,
+  pub p50: u64,
+  pub p95: u64,
+  pub p99: u64,
+  pub p999: u64,
+}
+
+/// A log-linear bucketed histogram, similar to an HDR histogram with two significant digits.
+///
+/// Values below 32 are tracked exactly. Larger values are tracked in 32 linear buckets per power of two, so memory
+/// usage grows with the log of the largest sample rather than with the number of samples.
+#[derive(Clone, Debug, Default)]
+pub struct Histogram {
+  buckets: Vec<u64>,
+  samples: u64,
+  sum: u128,
+  min: u64,
+  max: u64,
+}
+
+impl Histogram {
+  pub fn sample(&mut self, value: u64) {
+    let index = bucket_index(value);
+    if index >= self.buckets.len() {
+      self.buckets.resize(index + 1, 0);
+    }
+    self.buckets[index] += 1;
+
+    if self.samples == 0 {
+      self.min = value;
+      self.max = value;
+    } else {
+      self.min = cmp::min(self.min, value);
+      self.max = cmp::max(self.max, value);
+    }
+    self.samples += 1;
+    self.sum += value as u128;
+  }
+
+  /// Read the value at the provided quantile, between 0.0 and 1.0.
+  ///
+  /// The returned value is the upper bound of the bucket that contains the quantile, limited to the largest sample.
+  pub fn quantile(&self, quantile: f64) -> u64 {
+    if self.samples == 0 {
+      return 0;
+    }
+    let target = cmp::max(1, (quantile * self.samples as f64).ceil() as u64);
+
+    let mut count = 0;
+    for (index, bucket) in self.buckets.iter().enumerate() {
+      count += *bucket;
+      if count >= target {
+        return cmp::max(self.min, cmp::min(self.max, bucket_upper_bound(index)));
+      }
+    }
+    self.max
+  }
+
+  pub fn reset(&mut self) {
+    *self = Histogram::default();
+  }
+
+  pub fn read_metrics(&self) -> HistogramStats {
+    self.into()
+  }
+
+  pub fn take_metrics(&mut self) -> HistogramStats {
+    let metrics = self.read_metrics();
+    self.reset();
+    metrics
+  }
+}
+
+impl<'a> From<&'a Histogram> for HistogramStats {
+  fn from(histogram: &'a Histogram) -> HistogramStats {
+    let mean = if histogram.samples == 0 {
+      0.0
+    } else {
+      histogram.sum as f64 / histogram.samples as f64
+    };
+
+    HistogramStats {
+      samples: histogram.samples,
+      min: histogram.min,
+      max: histogram.max,
+      mean,
+      p50: histogram.quantile(0.5),
+      p95: histogram.quantile(0.95),
+      p99: histogram.quantile(0.99),
+      p999: histogram.quantile(0.999),
+    }
+  }
+}
+
+/// Latency and payload size histograms for each command and server.
+#[derive(Default)]
+pub struct CommandHistograms {
+  pub latency: HashMap<String, Histogram>,
+  pub server_latency: HashMap<Arc<String>, Histogram>,
+  pub req_size: HashMap<String, Histogram>,
+  pub res_size: HashMap<String, Histogram>,
+}
+
+fn sample_command(histograms: &mut HashMap<String, Histogram>, command: &str, value: u64) {
+  if let Some(histogram) = histograms.get_mut(command) {
+    histogram.sample(value);
+  } else {
+    let mut histogram = Histogram::default();
+    histogram.sample(value);
+    histograms.insert(command.to_owned(), histogram);
+  }
+}
+
+fn read_keyed<K: ToString>(histograms: &HashMap<K, Histogram>) -> HashMap<String, HistogramStats> {
+  histograms
+    .iter()
+    .map(|(key, histogram)| (key.to_string(), histogram.read_metrics()))
+    .collect()
+}
+
+fn take_keyed<K: ToString>(histograms: &mut HashMap<K, Histogram>) -> HashMap<String, HistogramStats> {
+  histograms
+    .drain()
+    .map(|(key, histogram)| (key.to_string(), histogram.read_metrics()))
+    .collect()
+}
+
+impl CommandHistograms {
+  /// Sample the latency (in microseconds) and payload sizes for a command sent to the provided server.
+  pub fn sample(&mut self, command: &str, server: &Arc<String>, latency: u64, req_size: u64, res_size: u64) {
+    sample_command(&mut self.latency, command, latency);
+    sample_command(&mut self.req_size, command, req_size);
+    sample_command(&mut self.res_size, command, res_size);
+    self
+      .server_latency
+      .entry(server.clone())
+      .or_insert_with(Histogram::default)
+      .sample(latency);
+  }
+
+  pub fn read_latency(&self) -> HashMap<String, HistogramStats> {
+    read_keyed(&self.latency)
+  }
+
+  pub fn take_latency(&mut self) -> HashMap<String, HistogramStats> {
+    take_keyed(&mut self.latency)
+  }
+
+  pub fn read_server_latency(&self) -> HashMap<String, HistogramStats> {
+    read_keyed(&self.server_latency)
+  }
+
+  pub fn take_server_latency(&mut self) -> HashMap<String, HistogramStats> {
+    take_keyed(&mut self.server_latency)
+  }
+
+  pub fn read_req_size(&self) -> HashMap<String, HistogramStats> {
+    read_keyed(&self.req_size)
+  }
+
+  pub fn take_req_size(&mut self) -> HashMap<String, HistogramStats> {
+    take_keyed(&mut self.req_size)
+  }
+
+  pub fn read_res_size(&self) -> HashMap<String, HistogramStats> {
+    read_keyed(&self.res_size)
+  }
+
+  pub fn take_res_size(&mut self) -> HashMap<String, HistogramStats> {
+    take_keyed(&mut self.res_size)
+  }
+}
+
+/// An interface for exporting client metrics to an external monitoring system.
+///
+/// Each function receives the ID of the client that recorded the event. The default implementations do nothing, so
+/// exporters only need to implement the events they care about. These functions are called from the tasks that
+/// drive each connection and should not block.
+///
+/// See [PrometheusExporter](crate::types::PrometheusExporter) and [OpenTelemetryExporter](crate::types::OpenTelemetryExporter)
+/// for implementations that use the `prometheus` and `opentelemetry` crates.
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub trait MetricsExporter: Debug + Send + Sync + 'static {
+  /// Record the latency of a command, including the time it spent waiting to be written to the server.
+  fn record_latency(&self, client: &str, server: &str, command: &str, latency: Duration) {}
+
+  /// Record an error that was returned to a caller.
+  fn record_error(&self, client: &str, kind: &RedisErrorKind) {}
+
+  /// Record that the client reconnected to the server(s).
+  fn record_reconnect(&self, client: &str) {}
+
+  /// Record that backpressure was applied to a command sent to the provided server.
+  fn record_backpressure(&self, client: &str, server: &str) {}
+
+  /// Record the number of commands that are waiting on a response from the provided server.
+  fn record_in_flight(&self, client: &str, server: &str, count: usize) {}
+
+  /// Record a `MOVED` or `ASK` redirection from a cluster node.
+  fn record_redirect(&self, client: &str, server: &str, kind: &str) {}
+}
+
+/// A [MetricsExporter] that records metrics with the `prometheus` crate.
+///
+/// The following metrics are registered:
+///
+/// * `redis_command_latency_seconds` - A histogram with `client`, `server`, and `command` labels.
+/// * `redis_errors_total` - A counter with `client` and `kind` labels.
+/// * `redis_reconnects_total` - A counter with a `client` label.
+/// * `redis_backpressure_total` - A counter with `client` and `server` labels.
+/// * `redis_in_flight_commands` - A gauge with `client` and `server` labels.
+/// * `redis_cluster_redirects_total` - A counter with `client`, `server`, and `kind` labels.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::types::PrometheusExporter;
+/// # use std::sync::Arc;
+/// let registry = prometheus::Registry::new();
+/// let mut config = RedisConfig::default();
+/// config.metrics_exporter = Some(Arc::new(PrometheusExporter::new(&registry)?));
+///
+/// let client = RedisClient::new(config);
+/// // ...
+/// let metric_families = registry.gather();
+/// # Ok::<(), RedisError>(())
+/// ```
+#[cfg(feature = "prometheus-exporter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prometheus-exporter")))]
+#[derive(Clone)]
+pub struct PrometheusExporter {
+  latency: HistogramVec,
+  errors: IntCounterVec,
+  reconnects: IntCounterVec,
+  backpressure: IntCounterVec,
+  in_flight: IntGaugeVec,
+  redirects: IntCounterVec,
+}
+
+#[cfg(feature = "prometheus-exporter")]
+impl fmt::Debug for PrometheusExporter {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("PrometheusExporter").finish()
+  }
+}
+
+#[cfg(feature = "prometheus-exporter")]
+impl PrometheusExporter {
+  /// Create the metrics and register them with the provided registry.
+  pub fn new(registry: &Registry) -> Result<Self, RedisError> {
+    let latency = HistogramVec::new(
+      HistogramOpts::new("redis_command_latency_seconds", "Latency of Redis commands."),
+      &["client", "server", "command"],
+    )?;
+    let errors = IntCounterVec::new(
+      Opts::new("redis_errors_total", "Errors returned by Redis commands."),
+      &["client", "kind"],
+    )?;
+    let reconnects = IntCounterVec::new(
+      Opts::new("redis_reconnects_total", "Reconnections to the Redis server(s)."),
+      &["client"],
+    )?;
+    let backpressure = IntCounterVec::new(
+      Opts::new("redis_backpressure_total", "Commands delayed by backpressure."),
+      &["client", "server"],
+    )?;
+    let in_flight = IntGaugeVec::new(
+      Opts::new("redis_in_flight_commands", "Commands waiting on a response."),
+      &["client", "server"],
+    )?;
+    let redirects = IntCounterVec::new(
+      Opts::new("redis_cluster_redirects_total", "MOVED and ASK redirections from cluster nodes."),
+      &["client", "server", "kind"],
+    )?;
+
+    registry.register(Box::new(latency.clone()))?;
+    registry.register(Box::new(errors.clone()))?;
+    registry.register(Box::new(reconnects.clone()))?;
+    registry.register(Box::new(backpressure.clone()))?;
+    registry.register(Box::new(in_flight.clone()))?;
+    registry.register(Box::new(redirects.clone()))?;
+
+    Ok(PrometheusExporter {
+      latency,
+      errors,
+      reconnects,
+      backpressure,
+      in_flight,
+      redirects,
+    })
+  }
+}
+
+#[cfg(feature = "prometheus-exporter")]
+impl MetricsExporter for PrometheusExporter {
+  fn record_latency(&self, client: &str, server: &str, command: &str, latency: Duration) {
+    self
+      .latency
+      .with_label_values(&[client, server, command])
+      .observe(latency.as_secs_f64());
+  }
+
+  fn record_error(&self, client: &str, kind: &RedisErrorKind) {
+    self.errors.with_label_values(&[client, kind.to_str()]).inc();
+  }
+
+  fn record_reconnect(&self, client: &str) {
+    self.reconnects.with_label_values(&[client]).inc();
+  }
+
+  fn record_backpressure(&self, client: &str, server: &str) {
+    self.backpressure.with_label_values(&[client, server]).inc();
+  }
+
+  fn record_in_flight(&self, client: &str, server: &str, count: usize) {
+    self.in_flight.with_label_values(&[client, server]).set(count as i64);
+  }
+
+  fn record_redirect(&self, client: &str, server: &str, kind: &str) {
+    self.redirects.with_label_values(&[client, server, kind]).inc();
+  }
+}
+
+/// A [MetricsExporter] that records metrics with the `opentelemetry` crate.
+///
+/// The following instruments are created on the provided meter:
+///
+/// * `redis.command.latency` - A histogram, in seconds, with `client`, `server`, and `command` attributes.
+/// * `redis.errors` - A counter with `client` and `kind` attributes.
+/// * `redis.reconnects` - A counter with a `client` attribute.
+/// * `redis.backpressure` - A counter with `client` and `server` attributes.
+/// * `redis.in_flight_commands` - A gauge with `client` and `server` attributes.
+/// * `redis.cluster.redirects` - A counter with `client`, `server`, and `kind` attributes.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::types::OpenTelemetryExporter;
+/// # use std::sync::Arc;
+/// let meter = opentelemetry::global::meter("fred");
+/// let mut config = RedisConfig::default();
+/// config.metrics_exporter = Some(Arc::new(OpenTelemetryExporter::new(&meter)?));
+///
+/// let client = RedisClient::new(config);
+/// # Ok::<(), RedisError>(())
+/// ```
+#[cfg(feature = "opentelemetry-exporter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry-exporter")))]
+#[derive(Clone)]
+pub struct OpenTelemetryExporter {
+  latency: OtelHistogram<f64>,
+  errors: Counter<u64>,
+  reconnects: Counter<u64>,
+  backpressure: Counter<u64>,
+  redirects: Counter<u64>,
+  in_flight: Arc<Mutex<HashMap<(String, String), i64>>>,
+  _in_flight_gauge: ObservableGauge<i64>,
+}
+
+#[cfg(feature = "opentelemetry-exporter")]
+impl fmt::Debug for OpenTelemetryExporter {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("OpenTelemetryExporter").finish()
+  }
+}
+
+#[cfg(feature = "opentelemetry-exporter")]
+impl OpenTelemetryExporter {
+  /// Create the instruments on the provided meter.
+  pub fn new(meter: &Meter) -> Result<Self, RedisError> {
+    let latency = meter
+      .f64_histogram("redis.command.latency")
+      .with_description("Latency of Redis commands.")
+      .with_unit(Unit::new("s"))
+      .try_init()?;
+    let errors = meter
+      .u64_counter("redis.errors")
+      .with_description("Errors returned by Redis commands.")
+      .try_init()?;
+    let reconnects = meter
+      .u64_counter("redis.reconnects")
+      .with_description("Reconnections to the Redis server(s).")
+      .try_init()?;
+    let backpressure = meter
+      .u64_counter("redis.backpressure")
+      .with_description("Commands delayed by backpressure.")
+      .try_init()?;
+    let redirects = meter
+      .u64_counter("redis.cluster.redirects")
+      .with_description("MOVED and ASK redirections from cluster nodes.")
+      .try_init()?;
+
+    let in_flight: Arc<Mutex<HashMap<(String, String), i64>>> = Arc::new(Mutex::new(HashMap::new()));
+    let observed = in_flight.clone();
+    let _in_flight_gauge = meter
+      .i64_observable_gauge("redis.in_flight_commands")
+      .with_description("Commands waiting on a response.")
+      .with_callback(move |observer| {
+        for ((client, server), count) in observed.lock().iter() {
+          observer.observe(*count, &[
+            KeyValue::new("client", client.clone()),
+            KeyValue::new("server", server.clone()),
+          ]);
+        }
+      })
+      .try_init()?;
+
+    Ok(OpenTelemetryExporter {
+      latency,
+      errors,
+      reconnects,
+      backpressure,
+      redirects,
+      in_flight,
+      _in_flight_gauge,
+    })
+  }
+}
+
+#[cfg(feature = "opentelemetry-exporter")]
+impl MetricsExporter for OpenTelemetryExporter {
+  fn record_latency(&self, client: &str, server: &str, command: &str, latency: Duration) {
+    self.latency.record(latency.as_secs_f64(), &[
+      KeyValue::new("client", client.to_owned()),
+      KeyValue::new("server", server.to_owned()),
+      KeyValue::new("command", command.to_owned()),
+    ]);
+  }
+
+  fn record_error(&self, client: &str, kind: &RedisErrorKind) {
+    self.errors.add(1, &[
+      KeyValue::new("client", client.to_owned()),
+      KeyValue::new("kind", kind.to_str()),
+    ]);
+  }
+
+  fn record_reconnect(&self, client: &str) {
+    self.reconnects.add(1, &[KeyValue::new("client", client.to_owned())]);
+  }
+
+  fn record_backpressure(&self, client: &str, server: &str) {
+    self.backpressure.add(1, &[
+      KeyValue::new("client", client.to_owned()),
+      KeyValue::new("server", server.to_owned()),
+    ]);
+  }
+
+  fn record_in_flight(&self, client: &str, server: &str, count: usize) {
+    self
+      .in_flight
+      .lock()
+      .insert((client.to_owned(), server.to_owned()), count as i64);
+  }
+
+  fn record_redirect(&self, client: &str, server: &str, kind: &str) {
+    self.redirects.add(1, &[
+      KeyValue::new("client", client.to_owned()),
+      KeyValue::new("server", server.to_owned()),
+      KeyValue::new("kind", kind.to_owned()),
+    ]);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_track_small_values_exactly() {
+    let mut histogram = Histogram::default();
+    for value in 1 .. 11 {
+      histogram.sample(value);
+    }
+
+    let stats = histogram.read_metrics();
+    assert_eq!(stats.samples, 10);
+    assert_eq!(stats.min, 1);
+    assert_eq!(stats.max, 10);
+    assert_eq!(stats.mean, 5.5);
+    assert_eq!(stats.p50, 5);
+    assert_eq!(stats.p95, 10);
+    assert_eq!(stats.p999, 10);
+  }
+
+  #[test]
+  fn should_read_tail_quantiles_within_bucket_precision() {
+    let mut histogram = Histogram::default();
+    for value in 1 .. 10_001 {
+      histogram.sample(value);
+    }
+
+    let stats = histogram.take_metrics();
+    for (actual, expected) in vec![(stats.p50, 5_000), (stats.p95, 9_500), (stats.p99, 9_900)].into_iter() {
+      let error = (actual as f64 - expected as f64).abs() / expected as f64;
+      assert!(error <= 1.0 / SUB_BUCKETS as f64, "{} is not close to {}", actual, expected);
+    }
+    assert_eq!(stats.max, 10_000);
+    assert_eq!(histogram.read_metrics(), HistogramStats::default());
+  }
+
+  #[test]
+  fn should_map_bucket_bounds_to_indexes() {
+    for value in vec![0, 31, 32, 33, 63, 64, 1_000, 123_456_789, u64::MAX].into_iter() {
+      let index = bucket_index(value);
+      assert!(bucket_upper_bound(index) >= value);
+      if index > 0 {
+        assert!(bucket_upper_bound(index - 1) < value);
+      }
+    }
+  }
+
+  #[test]
+  fn should_key_command_histograms_by_command_and_server() {
+    let mut histograms = CommandHistograms::default();
+    let server = Arc::new("127.0.0.1:6379".to_owned());
+    histograms.sample("GET", &server, 100, 10, 5);
+    histograms.sample("GET", &server, 200, 10, 5);
+    histograms.sample("KEYS", &server, 50_000, 20, 500);
+
+    let latency = histograms.read_latency();
+    assert_eq!(latency.get("GET").unwrap().samples, 2);
+    assert_eq!(latency.get("KEYS").unwrap().max, 50_000);
+    assert_eq!(histograms.read_server_latency().get("127.0.0.1:6379").unwrap().samples, 3);
+    assert_eq!(histograms.take_res_size().get("KEYS").unwrap().p50, 500);
+    assert!(histograms.read_res_size().is_empty());
+  }
+
+  #[test]
+  #[cfg(feature = "opentelemetry-exporter")]
+  fn should_track_latest_in_flight_count_per_server() {
+    let meter = opentelemetry::global::meter("fred-tests");
+    let exporter = OpenTelemetryExporter::new(&meter).unwrap();
+    exporter.record_in_flight("a", "127.0.0.1:6379", 3);
+    exporter.record_in_flight("a", "127.0.0.1:6379", 1);
+    exporter.record_in_flight("a", "127.0.0.1:6380", 2);
+
+    let in_flight = exporter.in_flight.lock();
+    assert_eq!(in_flight.get(&("a".into(), "127.0.0.1:6379".into())), Some(&1));
+    assert_eq!(in_flight.get(&("a".into(), "127.0.0.1:6380".into())), Some(&2));
+  }
+}
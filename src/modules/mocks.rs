@@ -0,0 +1,1916 @@
+//! An interface for mocking Redis commands.
+//!
+//! When the `mocks` field on a client's [RedisConfig](crate::types::RedisConfig) is set, every command sent by that
+//! client will be routed to the provided [Mocks](crate::mocks::Mocks) implementation instead of a connection to a
+//! Redis server. This can be used to test code that uses the client interfaces without running a Redis server.
+//! Clients without a mocking layer connect to the server as usual, even when the `mocks` feature is enabled.
+//!
+//! The [MemoryStore](crate::mocks::MemoryStore) supports most of the string, key, hash, list, set, and sorted set
+//! commands along with key expirations.
+//!
+//! Note: A client with a mocking layer never touches the network, and its commands succeed or fail based only on
+//! the mocking layer. Enable this feature in `dev-dependencies` only, so that a config accidentally built with
+//! `mocks` in a release build fails to compile rather than silently discarding writes.
+//!
+//! ```rust no_run
+//! use fred::prelude::*;
+//! use fred::mocks::{MemoryStore, Mocks};
+//! use std::sync::Arc;
+//!
+//! #[tokio::test]
+//! async fn should_use_mocked_client() -> Result<(), RedisError> {
+//!   let config = RedisConfig {
+//!     mocks: Some(Arc::new(MemoryStore::new())),
+//!     ..Default::default()
+//!   };
+//!   let client = RedisClient::new(config);
+//!   let _ = client.connect(None);
+//!   let _ = client.wait_for_connect().await?;
+//!
+//!   let _: () = client.set("foo", "bar", None, None, false).await?;
+//!   assert_eq!(client.get::<String, _>("foo").await?, "bar");
+//!   Ok(())
+//! }
+//! ```
+
+use crate::error::{RedisError, RedisErrorKind};
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::types::{RedisCommand, RedisCommandKind};
+use crate::protocol::utils as protocol_utils;
+use crate::types::{RedisMap, RedisValue};
+use crate::utils;
+use bytes::Bytes;
+use bytes_utils::Str;
+use parking_lot::Mutex;
+use redis_protocol::resp3::types::Frame as Resp3Frame;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::str;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static WRONGTYPE: &'static str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+static NOT_INTEGER: &'static str = "ERR value is not an integer or out of range";
+static NOT_FLOAT: &'static str = "ERR value is not a valid float";
+static SYNTAX: &'static str = "ERR syntax error";
+static NO_SUCH_KEY: &'static str = "ERR no such key";
+
+/// A command sent to a mocking layer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MockCommand {
+  /// The first word in the command string. For example: `SET`, `CLIENT`, etc.
+  pub cmd: Str,
+  /// The optional subcommand string. For example: `SETNAME` in `CLIENT SETNAME`.
+  pub subcommand: Option<Str>,
+  /// The ordered list of arguments to the command.
+  pub args: Vec<RedisValue>,
+}
+
+impl MockCommand {
+  /// Create a new mocked command from a command string and arguments.
+  ///
+  /// Multi-word commands such as `CLIENT SETNAME` will be split into the `cmd` and `subcommand` fields.
+  pub fn new<S>(cmd: S, args: Vec<RedisValue>) -> Self
+  where
+    S: Into<Str>,
+  {
+    let cmd: Str = cmd.into();
+    let mut parts = cmd.trim().splitn(2, ' ');
+    let first = parts.next().unwrap_or("").to_uppercase();
+    let subcommand = parts
+      .next()
+      .map(|s| s.trim())
+      .filter(|s| !s.is_empty())
+      .map(|s| Str::from(s.to_uppercase()));
+
+    MockCommand {
+      cmd: first.into(),
+      subcommand,
+      args,
+    }
+  }
+
+  pub(crate) fn from_command(command: &RedisCommand) -> Self {
+    match command.kind {
+      RedisCommandKind::_Custom(ref kind) => MockCommand::new(kind.cmd.clone(), command.args.clone()),
+      _ => MockCommand {
+        cmd: command.kind.cmd_str(),
+        subcommand: command.kind.subcommand_str().map(utils::static_str),
+        args: command.args.clone(),
+      },
+    }
+  }
+}
+
+/// An interface for intercepting and processing Redis commands in a mocking layer.
+pub trait Mocks: Debug + Send + Sync + 'static {
+  /// Intercept and process a Redis command, returning any `RedisValue`.
+  ///
+  /// Errors returned here will be returned to the caller as if they came from the server.
+  fn process_command(&self, command: MockCommand) -> Result<RedisValue, RedisError>;
+}
+
+/// An implementation of a mocking layer that returns the provided arguments to the caller.
+#[derive(Clone, Debug, Default)]
+pub struct Echo;
+
+impl Mocks for Echo {
+  fn process_command(&self, command: MockCommand) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Array(command.args))
+  }
+}
+
+/// A mocking layer that buffers commands internally and responds with `QUEUED` to each command.
+///
+/// This can be used to inspect the commands sent by the client.
+#[derive(Debug, Default)]
+pub struct Buffer {
+  commands: Mutex<Vec<MockCommand>>,
+}
+
+impl Buffer {
+  /// Create a new empty buffer.
+  pub fn new() -> Self {
+    Buffer::default()
+  }
+
+  /// Read the number of buffered commands.
+  pub fn len(&self) -> usize {
+    self.commands.lock().len()
+  }
+
+  /// Whether or not the buffer is empty.
+  pub fn is_empty(&self) -> bool {
+    self.commands.lock().is_empty()
+  }
+
+  /// Take the buffered commands, leaving the buffer empty.
+  pub fn take(&self) -> Vec<MockCommand> {
+    self.commands.lock().drain(..).collect()
+  }
+
+  /// Read a copy of the buffered commands.
+  pub fn inner(&self) -> Vec<MockCommand> {
+    self.commands.lock().clone()
+  }
+
+  /// Clear the buffered commands.
+  pub fn clear(&self) {
+    self.commands.lock().clear();
+  }
+}
+
+impl Mocks for Buffer {
+  fn process_command(&self, command: MockCommand) -> Result<RedisValue, RedisError> {
+    self.commands.lock().push(command);
+    Ok(RedisValue::Queued)
+  }
+}
+
+#[derive(Clone, Debug)]
+enum StoredValue {
+  String(Bytes),
+  Hash(HashMap<Bytes, Bytes>),
+  List(VecDeque<Bytes>),
+  Set(HashSet<Bytes>),
+  SortedSet(HashMap<Bytes, f64>),
+}
+
+impl StoredValue {
+  fn type_str(&self) -> &'static str {
+    match *self {
+      StoredValue::String(_) => "string",
+      StoredValue::Hash(_) => "hash",
+      StoredValue::List(_) => "list",
+      StoredValue::Set(_) => "set",
+      StoredValue::SortedSet(_) => "zset",
+    }
+  }
+
+  fn is_empty(&self) -> bool {
+    match *self {
+      StoredValue::String(_) => false,
+      StoredValue::Hash(ref h) => h.is_empty(),
+      StoredValue::List(ref l) => l.is_empty(),
+      StoredValue::Set(ref s) => s.is_empty(),
+      StoredValue::SortedSet(ref z) => z.is_empty(),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+  value: StoredValue,
+  /// The expiration time, as a unix timestamp in milliseconds.
+  expire_at: Option<i64>,
+}
+
+impl Entry {
+  fn new(value: StoredValue) -> Self {
+    Entry { value, expire_at: None }
+  }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ScoreBound {
+  Inclusive(f64),
+  Exclusive(f64),
+}
+
+impl ScoreBound {
+  fn above(&self, score: f64) -> bool {
+    match *self {
+      ScoreBound::Inclusive(min) => score >= min,
+      ScoreBound::Exclusive(min) => score > min,
+    }
+  }
+
+  fn below(&self, score: f64) -> bool {
+    match *self {
+      ScoreBound::Inclusive(max) => score <= max,
+      ScoreBound::Exclusive(max) => score < max,
+    }
+  }
+}
+
+type Data = HashMap<Bytes, Entry>;
+
+fn now_ms() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis() as i64)
+    .unwrap_or(0)
+}
+
+fn err(msg: &'static str) -> RedisError {
+  protocol_utils::pretty_error(msg)
+}
+
+fn wrong_args(cmd: &str) -> RedisError {
+  protocol_utils::pretty_error(&format!(
+    "ERR wrong number of arguments for '{}' command",
+    cmd.to_lowercase()
+  ))
+}
+
+fn to_bytes(value: &RedisValue) -> Result<Bytes, RedisError> {
+  match *value {
+    RedisValue::String(ref s) => Ok(s.inner().clone()),
+    RedisValue::Bytes(ref b) => Ok(b.clone()),
+    RedisValue::Integer(ref i) => Ok(i.to_string().into()),
+    RedisValue::Double(ref f) => Ok(f.to_string().into()),
+    RedisValue::Boolean(ref b) => Ok(b.to_string().into()),
+    _ => Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      format!("Invalid argument type: {}", value.kind()),
+    )),
+  }
+}
+
+fn to_i64(value: &RedisValue) -> Result<i64, RedisError> {
+  match *value {
+    RedisValue::Integer(i) => Ok(i),
+    _ => str::from_utf8(&to_bytes(value)?)
+      .ok()
+      .and_then(|s| s.parse::<i64>().ok())
+      .ok_or_else(|| err(NOT_INTEGER)),
+  }
+}
+
+fn to_f64(value: &RedisValue) -> Result<f64, RedisError> {
+  match *value {
+    RedisValue::Double(f) => Ok(f),
+    RedisValue::Integer(i) => Ok(i as f64),
+    _ => str::from_utf8(&to_bytes(value)?)
+      .ok()
+      .and_then(|s| utils::redis_string_to_f64(s).ok())
+      .ok_or_else(|| err(NOT_FLOAT)),
+  }
+}
+
+fn to_upper(value: &RedisValue) -> Option<String> {
+  value.as_str().map(|s| s.to_uppercase())
+}
+
+fn to_score_bound(value: &RedisValue) -> Result<ScoreBound, RedisError> {
+  if let RedisValue::Double(f) = *value {
+    return Ok(ScoreBound::Inclusive(f));
+  }
+  if let RedisValue::Integer(i) = *value {
+    return Ok(ScoreBound::Inclusive(i as f64));
+  }
+
+  let bytes = to_bytes(value)?;
+  let s = str::from_utf8(&bytes).map_err(|_| err("ERR min or max is not a float"))?;
+  let (exclusive, s) = if let Some(s) = s.strip_prefix('(') {
+    (true, s)
+  } else {
+    (false, s)
+  };
+  let score = if s == "-inf" {
+    f64::NEG_INFINITY
+  } else if s == "+inf" || s == "inf" {
+    f64::INFINITY
+  } else {
+    s.parse::<f64>().map_err(|_| err("ERR min or max is not a float"))?
+  };
+
+  Ok(if exclusive {
+    ScoreBound::Exclusive(score)
+  } else {
+    ScoreBound::Inclusive(score)
+  })
+}
+
+fn bytes_to_value(data: Bytes) -> RedisValue {
+  protocol_utils::string_or_bytes(data)
+}
+
+fn score_to_value(score: f64) -> RedisValue {
+  utils::f64_to_redis_string(score).unwrap_or(RedisValue::Null)
+}
+
+fn bool_to_value(b: bool) -> RedisValue {
+  RedisValue::Integer(if b { 1 } else { 0 })
+}
+
+fn parse_i64_bytes(data: &Bytes) -> Result<i64, RedisError> {
+  str::from_utf8(data)
+    .ok()
+    .and_then(|s| s.parse::<i64>().ok())
+    .ok_or_else(|| err(NOT_INTEGER))
+}
+
+fn parse_f64_bytes(data: &Bytes) -> Result<f64, RedisError> {
+  str::from_utf8(data)
+    .ok()
+    .and_then(|s| utils::redis_string_to_f64(s).ok())
+    .ok_or_else(|| err(NOT_FLOAT))
+}
+
+/// Convert the `start` and `stop` index arguments into an inclusive range of indexes into a collection of `len`
+/// elements.
+fn normalize_range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+  let len = len as i64;
+  let start = if start < 0 { (len + start).max(0) } else { start };
+  let stop = if stop < 0 { len + stop } else { stop.min(len - 1) };
+
+  if start > stop || start >= len || stop < 0 {
+    None
+  } else {
+    Some((start as usize, stop as usize))
+  }
+}
+
+fn sort_zset(zset: &HashMap<Bytes, f64>) -> Vec<(Bytes, f64)> {
+  let mut out: Vec<(Bytes, f64)> = zset.iter().map(|(k, v)| (k.clone(), *v)).collect();
+  out.sort_by(|(lk, ls), (rk, rs)| ls.partial_cmp(rs).unwrap_or(Ordering::Equal).then_with(|| lk.cmp(rk)));
+  out
+}
+
+fn zset_values(members: Vec<(Bytes, f64)>, withscores: bool) -> RedisValue {
+  let mut out = Vec::with_capacity(if withscores { members.len() * 2 } else { members.len() });
+  for (member, score) in members.into_iter() {
+    out.push(bytes_to_value(member));
+    if withscores {
+      out.push(score_to_value(score));
+    }
+  }
+
+  RedisValue::Array(out)
+}
+
+fn apply_limit<T>(values: Vec<T>, limit: Option<(i64, i64)>) -> Vec<T> {
+  match limit {
+    Some((offset, count)) => {
+      let offset = if offset < 0 { values.len() } else { offset as usize };
+      let iter = values.into_iter().skip(offset);
+
+      if count < 0 {
+        iter.collect()
+      } else {
+        iter.take(count as usize).collect()
+      }
+    }
+    None => values,
+  }
+}
+
+/// An in-memory mocking layer that implements a subset of the Redis interface.
+///
+/// This supports most commands that operate on strings, keys, hashes, lists, sets, and sorted sets, as well as key
+/// expirations. Expired keys are removed lazily when they are next accessed. All clients that share the same
+/// `MemoryStore` instance will also share the same data.
+///
+/// Commands that are not supported will return an error.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+  data: Mutex<Data>,
+}
+
+impl MemoryStore {
+  /// Create a new empty store.
+  pub fn new() -> Self {
+    MemoryStore::default()
+  }
+
+  /// Remove all keys from the store.
+  pub fn clear(&self) {
+    self.data.lock().clear();
+  }
+
+  /// Read the number of keys in the store that have not expired.
+  pub fn len(&self) -> usize {
+    let now = now_ms();
+    self
+      .data
+      .lock()
+      .values()
+      .filter(|e| e.expire_at.map(|t| t > now).unwrap_or(true))
+      .count()
+  }
+
+  /// Whether or not the store contains any keys that have not expired.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  fn remove_expired(data: &mut Data, key: &Bytes) {
+    let expired = data
+      .get(key)
+      .and_then(|e| e.expire_at)
+      .map(|t| t <= now_ms())
+      .unwrap_or(false);
+
+    if expired {
+      data.remove(key);
+    }
+  }
+
+  fn read<'a>(data: &'a mut Data, key: &Bytes) -> Option<&'a mut Entry> {
+    MemoryStore::remove_expired(data, key);
+    data.get_mut(key)
+  }
+
+  fn remove_if_empty(data: &mut Data, key: &Bytes) {
+    if data.get(key).map(|e| e.value.is_empty()).unwrap_or(false) {
+      data.remove(key);
+    }
+  }
+
+  fn read_string(data: &mut Data, key: &Bytes) -> Result<Option<Bytes>, RedisError> {
+    match MemoryStore::read(data, key) {
+      Some(entry) => match entry.value {
+        StoredValue::String(ref s) => Ok(Some(s.clone())),
+        _ => Err(err(WRONGTYPE)),
+      },
+      None => Ok(None),
+    }
+  }
+
+  fn hash_mut<'a>(
+    data: &'a mut Data,
+    key: &Bytes,
+    create: bool,
+  ) -> Result<Option<&'a mut HashMap<Bytes, Bytes>>, RedisError> {
+    MemoryStore::remove_expired(data, key);
+    if create && !data.contains_key(key) {
+      data.insert(key.clone(), Entry::new(StoredValue::Hash(HashMap::new())));
+    }
+
+    match data.get_mut(key) {
+      Some(entry) => match entry.value {
+        StoredValue::Hash(ref mut h) => Ok(Some(h)),
+        _ => Err(err(WRONGTYPE)),
+      },
+      None => Ok(None),
+    }
+  }
+
+  fn list_mut<'a>(
+    data: &'a mut Data,
+    key: &Bytes,
+    create: bool,
+  ) -> Result<Option<&'a mut VecDeque<Bytes>>, RedisError> {
+    MemoryStore::remove_expired(data, key);
+    if create && !data.contains_key(key) {
+      data.insert(key.clone(), Entry::new(StoredValue::List(VecDeque::new())));
+    }
+
+    match data.get_mut(key) {
+      Some(entry) => match entry.value {
+        StoredValue::List(ref mut l) => Ok(Some(l)),
+        _ => Err(err(WRONGTYPE)),
+      },
+      None => Ok(None),
+    }
+  }
+
+  fn set_mut<'a>(
+    data: &'a mut Data,
+    key: &Bytes,
+    create: bool,
+  ) -> Result<Option<&'a mut HashSet<Bytes>>, RedisError> {
+    MemoryStore::remove_expired(data, key);
+    if create && !data.contains_key(key) {
+      data.insert(key.clone(), Entry::new(StoredValue::Set(HashSet::new())));
+    }
+
+    match data.get_mut(key) {
+      Some(entry) => match entry.value {
+        StoredValue::Set(ref mut s) => Ok(Some(s)),
+        _ => Err(err(WRONGTYPE)),
+      },
+      None => Ok(None),
+    }
+  }
+
+  fn zset_mut<'a>(
+    data: &'a mut Data,
+    key: &Bytes,
+    create: bool,
+  ) -> Result<Option<&'a mut HashMap<Bytes, f64>>, RedisError> {
+    MemoryStore::remove_expired(data, key);
+    if create && !data.contains_key(key) {
+      data.insert(key.clone(), Entry::new(StoredValue::SortedSet(HashMap::new())));
+    }
+
+    match data.get_mut(key) {
+      Some(entry) => match entry.value {
+        StoredValue::SortedSet(ref mut z) => Ok(Some(z)),
+        _ => Err(err(WRONGTYPE)),
+      },
+      None => Ok(None),
+    }
+  }
+
+  fn set_expiration(data: &mut Data, key: &Bytes, expire_at: i64) -> RedisValue {
+    let now = now_ms();
+    match MemoryStore::read(data, key) {
+      Some(entry) => {
+        if expire_at <= now {
+          data.remove(key);
+        } else {
+          entry.expire_at = Some(expire_at);
+        }
+        bool_to_value(true)
+      }
+      None => bool_to_value(false),
+    }
+  }
+
+  fn incr_by(data: &mut Data, key: &Bytes, amount: i64) -> Result<RedisValue, RedisError> {
+    let current = match MemoryStore::read_string(data, key)? {
+      Some(s) => parse_i64_bytes(&s)?,
+      None => 0,
+    };
+    let next = current
+      .checked_add(amount)
+      .ok_or_else(|| err("ERR increment or decrement would overflow"))?;
+
+    match MemoryStore::read(data, key) {
+      Some(entry) => entry.value = StoredValue::String(next.to_string().into()),
+      None => {
+        data.insert(key.clone(), Entry::new(StoredValue::String(next.to_string().into())));
+      }
+    };
+    Ok(RedisValue::Integer(next))
+  }
+
+  fn write_string(data: &mut Data, key: Bytes, value: Bytes, expire_at: Option<i64>) {
+    data.insert(key, Entry {
+      value: StoredValue::String(value),
+      expire_at,
+    });
+  }
+
+  fn set(data: &mut Data, args: &[RedisValue]) -> Result<RedisValue, RedisError> {
+    if args.len() < 2 {
+      return Err(wrong_args("SET"));
+    }
+    let (key, value) = (to_bytes(&args[0])?, to_bytes(&args[1])?);
+    let (mut expire_at, mut keep_ttl, mut nx, mut xx, mut get) = (None, false, false, false, false);
+
+    let mut idx = 2;
+    while idx < args.len() {
+      let flag = to_upper(&args[idx]).ok_or_else(|| err(SYNTAX))?;
+      match flag.as_str() {
+        "EX" | "PX" | "EXAT" | "PXAT" => {
+          let amount = to_i64(args.get(idx + 1).ok_or_else(|| err(SYNTAX))?)?;
+          expire_at = Some(match flag.as_str() {
+            "EX" => now_ms() + amount * 1000,
+            "PX" => now_ms() + amount,
+            "EXAT" => amount * 1000,
+            _ => amount,
+          });
+          idx += 1;
+        }
+        "KEEPTTL" => keep_ttl = true,
+        "NX" => nx = true,
+        "XX" => xx = true,
+        "GET" => get = true,
+        _ => return Err(err(SYNTAX)),
+      };
+      idx += 1;
+    }
+
+    let previous = if get {
+      MemoryStore::read_string(data, &key)?
+    } else {
+      MemoryStore::read(data, &key).map(|_| Bytes::new())
+    };
+    let exists = MemoryStore::read(data, &key).is_some();
+    let old_value = || match previous {
+      Some(ref v) if get => bytes_to_value(v.clone()),
+      _ => RedisValue::Null,
+    };
+
+    if (nx && exists) || (xx && !exists) {
+      return Ok(if get { old_value() } else { RedisValue::Null });
+    }
+    if keep_ttl {
+      expire_at = MemoryStore::read(data, &key).and_then(|e| e.expire_at);
+    }
+
+    MemoryStore::write_string(data, key, value, expire_at);
+    Ok(if get { old_value() } else { RedisValue::new_ok() })
+  }
+
+  fn zadd(data: &mut Data, args: &[RedisValue]) -> Result<RedisValue, RedisError> {
+    if args.len() < 3 {
+      return Err(wrong_args("ZADD"));
+    }
+    let key = to_bytes(&args[0])?;
+    let (mut nx, mut xx, mut gt, mut lt, mut ch, mut incr) = (false, false, false, false, false, false);
+
+    let mut idx = 1;
+    while idx < args.len() {
+      match to_upper(&args[idx]).as_deref() {
+        Some("NX") => nx = true,
+        Some("XX") => xx = true,
+        Some("GT") => gt = true,
+        Some("LT") => lt = true,
+        Some("CH") => ch = true,
+        Some("INCR") => incr = true,
+        _ => break,
+      };
+      idx += 1;
+    }
+    let pairs = &args[idx ..];
+    if pairs.is_empty() || pairs.len() % 2 != 0 || (incr && pairs.len() != 2) {
+      return Err(err(SYNTAX));
+    }
+
+    let mut scored = Vec::with_capacity(pairs.len() / 2);
+    for chunk in pairs.chunks(2) {
+      scored.push((to_f64(&chunk[0])?, to_bytes(&chunk[1])?));
+    }
+    let zset = MemoryStore::zset_mut(data, &key, !xx)?;
+    let zset = match zset {
+      Some(zset) => zset,
+      None => return Ok(if incr { RedisValue::Null } else { RedisValue::Integer(0) }),
+    };
+
+    let (mut added, mut changed, mut last_score) = (0, 0, None);
+    for (score, member) in scored.into_iter() {
+      let current = zset.get(&member).cloned();
+      if (nx && current.is_some()) || (xx && current.is_none()) {
+        continue;
+      }
+
+      let next = match current {
+        Some(current) if incr => current + score,
+        _ => score,
+      };
+      if let Some(current) = current {
+        if (gt && next <= current) || (lt && next >= current) {
+          continue;
+        }
+        if !utils::f64_eq(current, next) {
+          changed += 1;
+        }
+      } else {
+        added += 1;
+      }
+
+      zset.insert(member, next);
+      last_score = Some(next);
+    }
+    MemoryStore::remove_if_empty(data, &key);
+
+    if incr {
+      Ok(last_score.map(score_to_value).unwrap_or(RedisValue::Null))
+    } else if ch {
+      Ok(RedisValue::Integer(added + changed))
+    } else {
+      Ok(RedisValue::Integer(added))
+    }
+  }
+
+  fn zrange_by_score(
+    data: &mut Data,
+    key: &Bytes,
+    min: ScoreBound,
+    max: ScoreBound,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+  ) -> Result<Vec<(Bytes, f64)>, RedisError> {
+    let zset = match MemoryStore::zset_mut(data, key, false)? {
+      Some(zset) => zset,
+      None => return Ok(Vec::new()),
+    };
+
+    let mut members: Vec<(Bytes, f64)> = sort_zset(zset)
+      .into_iter()
+      .filter(|(_, score)| min.above(*score) && max.below(*score))
+      .collect();
+    if rev {
+      members.reverse();
+    }
+
+    Ok(apply_limit(members, limit))
+  }
+
+  fn zrange_by_index(
+    data: &mut Data,
+    key: &Bytes,
+    start: i64,
+    stop: i64,
+    rev: bool,
+  ) -> Result<Vec<(Bytes, f64)>, RedisError> {
+    let zset = match MemoryStore::zset_mut(data, key, false)? {
+      Some(zset) => zset,
+      None => return Ok(Vec::new()),
+    };
+
+    let mut members = sort_zset(zset);
+    if rev {
+      members.reverse();
+    }
+
+    Ok(match normalize_range(start, stop, members.len()) {
+      Some((start, stop)) => members.drain(start ..= stop).collect(),
+      None => Vec::new(),
+    })
+  }
+
+  /// Parse the trailing `[WITHSCORES] [LIMIT offset count]` arguments used by the sorted set range commands.
+  fn parse_range_options(args: &[RedisValue]) -> Result<(bool, Option<(i64, i64)>, bool, bool), RedisError> {
+    let (mut withscores, mut limit, mut by_score, mut rev) = (false, None, false, false);
+
+    let mut idx = 0;
+    while idx < args.len() {
+      match to_upper(&args[idx]).as_deref() {
+        Some("WITHSCORES") => withscores = true,
+        Some("BYSCORE") => by_score = true,
+        Some("REV") => rev = true,
+        Some("LIMIT") => {
+          let offset = to_i64(args.get(idx + 1).ok_or_else(|| err(SYNTAX))?)?;
+          let count = to_i64(args.get(idx + 2).ok_or_else(|| err(SYNTAX))?)?;
+          limit = Some((offset, count));
+          idx += 2;
+        }
+        _ => return Err(err(SYNTAX)),
+      };
+      idx += 1;
+    }
+
+    Ok((withscores, limit, by_score, rev))
+  }
+
+  fn zpop(data: &mut Data, args: &[RedisValue], max: bool) -> Result<RedisValue, RedisError> {
+    let key = to_bytes(args.get(0).ok_or_else(|| wrong_args("ZPOPMIN"))?)?;
+    let count = match args.get(1) {
+      Some(count) => to_i64(count)?.max(0) as usize,
+      None => 1,
+    };
+    let zset = match MemoryStore::zset_mut(data, &key, false)? {
+      Some(zset) => zset,
+      None => return Ok(RedisValue::Array(Vec::new())),
+    };
+
+    let mut members = sort_zset(zset);
+    if max {
+      members.reverse();
+    }
+    members.truncate(count);
+    for (member, _) in members.iter() {
+      zset.remove(member);
+    }
+    MemoryStore::remove_if_empty(data, &key);
+
+    Ok(zset_values(members, true))
+  }
+
+  fn lpop(data: &mut Data, args: &[RedisValue], left: bool) -> Result<RedisValue, RedisError> {
+    let key = to_bytes(args.get(0).ok_or_else(|| wrong_args("LPOP"))?)?;
+    let count = match args.get(1) {
+      Some(count) => Some(to_i64(count)?.max(0) as usize),
+      None => None,
+    };
+    let list = match MemoryStore::list_mut(data, &key, false)? {
+      Some(list) => list,
+      None => return Ok(RedisValue::Null),
+    };
+
+    let mut out = Vec::with_capacity(count.unwrap_or(1));
+    for _ in 0 .. count.unwrap_or(1) {
+      let value = if left { list.pop_front() } else { list.pop_back() };
+      match value {
+        Some(value) => out.push(bytes_to_value(value)),
+        None => break,
+      };
+    }
+    MemoryStore::remove_if_empty(data, &key);
+
+    if count.is_some() {
+      Ok(RedisValue::Array(out))
+    } else {
+      Ok(out.pop().unwrap_or(RedisValue::Null))
+    }
+  }
+
+  fn push(data: &mut Data, args: &[RedisValue], left: bool, exists: bool) -> Result<RedisValue, RedisError> {
+    if args.len() < 2 {
+      return Err(wrong_args(if left { "LPUSH" } else { "RPUSH" }));
+    }
+    let key = to_bytes(&args[0])?;
+    let mut values = Vec::with_capacity(args.len() - 1);
+    for value in args[1 ..].iter() {
+      values.push(to_bytes(value)?);
+    }
+
+    let list = match MemoryStore::list_mut(data, &key, !exists)? {
+      Some(list) => list,
+      None => return Ok(RedisValue::Integer(0)),
+    };
+    for value in values.into_iter() {
+      if left {
+        list.push_front(value);
+      } else {
+        list.push_back(value);
+      }
+    }
+
+    Ok(RedisValue::Integer(list.len() as i64))
+  }
+
+  fn set_op(data: &mut Data, args: &[RedisValue], op: &str) -> Result<RedisValue, RedisError> {
+    if args.is_empty() {
+      return Err(wrong_args(op));
+    }
+
+    let mut sets = Vec::with_capacity(args.len());
+    for key in args.iter() {
+      let key = to_bytes(key)?;
+      sets.push(
+        MemoryStore::set_mut(data, &key, false)?
+          .map(|s| s.clone())
+          .unwrap_or_default(),
+      );
+    }
+
+    let mut iter = sets.into_iter();
+    let mut out = iter.next().unwrap_or_default();
+    for set in iter {
+      match op {
+        "SINTER" => out.retain(|v| set.contains(v)),
+        "SUNION" => out.extend(set.into_iter()),
+        _ => out.retain(|v| !set.contains(v)),
+      }
+    }
+
+    Ok(RedisValue::Array(out.into_iter().map(bytes_to_value).collect()))
+  }
+
+  fn process(&self, command: MockCommand) -> Result<RedisValue, RedisError> {
+    let mut guard = self.data.lock();
+    let data = &mut *guard;
+    let args = &command.args;
+    let arg = |idx: usize| -> Result<&RedisValue, RedisError> {
+      args.get(idx).ok_or_else(|| wrong_args(&command.cmd))
+    };
+
+    let result = match &*command.cmd {
+      // server
+      "PING" => match args.first() {
+        Some(value) => value.clone(),
+        None => RedisValue::from_static_str("PONG"),
+      },
+      "ECHO" => arg(0)?.clone(),
+      "SELECT" | "CLIENT" | "READONLY" | "READWRITE" | "AUTH" => RedisValue::new_ok(),
+      "QUIT" => RedisValue::new_ok(),
+      "FLUSHALL" | "FLUSHDB" => {
+        data.clear();
+        RedisValue::new_ok()
+      }
+      "DBSIZE" => {
+        let now = now_ms();
+        data.retain(|_, e| e.expire_at.map(|t| t > now).unwrap_or(true));
+        RedisValue::Integer(data.len() as i64)
+      }
+
+      // keys
+      "DEL" | "UNLINK" => {
+        let mut count = 0;
+        for key in args.iter() {
+          let key = to_bytes(key)?;
+          MemoryStore::remove_expired(data, &key);
+          if data.remove(&key).is_some() {
+            count += 1;
+          }
+        }
+        RedisValue::Integer(count)
+      }
+      "EXISTS" => {
+        let mut count = 0;
+        for key in args.iter() {
+          if MemoryStore::read(data, &to_bytes(key)?).is_some() {
+            count += 1;
+          }
+        }
+        RedisValue::Integer(count)
+      }
+      "EXPIRE" => {
+        let expire_at = now_ms() + to_i64(arg(1)?)? * 1000;
+        MemoryStore::set_expiration(data, &to_bytes(arg(0)?)?, expire_at)
+      }
+      "PEXPIRE" => {
+        let expire_at = now_ms() + to_i64(arg(1)?)?;
+        MemoryStore::set_expiration(data, &to_bytes(arg(0)?)?, expire_at)
+      }
+      "EXPIREAT" => MemoryStore::set_expiration(data, &to_bytes(arg(0)?)?, to_i64(arg(1)?)? * 1000),
+      "PEXPIREAT" => MemoryStore::set_expiration(data, &to_bytes(arg(0)?)?, to_i64(arg(1)?)?),
+      "TTL" | "PTTL" => {
+        let ttl = match MemoryStore::read(data, &to_bytes(arg(0)?)?) {
+          Some(entry) => match entry.expire_at {
+            Some(expire_at) => {
+              let remaining = (expire_at - now_ms()).max(0);
+              if &*command.cmd == "TTL" {
+                (remaining + 500) / 1000
+              } else {
+                remaining
+              }
+            }
+            None => -1,
+          },
+          None => -2,
+        };
+        RedisValue::Integer(ttl)
+      }
+      "PERSIST" => match MemoryStore::read(data, &to_bytes(arg(0)?)?) {
+        Some(entry) => bool_to_value(entry.expire_at.take().is_some()),
+        None => bool_to_value(false),
+      },
+      "TYPE" => match MemoryStore::read(data, &to_bytes(arg(0)?)?) {
+        Some(entry) => RedisValue::from_static_str(entry.value.type_str()),
+        None => RedisValue::from_static_str("none"),
+      },
+      "KEYS" => {
+        let pattern = to_bytes(arg(0)?)?;
+        let now = now_ms();
+        data.retain(|_, e| e.expire_at.map(|t| t > now).unwrap_or(true));
+
+        let keys = data
+          .keys()
+          .filter(|k| utils::glob_match(&pattern, k))
+          .map(|k| bytes_to_value(k.clone()))
+          .collect();
+        RedisValue::Array(keys)
+      }
+      "RENAME" | "RENAMENX" => {
+        let (source, destination) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?);
+        MemoryStore::remove_expired(data, &source);
+        MemoryStore::remove_expired(data, &destination);
+
+        if !data.contains_key(&source) {
+          return Err(err(NO_SUCH_KEY));
+        }
+        if &*command.cmd == "RENAMENX" && data.contains_key(&destination) {
+          bool_to_value(false)
+        } else {
+          let entry = data.remove(&source).unwrap();
+          data.insert(destination, entry);
+
+          if &*command.cmd == "RENAMENX" {
+            bool_to_value(true)
+          } else {
+            RedisValue::new_ok()
+          }
+        }
+      }
+
+      // strings
+      "GET" => match MemoryStore::read_string(data, &to_bytes(arg(0)?)?)? {
+        Some(value) => bytes_to_value(value),
+        None => RedisValue::Null,
+      },
+      "SET" => MemoryStore::set(data, args)?,
+      "SETNX" => {
+        let key = to_bytes(arg(0)?)?;
+        if MemoryStore::read(data, &key).is_some() {
+          bool_to_value(false)
+        } else {
+          MemoryStore::write_string(data, key, to_bytes(arg(1)?)?, None);
+          bool_to_value(true)
+        }
+      }
+      "SETEX" | "PSETEX" => {
+        let amount = to_i64(arg(1)?)?;
+        let expire_at = if &*command.cmd == "SETEX" {
+          now_ms() + amount * 1000
+        } else {
+          now_ms() + amount
+        };
+
+        MemoryStore::write_string(data, to_bytes(arg(0)?)?, to_bytes(arg(2)?)?, Some(expire_at));
+        RedisValue::new_ok()
+      }
+      "GETSET" => {
+        let key = to_bytes(arg(0)?)?;
+        let previous = MemoryStore::read_string(data, &key)?;
+        MemoryStore::write_string(data, key, to_bytes(arg(1)?)?, None);
+        previous.map(bytes_to_value).unwrap_or(RedisValue::Null)
+      }
+      "GETDEL" => {
+        let key = to_bytes(arg(0)?)?;
+        let previous = MemoryStore::read_string(data, &key)?;
+        data.remove(&key);
+        previous.map(bytes_to_value).unwrap_or(RedisValue::Null)
+      }
+      "MGET" => {
+        let mut out = Vec::with_capacity(args.len());
+        for key in args.iter() {
+          // MGET returns nil for keys holding the wrong type
+          let value = MemoryStore::read_string(data, &to_bytes(key)?).ok().flatten();
+          out.push(value.map(bytes_to_value).unwrap_or(RedisValue::Null));
+        }
+        RedisValue::Array(out)
+      }
+      "MSET" | "MSETNX" => {
+        if args.is_empty() || args.len() % 2 != 0 {
+          return Err(wrong_args(&command.cmd));
+        }
+        let mut pairs = Vec::with_capacity(args.len() / 2);
+        for chunk in args.chunks(2) {
+          pairs.push((to_bytes(&chunk[0])?, to_bytes(&chunk[1])?));
+        }
+
+        if &*command.cmd == "MSETNX" {
+          let any_exist = pairs.iter().any(|(k, _)| MemoryStore::read(data, k).is_some());
+          if !any_exist {
+            for (key, value) in pairs.into_iter() {
+              MemoryStore::write_string(data, key, value, None);
+            }
+          }
+          bool_to_value(!any_exist)
+        } else {
+          for (key, value) in pairs.into_iter() {
+            MemoryStore::write_string(data, key, value, None);
+          }
+          RedisValue::new_ok()
+        }
+      }
+      "INCR" => MemoryStore::incr_by(data, &to_bytes(arg(0)?)?, 1)?,
+      "DECR" => MemoryStore::incr_by(data, &to_bytes(arg(0)?)?, -1)?,
+      "INCRBY" => MemoryStore::incr_by(data, &to_bytes(arg(0)?)?, to_i64(arg(1)?)?)?,
+      "DECRBY" => MemoryStore::incr_by(data, &to_bytes(arg(0)?)?, -to_i64(arg(1)?)?)?,
+      "INCRBYFLOAT" => {
+        let key = to_bytes(arg(0)?)?;
+        let current = match MemoryStore::read_string(data, &key)? {
+          Some(s) => parse_f64_bytes(&s)?,
+          None => 0.0,
+        };
+        let next = score_to_value(current + to_f64(arg(1)?)?);
+        let expire_at = MemoryStore::read(data, &key).and_then(|e| e.expire_at);
+
+        MemoryStore::write_string(data, key, to_bytes(&next)?, expire_at);
+        next
+      }
+      "APPEND" => {
+        let key = to_bytes(arg(0)?)?;
+        let mut current = MemoryStore::read_string(data, &key)?.map(|b| b.to_vec()).unwrap_or_default();
+        current.extend_from_slice(&to_bytes(arg(1)?)?);
+        let len = current.len();
+
+        match MemoryStore::read(data, &key) {
+          Some(entry) => entry.value = StoredValue::String(current.into()),
+          None => MemoryStore::write_string(data, key, current.into(), None),
+        };
+        RedisValue::Integer(len as i64)
+      }
+      "STRLEN" => {
+        let len = MemoryStore::read_string(data, &to_bytes(arg(0)?)?)?.map(|s| s.len()).unwrap_or(0);
+        RedisValue::Integer(len as i64)
+      }
+      "GETRANGE" => {
+        let value = MemoryStore::read_string(data, &to_bytes(arg(0)?)?)?.unwrap_or_default();
+        match normalize_range(to_i64(arg(1)?)?, to_i64(arg(2)?)?, value.len()) {
+          Some((start, stop)) => bytes_to_value(value.slice(start ..= stop)),
+          None => RedisValue::from_static_str(""),
+        }
+      }
+
+      // hashes
+      "HSET" | "HMSET" => {
+        if args.len() < 3 || args.len() % 2 != 1 {
+          return Err(wrong_args(&command.cmd));
+        }
+        let key = to_bytes(&args[0])?;
+        let mut pairs = Vec::with_capacity(args.len() / 2);
+        for chunk in args[1 ..].chunks(2) {
+          pairs.push((to_bytes(&chunk[0])?, to_bytes(&chunk[1])?));
+        }
+
+        let hash = MemoryStore::hash_mut(data, &key, true)?.unwrap();
+        let mut added = 0;
+        for (field, value) in pairs.into_iter() {
+          if hash.insert(field, value).is_none() {
+            added += 1;
+          }
+        }
+
+        if &*command.cmd == "HMSET" {
+          RedisValue::new_ok()
+        } else {
+          RedisValue::Integer(added)
+        }
+      }
+      "HSETNX" => {
+        let (key, field, value) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?, to_bytes(arg(2)?)?);
+        let hash = MemoryStore::hash_mut(data, &key, true)?.unwrap();
+
+        if hash.contains_key(&field) {
+          bool_to_value(false)
+        } else {
+          hash.insert(field, value);
+          bool_to_value(true)
+        }
+      }
+      "HGET" => {
+        let (key, field) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?);
+        MemoryStore::hash_mut(data, &key, false)?
+          .and_then(|h| h.get(&field).cloned())
+          .map(bytes_to_value)
+          .unwrap_or(RedisValue::Null)
+      }
+      "HMGET" => {
+        let key = to_bytes(arg(0)?)?;
+        let mut fields = Vec::with_capacity(args.len());
+        for field in args[1 ..].iter() {
+          fields.push(to_bytes(field)?);
+        }
+
+        let hash = MemoryStore::hash_mut(data, &key, false)?;
+        let values = fields
+          .iter()
+          .map(|f| {
+            hash
+              .as_ref()
+              .and_then(|h| h.get(f).cloned())
+              .map(bytes_to_value)
+              .unwrap_or(RedisValue::Null)
+          })
+          .collect();
+        RedisValue::Array(values)
+      }
+      "HGETALL" => {
+        let mut out = RedisMap::new();
+        if let Some(hash) = MemoryStore::hash_mut(data, &to_bytes(arg(0)?)?, false)? {
+          for (field, value) in hash.iter() {
+            out.insert(field.clone().into(), bytes_to_value(value.clone()));
+          }
+        }
+        RedisValue::Map(out)
+      }
+      "HDEL" => {
+        let key = to_bytes(arg(0)?)?;
+        let mut fields = Vec::with_capacity(args.len());
+        for field in args[1 ..].iter() {
+          fields.push(to_bytes(field)?);
+        }
+
+        let count = match MemoryStore::hash_mut(data, &key, false)? {
+          Some(hash) => fields.iter().filter(|f| hash.remove(*f).is_some()).count(),
+          None => 0,
+        };
+        MemoryStore::remove_if_empty(data, &key);
+        RedisValue::Integer(count as i64)
+      }
+      "HEXISTS" => {
+        let (key, field) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?);
+        let exists = MemoryStore::hash_mut(data, &key, false)?
+          .map(|h| h.contains_key(&field))
+          .unwrap_or(false);
+        bool_to_value(exists)
+      }
+      "HLEN" => {
+        let len = MemoryStore::hash_mut(data, &to_bytes(arg(0)?)?, false)?
+          .map(|h| h.len())
+          .unwrap_or(0);
+        RedisValue::Integer(len as i64)
+      }
+      "HKEYS" | "HVALS" => {
+        let keys = &*command.cmd == "HKEYS";
+        let values = match MemoryStore::hash_mut(data, &to_bytes(arg(0)?)?, false)? {
+          Some(hash) => hash
+            .iter()
+            .map(|(k, v)| bytes_to_value(if keys { k.clone() } else { v.clone() }))
+            .collect(),
+          None => Vec::new(),
+        };
+        RedisValue::Array(values)
+      }
+      "HSTRLEN" => {
+        let (key, field) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?);
+        let len = MemoryStore::hash_mut(data, &key, false)?
+          .and_then(|h| h.get(&field).map(|v| v.len()))
+          .unwrap_or(0);
+        RedisValue::Integer(len as i64)
+      }
+      "HINCRBY" => {
+        let (key, field, amount) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?, to_i64(arg(2)?)?);
+        let hash = MemoryStore::hash_mut(data, &key, true)?.unwrap();
+        let current = match hash.get(&field) {
+          Some(value) => parse_i64_bytes(value)?,
+          None => 0,
+        };
+        let next = current
+          .checked_add(amount)
+          .ok_or_else(|| err("ERR increment or decrement would overflow"))?;
+
+        hash.insert(field, next.to_string().into());
+        RedisValue::Integer(next)
+      }
+      "HINCRBYFLOAT" => {
+        let (key, field, amount) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?, to_f64(arg(2)?)?);
+        let hash = MemoryStore::hash_mut(data, &key, true)?.unwrap();
+        let current = match hash.get(&field) {
+          Some(value) => parse_f64_bytes(value)?,
+          None => 0.0,
+        };
+        let next = score_to_value(current + amount);
+
+        hash.insert(field, to_bytes(&next)?);
+        next
+      }
+
+      // lists
+      "LPUSH" => MemoryStore::push(data, args, true, false)?,
+      "RPUSH" => MemoryStore::push(data, args, false, false)?,
+      "LPUSHX" => MemoryStore::push(data, args, true, true)?,
+      "RPUSHX" => MemoryStore::push(data, args, false, true)?,
+      "LPOP" => MemoryStore::lpop(data, args, true)?,
+      "RPOP" => MemoryStore::lpop(data, args, false)?,
+      "LLEN" => {
+        let len = MemoryStore::list_mut(data, &to_bytes(arg(0)?)?, false)?
+          .map(|l| l.len())
+          .unwrap_or(0);
+        RedisValue::Integer(len as i64)
+      }
+      "LRANGE" => {
+        let (key, start, stop) = (to_bytes(arg(0)?)?, to_i64(arg(1)?)?, to_i64(arg(2)?)?);
+        let values = match MemoryStore::list_mut(data, &key, false)? {
+          Some(list) => match normalize_range(start, stop, list.len()) {
+            Some((start, stop)) => list.range(start ..= stop).cloned().map(bytes_to_value).collect(),
+            None => Vec::new(),
+          },
+          None => Vec::new(),
+        };
+        RedisValue::Array(values)
+      }
+      "LINDEX" => {
+        let (key, index) = (to_bytes(arg(0)?)?, to_i64(arg(1)?)?);
+        MemoryStore::list_mut(data, &key, false)?
+          .and_then(|l| {
+            let index = if index < 0 { l.len() as i64 + index } else { index };
+            if index < 0 {
+              None
+            } else {
+              l.get(index as usize).cloned()
+            }
+          })
+          .map(bytes_to_value)
+          .unwrap_or(RedisValue::Null)
+      }
+      "LSET" => {
+        let (key, index, value) = (to_bytes(arg(0)?)?, to_i64(arg(1)?)?, to_bytes(arg(2)?)?);
+        let list = MemoryStore::list_mut(data, &key, false)?.ok_or_else(|| err(NO_SUCH_KEY))?;
+        let index = if index < 0 { list.len() as i64 + index } else { index };
+
+        match list.get_mut(index.max(0) as usize) {
+          Some(current) if index >= 0 => *current = value,
+          _ => return Err(err("ERR index out of range")),
+        };
+        RedisValue::new_ok()
+      }
+      "LREM" => {
+        let (key, count, value) = (to_bytes(arg(0)?)?, to_i64(arg(1)?)?, to_bytes(arg(2)?)?);
+        let removed = match MemoryStore::list_mut(data, &key, false)? {
+          Some(list) => {
+            let limit = if count == 0 { usize::MAX } else { count.unsigned_abs() as usize };
+            let mut indexes: Vec<usize> = list
+              .iter()
+              .enumerate()
+              .filter(|(_, v)| **v == value)
+              .map(|(i, _)| i)
+              .collect();
+            if count < 0 {
+              indexes.reverse();
+            }
+            indexes.truncate(limit);
+            indexes.sort_unstable();
+
+            for index in indexes.iter().rev() {
+              list.remove(*index);
+            }
+            indexes.len()
+          }
+          None => 0,
+        };
+        MemoryStore::remove_if_empty(data, &key);
+        RedisValue::Integer(removed as i64)
+      }
+      "LTRIM" => {
+        let (key, start, stop) = (to_bytes(arg(0)?)?, to_i64(arg(1)?)?, to_i64(arg(2)?)?);
+        if let Some(list) = MemoryStore::list_mut(data, &key, false)? {
+          match normalize_range(start, stop, list.len()) {
+            Some((start, stop)) => {
+              list.truncate(stop + 1);
+              list.drain(.. start);
+            }
+            None => list.clear(),
+          };
+        }
+        MemoryStore::remove_if_empty(data, &key);
+        RedisValue::new_ok()
+      }
+
+      // sets
+      "SADD" => {
+        let key = to_bytes(arg(0)?)?;
+        let mut members = Vec::with_capacity(args.len());
+        for member in args[1 ..].iter() {
+          members.push(to_bytes(member)?);
+        }
+        if members.is_empty() {
+          return Err(wrong_args(&command.cmd));
+        }
+
+        let set = MemoryStore::set_mut(data, &key, true)?.unwrap();
+        let added = members.into_iter().filter(|m| set.insert(m.clone())).count();
+        RedisValue::Integer(added as i64)
+      }
+      "SREM" => {
+        let key = to_bytes(arg(0)?)?;
+        let mut members = Vec::with_capacity(args.len());
+        for member in args[1 ..].iter() {
+          members.push(to_bytes(member)?);
+        }
+
+        let removed = match MemoryStore::set_mut(data, &key, false)? {
+          Some(set) => members.iter().filter(|m| set.remove(*m)).count(),
+          None => 0,
+        };
+        MemoryStore::remove_if_empty(data, &key);
+        RedisValue::Integer(removed as i64)
+      }
+      "SMEMBERS" => {
+        let members = match MemoryStore::set_mut(data, &to_bytes(arg(0)?)?, false)? {
+          Some(set) => set.iter().cloned().map(bytes_to_value).collect(),
+          None => Vec::new(),
+        };
+        RedisValue::Array(members)
+      }
+      "SISMEMBER" => {
+        let (key, member) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?);
+        let exists = MemoryStore::set_mut(data, &key, false)?
+          .map(|s| s.contains(&member))
+          .unwrap_or(false);
+        bool_to_value(exists)
+      }
+      "SMISMEMBER" => {
+        let key = to_bytes(arg(0)?)?;
+        let mut members = Vec::with_capacity(args.len());
+        for member in args[1 ..].iter() {
+          members.push(to_bytes(member)?);
+        }
+
+        let set = MemoryStore::set_mut(data, &key, false)?;
+        let values = members
+          .iter()
+          .map(|m| bool_to_value(set.as_ref().map(|s| s.contains(m)).unwrap_or(false)))
+          .collect();
+        RedisValue::Array(values)
+      }
+      "SCARD" => {
+        let len = MemoryStore::set_mut(data, &to_bytes(arg(0)?)?, false)?
+          .map(|s| s.len())
+          .unwrap_or(0);
+        RedisValue::Integer(len as i64)
+      }
+      "SPOP" => {
+        let key = to_bytes(arg(0)?)?;
+        let count = match args.get(1) {
+          Some(count) => Some(to_i64(count)?.max(0) as usize),
+          None => None,
+        };
+
+        let popped: Vec<Bytes> = match MemoryStore::set_mut(data, &key, false)? {
+          Some(set) => {
+            let members: Vec<Bytes> = set.iter().take(count.unwrap_or(1)).cloned().collect();
+            for member in members.iter() {
+              set.remove(member);
+            }
+            members
+          }
+          None => Vec::new(),
+        };
+        MemoryStore::remove_if_empty(data, &key);
+
+        let mut values: Vec<RedisValue> = popped.into_iter().map(bytes_to_value).collect();
+        if count.is_some() {
+          RedisValue::Array(values)
+        } else {
+          values.pop().unwrap_or(RedisValue::Null)
+        }
+      }
+      "SINTER" | "SUNION" | "SDIFF" => MemoryStore::set_op(data, args, &command.cmd)?,
+
+      // sorted sets
+      "ZADD" => MemoryStore::zadd(data, args)?,
+      "ZREM" => {
+        let key = to_bytes(arg(0)?)?;
+        let mut members = Vec::with_capacity(args.len());
+        for member in args[1 ..].iter() {
+          members.push(to_bytes(member)?);
+        }
+
+        let removed = match MemoryStore::zset_mut(data, &key, false)? {
+          Some(zset) => members.iter().filter(|m| zset.remove(*m).is_some()).count(),
+          None => 0,
+        };
+        MemoryStore::remove_if_empty(data, &key);
+        RedisValue::Integer(removed as i64)
+      }
+      "ZSCORE" => {
+        let (key, member) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?);
+        MemoryStore::zset_mut(data, &key, false)?
+          .and_then(|z| z.get(&member).cloned())
+          .map(score_to_value)
+          .unwrap_or(RedisValue::Null)
+      }
+      "ZMSCORE" => {
+        let key = to_bytes(arg(0)?)?;
+        let mut members = Vec::with_capacity(args.len());
+        for member in args[1 ..].iter() {
+          members.push(to_bytes(member)?);
+        }
+
+        let zset = MemoryStore::zset_mut(data, &key, false)?;
+        let values = members
+          .iter()
+          .map(|m| {
+            zset
+              .as_ref()
+              .and_then(|z| z.get(m).cloned())
+              .map(score_to_value)
+              .unwrap_or(RedisValue::Null)
+          })
+          .collect();
+        RedisValue::Array(values)
+      }
+      "ZCARD" => {
+        let len = MemoryStore::zset_mut(data, &to_bytes(arg(0)?)?, false)?
+          .map(|z| z.len())
+          .unwrap_or(0);
+        RedisValue::Integer(len as i64)
+      }
+      "ZINCRBY" => {
+        let (key, amount, member) = (to_bytes(arg(0)?)?, to_f64(arg(1)?)?, to_bytes(arg(2)?)?);
+        let zset = MemoryStore::zset_mut(data, &key, true)?.unwrap();
+        let next = zset.get(&member).cloned().unwrap_or(0.0) + amount;
+
+        zset.insert(member, next);
+        score_to_value(next)
+      }
+      "ZCOUNT" => {
+        let (key, min, max) = (to_bytes(arg(0)?)?, to_score_bound(arg(1)?)?, to_score_bound(arg(2)?)?);
+        let count = MemoryStore::zrange_by_score(data, &key, min, max, false, None)?.len();
+        RedisValue::Integer(count as i64)
+      }
+      "ZRANK" | "ZREVRANK" => {
+        let (key, member) = (to_bytes(arg(0)?)?, to_bytes(arg(1)?)?);
+        let rank = match MemoryStore::zset_mut(data, &key, false)? {
+          Some(zset) => {
+            let mut members = sort_zset(zset);
+            if &*command.cmd == "ZREVRANK" {
+              members.reverse();
+            }
+            members.iter().position(|(m, _)| *m == member)
+          }
+          None => None,
+        };
+
+        rank.map(|r| RedisValue::Integer(r as i64)).unwrap_or(RedisValue::Null)
+      }
+      "ZRANGE" => {
+        let key = to_bytes(arg(0)?)?;
+        let (withscores, limit, by_score, rev) = MemoryStore::parse_range_options(&args[3.min(args.len()) ..])?;
+
+        let members = if by_score {
+          // with REV the arguments are provided as `max min`
+          let (min, max) = if rev { (arg(2)?, arg(1)?) } else { (arg(1)?, arg(2)?) };
+          let (min, max) = (to_score_bound(min)?, to_score_bound(max)?);
+          MemoryStore::zrange_by_score(data, &key, min, max, rev, limit)?
+        } else {
+          MemoryStore::zrange_by_index(data, &key, to_i64(arg(1)?)?, to_i64(arg(2)?)?, rev)?
+        };
+        zset_values(members, withscores)
+      }
+      "ZREVRANGE" => {
+        let key = to_bytes(arg(0)?)?;
+        let (withscores, _, _, _) = MemoryStore::parse_range_options(&args[3.min(args.len()) ..])?;
+        let members = MemoryStore::zrange_by_index(data, &key, to_i64(arg(1)?)?, to_i64(arg(2)?)?, true)?;
+        zset_values(members, withscores)
+      }
+      "ZRANGEBYSCORE" | "ZREVRANGEBYSCORE" => {
+        let key = to_bytes(arg(0)?)?;
+        let rev = &*command.cmd == "ZREVRANGEBYSCORE";
+        let (withscores, limit, _, _) = MemoryStore::parse_range_options(&args[3.min(args.len()) ..])?;
+        let (min, max) = if rev { (arg(2)?, arg(1)?) } else { (arg(1)?, arg(2)?) };
+        let (min, max) = (to_score_bound(min)?, to_score_bound(max)?);
+
+        let members = MemoryStore::zrange_by_score(data, &key, min, max, rev, limit)?;
+        zset_values(members, withscores)
+      }
+      "ZPOPMIN" => MemoryStore::zpop(data, args, false)?,
+      "ZPOPMAX" => MemoryStore::zpop(data, args, true)?,
+
+      _ => {
+        return Err(RedisError::new(
+          RedisErrorKind::InvalidCommand,
+          format!("Unsupported mocked command: {}", command.cmd),
+        ))
+      }
+    };
+
+    Ok(result)
+  }
+}
+
+impl Mocks for MemoryStore {
+  fn process_command(&self, command: MockCommand) -> Result<RedisValue, RedisError> {
+    self.process(command)
+  }
+}
+
+/// Convert a value returned from a mocking layer into a response frame, as if it came from the server.
+fn value_to_frame(value: RedisValue) -> Resp3Frame {
+  match value {
+    RedisValue::Null => Resp3Frame::Null,
+    RedisValue::Queued => Resp3Frame::SimpleString {
+      data: Bytes::from_static(crate::types::QUEUED.as_bytes()),
+      attributes: None,
+    },
+    RedisValue::Boolean(data) => Resp3Frame::Boolean { data, attributes: None },
+    RedisValue::Integer(data) => Resp3Frame::Number { data, attributes: None },
+    RedisValue::Double(data) => Resp3Frame::Double { data, attributes: None },
+    RedisValue::String(s) => Resp3Frame::BlobString {
+      data: s.into_inner(),
+      attributes: None,
+    },
+    RedisValue::Bytes(data) => Resp3Frame::BlobString { data, attributes: None },
+    RedisValue::Array(values) => Resp3Frame::Array {
+      data: values.into_iter().map(value_to_frame).collect(),
+      attributes: None,
+    },
+    RedisValue::Map(map) => Resp3Frame::Map {
+      data: map
+        .inner()
+        .into_iter()
+        .map(|(key, value)| {
+          let key = Resp3Frame::BlobString {
+            data: key.into_bytes(),
+            attributes: None,
+          };
+          (key, value_to_frame(value))
+        })
+        .collect(),
+      attributes: None,
+    },
+  }
+}
+
+/// Process a command with the client's mocking layer and respond to the caller.
+pub(crate) fn process_command(inner: &Arc<RedisClientInner>, mut command: RedisCommand) {
+  let mocks = match inner.config.read().mocks.clone() {
+    Some(mocks) => mocks,
+    None => {
+      if let Some(tx) = command.tx.take() {
+        let _ = tx.send(Err(RedisError::new(RedisErrorKind::Config, "Missing mocking layer.")));
+      }
+      return;
+    }
+  };
+
+  if command.kind.is_split() {
+    if let RedisCommandKind::_Split(ref split) = command.kind {
+      if let Some(tx) = split.tx.write().take() {
+        let _ = tx.send(Err(RedisError::new(
+          RedisErrorKind::Unknown,
+          "Cannot split a mocked client.",
+        )));
+      }
+    }
+    return;
+  }
+
+  _trace!(inner, "Sending mocked command: {}", command.kind.to_str_debug());
+  let result = mocks.process_command(MockCommand::from_command(&command));
+
+  if let Some(response) = command.kind.all_nodes_response() {
+    if let Some(tx) = response.take_tx() {
+      let _ = tx.send(result.map(|_| ()));
+    }
+  } else if let Some(tx) = command.tx.take() {
+    let _ = tx.send(result.map(value_to_frame));
+  }
+
+  if let Some(tx) = command.take_resp_tx() {
+    let _ = tx.send(());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::clients::RedisClient;
+  use crate::interfaces::*;
+  use crate::types::{ClientState, RedisConfig, ServerConfig};
+  use std::collections::HashMap;
+
+  fn cmd(cmd: &'static str, args: Vec<RedisValue>) -> MockCommand {
+    MockCommand::new(cmd, args)
+  }
+
+  fn create_mocked_client() -> RedisClient {
+    let config = RedisConfig {
+      mocks: Some(Arc::new(MemoryStore::new())),
+      ..Default::default()
+    };
+    let client = RedisClient::new(config);
+    let _ = client.connect(None);
+    client
+  }
+
+  #[test]
+  fn should_split_custom_command_strings() {
+    let command = MockCommand::new("client setname", vec![]);
+    assert_eq!(&*command.cmd, "CLIENT");
+    assert_eq!(command.subcommand.as_deref(), Some("SETNAME"));
+  }
+
+
+  #[test]
+  fn should_normalize_ranges() {
+    assert_eq!(normalize_range(0, -1, 3), Some((0, 2)));
+    assert_eq!(normalize_range(-2, -1, 3), Some((1, 2)));
+    assert_eq!(normalize_range(1, 100, 3), Some((1, 2)));
+    assert_eq!(normalize_range(5, 10, 3), None);
+    assert_eq!(normalize_range(0, -1, 0), None);
+  }
+
+  #[test]
+  fn should_set_and_get_strings() {
+    let store = MemoryStore::new();
+    let result = store.process(cmd("SET", vec!["foo".into(), "bar".into()])).unwrap();
+    assert!(result.is_ok());
+    assert_eq!(store.process(cmd("GET", vec!["foo".into()])).unwrap(), "bar".into());
+
+    let result = store
+      .process(cmd("SET", vec!["foo".into(), "baz".into(), "NX".into()]))
+      .unwrap();
+    assert_eq!(result, RedisValue::Null);
+    let result = store
+      .process(cmd("SET", vec!["foo".into(), "baz".into(), "XX".into(), "GET".into()]))
+      .unwrap();
+    assert_eq!(result, "bar".into());
+    assert_eq!(store.process(cmd("GET", vec!["foo".into()])).unwrap(), "baz".into());
+  }
+
+  #[test]
+  fn should_incr_and_decr_values() {
+    let store = MemoryStore::new();
+    assert_eq!(store.process(cmd("INCR", vec!["foo".into()])).unwrap(), 1.into());
+    assert_eq!(
+      store.process(cmd("INCRBY", vec!["foo".into(), 10.into()])).unwrap(),
+      11.into()
+    );
+    assert_eq!(store.process(cmd("DECR", vec!["foo".into()])).unwrap(), 10.into());
+
+    let _ = store.process(cmd("SET", vec!["bar".into(), "abc".into()])).unwrap();
+    assert!(store.process(cmd("INCR", vec!["bar".into()])).is_err());
+  }
+
+  #[test]
+  fn should_return_wrongtype_errors() {
+    let store = MemoryStore::new();
+    let _ = store.process(cmd("LPUSH", vec!["foo".into(), "bar".into()])).unwrap();
+
+    let error = store.process(cmd("GET", vec!["foo".into()])).unwrap_err();
+    assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+    assert!(store.process(cmd("HGET", vec!["foo".into(), "bar".into()])).is_err());
+  }
+
+  #[test]
+  fn should_expire_keys() {
+    let store = MemoryStore::new();
+    let _ = store
+      .process(cmd("SET", vec!["foo".into(), "bar".into(), "PX".into(), 100.into()]))
+      .unwrap();
+    let ttl = store.process(cmd("PTTL", vec!["foo".into()])).unwrap();
+    assert!(ttl.as_i64().unwrap() > 0);
+
+    let _ = store
+      .process(cmd("PEXPIREAT", vec!["foo".into(), (now_ms() - 1).into()]))
+      .unwrap();
+    assert_eq!(store.process(cmd("GET", vec!["foo".into()])).unwrap(), RedisValue::Null);
+    assert_eq!(store.process(cmd("TTL", vec!["foo".into()])).unwrap(), (-2).into());
+
+    let _ = store.process(cmd("SET", vec!["bar".into(), "baz".into()])).unwrap();
+    let _ = store.process(cmd("EXPIRE", vec!["bar".into(), 100.into()])).unwrap();
+    assert_eq!(store.process(cmd("PERSIST", vec!["bar".into()])).unwrap(), 1.into());
+    assert_eq!(store.process(cmd("TTL", vec!["bar".into()])).unwrap(), (-1).into());
+  }
+
+  #[test]
+  fn should_modify_lists() {
+    let store = MemoryStore::new();
+    let _ = store
+      .process(cmd("RPUSH", vec!["foo".into(), "a".into(), "b".into(), "c".into()]))
+      .unwrap();
+    let _ = store.process(cmd("LPUSH", vec!["foo".into(), "z".into()])).unwrap();
+
+    let values = store
+      .process(cmd("LRANGE", vec!["foo".into(), 0.into(), (-1).into()]))
+      .unwrap();
+    assert_eq!(values, RedisValue::Array(vec!["z".into(), "a".into(), "b".into(), "c".into()]));
+    assert_eq!(store.process(cmd("RPOP", vec!["foo".into()])).unwrap(), "c".into());
+    assert_eq!(
+      store.process(cmd("LINDEX", vec!["foo".into(), (-1).into()])).unwrap(),
+      "b".into()
+    );
+
+    let _ = store
+      .process(cmd("LTRIM", vec!["foo".into(), 1.into(), 1.into()]))
+      .unwrap();
+    assert_eq!(store.process(cmd("LLEN", vec!["foo".into()])).unwrap(), 1.into());
+  }
+
+  #[test]
+  fn should_range_sorted_sets() {
+    let store = MemoryStore::new();
+    let _ = store
+      .process(cmd("ZADD", vec![
+        "foo".into(),
+        1.0.into(),
+        "a".into(),
+        2.0.into(),
+        "b".into(),
+        3.0.into(),
+        "c".into(),
+      ]))
+      .unwrap();
+
+    let values = store
+      .process(cmd("ZRANGE", vec!["foo".into(), 0.into(), 1.into()]))
+      .unwrap();
+    assert_eq!(values, RedisValue::Array(vec!["a".into(), "b".into()]));
+    let values = store
+      .process(cmd("ZRANGEBYSCORE", vec![
+        "foo".into(),
+        "(1".into(),
+        "+inf".into(),
+        "WITHSCORES".into(),
+      ]))
+      .unwrap();
+    assert_eq!(
+      values,
+      RedisValue::Array(vec!["b".into(), "2".into(), "c".into(), "3".into()])
+    );
+    assert_eq!(
+      store.process(cmd("ZREVRANK", vec!["foo".into(), "a".into()])).unwrap(),
+      2.into()
+    );
+    assert_eq!(
+      store.process(cmd("ZPOPMIN", vec!["foo".into()])).unwrap(),
+      RedisValue::Array(vec!["a".into(), "1".into()])
+    );
+  }
+
+  #[tokio::test]
+  async fn should_use_mocked_client_interfaces() {
+    let client = create_mocked_client();
+    client.wait_for_connect().await.unwrap();
+
+    let _: () = client.set("foo", "bar", None, None, false).await.unwrap();
+    let foo: String = client.get("foo").await.unwrap();
+    assert_eq!(foo, "bar");
+
+    let _: () = client.hset("baz", ("a", 1)).await.unwrap();
+    let _: () = client.hset("baz", ("b", 2)).await.unwrap();
+    let values: HashMap<String, i64> = client.hgetall("baz").await.unwrap();
+    assert_eq!(values.get("a"), Some(&1));
+    assert_eq!(values.get("b"), Some(&2));
+
+    let deleted: i64 = client.del(vec!["foo", "baz"]).await.unwrap();
+    assert_eq!(deleted, 2);
+    let foo: Option<String> = client.get("foo").await.unwrap();
+    assert!(foo.is_none());
+
+    let _ = client.quit().await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn should_read_parent_state_from_replicas() {
+    let config = RedisConfig {
+      mocks: Some(Arc::new(MemoryStore::new())),
+      ..Default::default()
+    };
+    let client = RedisClient::new(config);
+    let replicas = client.replicas();
+    assert_eq!(replicas.state(), ClientState::Disconnected);
+
+    let _ = client.connect(None);
+    client.wait_for_connect().await.unwrap();
+    assert_eq!(replicas.state(), ClientState::Connected);
+
+    client.inner.config.write().database = Some(2);
+    assert_eq!(replicas.client_config().database, Some(2));
+    let _: () = client.set("foo", "bar", None, None, false).await.unwrap();
+    let foo: String = replicas.get("foo").await.unwrap();
+    assert_eq!(foo, "bar");
+  }
+
+  #[tokio::test]
+  async fn should_pipeline_mocked_commands() {
+    let client = create_mocked_client();
+    client.wait_for_connect().await.unwrap();
+    let _: () = client.set("foo", "bar", None, None, false).await.unwrap();
+
+    let pipeline = client.pipeline();
+    let _: () = pipeline.incr("baz").await.unwrap();
+    let _: () = pipeline.hgetall("foo").await.unwrap();
+    let _: () = pipeline.get("foo").await.unwrap();
+    assert_eq!(pipeline.len(), 3);
+
+    let results = pipeline.try_all::<RedisValue>().await.unwrap();
+    assert_eq!(results[0].as_ref().unwrap().as_i64(), Some(1));
+    assert_eq!(*results[1].as_ref().unwrap_err().kind(), RedisErrorKind::InvalidArgument);
+    assert_eq!(results[2].as_ref().unwrap().as_str().unwrap(), "bar");
+    assert!(pipeline.is_empty());
+
+    let _: () = pipeline.incr("baz").await.unwrap();
+    let _: () = pipeline.incr("baz").await.unwrap();
+    let (first, second): (i64, i64) = pipeline.all().await.unwrap();
+    assert_eq!((first, second), (2, 3));
+  }
+
+  #[tokio::test]
+  async fn should_buffer_commands() {
+    let buffer = Arc::new(Buffer::new());
+    let config = RedisConfig {
+      mocks: Some(buffer.clone()),
+      ..Default::default()
+    };
+    let client = RedisClient::new(config);
+    let _ = client.connect(None);
+    client.wait_for_connect().await.unwrap();
+
+    let _: () = client.set("foo", "bar", None, None, false).await.unwrap();
+    let _: () = client.incr("baz").await.unwrap();
+    let commands: Vec<(String, Vec<String>)> = buffer
+      .take()
+      .into_iter()
+      .map(|c| (c.cmd.to_string(), c.args.iter().filter_map(|a| a.as_string()).collect()))
+      .collect();
+    assert_eq!(commands, vec![
+      ("SET".to_owned(), vec!["foo".to_owned(), "bar".to_owned()]),
+      ("INCR".to_owned(), vec!["baz".to_owned()])
+    ]);
+  }
+
+  #[tokio::test]
+  async fn should_connect_without_mocking_layer() {
+    let config = RedisConfig {
+      server: ServerConfig::new_centralized("127.0.0.1", 1),
+      ..Default::default()
+    };
+    let client = RedisClient::new(config);
+    let _ = client.connect(None);
+
+    assert!(client.wait_for_connect().await.is_err());
+  }
+}
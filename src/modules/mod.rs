@@ -1,8 +1,15 @@
 pub mod backchannel;
+pub mod cache;
+#[cfg(feature = "credential-provider")]
+pub mod credentials;
 /// Utility functions for manipulating global values that can affect performance.
 pub mod globals;
 pub mod inner;
 pub mod metrics;
+#[cfg(feature = "mocks")]
+pub mod mocks;
 /// Client pooling structs.
 pub mod pool;
 pub mod response;
+#[cfg(feature = "serde-value")]
+pub mod serde_value;
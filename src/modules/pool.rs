@@ -1,23 +1,163 @@
 use crate::clients::RedisClient;
 use crate::error::{RedisError, RedisErrorKind};
-use crate::interfaces::ClientLike;
-use crate::types::{ConnectHandle, ReconnectPolicy, RedisConfig};
+use crate::interfaces::{wrap_async, *};
+use crate::modules::inner::RedisClientInner;
+use crate::types::{
+  ClientState, ConnectHandle, PerformanceConfig, PoolConfig, ReconnectPolicy, RedisConfig, RespVersion,
+};
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use crate::types::TlsConfig;
 use crate::utils;
+use bytes_utils::Str;
 use futures::future::{join_all, try_join_all};
+use futures::Future;
+use parking_lot::{Mutex, RwLock};
+use std::collections::hash_map::DefaultHasher;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 use std::sync::atomic::AtomicUsize;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// Map a hash to one of `buckets` buckets with a [jump consistent hash](https://arxiv.org/abs/1406.2294).
+///
+/// When a bucket is added only the keys that move to the new bucket change buckets, about `1 / buckets` of the keys.
+fn jump_hash(mut key: u64, buckets: usize) -> usize {
+  let (mut bucket, mut next) = (-1_i64, 0_i64);
+
+  while next < buckets as i64 {
+    bucket = next;
+    key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+    next = ((bucket + 1) as f64 * ((1_i64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+  }
+  bucket as usize
+}
+
+/// Estimate the load on a client from the number of queued and in-flight commands.
+fn client_load(client: &RedisClient) -> usize {
+  utils::read_atomic(&client.inner.cmd_buffer_len).saturating_add(client.inner.in_flight_commands())
+}
+
+/// Whether or not the client can accept commands from the pool without interleaving them with another caller's
+/// transaction.
+#[cfg(not(feature = "pool-prefer-active"))]
+fn is_available(client: &RedisClient) -> bool {
+  client.inner.multi_block.read().is_none()
+}
+
+/// Whether or not the client is connected and can accept commands from the pool without interleaving them with
+/// another caller's transaction.
+#[cfg(feature = "pool-prefer-active")]
+fn is_available(client: &RedisClient) -> bool {
+  client.inner.multi_block.read().is_none() && client.is_connected()
+}
+
+/// Whether or not a `PING` would be added to a caller's transaction or wait behind a caller's blocking command.
+async fn is_busy(client: &RedisClient) -> bool {
+  client.inner.multi_block.read().is_some() || client.inner.backchannel.read().await.is_blocked()
+}
+
+/// Check that a pooled client responds to `PING`, reconnecting the client if not.
+///
+/// Clients within a `MULTI` block or running a blocking command are skipped.
+async fn check_client_health(client: RedisClient, policy: Option<ReconnectPolicy>, timeout_ms: u64) {
+  let inner = &client.inner;
+  if client.is_connected() {
+    if is_busy(&client).await {
+      _trace!(inner, "Skipping health check on busy pooled client.");
+      return;
+    }
+
+    let duration = Duration::from_millis(timeout_ms);
+    if let Ok(Ok(_)) = timeout(duration, client.ping()).await {
+      return;
+    }
+
+    _warn!(inner, "Closing pooled client after failed health check.");
+    let _ = timeout(duration, client.quit()).await;
+  }
+
+  // the connection task stores the command receiver when it exits, at which point the client can connect again
+  if client.state() == ClientState::Disconnected && inner.has_command_rx() {
+    _debug!(inner, "Reconnecting pooled client after failed health check.");
+    let _ = client.connect(policy);
+  }
+}
+
+/// Run health checks against the active clients in the pool until the pool is dropped or the task is aborted.
+async fn run_health_checks(pool: Weak<RedisPoolInner>, interval_ms: u64) {
+  loop {
+    sleep(Duration::from_millis(interval_ms)).await;
+
+    let inner = match pool.upgrade() {
+      Some(inner) => inner,
+      None => break,
+    };
+    let policy = match inner.policy.read().clone() {
+      Some(policy) => policy,
+      None => continue,
+    };
+
+    let size = utils::read_atomic(&inner.size);
+    let timeout_ms = inner.config.health_check_timeout_ms;
+    let checks = inner.clients[.. size]
+      .iter()
+      .map(|client| check_client_health(client.clone(), policy.clone(), timeout_ms));
+    let _ = join_all(checks).await;
+  }
+}
 
 /// The inner state used by a `RedisPool`.
-#[derive(Clone)]
 pub(crate) struct RedisPoolInner {
+  /// Every client that can be used by the pool. Only the first `size` clients are active.
   clients: Vec<RedisClient>,
+  size: Arc<AtomicUsize>,
   last: Arc<AtomicUsize>,
+  config: PoolConfig,
+  /// The reconnect policy provided to `connect`, or `None` if the pool is not connected.
+  policy: RwLock<Option<Option<ReconnectPolicy>>>,
+  /// A lock held while adding or removing active clients.
+  resize_lock: Mutex<()>,
+  health_check_jh: Mutex<Option<JoinHandle<()>>>,
 }
 
-/// A struct to pool multiple Redis clients together into one interface that will round-robin requests among clients,
-/// preferring clients with an active connection if specified.
+/// A struct to pool multiple Redis clients together into one interface.
+///
+/// The pool implements the same command interfaces as the [RedisClient](crate::clients::RedisClient), sending each
+/// command to the client with the fewest queued and in-flight commands. Clients within a `MULTI` block are skipped so
+/// that commands from other callers are not added to the transaction.
+///
+/// Commands that change connection state, such as `AUTH`, `HELLO`, `SELECT`, and `CLIENT SETNAME`, are sent to
+/// every active client in the pool. `SELECT` also changes the database used by clients that are added or reconnected
+/// later.
+///
+/// The pool does not implement the transaction, pubsub, or metrics interfaces since those depend on the state of a
+/// single connection. Sequences such as `WATCH` followed by `MULTI` should use [with_affinity](Self::with_affinity)
+/// or [least_loaded](Self::least_loaded) to run on one client.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::pool::RedisPool;
+/// let pool = RedisPool::new(RedisConfig::default(), 5)?;
+/// let _ = pool.connect(None);
+/// let _ = pool.wait_for_connect().await?;
+///
+/// // each command is sent to the least loaded client
+/// let _: () = pool.set("foo", 1, None, None, false).await?;
+/// let value: i64 = pool.incr("foo").await?;
+///
+/// // run a WATCH and MULTI sequence on the same client
+/// let client = pool.with_affinity("foo");
+/// let _ = client.watch("foo").await?;
+/// let trx = client.multi(true).await?;
+/// let _: () = trx.incr("foo").await?;
+/// let _: Option<Vec<i64>> = trx.exec().await?;
+/// ```
 #[derive(Clone)]
 pub struct RedisPool {
   inner: Arc<RedisPoolInner>,
@@ -50,78 +190,486 @@ impl<'a> From<&'a RedisPool> for RedisClient {
 }
 
 impl RedisPool {
-  /// Create a new pool without connecting to the server.
+  /// Create a new pool with a fixed number of clients without connecting to the server.
+  ///
+  /// The pool can be shrunk and grown back to `size` clients with [resize](Self::resize).
   pub fn new(config: RedisConfig, size: usize) -> Result<Self, RedisError> {
     if size > 0 {
-      let mut clients = Vec::with_capacity(size);
-      for _ in 0..size {
-        clients.push(RedisClient::new(config.clone()));
-      }
-      let last = Arc::new(AtomicUsize::new(0));
+      let pool_config = PoolConfig {
+        min_size: 1,
+        max_size: size,
+        scale_up_threshold: None,
+        ..Default::default()
+      };
 
-      Ok(RedisPool {
-        inner: Arc::new(RedisPoolInner { clients, last }),
+      RedisPool::new_elastic(config, pool_config).map(|pool| {
+        utils::set_atomic(&pool.inner.size, size);
+        pool
       })
     } else {
       Err(RedisError::new(RedisErrorKind::Config, "Pool cannot be empty."))
     }
   }
 
-  /// Read the individual clients in the pool.
+  /// Create a new pool that starts with `min_size` clients and can grow to `max_size` clients, without connecting
+  /// to the server.
+  ///
+  /// See [PoolConfig](crate::types::PoolConfig) for more information.
+  pub fn new_elastic(config: RedisConfig, pool_config: PoolConfig) -> Result<Self, RedisError> {
+    if pool_config.min_size == 0 || pool_config.min_size > pool_config.max_size {
+      return Err(RedisError::new(RedisErrorKind::Config, "Invalid pool size bounds."));
+    }
+
+    let mut clients = Vec::with_capacity(pool_config.max_size);
+    for _ in 0 .. pool_config.max_size {
+      clients.push(RedisClient::new(config.clone()));
+    }
+
+    Ok(RedisPool {
+      inner: Arc::new(RedisPoolInner {
+        clients,
+        size: Arc::new(AtomicUsize::new(pool_config.min_size)),
+        last: Arc::new(AtomicUsize::new(0)),
+        config: pool_config,
+        policy: RwLock::new(None),
+        resize_lock: Mutex::new(()),
+        health_check_jh: Mutex::new(None),
+      }),
+    })
+  }
+
+  /// Read the active clients in the pool.
   pub fn clients(&self) -> &[RedisClient] {
-    &self.inner.clients
+    &self.inner.clients[.. utils::read_atomic(&self.inner.size)]
   }
 
-  /// Connect each client to the server, returning the task driving each connection.
+  /// Read the pool config.
+  pub fn pool_config(&self) -> &PoolConfig {
+    &self.inner.config
+  }
+
+  /// Connect each active client to the server, returning the task driving each connection.
   ///
-  /// The caller is responsible for calling `wait_for_connect` or any `on_*` functions on each client.
+  /// Clients added to the pool later will use the same reconnect policy. The caller is responsible for calling
+  /// `wait_for_connect` or any `on_*` functions on each client.
   pub fn connect(&self, policy: Option<ReconnectPolicy>) -> Vec<ConnectHandle> {
-    self.inner.clients.iter().map(|c| c.connect(policy.clone())).collect()
+    let _guard = self.inner.resize_lock.lock();
+    utils::set_locked(&self.inner.policy, Some(policy.clone()));
+
+    self.clients().iter().map(|c| c.connect(policy.clone())).collect()
   }
 
-  /// Wait for all the clients to connect to the server.
+  /// Wait for all the active clients to connect to the server.
   pub async fn wait_for_connect(&self) -> Result<(), RedisError> {
-    let futures = self.inner.clients.iter().map(|c| c.wait_for_connect());
+    let futures = self.clients().iter().map(|c| c.wait_for_connect());
     let _ = try_join_all(futures).await?;
 
     Ok(())
   }
 
-  /// Read the size of the pool.
+  /// Replace the [TlsConfig](crate::types::TlsConfig) used by each client in the pool, including inactive clients.
+  ///
+  /// See [update_tls_config](crate::interfaces::ClientLike::update_tls_config) for more information.
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "enable-tls", feature = "enable-rustls"))))]
+  pub fn update_tls_config(&self, config: TlsConfig) {
+    for client in self.inner.clients.iter() {
+      client.update_tls_config(config.clone());
+    }
+  }
+
+  /// Read the number of active clients in the pool.
   pub fn size(&self) -> usize {
-    self.inner.clients.len()
+    utils::read_atomic(&self.inner.size)
+  }
+
+  /// Change the number of active clients in the pool.
+  ///
+  /// New clients are connected with the reconnect policy provided to [connect](Self::connect), and this function
+  /// waits for them to connect. Removed clients are closed with `QUIT`, which will also end any transaction or
+  /// blocking command that a caller is running on them.
+  pub async fn resize(&self, size: usize) -> Result<(), RedisError> {
+    let (min, max) = (self.inner.config.min_size, self.inner.config.max_size);
+    if size < min || size > max {
+      return Err(RedisError::new(
+        RedisErrorKind::InvalidArgument,
+        format!("Pool size must be between {} and {}.", min, max),
+      ));
+    }
+
+    let (previous, connected) = {
+      let _guard = self.inner.resize_lock.lock();
+      let previous = utils::read_atomic(&self.inner.size);
+      let policy = self.inner.policy.read().clone();
+
+      if size > previous {
+        if let Some(ref policy) = policy {
+          for client in self.inner.clients[previous .. size].iter() {
+            let _ = client.connect(policy.clone());
+          }
+        }
+      }
+      utils::set_atomic(&self.inner.size, size);
+      (previous, policy.is_some())
+    };
+
+    if size > previous && connected {
+      let futures = self.inner.clients[previous .. size].iter().map(|c| c.wait_for_connect());
+      let _ = try_join_all(futures).await?;
+    } else if size < previous {
+      let futures = self.inner.clients[size .. previous].iter().map(|c| c.quit());
+      let _ = join_all(futures).await;
+    }
+
+    Ok(())
+  }
+
+  /// Add an active client if the pool is connected and has not reached the maximum size.
+  fn scale_up(&self, observed_size: usize) {
+    let _guard = match self.inner.resize_lock.try_lock() {
+      Some(guard) => guard,
+      // another caller is already changing the pool size
+      None => return,
+    };
+    let size = utils::read_atomic(&self.inner.size);
+    if size != observed_size || size >= self.inner.config.max_size {
+      return;
+    }
+
+    if let Some(policy) = self.inner.policy.read().clone() {
+      let client = &self.inner.clients[size];
+      let inner = &client.inner;
+      _debug!(inner, "Adding client to pool with {} active clients.", size);
+
+      let _ = client.connect(policy);
+      utils::set_atomic(&self.inner.size, size + 1);
+    }
+  }
+
+  /// Start a task that periodically sends `PING` through each active client, replacing the connection of any client
+  /// that does not respond within the `health_check_timeout_ms` or that is no longer trying to reconnect. Clients
+  /// within a `MULTI` block or running a blocking command are not checked.
+  ///
+  /// Replaced clients are closed with `QUIT` and then connected again, which will also close any `on_*` streams on
+  /// those clients. The task stops when [quit_pool](Self::quit_pool) is called or when the pool is dropped.
+  pub fn start_health_checks(&self) {
+    let pool = Arc::downgrade(&self.inner);
+    let interval_ms = self.inner.config.health_check_interval_ms;
+    let jh = tokio::spawn(run_health_checks(pool, interval_ms));
+
+    if let Some(jh) = self.inner.health_check_jh.lock().replace(jh) {
+      jh.abort();
+    }
+  }
+
+  /// Read the client with the fewest queued and in-flight commands, skipping clients within a `MULTI` block.
+  ///
+  /// This is the client used by the command interfaces on the pool. If the `pool-prefer-active` feature is enabled
+  /// clients with an active connection are preferred over disconnected clients. If the least loaded client has more
+  /// than `scale_up_threshold` queued commands another client is added to the pool.
+  pub fn least_loaded(&self) -> &RedisClient {
+    let clients = self.clients();
+    let start = utils::read_atomic(&self.inner.last).wrapping_add(1);
+
+    let mut selected: Option<(usize, usize)> = None;
+    for offset in 0 .. clients.len() {
+      let idx = start.wrapping_add(offset) % clients.len();
+      let client = &clients[idx];
+      if !is_available(client) {
+        continue;
+      }
+
+      let load = client_load(client);
+      if selected.map(|(_, min)| load < min).unwrap_or(true) {
+        selected = Some((idx, load));
+      }
+      if load == 0 {
+        break;
+      }
+    }
+
+    if let (Some((idx, _)), Some(threshold)) = (selected, self.inner.config.scale_up_threshold) {
+      if clients[idx].command_queue_len() > threshold {
+        self.scale_up(clients.len());
+      }
+    }
+
+    // fall back to round-robin if every client is disconnected or busy with a transaction
+    let idx = selected.map(|(idx, _)| idx).unwrap_or(start % clients.len());
+    utils::set_atomic(&self.inner.last, idx);
+    &clients[idx]
+  }
+
+  /// Read the client assigned to the provided key.
+  ///
+  /// The same key always maps to the same client while the pool size does not change, so callers can use this to run
+  /// a sequence of commands such as `WATCH` and `MULTI` on one connection without holding a reference to the client.
+  ///
+  /// Keys are assigned with a consistent hash, so when the pool is resized only the keys assigned to the added or
+  /// removed clients move. Callers should not hold an assignment across a [resize](Self::resize).
+  pub fn with_affinity<K>(&self, key: K) -> &RedisClient
+  where
+    K: Hash,
+  {
+    let clients = self.clients();
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    &clients[jump_hash(hasher.finish(), clients.len())]
+  }
+
+  /// Run the function on each active client in the pool concurrently, returning the first error.
+  async fn fan_out<F, Fut>(&self, func: F) -> Result<(), RedisError>
+  where
+    F: Fn(RedisClient) -> Fut,
+    Fut: Future<Output = Result<(), RedisError>>,
+  {
+    let _ = try_join_all(self.clients().iter().map(|client| func(client.clone()))).await?;
+    Ok(())
   }
 
   /// Read the client that should run the next command.
   #[cfg(feature = "pool-prefer-active")]
   pub fn next(&self) -> &RedisClient {
-    let mut idx = utils::incr_atomic(&self.inner.last) % self.inner.clients.len();
+    let clients = self.clients();
+    let mut idx = utils::incr_atomic(&self.inner.last) % clients.len();
 
-    for _ in 0..self.inner.clients.len() {
-      let client = &self.inner.clients[idx];
+    for _ in 0 .. clients.len() {
+      let client = &clients[idx];
       if client.is_connected() {
         return client;
       }
-      idx = (idx + 1) % self.inner.clients.len();
+      idx = (idx + 1) % clients.len();
     }
 
-    &self.inner.clients[idx]
+    &clients[idx]
   }
 
   /// Read the client that should run the next command.
   #[cfg(not(feature = "pool-prefer-active"))]
   pub fn next(&self) -> &RedisClient {
-    &self.inner.clients[utils::incr_atomic(&self.inner.last) % self.inner.clients.len()]
+    let clients = self.clients();
+    &clients[utils::incr_atomic(&self.inner.last) % clients.len()]
   }
 
   /// Read the client that ran the last command.
   pub fn last(&self) -> &RedisClient {
-    &self.inner.clients[utils::read_atomic(&self.inner.last) % self.inner.clients.len()]
+    let clients = self.clients();
+    &clients[utils::read_atomic(&self.inner.last) % clients.len()]
   }
 
-  /// Call `QUIT` on each client in the pool.
+  /// Stop any health checks and call `QUIT` on each active client in the pool.
   pub async fn quit_pool(&self) {
-    let futures = self.inner.clients.iter().map(|c| c.quit());
+    if let Some(jh) = self.inner.health_check_jh.lock().take() {
+      jh.abort();
+    }
+    utils::set_locked(&self.inner.policy, None);
+
+    let futures = self.clients().iter().map(|c| c.quit());
     let _ = join_all(futures).await;
   }
 }
+
+/// Connection-level functions are applied to every client in the pool. Functions that read the client's config or
+/// identity use the first client in the pool, which is always active.
+impl ClientLike for RedisPool {
+  #[doc(hidden)]
+  fn inner(&self) -> &Arc<RedisClientInner> {
+    &self.least_loaded().inner
+  }
+
+  fn id(&self) -> &Arc<String> {
+    self.inner.clients[0].id()
+  }
+
+  fn client_config(&self) -> RedisConfig {
+    self.inner.clients[0].client_config()
+  }
+
+  fn client_reconnect_policy(&self) -> Option<ReconnectPolicy> {
+    self.inner.clients[0].client_reconnect_policy()
+  }
+
+  fn protocol_version(&self) -> RespVersion {
+    self.inner.clients[0].protocol_version()
+  }
+
+  fn has_reconnect_policy(&self) -> bool {
+    self.inner.clients[0].has_reconnect_policy()
+  }
+
+  fn is_pipelined(&self) -> bool {
+    self.inner.clients[0].is_pipelined()
+  }
+
+  /// Update the [PerformanceConfig](crate::types::PerformanceConfig) on each client in the pool, including inactive
+  /// clients.
+  fn update_perf_config(&self, config: PerformanceConfig) {
+    for client in self.inner.clients.iter() {
+      client.update_perf_config(config.clone());
+    }
+  }
+
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "enable-tls", feature = "enable-rustls"))))]
+  fn update_tls_config(&self, config: TlsConfig) {
+    RedisPool::update_tls_config(self, config)
+  }
+
+  /// Read the state of the least healthy active client in the pool.
+  fn state(&self) -> ClientState {
+    self
+      .clients()
+      .iter()
+      .map(|client| client.state())
+      .find(|state| *state != ClientState::Connected)
+      .unwrap_or(ClientState::Connected)
+  }
+
+  /// Whether or not every active client in the pool is connected.
+  fn is_connected(&self) -> bool {
+    self.clients().iter().all(|client| client.is_connected())
+  }
+
+  /// Connect each active client in the pool, returning a task that resolves when every connection task finishes.
+  ///
+  /// See [connect](RedisPool::connect) to read the connection task for each client.
+  fn connect(&self, policy: Option<ReconnectPolicy>) -> ConnectHandle {
+    let handles = RedisPool::connect(self, policy);
+
+    tokio::spawn(async move {
+      for result in join_all(handles).await.into_iter() {
+        let _ = result??;
+      }
+      Ok(())
+    })
+  }
+
+  fn wait_for_connect(&self) -> AsyncResult<()> {
+    let pool = self.clone();
+    wrap_async(|| async move { RedisPool::wait_for_connect(&pool).await })
+  }
+
+  /// Listen for protocol and connection errors on every client in the pool, including inactive clients.
+  fn on_error(&self) -> AsyncStream<RedisError> {
+    let (tx, rx) = unbounded_channel();
+    for client in self.inner.clients.iter() {
+      client.inner.error_tx.write().push_back(tx.clone());
+    }
+
+    UnboundedReceiverStream::new(rx).into()
+  }
+
+  /// Stop any health checks and close the connection on each active client. See [quit_pool](RedisPool::quit_pool).
+  fn quit(&self) -> AsyncResult<()> {
+    let pool = self.clone();
+    wrap_async(|| async move {
+      pool.quit_pool().await;
+      Ok(())
+    })
+  }
+}
+
+impl AclInterface for RedisPool {}
+impl ClusterInterface for RedisPool {}
+impl ConfigInterface for RedisPool {}
+impl BitmapInterface for RedisPool {}
+impl GeoInterface for RedisPool {}
+impl HashesInterface for RedisPool {}
+impl HyperloglogInterface for RedisPool {}
+impl KeysInterface for RedisPool {}
+impl LuaInterface for RedisPool {}
+impl FunctionInterface for RedisPool {}
+impl ListInterface for RedisPool {}
+impl MemoryInterface for RedisPool {}
+impl SlowlogInterface for RedisPool {}
+impl SetsInterface for RedisPool {}
+impl SortedSetsInterface for RedisPool {}
+impl HeartbeatInterface for RedisPool {}
+impl StreamsInterface for RedisPool {}
+
+impl ClientInterface for RedisPool {
+  /// Set the name of each active connection in the pool.
+  fn client_setname<S>(&self, name: S) -> AsyncResult<()>
+  where
+    S: Into<Str>,
+  {
+    into!(name);
+    let pool = self.clone();
+    wrap_async(|| async move {
+      pool
+        .fan_out(|client| {
+          let name = name.clone();
+          async move { client.client_setname(name).await }
+        })
+        .await
+    })
+  }
+}
+
+impl AuthInterface for RedisPool {
+  /// Authenticate each active connection in the pool.
+  fn auth<S>(&self, username: Option<String>, password: S) -> AsyncResult<()>
+  where
+    S: Into<Str>,
+  {
+    into!(password);
+    let pool = self.clone();
+    wrap_async(|| async move {
+      pool
+        .fan_out(|client| {
+          let (username, password) = (username.clone(), password.clone());
+          async move { client.auth(username, password).await }
+        })
+        .await
+    })
+  }
+
+  /// Switch the protocol on each active connection in the pool.
+  fn hello(&self, version: RespVersion, auth: Option<(String, String)>) -> AsyncResult<()> {
+    let pool = self.clone();
+    wrap_async(|| async move {
+      pool
+        .fan_out(|client| {
+          let (version, auth) = (version.clone(), auth.clone());
+          async move { client.hello(version, auth).await }
+        })
+        .await
+    })
+  }
+}
+
+impl ServerInterface for RedisPool {
+  /// Select the database on each active connection in the pool, and on any client that connects later.
+  fn select(&self, db: u8) -> AsyncResult<()> {
+    let pool = self.clone();
+    wrap_async(|| async move {
+      let _ = pool.fan_out(|client| async move { client.select(db).await }).await?;
+      for client in pool.inner.clients.iter() {
+        client.inner.config.write().database = Some(db);
+      }
+      Ok(())
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_move_few_keys_with_jump_hash() {
+    let keys: Vec<u64> = (0 .. 1000_u64).map(|key| key.wrapping_mul(0x9e3779b97f4a7c15)).collect();
+    for key in keys.iter() {
+      assert_eq!(jump_hash(*key, 1), 0);
+      assert!(jump_hash(*key, 4) < 4);
+    }
+
+    // keys only move to the added bucket when the pool grows
+    let moved = keys
+      .iter()
+      .filter(|key| jump_hash(**key, 4) != jump_hash(**key, 5))
+      .inspect(|key| assert_eq!(jump_hash(**key, 5), 4))
+      .count();
+    assert!(moved > 100 && moved < 300);
+  }
+}
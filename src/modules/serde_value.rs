@@ -0,0 +1,1008 @@
+use crate::error::RedisError;
+use crate::modules::response::FromRedis;
+use crate::types::{RedisKey, RedisMap, RedisValue, QUEUED};
+use bytes::Bytes;
+use serde::de::{
+  self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::str::{self, FromStr};
+use std::vec::IntoIter;
+
+/// Convert any type that implements `Serialize` into a `RedisValue`.
+///
+/// Structs and maps are converted to `RedisValue::Map`, sequences and tuples to `RedisValue::Array`, and `None` or
+/// unit values to `RedisValue::Null`. Unit enum variants are converted to their name, and other enum variants to a
+/// map with a single entry keyed by the variant name.
+pub fn to_redis_value<T>(value: &T) -> Result<RedisValue, RedisError>
+where
+  T: Serialize + ?Sized,
+{
+  value.serialize(RedisValueSerializer)
+}
+
+/// Convert any type that implements `Serialize` into a `RedisMap` that can be used with `HSET`, `HMSET`, etc.
+///
+/// Fields with a `None` value are skipped since hashes cannot contain `nil` values. Fields that serialize to an array or
+/// map will be rejected by the server, so nested structs should either use `#[serde(flatten)]` or be serialized to a
+/// string first.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::types::{to_redis_map, Serde};
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///   name: String,
+///   #[serde(rename = "user_age")]
+///   age: u32,
+///   #[serde(skip)]
+///   password: String,
+/// }
+///
+/// # async fn example(client: RedisClient, user: User) -> Result<(), RedisError> {
+/// let _: () = client.hset("user:1", to_redis_map(&user)?).await?;
+/// // or use the `Serde` wrapper type
+/// let _: () = client.hset("user:2", Serde(user)).await?;
+/// let Serde(user): Serde<User> = client.hgetall("user:2").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_redis_map<T>(value: &T) -> Result<RedisMap, RedisError>
+where
+  T: Serialize + ?Sized,
+{
+  match to_redis_value(value)? {
+    RedisValue::Map(mut map) => {
+      map.retain(|_, value| !value.is_null());
+      Ok(map)
+    }
+    value => Err(RedisError::new_parse(format!("Cannot convert {} to map.", value.kind()))),
+  }
+}
+
+/// Convert a `RedisValue` into any type that implements `Deserialize`.
+///
+/// Values are converted leniently since most values are returned from the server as strings. For example, a
+/// `RedisValue::String("42")` can be converted to an integer and `"true"` or `"1"` can be converted to a `bool`.
+///
+/// Fields within a `#[serde(flatten)]` struct or an untagged enum are read without this type information, so they
+/// should use `String` or `Option<String>` for any values that come from a hash.
+pub fn from_redis_value<T>(value: RedisValue) -> Result<T, RedisError>
+where
+  T: DeserializeOwned,
+{
+  T::deserialize(value)
+}
+
+/// A wrapper type that uses `serde` to convert values to or from a `RedisValue` or `RedisMap`.
+///
+/// This can be used as an argument to any command that accepts a `RedisValue` or `RedisMap`, or as the response type
+/// for any command.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Serde<T>(pub T);
+
+impl<T> Serde<T> {
+  /// Take the inner value.
+  pub fn into_inner(self) -> T {
+    self.0
+  }
+}
+
+impl<T> TryFrom<Serde<T>> for RedisValue
+where
+  T: Serialize,
+{
+  type Error = RedisError;
+
+  fn try_from(value: Serde<T>) -> Result<Self, Self::Error> {
+    to_redis_value(&value.0)
+  }
+}
+
+impl<T> TryFrom<Serde<T>> for RedisMap
+where
+  T: Serialize,
+{
+  type Error = RedisError;
+
+  fn try_from(value: Serde<T>) -> Result<Self, Self::Error> {
+    to_redis_map(&value.0)
+  }
+}
+
+impl<T> FromRedis for Serde<T>
+where
+  T: DeserializeOwned,
+{
+  fn from_value(value: RedisValue) -> Result<Self, RedisError> {
+    from_redis_value(value).map(Serde)
+  }
+}
+
+fn key_to_value(key: RedisKey) -> RedisValue {
+  match key.as_bytes_str() {
+    Some(s) => RedisValue::String(s),
+    None => RedisValue::Bytes(key.into_bytes()),
+  }
+}
+
+fn value_to_key(value: RedisValue) -> Result<RedisKey, RedisError> {
+  let kind = value.kind();
+  RedisKey::try_from(value).map_err(|_| RedisError::new_parse(format!("Cannot convert {} to map key.", kind)))
+}
+
+/// A `Serializer` that converts values into a `RedisValue`.
+///
+/// Callers should usually use [to_redis_value](crate::types::to_redis_value) or
+/// [to_redis_map](crate::types::to_redis_map) instead.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RedisValueSerializer;
+
+impl ser::Serializer for RedisValueSerializer {
+  type Ok = RedisValue;
+  type Error = RedisError;
+  type SerializeSeq = SerializeArray;
+  type SerializeTuple = SerializeArray;
+  type SerializeTupleStruct = SerializeArray;
+  type SerializeTupleVariant = SerializeTupleVariant;
+  type SerializeMap = SerializeMap;
+  type SerializeStruct = SerializeMap;
+  type SerializeStructVariant = SerializeStructVariant;
+
+  fn serialize_bool(self, v: bool) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Boolean(v))
+  }
+
+  fn serialize_i8(self, v: i8) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Integer(v as i64))
+  }
+
+  fn serialize_i16(self, v: i16) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Integer(v as i64))
+  }
+
+  fn serialize_i32(self, v: i32) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Integer(v as i64))
+  }
+
+  fn serialize_i64(self, v: i64) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Integer(v))
+  }
+
+  fn serialize_u8(self, v: u8) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Integer(v as i64))
+  }
+
+  fn serialize_u16(self, v: u16) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Integer(v as i64))
+  }
+
+  fn serialize_u32(self, v: u32) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Integer(v as i64))
+  }
+
+  fn serialize_u64(self, v: u64) -> Result<RedisValue, RedisError> {
+    if v > i64::MAX as u64 {
+      // the server stores integers as strings so larger values can still round trip
+      Ok(RedisValue::String(v.to_string().into()))
+    } else {
+      Ok(RedisValue::Integer(v as i64))
+    }
+  }
+
+  fn serialize_f32(self, v: f32) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Double(v as f64))
+  }
+
+  fn serialize_f64(self, v: f64) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Double(v))
+  }
+
+  fn serialize_char(self, v: char) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::String(v.to_string().into()))
+  }
+
+  fn serialize_str(self, v: &str) -> Result<RedisValue, RedisError> {
+    Ok(v.into())
+  }
+
+  fn serialize_bytes(self, v: &[u8]) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Bytes(Bytes::copy_from_slice(v)))
+  }
+
+  fn serialize_none(self) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Null)
+  }
+
+  fn serialize_some<T>(self, value: &T) -> Result<RedisValue, RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    value.serialize(self)
+  }
+
+  fn serialize_unit(self) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Null)
+  }
+
+  fn serialize_unit_struct(self, _: &'static str) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Null)
+  }
+
+  fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::from_static_str(variant))
+  }
+
+  fn serialize_newtype_struct<T>(self, _: &'static str, value: &T) -> Result<RedisValue, RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    value.serialize(self)
+  }
+
+  fn serialize_newtype_variant<T>(
+    self,
+    _: &'static str,
+    _: u32,
+    variant: &'static str,
+    value: &T,
+  ) -> Result<RedisValue, RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    let mut inner = HashMap::with_capacity(1);
+    inner.insert(RedisKey::from_static_str(variant), value.serialize(self)?);
+    Ok(RedisValue::Map(RedisMap { inner }))
+  }
+
+  fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, RedisError> {
+    Ok(SerializeArray {
+      values: Vec::with_capacity(len.unwrap_or(0)),
+    })
+  }
+
+  fn serialize_tuple(self, len: usize) -> Result<SerializeArray, RedisError> {
+    self.serialize_seq(Some(len))
+  }
+
+  fn serialize_tuple_struct(self, _: &'static str, len: usize) -> Result<SerializeArray, RedisError> {
+    self.serialize_seq(Some(len))
+  }
+
+  fn serialize_tuple_variant(
+    self,
+    _: &'static str,
+    _: u32,
+    variant: &'static str,
+    len: usize,
+  ) -> Result<SerializeTupleVariant, RedisError> {
+    Ok(SerializeTupleVariant {
+      variant,
+      values: Vec::with_capacity(len),
+    })
+  }
+
+  fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, RedisError> {
+    Ok(SerializeMap {
+      inner: HashMap::with_capacity(len.unwrap_or(0)),
+      next_key: None,
+    })
+  }
+
+  fn serialize_struct(self, _: &'static str, len: usize) -> Result<SerializeMap, RedisError> {
+    self.serialize_map(Some(len))
+  }
+
+  fn serialize_struct_variant(
+    self,
+    _: &'static str,
+    _: u32,
+    variant: &'static str,
+    len: usize,
+  ) -> Result<SerializeStructVariant, RedisError> {
+    Ok(SerializeStructVariant {
+      variant,
+      inner: HashMap::with_capacity(len),
+    })
+  }
+}
+
+#[doc(hidden)]
+pub struct SerializeArray {
+  values: Vec<RedisValue>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+  type Ok = RedisValue;
+  type Error = RedisError;
+
+  fn serialize_element<T>(&mut self, value: &T) -> Result<(), RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    self.values.push(to_redis_value(value)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Array(self.values))
+  }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+  type Ok = RedisValue;
+  type Error = RedisError;
+
+  fn serialize_element<T>(&mut self, value: &T) -> Result<(), RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    ser::SerializeSeq::serialize_element(self, value)
+  }
+
+  fn end(self) -> Result<RedisValue, RedisError> {
+    ser::SerializeSeq::end(self)
+  }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+  type Ok = RedisValue;
+  type Error = RedisError;
+
+  fn serialize_field<T>(&mut self, value: &T) -> Result<(), RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    ser::SerializeSeq::serialize_element(self, value)
+  }
+
+  fn end(self) -> Result<RedisValue, RedisError> {
+    ser::SerializeSeq::end(self)
+  }
+}
+
+#[doc(hidden)]
+pub struct SerializeTupleVariant {
+  variant: &'static str,
+  values: Vec<RedisValue>,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+  type Ok = RedisValue;
+  type Error = RedisError;
+
+  fn serialize_field<T>(&mut self, value: &T) -> Result<(), RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    self.values.push(to_redis_value(value)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<RedisValue, RedisError> {
+    let mut inner = HashMap::with_capacity(1);
+    inner.insert(RedisKey::from_static_str(self.variant), RedisValue::Array(self.values));
+    Ok(RedisValue::Map(RedisMap { inner }))
+  }
+}
+
+#[doc(hidden)]
+pub struct SerializeMap {
+  inner: HashMap<RedisKey, RedisValue>,
+  next_key: Option<RedisKey>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+  type Ok = RedisValue;
+  type Error = RedisError;
+
+  fn serialize_key<T>(&mut self, key: &T) -> Result<(), RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    self.next_key = Some(value_to_key(to_redis_value(key)?)?);
+    Ok(())
+  }
+
+  fn serialize_value<T>(&mut self, value: &T) -> Result<(), RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    let key = match self.next_key.take() {
+      Some(key) => key,
+      None => return Err(RedisError::new_parse("Missing map key.")),
+    };
+    self.inner.insert(key, to_redis_value(value)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<RedisValue, RedisError> {
+    Ok(RedisValue::Map(RedisMap { inner: self.inner }))
+  }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+  type Ok = RedisValue;
+  type Error = RedisError;
+
+  fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    self.inner.insert(RedisKey::from_static_str(key), to_redis_value(value)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<RedisValue, RedisError> {
+    ser::SerializeMap::end(self)
+  }
+}
+
+#[doc(hidden)]
+pub struct SerializeStructVariant {
+  variant: &'static str,
+  inner: HashMap<RedisKey, RedisValue>,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+  type Ok = RedisValue;
+  type Error = RedisError;
+
+  fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), RedisError>
+  where
+    T: Serialize + ?Sized,
+  {
+    self.inner.insert(RedisKey::from_static_str(key), to_redis_value(value)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<RedisValue, RedisError> {
+    let mut inner = HashMap::with_capacity(1);
+    let value = RedisValue::Map(RedisMap { inner: self.inner });
+    inner.insert(RedisKey::from_static_str(self.variant), value);
+    Ok(RedisValue::Map(RedisMap { inner }))
+  }
+}
+
+fn parse_number<T>(s: &str) -> Result<T, RedisError>
+where
+  T: FromStr,
+  T::Err: Display,
+{
+  s.parse::<T>()
+    .map_err(|e| RedisError::new_parse(format!("Cannot parse {:?} as number: {}", s, e)))
+}
+
+fn visit_array<'de, V>(values: Vec<RedisValue>, visitor: V) -> Result<V::Value, RedisError>
+where
+  V: Visitor<'de>,
+{
+  visitor.visit_seq(SeqDeserializer {
+    iter: values.into_iter(),
+  })
+}
+
+fn visit_map<'de, V>(map: RedisMap, visitor: V) -> Result<V::Value, RedisError>
+where
+  V: Visitor<'de>,
+{
+  let pairs: Vec<(RedisValue, RedisValue)> = map
+    .inner
+    .into_iter()
+    .map(|(key, value)| (key_to_value(key), value))
+    .collect();
+
+  visitor.visit_map(MapDeserializer {
+    iter: pairs.into_iter(),
+    value: None,
+  })
+}
+
+/// Visit an array of alternating key/value pairs, such as a hash in RESP2 mode.
+fn visit_pairs<'de, V>(values: Vec<RedisValue>, visitor: V) -> Result<V::Value, RedisError>
+where
+  V: Visitor<'de>,
+{
+  let mut pairs = Vec::with_capacity(values.len() / 2);
+  let mut values = values.into_iter();
+  while let (Some(key), Some(value)) = (values.next(), values.next()) {
+    pairs.push((key, value));
+  }
+
+  visitor.visit_map(MapDeserializer {
+    iter: pairs.into_iter(),
+    value: None,
+  })
+}
+
+macro_rules! deserialize_number (
+  ($method:ident, $visit:ident, $t:ty) => {
+    fn $method<V>(self, visitor: V) -> Result<V::Value, RedisError>
+    where
+      V: Visitor<'de>,
+    {
+      match self {
+        RedisValue::String(s) => visitor.$visit(parse_number::<$t>(&s)?),
+        RedisValue::Bytes(b) => visitor.$visit(parse_number::<$t>(str::from_utf8(&b)?)?),
+        value => value.deserialize_any(visitor),
+      }
+    }
+  }
+);
+
+impl<'de> de::Deserializer<'de> for RedisValue {
+  type Error = RedisError;
+
+  fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::Boolean(b) => visitor.visit_bool(b),
+      RedisValue::Integer(i) => visitor.visit_i64(i),
+      RedisValue::Double(f) => visitor.visit_f64(f),
+      RedisValue::String(s) => visitor.visit_str(&s),
+      RedisValue::Bytes(b) => visitor.visit_bytes(&b),
+      RedisValue::Null => visitor.visit_unit(),
+      RedisValue::Queued => visitor.visit_str(QUEUED),
+      RedisValue::Map(map) => visit_map(map, visitor),
+      RedisValue::Array(values) => visit_array(values, visitor),
+    }
+  }
+
+  fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::Boolean(b) => visitor.visit_bool(b),
+      RedisValue::Integer(_) | RedisValue::String(_) => match self.as_bool() {
+        Some(b) => visitor.visit_bool(b),
+        None => self.deserialize_any(visitor),
+      },
+      value => value.deserialize_any(visitor),
+    }
+  }
+
+  deserialize_number!(deserialize_i8, visit_i8, i8);
+  deserialize_number!(deserialize_i16, visit_i16, i16);
+  deserialize_number!(deserialize_i32, visit_i32, i32);
+  deserialize_number!(deserialize_i64, visit_i64, i64);
+  deserialize_number!(deserialize_u8, visit_u8, u8);
+  deserialize_number!(deserialize_u16, visit_u16, u16);
+  deserialize_number!(deserialize_u32, visit_u32, u32);
+  deserialize_number!(deserialize_u64, visit_u64, u64);
+  deserialize_number!(deserialize_f32, visit_f32, f32);
+  deserialize_number!(deserialize_f64, visit_f64, f64);
+
+  fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_str(visitor)
+  }
+
+  fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::String(s) => visitor.visit_str(&s),
+      RedisValue::Bytes(b) => match str::from_utf8(&b) {
+        Ok(s) => visitor.visit_str(s),
+        Err(_) => visitor.visit_bytes(&b),
+      },
+      RedisValue::Integer(i) => visitor.visit_string(i.to_string()),
+      RedisValue::Double(f) => visitor.visit_string(f.to_string()),
+      RedisValue::Boolean(b) => visitor.visit_string(b.to_string()),
+      value => value.deserialize_any(visitor),
+    }
+  }
+
+  fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_str(visitor)
+  }
+
+  fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::String(s) => visitor.visit_bytes(s.as_bytes()),
+      RedisValue::Bytes(b) => visitor.visit_bytes(&b),
+      value => value.deserialize_any(visitor),
+    }
+  }
+
+  fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_bytes(visitor)
+  }
+
+  fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::Null => visitor.visit_none(),
+      value => visitor.visit_some(value),
+    }
+  }
+
+  fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::Null => visitor.visit_unit(),
+      value => value.deserialize_any(visitor),
+    }
+  }
+
+  fn deserialize_unit_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_unit(visitor)
+  }
+
+  fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::Array(values) => visit_array(values, visitor),
+      RedisValue::Null => visit_array(Vec::new(), visitor),
+      value => value.deserialize_any(visitor),
+    }
+  }
+
+  fn deserialize_tuple<V>(self, _: usize, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_seq(visitor)
+  }
+
+  fn deserialize_tuple_struct<V>(self, _: &'static str, _: usize, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_seq(visitor)
+  }
+
+  fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::Map(map) => visit_map(map, visitor),
+      RedisValue::Array(values) if values.len() % 2 == 0 => visit_pairs(values, visitor),
+      RedisValue::Null => visit_map(RedisMap::new(), visitor),
+      value => value.deserialize_any(visitor),
+    }
+  }
+
+  fn deserialize_struct<V>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_map(visitor)
+  }
+
+  fn deserialize_enum<V>(
+    self,
+    _: &'static str,
+    _: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self {
+      RedisValue::String(_) | RedisValue::Bytes(_) => visitor.visit_enum(EnumDeserializer {
+        variant: self,
+        value: None,
+      }),
+      RedisValue::Map(map) if map.len() == 1 => {
+        let (variant, value) = map.inner.into_iter().next().unwrap();
+        visitor.visit_enum(EnumDeserializer {
+          variant: key_to_value(variant),
+          value: Some(value),
+        })
+      }
+      value => Err(RedisError::new_parse(format!(
+        "Cannot convert {} to enum. Expected a string or a map with one entry.",
+        value.kind()
+      ))),
+    }
+  }
+
+  fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_str(visitor)
+  }
+
+  fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_unit()
+  }
+}
+
+impl<'de> IntoDeserializer<'de, RedisError> for RedisValue {
+  type Deserializer = Self;
+
+  fn into_deserializer(self) -> Self {
+    self
+  }
+}
+
+struct SeqDeserializer {
+  iter: IntoIter<RedisValue>,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+  type Error = RedisError;
+
+  fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, RedisError>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    match self.iter.next() {
+      Some(value) => seed.deserialize(value).map(Some),
+      None => Ok(None),
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.iter.len())
+  }
+}
+
+struct MapDeserializer {
+  iter: IntoIter<(RedisValue, RedisValue)>,
+  value: Option<RedisValue>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+  type Error = RedisError;
+
+  fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, RedisError>
+  where
+    K: DeserializeSeed<'de>,
+  {
+    match self.iter.next() {
+      Some((key, value)) => {
+        self.value = Some(value);
+        seed.deserialize(key).map(Some)
+      }
+      None => Ok(None),
+    }
+  }
+
+  fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, RedisError>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    match self.value.take() {
+      Some(value) => seed.deserialize(value),
+      None => Err(RedisError::new_parse("Missing map value.")),
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.iter.len())
+  }
+}
+
+struct EnumDeserializer {
+  variant: RedisValue,
+  value: Option<RedisValue>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+  type Error = RedisError;
+  type Variant = VariantDeserializer;
+
+  fn variant_seed<V>(self, seed: V) -> Result<(V::Value, VariantDeserializer), RedisError>
+  where
+    V: DeserializeSeed<'de>,
+  {
+    let variant = seed.deserialize(self.variant)?;
+    Ok((variant, VariantDeserializer { value: self.value }))
+  }
+}
+
+struct VariantDeserializer {
+  value: Option<RedisValue>,
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+  type Error = RedisError;
+
+  fn unit_variant(self) -> Result<(), RedisError> {
+    match self.value {
+      None | Some(RedisValue::Null) => Ok(()),
+      Some(value) => Err(RedisError::new_parse(format!(
+        "Cannot convert {} to unit variant.",
+        value.kind()
+      ))),
+    }
+  }
+
+  fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, RedisError>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    match self.value {
+      Some(value) => seed.deserialize(value),
+      None => Err(RedisError::new_parse("Missing newtype variant value.")),
+    }
+  }
+
+  fn tuple_variant<V>(self, _: usize, visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self.value {
+      Some(value) => de::Deserializer::deserialize_seq(value, visitor),
+      None => Err(RedisError::new_parse("Missing tuple variant value.")),
+    }
+  }
+
+  fn struct_variant<V>(self, _: &'static [&'static str], visitor: V) -> Result<V::Value, RedisError>
+  where
+    V: Visitor<'de>,
+  {
+    match self.value {
+      Some(value) => de::Deserializer::deserialize_map(value, visitor),
+      None => Err(RedisError::new_parse("Missing struct variant value.")),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+  enum Role {
+    Admin,
+    Member { team: String },
+  }
+
+  #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+  struct Address {
+    city: String,
+    zip: String,
+  }
+
+  #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+  struct User {
+    name: String,
+    #[serde(rename = "user_age")]
+    age: u32,
+    active: bool,
+    nickname: Option<String>,
+    #[serde(skip)]
+    password: String,
+    #[serde(flatten)]
+    address: Address,
+  }
+
+  fn user() -> User {
+    User {
+      name: "alice".into(),
+      age: 42,
+      active: true,
+      nickname: None,
+      password: "hunter2".into(),
+      address: Address {
+        city: "Berlin".into(),
+        zip: "10115".into(),
+      },
+    }
+  }
+
+  fn string_map(pairs: &[(&str, &str)]) -> RedisValue {
+    let inner = pairs
+      .iter()
+      .map(|(key, value)| (RedisKey::from(*key), RedisValue::from(*value)))
+      .collect();
+    RedisValue::Map(RedisMap { inner })
+  }
+
+  #[test]
+  fn should_serialize_struct_to_map() {
+    let map = to_redis_map(&user()).unwrap();
+
+    assert_eq!(map.len(), 5);
+    assert_eq!(map.get(&RedisKey::from("name")), Some(&RedisValue::from("alice")));
+    assert_eq!(map.get(&RedisKey::from("user_age")), Some(&RedisValue::Integer(42)));
+    assert_eq!(map.get(&RedisKey::from("active")), Some(&RedisValue::Boolean(true)));
+    assert_eq!(map.get(&RedisKey::from("city")), Some(&RedisValue::from("Berlin")));
+    assert_eq!(map.get(&RedisKey::from("zip")), Some(&RedisValue::from("10115")));
+    assert!(map.get(&RedisKey::from("nickname")).is_none());
+    assert!(map.get(&RedisKey::from("password")).is_none());
+  }
+
+  #[test]
+  fn should_deserialize_struct_from_string_map() {
+    let value = string_map(&[
+      ("name", "alice"),
+      ("user_age", "42"),
+      ("active", "true"),
+      ("city", "Berlin"),
+      ("zip", "10115"),
+    ]);
+    let expected = User {
+      password: String::new(),
+      ..user()
+    };
+
+    assert_eq!(from_redis_value::<User>(value).unwrap(), expected);
+  }
+
+  #[test]
+  fn should_deserialize_struct_from_resp2_array() {
+    let value = RedisValue::Array(vec!["city".into(), "Berlin".into(), "zip".into(), "10115".into()]);
+    let address: Address = from_redis_value(value).unwrap();
+
+    assert_eq!(address.city, "Berlin");
+    assert_eq!(address.zip, "10115");
+  }
+
+  #[test]
+  fn should_round_trip_enums() {
+    let admin = to_redis_value(&Role::Admin).unwrap();
+    assert_eq!(admin, RedisValue::from("Admin"));
+    assert_eq!(from_redis_value::<Role>(admin).unwrap(), Role::Admin);
+
+    let member = Role::Member { team: "ops".into() };
+    let value = to_redis_value(&member).unwrap();
+    assert_eq!(from_redis_value::<Role>(value).unwrap(), member);
+  }
+
+  #[test]
+  fn should_round_trip_collections() {
+    let values: Vec<(String, Option<u64>)> = vec![("a".into(), Some(u64::MAX)), ("b".into(), None)];
+    let value = to_redis_value(&values).unwrap();
+    assert_eq!(from_redis_value::<Vec<(String, Option<u64>)>>(value).unwrap(), values);
+
+    let mut map = HashMap::new();
+    map.insert(1_u32, vec![1.5_f64, 2.5]);
+    let value = to_redis_value(&map).unwrap();
+    assert_eq!(from_redis_value::<HashMap<u32, Vec<f64>>>(value).unwrap(), map);
+  }
+
+  #[test]
+  fn should_return_parse_errors() {
+    let value = string_map(&[("name", "alice"), ("user_age", "abc"), ("active", "true")]);
+    let error = from_redis_value::<User>(value).unwrap_err();
+    assert_eq!(*error.kind(), crate::error::RedisErrorKind::Parse);
+
+    let error = to_redis_map(&"foo").unwrap_err();
+    assert_eq!(*error.kind(), crate::error::RedisErrorKind::Parse);
+  }
+}
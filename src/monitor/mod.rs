@@ -6,8 +6,11 @@ use futures::Stream;
 use std::fmt;
 
 mod parser;
+mod replay;
 mod utils;
 
+pub use crate::monitor::replay::{read_log, replay, write_log, LogFormat};
+
 /// A command parsed from a [MONITOR](https://redis.io/commands/monitor) stream.
 ///
 /// Formatting with the [Display](https://doc.rust-lang.org/std/fmt/trait.Display.html) trait will print the same output as `redis-cli`.
@@ -53,6 +56,54 @@ impl fmt::Display for Command {
   }
 }
 
+/// Filters applied to commands on a `MONITOR` stream before they're sent to the caller.
+///
+/// Each filter is optional, and a command must match every provided filter. Patterns use the same glob-style syntax
+/// as the [KEYS](https://redis.io/commands/keys) command.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Filter {
+  /// Command names to include, compared without case sensitivity.
+  pub commands: Option<Vec<String>>,
+  /// A pattern matched against the first argument of each command, which is the key for most commands.
+  pub key_pattern: Option<String>,
+  /// A pattern matched against the `host:port` address of the client that ran the command.
+  pub client_pattern: Option<String>,
+  /// The database against which the command was run.
+  pub db: Option<u8>,
+}
+
+impl Filter {
+  /// Whether or not the command matches every filter.
+  pub fn matches(&self, command: &Command) -> bool {
+    if let Some(ref commands) = self.commands {
+      if !commands.iter().any(|c| c.eq_ignore_ascii_case(&command.command)) {
+        return false;
+      }
+    }
+    if let Some(db) = self.db {
+      if db != command.db {
+        return false;
+      }
+    }
+    if let Some(ref pattern) = self.client_pattern {
+      if !client_utils::glob_match(pattern.as_bytes(), command.client.as_bytes()) {
+        return false;
+      }
+    }
+    if let Some(ref pattern) = self.key_pattern {
+      let key = match command.args.first().and_then(|arg| arg.as_bytes()) {
+        Some(key) => key,
+        None => return false,
+      };
+      if !client_utils::glob_match(pattern.as_bytes(), key) {
+        return false;
+      }
+    }
+
+    true
+  }
+}
+
 /// Configuration options for the `MONITOR` command.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Config {
@@ -60,12 +111,19 @@ pub struct Config {
   pub port: u16,
   pub username: Option<String>,
   pub password: Option<String>,
-  #[cfg(feature = "enable-tls")]
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
   pub tls: Option<TlsConfig>,
+  /// Filters applied to the stream of commands.
+  pub filter: Filter,
+  /// Whether or not `host` and `port` refer to a node in a cluster. If `true` the `MONITOR` command will run on every
+  /// primary node in the cluster and the streams will be merged by timestamp.
+  pub cluster: bool,
+  /// The amount of time commands are buffered in order to sort them by timestamp when monitoring a cluster.
+  pub merge_window_ms: u64,
 }
 
 impl Default for Config {
-  #[cfg(feature = "enable-tls")]
+  #[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
   fn default() -> Self {
     Config {
       host: "127.0.0.1".into(),
@@ -73,21 +131,28 @@ impl Default for Config {
       username: None,
       password: None,
       tls: None,
+      filter: Filter::default(),
+      cluster: false,
+      merge_window_ms: 100,
     }
   }
 
-  #[cfg(not(feature = "enable-tls"))]
+  #[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
   fn default() -> Self {
     Config {
       host: "127.0.0.1".into(),
       port: 6379,
       username: None,
       password: None,
+      filter: Filter::default(),
+      cluster: false,
+      merge_window_ms: 100,
     }
   }
 }
 
-/// Run the [MONITOR](https://redis.io/commands/monitor) command against the provided server.
+/// Run the [MONITOR](https://redis.io/commands/monitor) command against the provided server, or against every primary
+/// node in a cluster.
 pub async fn run(config: Config) -> Result<impl Stream<Item = Command>, RedisError> {
   utils::start(config).await
 }
@@ -108,6 +108,11 @@ fn d_parse_frame(input: &[u8]) -> Result<Command, RedisParseError<&[u8]>> {
   })
 }
 
+/// Parse a line of `redis-cli` formatted output from the `MONITOR` command.
+pub fn parse_line(line: &str) -> Option<Command> {
+  d_parse_frame(line.as_bytes()).ok()
+}
+
 #[cfg(feature = "network-logs")]
 fn log_frame(inner: &Arc<RedisClientInner>, frame: &[u8]) {
   if let Ok(s) = str::from_utf8(frame) {
@@ -0,0 +1,311 @@
+use crate::error::{RedisError, RedisErrorKind};
+use crate::interfaces::ClientLike;
+use crate::monitor::parser;
+use crate::monitor::Command;
+use crate::protocol::types::ClusterKeyCache;
+use crate::types::{CustomCommand, RedisValue};
+use crate::utils as client_utils;
+use futures::stream::{self, Stream, StreamExt};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::time::{sleep_until, Instant};
+
+/// The client name used by the server for commands run from a Lua script.
+const LUA_CLIENT: &'static str = "lua";
+
+/// The format used to record a `MONITOR` stream.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LogFormat {
+  /// One JSON object per line with `timestamp`, `db`, `client`, `command`, and `args` fields.
+  ///
+  /// Arguments that are not valid UTF-8 are written as objects with the hex encoded bytes, such as `{"hex":"00ff"}`.
+  Jsonl,
+  /// The same format as `redis-cli monitor`.
+  RedisCli,
+}
+
+/// Convert an argument to a JSON string, or an object with the hex encoded bytes if it's not valid UTF-8.
+fn arg_to_json(arg: &RedisValue) -> Value {
+  if let Some(s) = arg.as_str() {
+    return Value::String(s.into_owned());
+  }
+
+  match arg.as_bytes() {
+    Some(bytes) => {
+      let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+      json!({ "hex": hex })
+    }
+    None => Value::String(String::new()),
+  }
+}
+
+/// Read an argument written by [arg_to_json].
+fn json_to_arg(value: &Value) -> Option<RedisValue> {
+  if let Some(s) = value.as_str() {
+    return Some(RedisValue::String(s.into()));
+  }
+
+  let hex = value.get("hex")?.as_str()?;
+  if hex.len() % 2 != 0 {
+    return None;
+  }
+  let bytes = (0 .. hex.len())
+    .step_by(2)
+    .map(|idx| hex.get(idx .. idx + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+    .collect::<Option<Vec<u8>>>()?;
+
+  Some(RedisValue::Bytes(bytes.into()))
+}
+
+fn format_command(command: &Command, format: LogFormat) -> String {
+  match format {
+    LogFormat::RedisCli => command.to_string(),
+    LogFormat::Jsonl => {
+      let args: Vec<Value> = command.args.iter().map(arg_to_json).collect();
+
+      json!({
+        "timestamp": command.timestamp,
+        "db": command.db,
+        "client": command.client,
+        "command": command.command,
+        "args": args,
+      })
+      .to_string()
+    }
+  }
+}
+
+fn parse_json_line(line: &str) -> Option<Command> {
+  let value: Value = serde_json::from_str(line).ok()?;
+  let args = value
+    .get("args")?
+    .as_array()?
+    .iter()
+    .map(json_to_arg)
+    .collect::<Option<Vec<RedisValue>>>()?;
+
+  Some(Command {
+    timestamp: value.get("timestamp")?.as_f64()?,
+    db: value.get("db")?.as_u64()? as u8,
+    client: value.get("client")?.as_str()?.to_owned(),
+    command: value.get("command")?.as_str()?.to_owned(),
+    args,
+  })
+}
+
+fn parse_line(line: &str, format: LogFormat) -> Result<Command, RedisError> {
+  let command = match format {
+    LogFormat::RedisCli => parser::parse_line(line),
+    LogFormat::Jsonl => parse_json_line(line),
+  };
+
+  command.ok_or_else(|| RedisError::new(RedisErrorKind::Parse, format!("Invalid monitor log line: {}", line)))
+}
+
+/// Reverse the escaping applied by the server to arguments in the `MONITOR` output.
+fn unescape(arg: &str) -> Vec<u8> {
+  let bytes = arg.as_bytes();
+  let mut out = Vec::with_capacity(bytes.len());
+  let mut idx = 0;
+
+  while idx < bytes.len() {
+    if bytes[idx] != b'\\' || idx + 1 >= bytes.len() {
+      out.push(bytes[idx]);
+      idx += 1;
+      continue;
+    }
+
+    let escaped = match bytes[idx + 1] {
+      b'n' => b'\n',
+      b'r' => b'\r',
+      b't' => b'\t',
+      b'a' => 7,
+      b'b' => 8,
+      b'x' if idx + 3 < bytes.len() => {
+        let hex = String::from_utf8_lossy(&bytes[idx + 2 .. idx + 4]).into_owned();
+        if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+          out.push(byte);
+          idx += 4;
+          continue;
+        }
+        b'x'
+      }
+      c => c,
+    };
+    out.push(escaped);
+    idx += 2;
+  }
+
+  out
+}
+
+/// Write each command from the stream to the provided writer, returning the number of commands written.
+///
+/// This will run until the stream ends, which for a `MONITOR` stream means until the connection closes. Callers
+/// should use [Filter](crate::monitor::Filter) or stream combinators such as `take` to limit the output.
+pub async fn write_log<S, W>(mut commands: S, mut writer: W, format: LogFormat) -> Result<usize, RedisError>
+where
+  S: Stream<Item = Command> + Unpin,
+  W: AsyncWrite + Unpin,
+{
+  let mut count = 0;
+  while let Some(command) = commands.next().await {
+    let mut line = format_command(&command, format);
+    line.push('\n');
+
+    let _ = writer.write_all(line.as_bytes()).await?;
+    count += 1;
+  }
+
+  let _ = writer.flush().await?;
+  Ok(count)
+}
+
+/// Read commands from a log written by [write_log](crate::monitor::write_log) or by `redis-cli monitor`.
+///
+/// Empty lines are skipped. Lines that cannot be parsed are returned as errors.
+pub fn read_log<R>(reader: R, format: LogFormat) -> impl Stream<Item = Result<Command, RedisError>>
+where
+  R: AsyncBufRead + Unpin,
+{
+  stream::unfold(reader.lines(), move |mut lines| async move {
+    loop {
+      match lines.next_line().await {
+        Ok(Some(line)) => {
+          if line.trim().is_empty() {
+            continue;
+          }
+          return Some((parse_line(&line, format), lines));
+        }
+        Ok(None) => return None,
+        Err(e) => return Some((Err(e.into()), lines)),
+      }
+    }
+  })
+}
+
+/// Replay commands against the provided client, returning the number of commands that were sent.
+///
+/// A `speed` of `1.0` replays commands with the same delays between them as the original stream, `2.0` replays them
+/// twice as fast, etc. Commands are sent as fast as possible if `speed` is not positive. Against a centralized server
+/// the client will `SELECT` the database on which each command originally ran.
+///
+/// Commands run by Lua scripts are skipped since they will run again when the script is replayed. Errors returned by
+/// the server are logged and do not stop the replay, but errors reading the stream will.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::monitor::{self, LogFormat};
+/// # use tokio::fs::File;
+/// # use tokio::io::BufReader;
+/// # async fn example(client: RedisClient) -> Result<(), RedisError> {
+/// let file = File::open("monitor.jsonl").await?;
+/// let commands = monitor::read_log(BufReader::new(file), LogFormat::Jsonl);
+/// let replayed = monitor::replay(&client, Box::pin(commands), 2.0).await?;
+/// println!("Replayed {} commands", replayed);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn replay<C, S>(client: &C, mut commands: S, speed: f64) -> Result<usize, RedisError>
+where
+  C: ClientLike,
+  S: Stream<Item = Result<Command, RedisError>> + Unpin,
+{
+  let is_clustered = client_utils::is_clustered(&client.inner().config);
+  let mut start: Option<(f64, Instant)> = None;
+  let mut db = None;
+  let mut count = 0;
+
+  while let Some(command) = commands.next().await {
+    let command = command?;
+    if command.client == LUA_CLIENT {
+      continue;
+    }
+
+    if speed > 0.0 {
+      if let Some((first_timestamp, started)) = start {
+        let offset = (command.timestamp - first_timestamp).max(0.0) / speed;
+        sleep_until(started + Duration::from_secs_f64(offset)).await;
+      } else {
+        start = Some((command.timestamp, Instant::now()));
+      }
+    }
+
+    if !is_clustered && db != Some(command.db) {
+      let cmd = CustomCommand::new_static("SELECT", None, false);
+      let _: () = client.custom(cmd, vec![command.db]).await?;
+      db = Some(command.db);
+    }
+
+    let args: Vec<RedisValue> = command
+      .args
+      .iter()
+      .map(|arg| match arg.as_str() {
+        Some(s) => RedisValue::Bytes(unescape(&s).into()),
+        None => arg.clone(),
+      })
+      .collect();
+    let hash_slot = args
+      .first()
+      .and_then(|arg| arg.as_bytes())
+      .map(|key| ClusterKeyCache::hash_key(key));
+
+    let cmd = CustomCommand::new(command.command.to_uppercase(), hash_slot, false);
+    if let Err(e) = client.custom::<RedisValue, _>(cmd, args).await {
+      warn!("Error replaying {}: {:?}", command, e);
+    }
+    count += 1;
+  }
+
+  Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn command() -> Command {
+    Command {
+      timestamp: 1631469940.785623,
+      db: 1,
+      client: "127.0.0.1:46998".into(),
+      command: "set".into(),
+      args: vec!["foo".into(), "bar \\\"baz\\\"".into()],
+    }
+  }
+
+  #[test]
+  fn should_format_and_parse_jsonl() {
+    let line = format_command(&command(), LogFormat::Jsonl);
+    assert_eq!(parse_line(&line, LogFormat::Jsonl).unwrap(), command());
+  }
+
+  #[test]
+  fn should_format_and_parse_jsonl_with_binary_args() {
+    let mut command = command();
+    command.args.push(RedisValue::Bytes(vec![0, 159, 146, 150, 255].into()));
+
+    let line = format_command(&command, LogFormat::Jsonl);
+    assert!(line.contains("{\"hex\":\"009f9296ff\"}"));
+    assert_eq!(parse_line(&line, LogFormat::Jsonl).unwrap(), command);
+    assert!(parse_line(&line.replace("009f9296ff", "009f9296f"), LogFormat::Jsonl).is_err());
+  }
+
+  #[test]
+  fn should_format_and_parse_redis_cli() {
+    let line = format_command(&command(), LogFormat::RedisCli);
+    assert_eq!(
+      line,
+      "1631469940.785623 [1 127.0.0.1:46998] \"set\" \"foo\" \"bar \\\"baz\\\"\""
+    );
+    assert_eq!(parse_line(&line, LogFormat::RedisCli).unwrap(), command());
+  }
+
+  #[test]
+  fn should_unescape_monitor_args() {
+    assert_eq!(unescape("bar \\\"baz\\\""), b"bar \"baz\"".to_vec());
+    assert_eq!(unescape("a\\r\\n\\\\b"), b"a\r\n\\b".to_vec());
+    assert_eq!(unescape("\\x00\\xff"), vec![0, 255]);
+  }
+}
@@ -1,16 +1,21 @@
 use crate::error::RedisError;
 use crate::modules::inner::RedisClientInner;
 use crate::monitor::parser;
-use crate::monitor::{Command, Config};
+use crate::monitor::{Command, Config, Filter};
 use crate::protocol::codec::RedisCodec;
 use crate::protocol::connection::{self, RedisTransport};
 use crate::protocol::types::{ProtocolFrame, RedisCommand, RedisCommandKind};
 use crate::protocol::utils as protocol_utils;
 use crate::types::{RedisConfig, ServerConfig};
 use futures::stream::{Stream, StreamExt};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tokio_util::codec::Framed;
 
@@ -65,31 +70,25 @@ async fn handle_monitor_frame(
   parser::parse(inner, frame)
 }
 
-#[cfg(feature = "enable-tls")]
-fn create_client_inner(config: Config) -> Arc<RedisClientInner> {
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+fn create_client_inner(config: &Config, server: ServerConfig) -> Arc<RedisClientInner> {
   let config = RedisConfig {
-    username: config.username,
-    password: config.password,
-    server: ServerConfig::Centralized {
-      host: config.host,
-      port: config.port,
-    },
-    tls: config.tls,
+    username: config.username.clone(),
+    password: config.password.clone(),
+    server,
+    tls: config.tls.clone(),
     ..Default::default()
   };
 
   RedisClientInner::new(config)
 }
 
-#[cfg(not(feature = "enable-tls"))]
-fn create_client_inner(config: Config) -> Arc<RedisClientInner> {
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
+fn create_client_inner(config: &Config, server: ServerConfig) -> Arc<RedisClientInner> {
   let config = RedisConfig {
-    username: config.username,
-    password: config.password,
-    server: ServerConfig::Centralized {
-      host: config.host,
-      port: config.port,
-    },
+    username: config.username.clone(),
+    password: config.password.clone(),
+    server,
     ..Default::default()
   };
 
@@ -112,6 +111,10 @@ async fn send_monitor_command(
       let (frame, framed) = connection::request_response(framed, &command, false).await?;
       (frame.into_resp3(), RedisTransport::Tls(framed))
     }
+    RedisTransport::Unix(framed) => {
+      let (frame, framed) = connection::request_response(framed, &command, false).await?;
+      (frame.into_resp3(), RedisTransport::Unix(framed))
+    }
   };
 
   _trace!(inner, "Recv MONITOR response: {:?}", frame);
@@ -123,12 +126,17 @@ async fn send_monitor_command(
 async fn forward_results<T>(
   inner: &Arc<RedisClientInner>,
   tx: UnboundedSender<Command>,
+  filter: &Filter,
   mut framed: Framed<T, RedisCodec>,
 ) where
   T: AsyncRead + AsyncWrite + Unpin + 'static,
 {
   while let Some(frame) = framed.next().await {
     if let Some(command) = handle_monitor_frame(inner, frame).await {
+      if !filter.matches(&command) {
+        continue;
+      }
+
       if let Err(_) = tx.send(command) {
         _warn!(inner, "Stopping monitor stream.");
         return;
@@ -139,29 +147,143 @@ async fn forward_results<T>(
   }
 }
 
-async fn process_stream(inner: &Arc<RedisClientInner>, tx: UnboundedSender<Command>, connection: RedisTransport) {
+async fn process_stream(
+  inner: &Arc<RedisClientInner>,
+  tx: UnboundedSender<Command>,
+  filter: Filter,
+  connection: RedisTransport,
+) {
   _debug!(inner, "Starting monitor stream processing...");
 
   match connection {
-    RedisTransport::Tcp(framed) => forward_results(inner, tx, framed).await,
-    RedisTransport::Tls(framed) => forward_results(inner, tx, framed).await,
+    RedisTransport::Tcp(framed) => forward_results(inner, tx, &filter, framed).await,
+    RedisTransport::Tls(framed) => forward_results(inner, tx, &filter, framed).await,
+    RedisTransport::Unix(framed) => forward_results(inner, tx, &filter, framed).await,
   };
 
   _warn!(inner, "Stopping monitor stream.");
 }
 
-pub async fn start(config: Config) -> Result<impl Stream<Item = Command>, RedisError> {
-  let inner = create_client_inner(config);
+/// A command waiting to be sorted by timestamp with commands from other cluster nodes.
+struct PendingCommand {
+  command: Command,
+  received: Instant,
+}
+
+impl PartialEq for PendingCommand {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for PendingCommand {}
+
+impl PartialOrd for PendingCommand {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for PendingCommand {
+  // reversed so that the `BinaryHeap` pops the oldest command first
+  fn cmp(&self, other: &Self) -> Ordering {
+    other
+      .command
+      .timestamp
+      .partial_cmp(&self.command.timestamp)
+      .unwrap_or(Ordering::Equal)
+  }
+}
+
+/// Merge commands from each cluster node, sorting them by timestamp within the provided window.
+async fn merge_streams(mut rx: UnboundedReceiver<Command>, tx: UnboundedSender<Command>, window: Duration) {
+  let mut pending = BinaryHeap::new();
+
+  loop {
+    match timeout(window, rx.recv()).await {
+      Ok(Some(command)) => pending.push(PendingCommand {
+        command,
+        received: Instant::now(),
+      }),
+      Ok(None) => break,
+      Err(_) => {}
+    };
+
+    while pending.peek().map(|p| p.received.elapsed() >= window).unwrap_or(false) {
+      if let Some(pending) = pending.pop() {
+        if let Err(_) = tx.send(pending.command) {
+          return;
+        }
+      }
+    }
+  }
+
+  while let Some(pending) = pending.pop() {
+    if let Err(_) = tx.send(pending.command) {
+      return;
+    }
+  }
+}
+
+/// Read the `host:port` of each primary node in the cluster.
+async fn read_cluster_servers(config: &Config) -> Result<Vec<(String, u16)>, RedisError> {
+  let server = ServerConfig::Clustered {
+    hosts: vec![(config.host.clone(), config.port)],
+  };
+  let inner = create_client_inner(config, server);
+  let cluster_state = connection::read_cluster_nodes(&inner).await?;
+
+  let mut servers = Vec::new();
+  for server in cluster_state.unique_main_nodes().iter() {
+    let (host, port) = protocol_utils::server_to_parts(server)?;
+    servers.push((host.to_owned(), port));
+  }
+  Ok(servers)
+}
+
+async fn start_node(
+  config: &Config,
+  host: String,
+  port: u16,
+  tx: UnboundedSender<Command>,
+) -> Result<JoinHandle<()>, RedisError> {
+  let inner = create_client_inner(config, ServerConfig::Centralized { host, port });
   let connection = connection::create_centralized_connection(&inner).await?;
   let connection = send_monitor_command(&inner, connection).await?;
 
   // there isn't really a mechanism to surface backpressure to the server for the MONITOR stream, so we use a
   // background task with a channel to process the frames so that the server can keep sending data even if the
   // stream consumer slows down processing the frames.
+  let filter = config.filter.clone();
+  Ok(tokio::spawn(async move {
+    process_stream(&inner, tx, filter, connection).await;
+  }))
+}
+
+pub async fn start(config: Config) -> Result<impl Stream<Item = Command>, RedisError> {
   let (tx, rx) = unbounded_channel();
-  let _ = tokio::spawn(async move {
-    process_stream(&inner, tx, connection).await;
-  });
+
+  if config.cluster {
+    let (node_tx, node_rx) = unbounded_channel();
+    let mut tasks = Vec::new();
+    for (host, port) in read_cluster_servers(&config).await?.into_iter() {
+      match start_node(&config, host, port, node_tx.clone()).await {
+        Ok(task) => tasks.push(task),
+        Err(e) => {
+          // close the connections to the nodes that were already monitored
+          for task in tasks.into_iter() {
+            task.abort();
+          }
+          return Err(e);
+        }
+      }
+    }
+
+    let window = Duration::from_millis(config.merge_window_ms);
+    let _ = tokio::spawn(merge_streams(node_rx, tx, window));
+  } else {
+    let _ = start_node(&config, config.host.clone(), config.port, tx).await?;
+  }
 
   Ok(UnboundedReceiverStream::new(rx))
 }
@@ -1,5 +1,6 @@
 use crate::clients::RedisClient;
 use crate::error::{RedisError, RedisErrorKind};
+use crate::modules::cache;
 use crate::modules::inner::RedisClientInner;
 use crate::multiplexer::{utils, SentCommand};
 use crate::multiplexer::{Backpressure, Multiplexer};
@@ -375,7 +376,8 @@ fn should_disable_pipeline(inner: &Arc<RedisClientInner>, command: &RedisCommand
 
   // prefer pipelining for all commands not in a multi block (unless specified above), unless the command is blocking.
   // but, in the context of a transaction blocking commands can be pipelined since the server responds immediately.
-  // otherwise defer to the `disable_pipeline` flag from the config.
+  // otherwise defer to the `disable_pipeline` flag from the config, unless the command came from an explicit pipeline.
+  let disable_pipeline = disable_pipeline && !command.pipelined;
   force_no_pipeline || (!in_multi_block && (disable_pipeline || command.kind.is_blocking()))
 }
 
@@ -672,12 +674,17 @@ async fn handle_command(
     cmd_buffer_len
   );
 
-  let command = match check_command_structure_t(&inner, &multiplexer, has_policy, command).await? {
+  let mut command = match check_command_structure_t(&inner, &multiplexer, has_policy, command).await? {
     Some(cmd) => cmd,
     None => return Ok(()),
   };
   let is_blocking = command.kind.is_blocking();
   let is_quit = command.kind.closes_connection();
+  let opt_in = cache::should_opt_in(inner, &command);
+  if opt_in {
+    // `CLIENT CACHING YES` is sent to the primary node, so the read must go to the same connection
+    command.primary_only = true;
+  }
 
   let rx = if should_disable_pipeline(&inner, &command, disable_pipeline) {
     _debug!(
@@ -703,6 +710,11 @@ async fn handle_command(
       }
     };
 
+    if opt_in {
+      // the server only tracks keys read by the command immediately after `CLIENT CACHING YES`
+      let _ = write_command_t(&inner, &multiplexer, cache::opt_in_command(&command)).await;
+    }
+
     let result = write_command_t(&inner, &multiplexer, command).await;
     if is_quit {
       _debug!(inner, "Closing command stream after Quit command.");
@@ -837,7 +849,20 @@ async fn connect_with_policy(
 ///
 /// This function runs until the connection closes or all retry attempts have failed.
 /// If a retry policy with infinite attempts is provided then this runs forever.
-pub async fn init(inner: &Arc<RedisClientInner>, mut policy: Option<ReconnectPolicy>) -> Result<(), RedisError> {
+pub async fn init(inner: &Arc<RedisClientInner>, policy: Option<ReconnectPolicy>) -> Result<(), RedisError> {
+  #[cfg(feature = "mocks")]
+  if inner.config.read().mocks.is_some() {
+    return init_mocks(inner, policy).await;
+  }
+
+  init_connections(inner, policy).await
+}
+
+/// Initialize the connections to the server(s) and run the multiplexer.
+async fn init_connections(
+  inner: &Arc<RedisClientInner>,
+  mut policy: Option<ReconnectPolicy>,
+) -> Result<(), RedisError> {
   if !client_utils::check_and_set_client_state(&inner.state, ClientState::Disconnected, ClientState::Connecting) {
     return Err(RedisError::new(
       RedisErrorKind::Unknown,
@@ -893,3 +918,48 @@ pub async fn init(inner: &Arc<RedisClientInner>, mut policy: Option<ReconnectPol
   inner.store_command_rx(rx);
   Ok(())
 }
+
+/// Initialize a mocked client that routes all commands to the `mocks` interface on the client's config.
+///
+/// This function runs until the client sends `QUIT` or `SHUTDOWN`.
+#[cfg(feature = "mocks")]
+async fn init_mocks(inner: &Arc<RedisClientInner>, mut policy: Option<ReconnectPolicy>) -> Result<(), RedisError> {
+  if !client_utils::check_and_set_client_state(&inner.state, ClientState::Disconnected, ClientState::Connecting) {
+    return Err(RedisError::new(
+      RedisErrorKind::Unknown,
+      "Connections are already initialized or connecting.",
+    ));
+  }
+  let mut rx = match inner.take_command_rx() {
+    Some(rx) => rx,
+    None => {
+      return Err(RedisError::new(
+        RedisErrorKind::Config,
+        "Redis client is already initialized.",
+      ))
+    }
+  };
+  if let Some(ref mut policy) = policy {
+    policy.reset_attempts();
+  }
+  client_utils::set_locked(&inner.policy, policy);
+
+  client_utils::set_client_state(&inner.state, ClientState::Connected);
+  utils::emit_connect(inner);
+  utils::emit_reconnect(inner);
+
+  _debug!(inner, "Starting mocked command stream...");
+  while let Some(command) = rx.recv().await {
+    client_utils::decr_atomic(&inner.cmd_buffer_len);
+    let closes_connection = command.kind.closes_connection() || command.kind.is_close();
+
+    crate::modules::mocks::process_command(inner, command);
+    if closes_connection {
+      break;
+    }
+  }
+
+  client_utils::set_client_state(&inner.state, ClientState::Disconnected);
+  inner.store_command_rx(rx);
+  Ok(())
+}
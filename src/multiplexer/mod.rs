@@ -7,14 +7,16 @@ use crate::types::ClientState;
 use crate::utils as client_utils;
 use parking_lot::{Mutex, RwLock};
 use std::collections::{BTreeMap, VecDeque};
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::broadcast::Sender as BroadcastSender;
 use tokio::sync::oneshot::{channel as oneshot_channel, Sender as OneshotSender};
 use tokio::sync::RwLock as AsyncRwLock;
+use tokio::task::JoinHandle;
 
 pub mod commands;
+pub mod replicas;
 pub mod responses;
 pub mod sentinel;
 pub mod types;
@@ -57,11 +59,15 @@ pub struct Counters {
 }
 
 impl Counters {
-  pub fn new(cmd_buffer_len: &Arc<AtomicUsize>) -> Self {
+  pub fn new(inner: &Arc<RedisClientInner>) -> Self {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    // the client reads the in-flight counters for each connection to estimate its load
+    inner.register_in_flight_counter(&in_flight);
+
     Counters {
-      cmd_buffer_len: cmd_buffer_len.clone(),
-      in_flight: Arc::new(AtomicUsize::new(0)),
+      cmd_buffer_len: inner.cmd_buffer_len.clone(),
       feed_count: Arc::new(AtomicUsize::new(0)),
+      in_flight,
     }
   }
 
@@ -79,7 +85,12 @@ impl Counters {
   }
 
   pub fn decr_in_flight(&self) -> usize {
-    client_utils::decr_atomic(&self.in_flight)
+    // the counter is reset when reconnecting, so responses that arrive afterwards must not wrap around
+    self
+      .in_flight
+      .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| Some(count.saturating_sub(1)))
+      .unwrap_or(0)
+      .saturating_sub(1)
   }
 
   pub fn reset_feed_count(&self) {
@@ -97,6 +108,46 @@ pub enum ConnectionIDs {
   Clustered(Arc<RwLock<BTreeMap<Arc<String>, i64>>>),
 }
 
+/// The command queue and counters for a connection to a replica node discovered via the sentinel nodes.
+///
+/// Each replica connection is managed like a centralized connection.
+#[derive(Clone)]
+pub struct ReplicaConnection {
+  pub commands: Arc<Mutex<SentCommands>>,
+  pub counters: Counters,
+}
+
+/// Connections to the replica nodes discovered via the sentinel nodes.
+#[derive(Clone)]
+pub struct ReplicaConnections {
+  pub writers: Arc<AsyncRwLock<BTreeMap<Arc<String>, RedisSink>>>,
+  pub connections: Arc<RwLock<BTreeMap<Arc<String>, ReplicaConnection>>>,
+  /// A sender that closes the replica connections without closing the connection to the primary node.
+  pub close_tx: Arc<RwLock<Option<CloseTx>>>,
+  /// The task that listens for sentinel events that affect the replica nodes.
+  pub watcher: Arc<RwLock<Option<JoinHandle<()>>>>,
+}
+
+impl ReplicaConnections {
+  pub fn new() -> Self {
+    ReplicaConnections {
+      writers: Arc::new(AsyncRwLock::new(BTreeMap::new())),
+      connections: Arc::new(RwLock::new(BTreeMap::new())),
+      close_tx: Arc::new(RwLock::new(None)),
+      watcher: Arc::new(RwLock::new(None)),
+    }
+  }
+
+  /// Take the commands that were sent to any replica but have not received a response.
+  pub fn take_commands(&self) -> SentCommands {
+    let mut out = VecDeque::new();
+    for (_, connection) in self.connections.read().iter() {
+      out.extend(connection.commands.lock().drain(..));
+    }
+    out
+  }
+}
+
 #[derive(Clone)]
 pub enum Connections {
   Centralized {
@@ -106,6 +157,7 @@ pub enum Connections {
     // TODO find a better way to do this that works with mutable servers due to sentinel changes, but where server names are cloned a lot
     server: Arc<AsyncRwLock<Arc<String>>>,
     connection_id: Arc<RwLock<Option<i64>>>,
+    replicas: ReplicaConnections,
   },
   Clustered {
     cache: Arc<RwLock<ClusterKeyCache>>,
@@ -117,15 +169,16 @@ pub enum Connections {
 }
 
 impl Connections {
-  pub fn new_centralized(inner: &Arc<RedisClientInner>, cmd_buffer_len: &Arc<AtomicUsize>) -> Self {
+  pub fn new_centralized(inner: &Arc<RedisClientInner>) -> Self {
     let server = utils::centralized_server_name(inner);
 
     Connections::Centralized {
       server: Arc::new(AsyncRwLock::new(Arc::new(server))),
-      counters: Counters::new(cmd_buffer_len),
+      counters: Counters::new(inner),
       writer: Arc::new(AsyncRwLock::new(None)),
       commands: Arc::new(Mutex::new(VecDeque::new())),
       connection_id: Arc::new(RwLock::new(None)),
+      replicas: ReplicaConnections::new(),
     }
   }
 
@@ -171,7 +224,7 @@ impl Multiplexer {
     let connections = if clustered {
       Connections::new_clustered()
     } else {
-      Connections::new_centralized(inner, &inner.cmd_buffer_len)
+      Connections::new_centralized(inner)
     };
 
     Multiplexer {
@@ -248,13 +301,14 @@ impl Multiplexer {
     }
 
     if let Connections::Clustered {
+      ref cache,
       ref writers,
       ref commands,
       ref counters,
       ..
     } = self.connections
     {
-      utils::write_all_nodes(&self.inner, writers, commands, counters, command).await
+      utils::write_all_nodes(&self.inner, cache, writers, commands, counters, command).await
     } else {
       Err(RedisError::new(
         RedisErrorKind::Config,
@@ -0,0 +1,126 @@
+use crate::modules::inner::RedisClientInner;
+use crate::protocol::types::RedisCommand;
+use crate::types::{ReplicaConfig, ReplicaPolicy};
+use rand::Rng;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Selects replica nodes for read-only commands according to the client's `ReplicaConfig`.
+pub struct ReplicaRouter {
+  config: ReplicaConfig,
+  next: AtomicUsize,
+}
+
+impl ReplicaRouter {
+  pub fn new(config: ReplicaConfig) -> Self {
+    ReplicaRouter {
+      config,
+      next: AtomicUsize::new(0),
+    }
+  }
+
+  /// Read the config used to create the router.
+  pub fn config(&self) -> &ReplicaConfig {
+    &self.config
+  }
+
+  /// Select a replica from `replicas`, skipping any servers for which `available` returns `false`.
+  pub fn select<F>(&self, replicas: &[Arc<String>], available: F) -> Option<Arc<String>>
+  where
+    F: Fn(&Arc<String>) -> bool,
+  {
+    let candidates: Vec<&Arc<String>> = replicas.iter().filter(|server| available(server)).collect();
+    if candidates.is_empty() {
+      return None;
+    }
+
+    let idx = match self.config.policy {
+      ReplicaPolicy::Random => rand::thread_rng().gen_range(0..candidates.len()),
+      ReplicaPolicy::RoundRobin => self.next.fetch_add(1, Ordering::SeqCst) % candidates.len(),
+      ReplicaPolicy::PreferZone(ref filter) => {
+        let local: Vec<&Arc<String>> = candidates
+          .iter()
+          .filter(|server| filter(server.as_str()))
+          .map(|server| *server)
+          .collect();
+
+        if !local.is_empty() {
+          let idx = rand::thread_rng().gen_range(0..local.len());
+          return Some(local[idx].clone());
+        }
+        rand::thread_rng().gen_range(0..candidates.len())
+      }
+    };
+
+    Some(candidates[idx].clone())
+  }
+}
+
+/// Whether or not the command can be sent to a replica node.
+pub fn should_use_replica(inner: &Arc<RedisClientInner>, command: &RedisCommand) -> bool {
+  let route_by_default = match inner.replicas {
+    Some(ref router) => router.config().route_by_default,
+    None => return false,
+  };
+
+  (route_by_default || command.use_replica)
+    && !command.primary_only
+    && command.kind.is_read()
+    && inner.multi_block.read().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn servers() -> Vec<Arc<String>> {
+    vec![
+      Arc::new("a:6379".to_owned()),
+      Arc::new("b:6379".to_owned()),
+      Arc::new("c:6379".to_owned()),
+    ]
+  }
+
+  #[test]
+  fn should_select_round_robin() {
+    let router = ReplicaRouter::new(ReplicaConfig {
+      policy: ReplicaPolicy::RoundRobin,
+      ..Default::default()
+    });
+    let servers = servers();
+
+    let selected: Vec<String> = (0..4)
+      .map(|_| router.select(&servers, |_| true).unwrap().as_str().to_owned())
+      .collect();
+    assert_eq!(selected, vec!["a:6379", "b:6379", "c:6379", "a:6379"]);
+  }
+
+  #[test]
+  fn should_skip_unavailable_replicas() {
+    let router = ReplicaRouter::new(ReplicaConfig::default());
+    let servers = servers();
+
+    for _ in 0..10 {
+      let selected = router.select(&servers, |server| server.as_str() == "b:6379").unwrap();
+      assert_eq!(selected.as_str(), "b:6379");
+    }
+    assert!(router.select(&servers, |_| false).is_none());
+    assert!(router.select(&[], |_| true).is_none());
+  }
+
+  #[test]
+  fn should_prefer_same_zone() {
+    let router = ReplicaRouter::new(ReplicaConfig {
+      policy: ReplicaPolicy::PreferZone(Arc::new(|server: &str| server.starts_with("c"))),
+      ..Default::default()
+    });
+    let servers = servers();
+
+    for _ in 0..10 {
+      let selected = router.select(&servers, |_| true).unwrap();
+      assert_eq!(selected.as_str(), "c:6379");
+    }
+    let selected = router.select(&servers, |server| server.as_str() != "c:6379").unwrap();
+    assert_ne!(selected.as_str(), "c:6379");
+  }
+}
@@ -1,4 +1,5 @@
 use crate::error::{RedisError, RedisErrorKind};
+use crate::modules::cache;
 use crate::modules::inner::RedisClientInner;
 use crate::multiplexer::utils;
 use crate::multiplexer::{Counters, SentCommand, SentCommands};
@@ -6,13 +7,16 @@ use crate::protocol::types::{RedisCommandKind, ResponseKind, ValueScanInner, Val
 use crate::protocol::utils as protocol_utils;
 use crate::protocol::utils::{frame_to_error, frame_to_single_result};
 use crate::trace;
-use crate::types::{HScanResult, KeyspaceEvent, RedisKey, RedisValue, SScanResult, ScanResult, ZScanResult};
+use crate::types::{
+  HScanResult, KeyspaceEvent, Message, MessageKind, RedisKey, RedisValue, SScanResult, ScanResult, ZScanResult,
+};
 use crate::utils as client_utils;
 use bytes_utils::Str;
 use parking_lot::{Mutex, RwLock};
 use redis_protocol::resp3::types::Frame as Resp3Frame;
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
 
 #[cfg(feature = "custom-reconnect-errors")]
 use crate::globals::globals;
@@ -40,17 +44,40 @@ fn sample_latency(latency_stats: &RwLock<MovingStats>, sent: Instant) {
   latency_stats.write().sample(dur_ms);
 }
 
-/// Sample overall and network latency values for a command.
+/// Sample overall and network latency values for a command, and the latency and payload size histograms for the
+/// command and server.
+///
+/// Centralized callers provide `None` for the server.
 #[cfg(feature = "metrics")]
-fn sample_command_latencies(inner: &Arc<RedisClientInner>, command: &mut SentCommand) {
+fn sample_command_metrics(
+  inner: &Arc<RedisClientInner>,
+  server: Option<&Arc<String>>,
+  command: &mut SentCommand,
+  frame: &Resp3Frame,
+) {
   if let Some(sent) = command.network_start.take() {
     sample_latency(&inner.network_latency_stats, sent);
   }
   sample_latency(&inner.latency_stats, command.command.sent);
+
+  let server = match server {
+    Some(server) => server.clone(),
+    None => Arc::new(utils::centralized_server_name(inner)),
+  };
+  let latency = command.command.sent.elapsed().as_micros() as u64;
+  let req_size = protocol_utils::args_size(&command.command.args) as u64;
+  let res_size = protocol_utils::resp3_frame_size(frame) as u64;
+  inner.command_histograms.write().sample(
+    command.command.kind.to_str_debug(),
+    &server,
+    latency,
+    req_size,
+    res_size,
+  );
 }
 
 #[cfg(not(feature = "metrics"))]
-fn sample_command_latencies(_: &Arc<RedisClientInner>, _: &mut SentCommand) {}
+fn sample_command_metrics(_: &Arc<RedisClientInner>, _: Option<&Arc<String>>, _: &mut SentCommand, _: &Resp3Frame) {}
 
 /// Merge multiple potentially nested frames into one flat array of frames.
 fn merge_multiple_frames(frames: &mut VecDeque<Resp3Frame>) -> Resp3Frame {
@@ -342,14 +369,18 @@ fn send_value_scan_error(
   Ok(())
 }
 
-/// Emit `(channel, message)` tuples on the pubsub interface, closing any senders for whom the receiver has been dropped.
-fn emit_pubsub_message(inner: &Arc<RedisClientInner>, channel: String, message: RedisValue) {
+/// Send a value to each listener, closing any senders for whom the receiver has been dropped.
+fn emit_to_listeners<T: Clone>(
+  inner: &Arc<RedisClientInner>,
+  listeners: &RwLock<VecDeque<UnboundedSender<T>>>,
+  value: T,
+) {
   let mut to_remove = BTreeSet::new();
 
   // check for closed senders as we emit messages, and drop them at the end
   {
-    for (idx, tx) in inner.message_tx.read().iter().enumerate() {
-      if let Err(_) = tx.send((channel.clone(), message.clone())) {
+    for (idx, tx) in listeners.read().iter().enumerate() {
+      if let Err(_) = tx.send(value.clone()) {
         to_remove.insert(idx);
       }
     }
@@ -357,7 +388,7 @@ fn emit_pubsub_message(inner: &Arc<RedisClientInner>, channel: String, message:
 
   if !to_remove.is_empty() {
     _trace!(inner, "Removing {} closed pubsub listeners", to_remove.len());
-    let mut message_tx_guard = inner.message_tx.write();
+    let mut message_tx_guard = listeners.write();
     let message_tx_ref = &mut *message_tx_guard;
 
     let mut new_listeners = VecDeque::with_capacity(message_tx_ref.len() - to_remove.len());
@@ -371,6 +402,12 @@ fn emit_pubsub_message(inner: &Arc<RedisClientInner>, channel: String, message:
   }
 }
 
+/// Emit messages on the pubsub interface as both `(channel, message)` tuples and `Message` structs.
+fn emit_pubsub_message(inner: &Arc<RedisClientInner>, message: Message) {
+  emit_to_listeners(inner, &inner.message_tx, (message.channel.clone(), message.value.clone()));
+  emit_to_listeners(inner, &inner.pubsub_message_tx, message);
+}
+
 /// Emit keyspace events on the keyspace interface, closing any senders for whom the receiver has been dropped.
 fn emit_keyspace_event(inner: &Arc<RedisClientInner>, event: KeyspaceEvent) {
   let mut to_remove = BTreeSet::new();
@@ -386,17 +423,17 @@ fn emit_keyspace_event(inner: &Arc<RedisClientInner>, event: KeyspaceEvent) {
 
   if !to_remove.is_empty() {
     _trace!(inner, "Removing {} closed keyspace listeners", to_remove.len());
-    let mut message_tx_guard = inner.message_tx.write();
-    let message_tx_ref = &mut *message_tx_guard;
+    let mut keyspace_tx_guard = inner.keyspace_tx.write();
+    let keyspace_tx_ref = &mut *keyspace_tx_guard;
 
-    let mut new_listeners = VecDeque::with_capacity(message_tx_ref.len() - to_remove.len());
+    let mut new_listeners = VecDeque::with_capacity(keyspace_tx_ref.len() - to_remove.len());
 
-    for (idx, tx) in message_tx_ref.drain(..).enumerate() {
+    for (idx, tx) in keyspace_tx_ref.drain(..).enumerate() {
       if !to_remove.contains(&idx) {
         new_listeners.push_back(tx);
       }
     }
-    *message_tx_ref = new_listeners;
+    *keyspace_tx_ref = new_listeners;
   }
 }
 
@@ -408,6 +445,12 @@ fn respond_to_caller(inner: &Arc<RedisClientInner>, last_command: SentCommand, f
     last_command.command.kind.to_str_debug()
   );
 
+  _export!(inner, |exporter, client| {
+    if let Some(error) = frame_to_error(&frame) {
+      exporter.record_error(client, error.kind());
+    }
+  });
+
   if let Some(tx) = last_command.command.tx {
     if let Err(_) = tx.send(Ok(frame)) {
       _warn!(inner, "Failed to respond to caller.");
@@ -425,6 +468,8 @@ fn respond_to_caller_error(inner: &Arc<RedisClientInner>, last_command: SentComm
     last_command.command.kind.to_str_debug()
   );
 
+  _export!(inner, |exporter, client| exporter.record_error(client, error.kind()));
+
   if let Some(tx) = last_command.command.tx {
     if let Err(_) = tx.send(Err(error)) {
       _warn!(inner, "Failed to respond to caller.");
@@ -578,7 +623,7 @@ async fn process_response(
     // types of commands non-pipelined in all cases, since there's no mechanism in the protocol to associate out-of-order responses.
     return handle_multiple_responses(inner, last_command, frame).await;
   } else if last_command.command.kind.is_scan() {
-    client_utils::decr_atomic(&counters.in_flight);
+    counters.decr_in_flight();
 
     let (next_cursor, keys) = match handle_key_scan_result(frame) {
       Ok(result) => result,
@@ -597,7 +642,7 @@ async fn process_response(
       _warn!(inner, "Failed to send key scan result");
     }
   } else if last_command.command.kind.is_value_scan() {
-    client_utils::decr_atomic(&counters.in_flight);
+    counters.decr_in_flight();
 
     let (next_cursor, values) = match handle_value_scan_result(frame) {
       Ok(result) => result,
@@ -618,8 +663,13 @@ async fn process_response(
   } else if last_command.command.kind.is_all_cluster_nodes() {
     return Ok(handle_all_nodes_response(inner, last_command, frame).await);
   } else {
-    client_utils::decr_atomic(&counters.in_flight);
-    sample_command_latencies(inner, &mut last_command);
+    counters.decr_in_flight();
+    sample_command_metrics(inner, Some(server), &mut last_command, &frame);
+    _export!(inner, |exporter, client| {
+      let latency = last_command.command.sent.elapsed();
+      exporter.record_latency(client, server, last_command.command.kind.to_str_debug(), latency);
+      exporter.record_in_flight(client, server, client_utils::read_atomic(&counters.in_flight));
+    });
 
     // update the protocol version after a non-error response is received from HELLO
     if last_command.command.kind.is_hello() {
@@ -635,7 +685,7 @@ async fn process_response(
 
 fn parse_keyspace_notification(channel: String, message: RedisValue) -> Result<KeyspaceEvent, (String, RedisValue)> {
   if channel.starts_with(KEYEVENT_PREFIX) {
-    let parts: Vec<&str> = channel.split("@").collect();
+    let parts: Vec<&str> = channel.splitn(2, '@').collect();
     if parts.len() != 2 {
       return Err((channel, message));
     }
@@ -657,12 +707,13 @@ fn parse_keyspace_notification(channel: String, message: RedisValue) -> Result<K
 
     Ok(KeyspaceEvent { db, key, operation })
   } else if channel.starts_with(KEYSPACE_PREFIX) {
-    let parts: Vec<&str> = channel.split("@").collect();
+    // keys can contain `@` and `:`, but the prefix and db cannot
+    let parts: Vec<&str> = channel.splitn(2, '@').collect();
     if parts.len() != 2 {
       return Err((channel, message));
     }
 
-    let suffix: Vec<&str> = parts[1].split(":").collect();
+    let suffix: Vec<&str> = parts[1].splitn(2, ':').collect();
     if suffix.len() != 2 {
       return Err((channel, message));
     }
@@ -708,13 +759,51 @@ fn check_pubsub_formats(frame: &Resp3Frame) -> (bool, bool) {
   )
 }
 
+/// Check whether the frame is a message on a sharded channel, which uses the `["smessage", channel, message]` format
+/// with both RESP2 and RESP3.
+fn is_sharded_pubsub_message(frame: &Resp3Frame) -> bool {
+  match frame {
+    Resp3Frame::Array { ref data, .. } | Resp3Frame::Push { ref data, .. } => {
+      data.len() == 3 && data[0].as_str().map(|s| s == "smessage").unwrap_or(false)
+    }
+    _ => false,
+  }
+}
+
+/// Check whether the frame is a message on a channel that matched a pattern, in either RESP2 or RESP3 formats.
+fn is_pattern_pubsub_message(frame: &Resp3Frame) -> bool {
+  if frame.is_pattern_pubsub_message() {
+    return true;
+  }
+
+  match frame {
+    Resp3Frame::Array { ref data, .. } | Resp3Frame::Push { ref data, .. } => {
+      data.len() == 4 && data[0].as_str().map(|s| s == "pmessage").unwrap_or(false)
+    }
+    _ => false,
+  }
+}
+
+/// Check whether the frame is a `SUNSUBSCRIBE` notification.
+fn is_sharded_unsubscribe(frame: &Resp3Frame) -> bool {
+  match frame {
+    Resp3Frame::Array { ref data, .. } | Resp3Frame::Push { ref data, .. } => {
+      data.len() == 3 && data[0].as_str().map(|s| s == "sunsubscribe").unwrap_or(false)
+    }
+    _ => false,
+  }
+}
+
 /// Try to parse the frame in either RESP2 or RESP3 pubsub formats.
 fn parse_pubsub_message(
   frame: Resp3Frame,
   is_resp3: bool,
   is_resp2: bool,
+  is_sharded: bool,
 ) -> Result<(String, RedisValue), RedisError> {
-  if is_resp3 {
+  if is_sharded {
+    protocol_utils::frame_to_sharded_pubsub(frame)
+  } else if is_resp3 {
     protocol_utils::frame_to_pubsub(frame)
   } else if is_resp2 {
     // this is safe to do in limited circumstances like this since RESP2 and RESP3 pubsub arrays are similar enough
@@ -734,9 +823,17 @@ fn check_pubsub_message(inner: &Arc<RedisClientInner>, frame: Resp3Frame) -> Opt
   // in this case using resp3 frames can cause issues, since resp3 push commands are represented
   // differently than resp2 array frames. to fix this we convert back to resp2 here if needed.
   let (is_resp3_pubsub, is_resp2_pubsub) = check_pubsub_formats(&frame);
-  if !is_resp3_pubsub && !is_resp2_pubsub {
+  let is_sharded = is_sharded_pubsub_message(&frame);
+  if !is_resp3_pubsub && !is_resp2_pubsub && !is_sharded {
     return Some(frame);
   }
+  let kind = if is_sharded {
+    MessageKind::SMessage
+  } else if is_pattern_pubsub_message(&frame) {
+    MessageKind::PMessage
+  } else {
+    MessageKind::Message
+  };
 
   let span = if inner.should_trace() {
     let span = trace::create_pubsub_span(inner, &frame);
@@ -748,9 +845,9 @@ fn check_pubsub_message(inner: &Arc<RedisClientInner>, frame: Resp3Frame) -> Opt
   _trace!(inner, "Processing pubsub message.");
   let parsed_frame = if let Some(ref span) = span {
     let _enter = span.enter();
-    parse_pubsub_message(frame, is_resp3_pubsub, is_resp2_pubsub)
+    parse_pubsub_message(frame, is_resp3_pubsub, is_resp2_pubsub, is_sharded)
   } else {
-    parse_pubsub_message(frame, is_resp3_pubsub, is_resp2_pubsub)
+    parse_pubsub_message(frame, is_resp3_pubsub, is_resp2_pubsub, is_sharded)
   };
 
   let (channel, message) = match parsed_frame {
@@ -764,14 +861,71 @@ fn check_pubsub_message(inner: &Arc<RedisClientInner>, frame: Resp3Frame) -> Opt
     span.record("channel", &channel.as_str());
   }
 
+  if is_sharded {
+    emit_pubsub_message(inner, Message {
+      channel,
+      value: message,
+      kind,
+    });
+    return None;
+  }
+
   match parse_keyspace_notification(channel, message) {
     Ok(event) => emit_keyspace_event(inner, event),
-    Err((channel, message)) => emit_pubsub_message(inner, channel, message),
+    Err((channel, value)) => emit_pubsub_message(inner, Message { channel, value, kind }),
   };
 
   None
 }
 
+/// Check if the frame is a `SUNSUBSCRIBE` notification that the server sent without a corresponding command, and if so
+/// refresh the cluster state.
+///
+/// The server sends these when the hash slot of a sharded channel moves to another node. Refreshing the cluster state
+/// emits a reconnection event, which the `SubscriberClient` uses to subscribe to the channel again on the new node.
+fn check_sharded_unsubscribe(
+  inner: &Arc<RedisClientInner>,
+  server: &Arc<String>,
+  commands: &Arc<Mutex<BTreeMap<Arc<String>, SentCommands>>>,
+  frame: Resp3Frame,
+) -> Option<Resp3Frame> {
+  if !is_sharded_unsubscribe(&frame) {
+    return Some(frame);
+  }
+  let expected = commands
+    .lock()
+    .get(server)
+    .and_then(|commands| commands.front().map(|c| c.command.kind == RedisCommandKind::Sunsubscribe))
+    .unwrap_or(false);
+  if expected {
+    return Some(frame);
+  }
+
+  _debug!(inner, "Recv SUNSUBSCRIBE notification without a command from {}", server);
+  let error = RedisError::new(RedisErrorKind::Cluster, "Sharded channel moved to another node.");
+  utils::sync_cluster_state(inner, error);
+  None
+}
+
+/// Check if the frame is a client tracking invalidation message, and if so evict the associated keys from the cache.
+///
+/// If not then return it to the caller for further processing.
+fn check_invalidation_message(inner: &Arc<RedisClientInner>, frame: Resp3Frame) -> Option<Resp3Frame> {
+  let cache = match inner.cache {
+    Some(ref cache) => cache,
+    None => return Some(frame),
+  };
+
+  match cache::parse_invalidation(&frame) {
+    Some(invalidation) => {
+      _trace!(inner, "Recv invalidation message: {:?}", invalidation);
+      cache.invalidate(invalidation);
+      None
+    }
+    None => Some(frame),
+  }
+}
+
 #[cfg(feature = "reconnect-on-auth-error")]
 /// Parse the response frame to see if it's an auth error.
 fn parse_redis_auth_error(frame: &Resp3Frame) -> Option<RedisError> {
@@ -954,7 +1108,7 @@ async fn end_centralized_multi_block(
     let _ = client_utils::take_locked(&inner.multi_block);
 
     if let Some(mut recent_cmd) = recent_cmd {
-      sample_command_latencies(inner, &mut recent_cmd);
+      sample_command_metrics(inner, None, &mut recent_cmd, &frame);
       check_command_resp_tx(inner, &recent_cmd).await;
       respond_to_caller(inner, recent_cmd, frame);
       return Ok(());
@@ -986,7 +1140,7 @@ async fn end_centralized_multi_block(
     ));
   }
 
-  sample_command_latencies(inner, &mut last_command);
+  sample_command_metrics(inner, None, &mut last_command, &frame);
   check_command_resp_tx(inner, &last_command).await;
   respond_to_caller(inner, last_command, frame);
 
@@ -1022,7 +1176,7 @@ async fn end_clustered_multi_block(
     let _ = client_utils::take_locked(&inner.multi_block);
 
     if let Some(mut recent_cmd) = recent_cmd {
-      sample_command_latencies(inner, &mut recent_cmd);
+      sample_command_metrics(inner, Some(server), &mut recent_cmd, &frame);
       check_command_resp_tx(inner, &recent_cmd).await;
       respond_to_caller(inner, recent_cmd, frame);
       return Ok(());
@@ -1052,7 +1206,7 @@ async fn end_clustered_multi_block(
     ));
   }
 
-  sample_command_latencies(inner, &mut last_command);
+  sample_command_metrics(inner, Some(server), &mut last_command, &frame);
   check_command_resp_tx(inner, &last_command).await;
   respond_to_caller(inner, last_command, frame);
 
@@ -1097,7 +1251,7 @@ async fn handle_clustered_queued_response(
     let _ = client_utils::take_locked(&inner.multi_block);
   }
 
-  sample_command_latencies(inner, &mut last_command);
+  sample_command_metrics(inner, Some(server), &mut last_command, &frame);
   check_command_resp_tx(inner, &last_command).await;
   respond_to_caller(inner, last_command, frame);
 
@@ -1140,7 +1294,7 @@ async fn handle_centralized_queued_response(
     let _ = client_utils::take_locked(&inner.multi_block);
   }
 
-  sample_command_latencies(inner, &mut last_command);
+  sample_command_metrics(inner, None, &mut last_command, &frame);
   check_command_resp_tx(inner, &last_command).await;
   respond_to_caller(inner, last_command, frame);
 
@@ -1218,6 +1372,14 @@ pub async fn process_clustered_frame(
   frame: Resp3Frame,
 ) -> Result<(), RedisError> {
   if let Some(error) = check_redirection_error(inner, &frame) {
+    _export!(inner, |exporter, client| {
+      let kind = if error.details().starts_with("ASK") { "ASK" } else { "MOVED" };
+      exporter.record_redirect(client, server, kind);
+    });
+    // the keys in the cache may have moved to a server that doesn't track them
+    if let Some(ref cache) = inner.cache {
+      cache.clear();
+    }
     handle_redirection_error(inner, server, commands, error)?;
     return Ok(());
   }
@@ -1225,6 +1387,14 @@ pub async fn process_clustered_frame(
     // this closes the stream and initiates a reconnect, if configured
     return Err(error);
   }
+  let frame = match check_invalidation_message(inner, frame) {
+    Some(frame) => frame,
+    None => return Ok(()),
+  };
+  let frame = match check_sharded_unsubscribe(inner, server, commands, frame) {
+    Some(frame) => frame,
+    None => return Ok(()),
+  };
 
   if let Some(frame) = check_pubsub_message(inner, frame) {
     if response_is_queued(&frame) {
@@ -1279,6 +1449,10 @@ pub async fn process_centralized_frame(
     // this closes the stream and initiates a reconnect, if configured
     return Err(error);
   }
+  let frame = match check_invalidation_message(inner, frame) {
+    Some(frame) => frame,
+    None => return Ok(()),
+  };
 
   if let Some(frame) = check_pubsub_message(inner, frame) {
     if response_is_queued(&frame) {
@@ -1314,3 +1488,130 @@ pub async fn process_centralized_frame(
     Ok(())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::protocol::types::RedisCommand;
+  use crate::types::RedisConfig;
+  use tokio::sync::mpsc::unbounded_channel;
+
+  fn blob_string(s: &str) -> Resp3Frame {
+    Resp3Frame::BlobString {
+      data: s.as_bytes().to_vec().into(),
+      attributes: None,
+    }
+  }
+
+  fn sunsubscribe_frame(channel: &str) -> Resp3Frame {
+    Resp3Frame::Push {
+      data: vec![
+        blob_string("sunsubscribe"),
+        blob_string(channel),
+        Resp3Frame::Number {
+          data: 0,
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    }
+  }
+
+  fn sent_commands(
+    server: &Arc<String>,
+    kind: Option<RedisCommandKind>,
+  ) -> Arc<Mutex<BTreeMap<Arc<String>, SentCommands>>> {
+    let mut commands = VecDeque::new();
+    if let Some(kind) = kind {
+      commands.push_back(RedisCommand::new(kind, vec![], None).into());
+    }
+
+    let mut sent = BTreeMap::new();
+    sent.insert(server.clone(), commands);
+    Arc::new(Mutex::new(sent))
+  }
+
+  #[test]
+  fn should_detect_sharded_unsubscribe_frames() {
+    assert!(is_sharded_unsubscribe(&sunsubscribe_frame("foo")));
+    assert!(!is_sharded_unsubscribe(&Resp3Frame::Push {
+      data: vec![blob_string("smessage"), blob_string("foo"), blob_string("bar")],
+      attributes: None,
+    }));
+    assert!(!is_sharded_unsubscribe(&blob_string("sunsubscribe")));
+  }
+
+  #[test]
+  fn should_sync_cluster_state_on_unexpected_sharded_unsubscribe() {
+    let inner = RedisClientInner::new(RedisConfig::default());
+    let (tx, mut rx) = unbounded_channel();
+    *inner.connection_closed_tx.write() = Some(tx);
+    let server = Arc::new("127.0.0.1:30001".to_owned());
+    let commands = sent_commands(&server, Some(RedisCommandKind::Get));
+
+    let frame = check_sharded_unsubscribe(&inner, &server, &commands, sunsubscribe_frame("foo"));
+    assert!(frame.is_none());
+
+    let closed = rx.try_recv().expect("Missing connection closed event");
+    assert_eq!(*closed.error.kind(), RedisErrorKind::Cluster);
+    assert!(closed.commands.is_empty());
+    // the in-flight command should not be modified
+    assert_eq!(commands.lock().get(&server).unwrap().len(), 1);
+  }
+
+  #[test]
+  fn should_not_sync_cluster_state_on_sharded_unsubscribe_response() {
+    let inner = RedisClientInner::new(RedisConfig::default());
+    let (tx, mut rx) = unbounded_channel();
+    *inner.connection_closed_tx.write() = Some(tx);
+    let server = Arc::new("127.0.0.1:30001".to_owned());
+    let commands = sent_commands(&server, Some(RedisCommandKind::Sunsubscribe));
+
+    let frame = check_sharded_unsubscribe(&inner, &server, &commands, sunsubscribe_frame("foo"));
+    assert_eq!(frame, Some(sunsubscribe_frame("foo")));
+    assert!(rx.try_recv().is_err());
+
+    let frame = check_sharded_unsubscribe(&inner, &server, &commands, blob_string("bar"));
+    assert_eq!(frame, Some(blob_string("bar")));
+    assert!(rx.try_recv().is_err());
+  }
+
+  #[test]
+  fn should_parse_keyspace_notification_with_special_characters() {
+    let event = parse_keyspace_notification("__keyspace@0__:user@example.com:1".into(), "set".into()).unwrap();
+    assert_eq!(event.db, 0);
+    assert_eq!(event.key, "user@example.com:1");
+    assert_eq!(event.operation, "set");
+  }
+
+  #[test]
+  fn should_parse_keyevent_notification_with_special_characters() {
+    let event = parse_keyspace_notification("__keyevent@1__:del".into(), "user@example.com:1".into()).unwrap();
+    assert_eq!(event.db, 1);
+    assert_eq!(event.key, "user@example.com:1");
+    assert_eq!(event.operation, "del");
+  }
+
+  #[test]
+  fn should_remove_closed_keyspace_listeners() {
+    let inner = RedisClientInner::new(RedisConfig::default());
+    let (message_tx, _message_rx) = unbounded_channel();
+    inner.message_tx.write().push_back(message_tx);
+    let (open_tx, mut open_rx) = unbounded_channel();
+    let (closed_tx, closed_rx) = unbounded_channel();
+    drop(closed_rx);
+    inner.keyspace_tx.write().push_back(closed_tx);
+    inner.keyspace_tx.write().push_back(open_tx);
+
+    let event = KeyspaceEvent {
+      db: 0,
+      operation: "set".into(),
+      key: "foo".into(),
+    };
+    emit_keyspace_event(&inner, event.clone());
+
+    assert_eq!(open_rx.try_recv().unwrap(), event);
+    assert_eq!(inner.keyspace_tx.read().len(), 1);
+    assert_eq!(inner.message_tx.read().len(), 1);
+  }
+}
@@ -1,31 +1,42 @@
 use crate::error::{RedisError, RedisErrorKind};
 use crate::globals::globals;
+use crate::modules::cache;
 use crate::modules::inner::RedisClientInner;
-use crate::multiplexer::{utils, CloseTx, Connections, Counters, SentCommand};
+use crate::multiplexer::{
+  responses, utils, CloseTx, Connections, Counters, ReplicaConnection, ReplicaConnections, SentCommand,
+};
 use crate::protocol::codec::RedisCodec;
-use crate::protocol::connection::{self, authenticate, select_database, FramedTcp, FramedTls, RedisTransport};
+use crate::protocol::connection::{
+  self, authenticate, select_database, FramedTcp, FramedTls, RedisStream, RedisTransport,
+};
 use crate::protocol::types::{RedisCommand, RedisCommandKind};
 use crate::protocol::utils as protocol_utils;
 use crate::types::ClientState;
 use crate::types::Resolve;
 use crate::types::{RedisValue, ServerConfig};
 use crate::utils as client_utils;
-use parking_lot::RwLock;
+use futures::{pin_mut, select, FutureExt, StreamExt, TryFutureExt, TryStreamExt};
+use parking_lot::{Mutex, RwLock};
 use std::collections::VecDeque;
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::TcpStream;
+use tokio::sync::broadcast::Receiver as BroadcastReceiver;
 use tokio::sync::RwLock as AsyncRwLock;
 use tokio_util::codec::Framed;
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use crate::protocol::tls;
 
 /// The amount of time to wait when trying to connect to the redis server.
 ///
 /// This is a different timeout than the timeout connecting to the sentinel, which is controlled via a global setting.
 const DEFAULT_CONNECTION_TIMEOUT_MS: u64 = 30_000;
+/// The sentinel channels that trigger a refresh of the replica connections.
+const REPLICA_EVENT_CHANNELS: [&'static str; 3] = ["+switch-master", "+sdown", "-sdown"];
+/// Replica flags that indicate the replica should not receive commands.
+const UNHEALTHY_REPLICA_FLAGS: [&'static str; 3] = ["s_down", "o_down", "disconnected"];
 
 macro_rules! try_continue (
   ($inner:ident, $expr:expr) => {
@@ -72,13 +83,8 @@ fn read_sentinel_auth(inner: &Arc<RedisClientInner>) -> Result<(Option<String>,
   Ok((guard.username.clone(), guard.password.clone()))
 }
 
-fn read_redis_auth(inner: &Arc<RedisClientInner>) -> (Option<String>, Option<String>) {
-  let guard = inner.config.read();
-  (guard.username.clone(), guard.password.clone())
-}
-
 // TODO clean this up in the next major release by breaking up the connection functions
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub async fn create_authenticated_connection_tls(
   addr: &SocketAddr,
   domain: &str,
@@ -91,12 +97,11 @@ pub async fn create_authenticated_connection_tls(
   let ((username, password), is_resp3) = if is_sentinel {
     (read_sentinel_auth(inner)?, false)
   } else {
-    (read_redis_auth(inner), inner.is_resp3())
+    (connection::read_credentials(inner).await?, inner.is_resp3())
   };
 
   let socket = TcpStream::connect(addr).await?;
-  let tls_stream = tls::create_tls_connector(&inner.config)?;
-  let socket = tls_stream.connect(domain, socket).await?;
+  let socket = tls::connect(&inner.config, domain, socket).await?;
   let framed = if is_sentinel {
     Framed::new(socket, codec)
   } else {
@@ -112,7 +117,7 @@ pub async fn create_authenticated_connection_tls(
   Ok(framed)
 }
 
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 pub(crate) async fn create_authenticated_connection_tls(
   addr: &SocketAddr,
   _domain: &str,
@@ -133,7 +138,7 @@ pub async fn create_authenticated_connection(
   let ((username, password), is_resp3) = if is_sentinel {
     (read_sentinel_auth(inner)?, false)
   } else {
-    (read_redis_auth(inner), inner.is_resp3())
+    (connection::read_credentials(inner).await?, inner.is_resp3())
   };
 
   let socket = TcpStream::connect(addr).await?;
@@ -309,6 +314,19 @@ async fn update_connection_id(
       };
       RedisTransport::Tls(framed)
     }
+    RedisTransport::Unix(framed) => {
+      let framed = match connection::read_client_id(inner, framed).await {
+        Ok((id, socket)) => {
+          if let Some(id) = id {
+            _debug!(inner, "Read sentinel connection ID: {}", id);
+            connection_id.write().replace(id);
+          }
+          socket
+        }
+        Err((_, socket)) => socket,
+      };
+      RedisTransport::Unix(framed)
+    }
   };
 
   Ok(transport)
@@ -350,6 +368,7 @@ async fn connect_centralized(
 
     let tx = update_sentinel_client_state(inner, server, counters, &new_server, close_tx).await;
     let transport = update_connection_id(inner, connection_id, transport).await?;
+    let transport = cache::enable_transport_tracking(inner, &new_server, transport).await?;
     let (sink, stream) = connection::split_transport(transport);
 
     client_utils::set_locked_async(writer, Some(sink)).await;
@@ -454,6 +473,318 @@ pub async fn update_sentinel_nodes(
   Ok(())
 }
 
+fn parse_sentinel_replicas_response(
+  inner: &Arc<RedisClientInner>,
+  value: RedisValue,
+) -> Result<Vec<(String, u16)>, RedisError> {
+  let result_maps: Vec<HashMap<String, String>> = stry!(value.convert());
+  let mut out = Vec::with_capacity(result_maps.len());
+
+  for mut map in result_maps.into_iter() {
+    let (ip, port) = match (map.remove("ip"), map.get("port")) {
+      (Some(ip), Some(port)) => (ip, port.parse::<u16>()?),
+      _ => {
+        _warn!(inner, "Failed to read IP or port for replica node.");
+        continue;
+      }
+    };
+    let flags = map.get("flags").map(|s| s.as_str()).unwrap_or("");
+    if flags.split(',').any(|flag| UNHEALTHY_REPLICA_FLAGS.contains(&flag)) {
+      _debug!(inner, "Skip replica {}:{} with flags {}", ip, port, flags);
+      continue;
+    }
+
+    out.push((ip, port));
+  }
+  Ok(out)
+}
+
+/// Read the healthy replicas of the primary node from the first sentinel node that responds.
+async fn discover_replica_nodes(inner: &Arc<RedisClientInner>) -> Result<Vec<(String, u16)>, RedisError> {
+  let (hosts, name) = client_utils::read_sentinel_host(inner)?;
+  let timeout = globals().sentinel_connection_timeout_ms() as u64;
+
+  for (sentinel_host, port) in hosts.into_iter() {
+    let transport = try_continue!(inner, connect_to_sentinel(inner, &sentinel_host, port, timeout).await);
+    let command = RedisCommand::new(RedisCommandKind::Sentinel, vec!["replicas".into(), name.into()], None);
+    let (frame, _) = stry!(connection::transport_request_response(transport, &command, false).await);
+    let response = stry!(protocol_utils::frame_to_results(frame.into_resp3()));
+    _trace!(inner, "Read sentinel replicas response: {:?}", response);
+
+    return parse_sentinel_replicas_response(inner, response);
+  }
+
+  Err(RedisError::new(
+    RedisErrorKind::Sentinel,
+    "Failed to connect to any sentinel node.",
+  ))
+}
+
+/// Remove the connection to a replica node, retrying any in-flight commands on the primary node.
+pub async fn remove_replica(inner: &Arc<RedisClientInner>, replicas: &ReplicaConnections, server: &Arc<String>) {
+  _debug!(inner, "Removing replica connection to {}", server);
+  let _ = replicas.writers.write().await.remove(server);
+  let connection = replicas.connections.write().remove(server);
+
+  if let Some(connection) = connection {
+    let commands = connection.commands.lock().drain(..).collect();
+    retry_on_primary(inner, commands);
+  }
+}
+
+fn retry_on_primary(inner: &Arc<RedisClientInner>, commands: VecDeque<SentCommand>) {
+  for command in commands.into_iter() {
+    let mut command = command.command;
+    command.primary_only = true;
+
+    utils::unblock_multiplexer(inner, &command);
+    if let Err(e) = client_utils::send_command(inner, command) {
+      _warn!(inner, "Failed to retry replica command: {:?}", e);
+    }
+  }
+}
+
+fn spawn_replica_listener(
+  inner: &Arc<RedisClientInner>,
+  replicas: &ReplicaConnections,
+  connection: ReplicaConnection,
+  server: &Arc<String>,
+  mut close_rx: BroadcastReceiver<RedisError>,
+  mut replica_close_rx: BroadcastReceiver<RedisError>,
+  stream: RedisStream,
+) {
+  let inner = inner.clone();
+  let replicas = replicas.clone();
+  let server = server.clone();
+
+  let _ = tokio::spawn(async move {
+    let memo = (inner.clone(), server.clone(), connection.counters, connection.commands);
+
+    let stream_ft = stream
+      .try_fold(memo, |(inner, server, counters, commands), frame| async {
+        let frame = frame.into_resp3();
+        responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
+        Ok((inner, server, counters, commands))
+      })
+      .and_then(|_| async { Ok(()) })
+      .fuse();
+    pin_mut!(stream_ft);
+    let close_ft = close_rx.recv().fuse();
+    pin_mut!(close_ft);
+    let replica_close_ft = replica_close_rx.recv().fuse();
+    pin_mut!(replica_close_ft);
+
+    let error = select! {
+      // the caller that closes the connections is responsible for the in-flight commands
+      _ = close_ft => return,
+      _ = replica_close_ft => return,
+      stream_res = stream_ft => match stream_res {
+        Ok(_) => RedisError::new_canceled(),
+        Err(e) => e,
+      }
+    };
+
+    if client_utils::read_client_state(&inner.state) != ClientState::Disconnecting {
+      _debug!(inner, "Replica frame stream from {} closed with error {:?}", server, error);
+      remove_replica(&inner, &replicas, &server).await;
+    }
+  });
+}
+
+async fn connect_replica(
+  inner: &Arc<RedisClientInner>,
+  replicas: &ReplicaConnections,
+  host: &str,
+  port: u16,
+  close_tx: &CloseTx,
+  replica_close_tx: &CloseTx,
+) -> Result<(), RedisError> {
+  let server = Arc::new(format!("{}:{}", host, port));
+  let addr = inner.resolver.resolve(host.to_owned(), port).await?;
+  let transport = connect_to_server(inner, host, &addr, DEFAULT_CONNECTION_TIMEOUT_MS, false).await?;
+  let transport = cache::enable_transport_tracking(inner, &server, transport).await?;
+  let (sink, stream) = connection::split_transport(transport);
+
+  let connection = ReplicaConnection {
+    commands: Arc::new(Mutex::new(VecDeque::new())),
+    counters: Counters::new(inner),
+  };
+  utils::insert_locked_map(&replicas.connections, server.clone(), connection.clone());
+  utils::insert_locked_map_async(&replicas.writers, server.clone(), sink).await;
+  spawn_replica_listener(
+    inner,
+    replicas,
+    connection,
+    &server,
+    close_tx.subscribe(),
+    replica_close_tx.subscribe(),
+    stream,
+  );
+  _debug!(inner, "Connected to replica {}", server);
+
+  Ok(())
+}
+
+/// Close the existing replica connections and connect to the provided replica nodes, returning any commands that were
+/// in flight on the old connections.
+async fn replace_replicas(
+  inner: &Arc<RedisClientInner>,
+  replicas: &ReplicaConnections,
+  nodes: Vec<(String, u16)>,
+  close_tx: &CloseTx,
+) -> VecDeque<SentCommand> {
+  if let Some(tx) = replicas.close_tx.write().take() {
+    let _ = tx.send(RedisError::new_canceled());
+  }
+  replicas.writers.write().await.clear();
+  let pending_commands = replicas.take_commands();
+  replicas.connections.write().clear();
+
+  let replica_close_tx = utils::get_or_create_close_tx(inner, &replicas.close_tx);
+  for (host, port) in nodes.into_iter() {
+    if let Err(e) = connect_replica(inner, replicas, &host, port, close_tx, &replica_close_tx).await {
+      // commands will be sent to the primary node if a replica is unavailable
+      _warn!(inner, "Failed to connect to replica {}:{}: {:?}", host, port, e);
+    }
+  }
+
+  pending_commands
+}
+
+/// Whether or not the sentinel event message refers to the provided service name.
+///
+/// `+switch-master` messages start with the service name, and `+sdown` and `-sdown` messages either start with
+/// `master <name>` or contain `@ <name>` for replicas and sentinels.
+fn event_names_service(message: &str, name: &str) -> bool {
+  let parts: Vec<&str> = message.split(' ').collect();
+
+  if parts.first() == Some(&name) {
+    return true;
+  }
+  if parts.first() == Some(&"master") && parts.get(1) == Some(&name) {
+    return true;
+  }
+  parts
+    .iter()
+    .position(|part| *part == "@")
+    .and_then(|idx| parts.get(idx + 1))
+    .map(|part| *part == name)
+    .unwrap_or(false)
+}
+
+async fn subscribe_to_replica_events(inner: &Arc<RedisClientInner>) -> Result<RedisTransport, RedisError> {
+  let (hosts, _) = client_utils::read_sentinel_host(inner)?;
+  let timeout = globals().sentinel_connection_timeout_ms() as u64;
+
+  for (sentinel_host, port) in hosts.into_iter() {
+    let transport = try_continue!(inner, connect_to_sentinel(inner, &sentinel_host, port, timeout).await);
+    let channels = REPLICA_EVENT_CHANNELS.iter().map(|c| (*c).into()).collect();
+    let command = RedisCommand::new(RedisCommandKind::Subscribe, channels, None);
+    // the remaining subscription responses are skipped when reading messages
+    let (_, transport) = stry!(connection::transport_request_response(transport, &command, false).await);
+
+    return Ok(transport);
+  }
+
+  Err(RedisError::new(
+    RedisErrorKind::Sentinel,
+    "Failed to connect to any sentinel node.",
+  ))
+}
+
+/// Spawn a task that refreshes the replica connections when a sentinel node publishes a `+switch-master`, `+sdown`,
+/// or `-sdown` event for the service.
+///
+/// The task stops when the connection to the primary node closes.
+fn spawn_replica_watcher(inner: &Arc<RedisClientInner>, replicas: &ReplicaConnections, close_tx: &CloseTx) {
+  let watcher = replicas.watcher.clone();
+  let (inner, _inner) = (inner.clone(), inner.clone());
+  let replicas = replicas.clone();
+  let close_tx = close_tx.clone();
+  let mut close_rx = close_tx.subscribe();
+
+  let jh = tokio::spawn(async move {
+    let name = match client_utils::read_sentinel_host(&inner) {
+      Ok((_, name)) => name,
+      Err(_) => return,
+    };
+    let transport = match subscribe_to_replica_events(&inner).await {
+      Ok(transport) => transport,
+      Err(e) => {
+        _warn!(inner, "Failed to subscribe to sentinel replica events: {:?}", e);
+        return;
+      }
+    };
+    let (_sink, mut stream) = connection::split_transport(transport);
+
+    let events_ft = async {
+      while let Some(frame) = stream.next().await {
+        let frame = match frame {
+          Ok(frame) => frame.into_resp3(),
+          Err(e) => {
+            _warn!(inner, "Error reading sentinel replica events: {:?}", e);
+            break;
+          }
+        };
+        let (channel, message) = match protocol_utils::parse_as_resp2_pubsub(frame) {
+          Ok(parts) => parts,
+          Err(_) => continue,
+        };
+        let message = message.as_str().map(|s| s.to_string()).unwrap_or_default();
+        if !event_names_service(&message, &name) {
+          continue;
+        }
+
+        _debug!(inner, "Refreshing replicas after sentinel event {}: {}", channel, message);
+        match discover_replica_nodes(&inner).await {
+          Ok(nodes) => {
+            let commands = replace_replicas(&inner, &replicas, nodes, &close_tx).await;
+            retry_on_primary(&inner, commands);
+          }
+          Err(e) => _warn!(inner, "Failed to read replicas from sentinel: {:?}", e),
+        };
+      }
+    }
+    .fuse();
+    pin_mut!(events_ft);
+    let close_ft = close_rx.recv().fuse();
+    pin_mut!(close_ft);
+
+    select! {
+      _ = events_ft => _debug!(inner, "Sentinel replica event stream closed."),
+      _ = close_ft => _debug!(inner, "Stop listening for sentinel replica events."),
+    };
+  });
+
+  let previous = watcher.write().replace(jh);
+  if let Some(jh) = previous {
+    _trace!(_inner, "Stopping previous sentinel replica event task.");
+    jh.abort();
+  }
+}
+
+/// Discover the replicas of the primary node and replace the replica connections, if replica reads are enabled.
+async fn connect_replicas(
+  inner: &Arc<RedisClientInner>,
+  connections: &Connections,
+  close_tx: &Arc<RwLock<Option<CloseTx>>>,
+) -> Result<(), RedisError> {
+  if inner.replicas.is_none() {
+    return Ok(());
+  }
+
+  if let Connections::Centralized { ref replicas, .. } = connections {
+    let close_tx = utils::get_or_create_close_tx(inner, close_tx);
+    let nodes = discover_replica_nodes(inner).await?;
+    _debug!(inner, "Found replica nodes from sentinel: {:?}", nodes);
+
+    // in-flight replica commands were already taken by the caller
+    let _ = replace_replicas(inner, replicas, nodes, &close_tx).await;
+    spawn_replica_watcher(inner, replicas, &close_tx);
+  }
+  Ok(())
+}
+
 /// Use the sentinel API to find the correct primary/main node that should act as the centralized server to the multiplexer.
 ///
 /// See [the documentation](https://redis.io/topics/sentinel-clients) for more information.
@@ -473,5 +804,59 @@ pub async fn connect_centralized_from_sentinel(
   if let Err(e) = update_sentinel_nodes(inner, sentinel_transport, &service_name).await {
     _warn!(inner, "Failed to update sentinel nodes with error: {:?}", e);
   };
+  if let Err(e) = connect_replicas(inner, connections, close_tx).await {
+    // read commands will be sent to the primary node until the replicas are refreshed
+    _warn!(inner, "Failed to connect to replicas with error: {:?}", e);
+  }
   Ok(pending_commands)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn replica(ip: &str, port: &str, flags: &str) -> RedisValue {
+    RedisValue::Array(vec![
+      "name".into(),
+      format!("{}:{}", ip, port).into(),
+      "ip".into(),
+      ip.into(),
+      "port".into(),
+      port.into(),
+      "flags".into(),
+      flags.into(),
+    ])
+  }
+
+  #[test]
+  fn should_parse_healthy_replicas() {
+    let inner = RedisClientInner::new(Default::default());
+    let response = RedisValue::Array(vec![
+      replica("10.0.0.1", "6379", "slave"),
+      replica("10.0.0.2", "6380", "s_down,slave"),
+      replica("10.0.0.3", "6381", "slave,disconnected"),
+      replica("10.0.0.4", "6382", "slave"),
+    ]);
+
+    let replicas = parse_sentinel_replicas_response(&inner, response).unwrap();
+    assert_eq!(replicas, vec![
+      ("10.0.0.1".to_owned(), 6379),
+      ("10.0.0.4".to_owned(), 6382)
+    ]);
+  }
+
+  #[test]
+  fn should_match_sentinel_events_for_service() {
+    assert!(event_names_service("mymaster 10.0.0.1 6379 10.0.0.2 6379", "mymaster"));
+    assert!(event_names_service("master mymaster 10.0.0.1 6379", "mymaster"));
+    assert!(event_names_service(
+      "slave 10.0.0.2:6379 10.0.0.2 6379 @ mymaster 10.0.0.1 6379",
+      "mymaster"
+    ));
+    assert!(!event_names_service("othermaster 10.0.0.1 6379 10.0.0.2 6379", "mymaster"));
+    assert!(!event_names_service(
+      "slave 10.0.0.2:6379 10.0.0.2 6379 @ othermaster 10.0.0.1 6379",
+      "mymaster"
+    ));
+  }
+}
@@ -1,9 +1,10 @@
 use crate::clients::RedisClient;
 use crate::error::{RedisError, RedisErrorKind};
+use crate::modules::cache;
 use crate::modules::inner::{ClosedState, RedisClientInner};
 use crate::multiplexer::types::ClusterChange;
-use crate::multiplexer::{responses, Multiplexer};
-use crate::multiplexer::{Backpressure, CloseTx, Connections, Counters, SentCommand, SentCommands};
+use crate::multiplexer::{replicas, responses, sentinel, Multiplexer};
+use crate::multiplexer::{Backpressure, CloseTx, Connections, Counters, ReplicaConnections, SentCommand, SentCommands};
 use crate::protocol::connection::{self, RedisSink, RedisStream};
 use crate::protocol::types::*;
 use crate::protocol::utils as protocol_utils;
@@ -11,7 +12,6 @@ use crate::protocol::utils::server_to_parts;
 use crate::trace;
 use crate::types::*;
 use crate::utils as client_utils;
-use futures::future::Either;
 use futures::pin_mut;
 use futures::select;
 use futures::{FutureExt, StreamExt, TryFutureExt, TryStreamExt};
@@ -43,7 +43,7 @@ pub fn close_reconnect_tx(reconnect_tx: &RwLock<VecDeque<UnboundedSender<RedisCl
   }
 }
 
-pub fn close_messages_tx(messages_tx: &RwLock<VecDeque<UnboundedSender<(String, RedisValue)>>>) {
+pub fn close_messages_tx<T>(messages_tx: &RwLock<VecDeque<UnboundedSender<T>>>) {
   for _ in messages_tx.write().drain(..) {
     trace!("Closing messages tx.");
   }
@@ -92,6 +92,7 @@ pub fn emit_error(inner: &Arc<RedisClientInner>, error: &RedisError) {
 }
 
 pub fn emit_reconnect(inner: &Arc<RedisClientInner>) {
+  _export!(inner, |exporter, client| exporter.record_reconnect(client));
   let mut new_tx = VecDeque::new();
   let mut tx_guard = inner.reconnect_tx.write();
 
@@ -159,6 +160,8 @@ pub fn refresh_cluster_state(inner: &Arc<RedisClientInner>, mut command: SentCom
   if let Some(tx) = closed_tx {
     // reset the attempted count since MOVED/ASK errors shouldn't count as failed write attempts
     command.command.attempted = 0;
+    // replicas respond with MOVED if they can't serve a read, so the retry goes to the primary node
+    command.command.primary_only = true;
     let mut commands = VecDeque::with_capacity(1);
     commands.push_back(command);
     _trace!(inner, "Emitting cluster refresh with {} messages", commands.len());
@@ -174,6 +177,25 @@ pub fn refresh_cluster_state(inner: &Arc<RedisClientInner>, mut command: SentCom
   }
 }
 
+/// Send an event to the reconnect task to sync the cluster state without retrying any commands.
+pub fn sync_cluster_state(inner: &Arc<RedisClientInner>, error: RedisError) {
+  _debug!(inner, "Refresh cluster state without a command: {:?}", error);
+  let closed_tx = { inner.connection_closed_tx.read().clone() };
+
+  if let Some(tx) = closed_tx {
+    let commands = VecDeque::new();
+
+    if let Err(_e) = tx.send(ClosedState { commands, error }) {
+      _warn!(
+        inner,
+        "Could not send refresh cluster event. Reconnection logic will not run."
+      );
+    }
+  } else {
+    _warn!(inner, "Redis client does not have connection closed sender.");
+  }
+}
+
 pub fn insert_locked_map<K: Ord, V>(locked: &RwLock<BTreeMap<K, V>>, key: K, value: V) -> Option<V> {
   locked.write().insert(key, value)
 }
@@ -258,6 +280,7 @@ pub fn should_apply_backpressure(
 pub fn centralized_server_name(inner: &Arc<RedisClientInner>) -> String {
   match inner.config.read().server {
     ServerConfig::Centralized { ref host, ref port, .. } => format!("{}:{}", host, port),
+    ServerConfig::Unix { ref path } => client_utils::unix_server_name(path),
     // for sentinel configs this will be replaced later after reading the primary node from the sentinel(s)
     _ => "unknown".to_owned(),
   }
@@ -291,13 +314,23 @@ pub fn unblock_multiplexer(inner: &Arc<RedisClientInner>, command: &RedisCommand
 /// The callback will come from the first node to respond to the request.
 pub async fn write_all_nodes(
   inner: &Arc<RedisClientInner>,
+  cache: &Arc<RwLock<ClusterKeyCache>>,
   writers: &Arc<AsyncRwLock<BTreeMap<Arc<String>, RedisSink>>>,
   commands: &Arc<Mutex<BTreeMap<Arc<String>, VecDeque<SentCommand>>>>,
   counters: &Arc<RwLock<BTreeMap<Arc<String>, Counters>>>,
   command: RedisCommand,
 ) -> Result<Backpressure, RedisError> {
+  // replica connections may also be in `writers`, but these commands only go to primary nodes
+  let main_nodes: BTreeSet<Arc<String>> = cache.read().unique_main_nodes().into_iter().collect();
+  let num_nodes = writers
+    .read()
+    .await
+    .keys()
+    .filter(|server| main_nodes.contains(*server))
+    .count();
+
   if let Some(inner) = command.kind.all_nodes_response() {
-    inner.set_num_nodes(writers.read().await.len());
+    inner.set_num_nodes(num_nodes);
   } else {
     return Err(RedisError::new(
       RedisErrorKind::Config,
@@ -306,6 +339,9 @@ pub async fn write_all_nodes(
   }
 
   for (server, writer) in writers.write().await.iter_mut() {
+    if !main_nodes.contains(server) {
+      continue;
+    }
     let counter = match counters.read().get(server) {
       Some(counter) => counter.clone(),
       None => {
@@ -382,7 +418,11 @@ pub async fn send_centralized_command(
     commands.lock().push_back(command.into());
   }
   // if writing the command fails it will be retried from this point forward since it has been added to the commands queue
-  connection::write_command(inner, writer, counters, frame, should_flush).await
+  connection::write_command(inner, writer, counters, frame, should_flush).await?;
+  _export!(inner, |exporter, client| {
+    exporter.record_in_flight(client, server, client_utils::read_atomic(&counters.in_flight));
+  });
+  Ok(())
 }
 
 pub async fn send_clustered_command(
@@ -414,7 +454,11 @@ pub async fn send_clustered_command(
     }
   }
   // if writing the command fails it will be retried from this point forward since it has been added to the commands queue
-  connection::write_command(inner, writer, counters, frame, should_flush).await
+  connection::write_command(inner, writer, counters, frame, should_flush).await?;
+  _export!(inner, |exporter, client| {
+    exporter.record_in_flight(client, server, client_utils::read_atomic(&counters.in_flight));
+  });
+  Ok(())
 }
 
 fn respond_early_to_caller_error(inner: &Arc<RedisClientInner>, mut command: RedisCommand, error: RedisError) {
@@ -435,7 +479,7 @@ fn respond_early_to_caller_error(inner: &Arc<RedisClientInner>, mut command: Red
 pub async fn write_centralized_command(
   inner: &Arc<RedisClientInner>,
   connections: &Connections,
-  command: RedisCommand,
+  mut command: RedisCommand,
   no_backpressure: bool,
 ) -> Result<Backpressure, RedisError> {
   if !no_backpressure {
@@ -449,6 +493,9 @@ pub async fn write_centralized_command(
 
     if let Some(backpressure) = backpressure {
       _warn!(inner, "Applying backpressure for {} ms", backpressure);
+      _export!(inner, |exporter, client| {
+        exporter.record_backpressure(client, &centralized_server_name(inner));
+      });
       return Ok(Backpressure::Wait((Duration::from_millis(backpressure), command)));
     }
   }
@@ -458,9 +505,25 @@ pub async fn write_centralized_command(
     ref commands,
     ref writer,
     ref server,
+    ref replicas,
     ..
   } = connections
   {
+    if inner.replicas.is_some() {
+      let replica = {
+        let primary = server.read().await.clone();
+        let servers: Vec<Arc<String>> = replicas.writers.read().await.keys().cloned().collect();
+        select_replica(inner, &replicas.writers, &primary, &servers, &command).await
+      };
+
+      if let Some(replica) = replica {
+        command = match write_sentinel_replica_command(inner, replicas, replica, command).await {
+          Ok(backpressure) => return Ok(backpressure),
+          Err(command) => command,
+        };
+      }
+    }
+
     if let Some(writer) = writer.write().await.deref_mut() {
       let server_guard = server.read().await;
 
@@ -483,6 +546,70 @@ pub async fn write_centralized_command(
   }
 }
 
+/// Select a replica node for the command, if the command can be sent to a replica and a replica is connected.
+async fn select_replica(
+  inner: &Arc<RedisClientInner>,
+  writers: &Arc<AsyncRwLock<BTreeMap<Arc<String>, RedisSink>>>,
+  primary: &Arc<String>,
+  replicas: &[Arc<String>],
+  command: &RedisCommand,
+) -> Option<Arc<String>> {
+  if replicas.is_empty() || !replicas::should_use_replica(inner, command) {
+    return None;
+  }
+
+  let router = match inner.replicas {
+    Some(ref router) => router,
+    None => return None,
+  };
+  let writers_guard = writers.read().await;
+  let replica = router.select(replicas, |server| writers_guard.contains_key(server));
+
+  if replica.is_none() {
+    _debug!(
+      inner,
+      "Sending {} to primary {} without an available replica.",
+      command.kind.to_str_debug(),
+      primary
+    );
+  }
+  replica
+}
+
+/// Write the command to a replica discovered via the sentinel nodes, returning the command if the replica connection
+/// no longer exists.
+///
+/// If the write fails the replica is removed and the command is retried on the primary node.
+async fn write_sentinel_replica_command(
+  inner: &Arc<RedisClientInner>,
+  replicas: &ReplicaConnections,
+  server: Arc<String>,
+  command: RedisCommand,
+) -> Result<Backpressure, RedisCommand> {
+  let connection = match replicas.connections.read().get(&server) {
+    Some(connection) => connection.clone(),
+    None => return Err(command),
+  };
+
+  let result = {
+    let mut writers_guard = replicas.writers.write().await;
+    match writers_guard.get_mut(&server) {
+      Some(writer) => {
+        let (counters, commands) = (&connection.counters, &connection.commands);
+        send_centralized_command(inner, &server, counters, writer, commands, command).await
+      }
+      None => return Err(command),
+    }
+  };
+
+  if let Err(e) = result {
+    _warn!(inner, "Error writing to replica {}: {:?}", server, e);
+    // the command was added to the replica's command queue before writing, so it will be retried from there
+    sentinel::remove_replica(inner, replicas, &server).await;
+  }
+  Ok(Backpressure::Ok(server))
+}
+
 pub async fn write_clustered_command(
   inner: &Arc<RedisClientInner>,
   connections: &Connections,
@@ -502,9 +629,9 @@ pub async fn write_clustered_command(
       Some(slot) => Some(slot),
       None => command.extract_key().map(|key| redis_keyslot(key)),
     };
-    let server = match hash_slot {
+    let slot = match hash_slot {
       Some(hash_slot) => match cache.read().get_server(hash_slot) {
-        Some(slot) => slot.server.clone(),
+        Some(slot) => slot,
         None => {
           return Err(RedisError::new_context(
             RedisErrorKind::Unknown,
@@ -514,7 +641,7 @@ pub async fn write_clustered_command(
         }
       },
       None => match cache.read().random_slot() {
-        Some(slot) => slot.server.clone(),
+        Some(slot) => slot,
         None => {
           return Err(RedisError::new_context(
             RedisErrorKind::Unknown,
@@ -524,6 +651,10 @@ pub async fn write_clustered_command(
         }
       },
     };
+    let server = match select_replica(inner, writers, &slot.server, &slot.replicas, &command).await {
+      Some(replica) => replica,
+      None => slot.server.clone(),
+    };
 
     if !no_backpressure {
       let backpressure = match should_apply_backpressure(inner, connections, Some(&server)) {
@@ -536,6 +667,7 @@ pub async fn write_clustered_command(
 
       if let Some(backpressure) = backpressure {
         _warn!(inner, "Applying backpressure for {} ms", backpressure);
+        _export!(inner, |exporter, client| exporter.record_backpressure(client, &server));
         return Ok(Backpressure::Wait((Duration::from_millis(backpressure), command)));
       }
     }
@@ -584,7 +716,15 @@ pub async fn write_clustered_command(
 
 pub fn take_sent_commands(connections: &Connections) -> VecDeque<SentCommand> {
   match connections {
-    Connections::Centralized { ref commands, .. } => commands.lock().drain(..).collect(),
+    Connections::Centralized {
+      ref commands,
+      ref replicas,
+      ..
+    } => {
+      let mut out: VecDeque<SentCommand> = commands.lock().drain(..).collect();
+      out.extend(replicas.take_commands());
+      out
+    }
     Connections::Clustered {
       ref cache,
       ref commands,
@@ -681,27 +821,14 @@ pub fn spawn_clustered_listener(
   let _ = tokio::spawn(async move {
     let memo = (inner.clone(), server.clone(), counters, commands);
 
-    let stream_ft = match stream {
-      RedisStream::Tls(stream) => Either::Left(
-        stream
-          .try_fold(memo, |(inner, server, counters, commands), frame| async {
-            let frame = frame.into_resp3();
-            responses::process_clustered_frame(&inner, &server, &counters, &commands, frame).await?;
-            Ok((inner, server, counters, commands))
-          })
-          .and_then(|_| async { Ok(()) }),
-      ),
-      RedisStream::Tcp(stream) => Either::Right(
-        stream
-          .try_fold(memo, |(inner, server, counters, commands), frame| async {
-            let frame = frame.into_resp3();
-            responses::process_clustered_frame(&inner, &server, &counters, &commands, frame).await?;
-            Ok((inner, server, counters, commands))
-          })
-          .and_then(|_| async { Ok(()) }),
-      ),
-    }
-    .fuse();
+    let stream_ft = stream
+      .try_fold(memo, |(inner, server, counters, commands), frame| async {
+        let frame = frame.into_resp3();
+        responses::process_clustered_frame(&inner, &server, &counters, &commands, frame).await?;
+        Ok((inner, server, counters, commands))
+      })
+      .and_then(|_| async { Ok(()) })
+      .fuse();
     pin_mut!(stream_ft);
 
     let close_inner = inner.clone();
@@ -743,6 +870,7 @@ async fn create_cluster_connection(
   connection_ids: &Arc<RwLock<BTreeMap<Arc<String>, i64>>>,
   server: &Arc<String>,
   uses_tls: bool,
+  replica: bool,
 ) -> Result<(RedisSink, RedisStream), RedisError> {
   let (host, port) = protocol_utils::server_to_parts(server)?;
   let addr = inner.resolver.resolve(host.to_owned(), port).await?;
@@ -760,6 +888,12 @@ async fn create_cluster_connection(
       }
       Err((_, socket)) => socket,
     };
+    let socket = if replica {
+      connection::send_readonly(inner, socket).await?
+    } else {
+      socket
+    };
+    let socket = cache::enable_tracking(inner, server, socket).await?;
 
     let (sink, stream) = socket.split();
     Ok((RedisSink::Tls(sink), RedisStream::Tls(stream)))
@@ -774,12 +908,27 @@ async fn create_cluster_connection(
       }
       Err((_, socket)) => socket,
     };
+    let socket = if replica {
+      connection::send_readonly(inner, socket).await?
+    } else {
+      socket
+    };
+    let socket = cache::enable_tracking(inner, server, socket).await?;
 
     let (sink, stream) = socket.split();
     Ok((RedisSink::Tcp(sink), RedisStream::Tcp(stream)))
   }
 }
 
+/// Read the replica nodes that should be used by the client, if replica reads are enabled.
+fn read_replica_nodes(inner: &Arc<RedisClientInner>, cluster_state: &ClusterKeyCache) -> Vec<Arc<String>> {
+  if inner.replicas.is_some() {
+    cluster_state.unique_replica_nodes()
+  } else {
+    Vec::new()
+  }
+}
+
 pub fn get_or_create_close_tx(inner: &Arc<RedisClientInner>, close_tx: &Arc<RwLock<Option<CloseTx>>>) -> CloseTx {
   let mut guard = close_tx.write();
 
@@ -812,16 +961,32 @@ pub async fn connect_clustered(
     let uses_tls = protocol_utils::uses_tls(inner);
     let cluster_state = connection::read_cluster_nodes(inner).await?;
     let main_nodes = cluster_state.unique_main_nodes();
+    let replica_nodes = read_replica_nodes(inner, &cluster_state);
     client_utils::set_locked(cache, cluster_state);
     connection_ids.write().clear();
 
     let tx = get_or_create_close_tx(inner, close_tx);
     for server in main_nodes.into_iter() {
-      let (sink, stream) = create_cluster_connection(inner, connection_ids, &server, uses_tls).await?;
+      let (sink, stream) = create_cluster_connection(inner, connection_ids, &server, uses_tls, false).await?;
+
+      insert_locked_map_mutex(commands, server.clone(), VecDeque::new());
+      insert_locked_map_async(writers, server.clone(), sink).await;
+      insert_locked_map(counters, server.clone(), Counters::new(inner));
+      spawn_clustered_listener(inner, connections, commands, counters, tx.subscribe(), &server, stream);
+    }
+    for server in replica_nodes.into_iter() {
+      // commands will be sent to the primary node if a replica is unavailable
+      let (sink, stream) = match create_cluster_connection(inner, connection_ids, &server, uses_tls, true).await {
+        Ok(parts) => parts,
+        Err(e) => {
+          _warn!(inner, "Failed to connect to replica {}: {:?}", server, e);
+          continue;
+        }
+      };
 
       insert_locked_map_mutex(commands, server.clone(), VecDeque::new());
       insert_locked_map_async(writers, server.clone(), sink).await;
-      insert_locked_map(counters, server.clone(), Counters::new(&inner.cmd_buffer_len));
+      insert_locked_map(counters, server.clone(), Counters::new(inner));
       spawn_clustered_listener(inner, connections, commands, counters, tx.subscribe(), &server, stream);
     }
 
@@ -854,27 +1019,14 @@ pub fn spawn_centralized_listener(
   let _ = tokio::spawn(async move {
     let memo = (inner.clone(), server.clone(), counters, commands);
 
-    let stream_ft = match stream {
-      RedisStream::Tls(stream) => Either::Left(
-        stream
-          .try_fold(memo, |(inner, server, counters, commands), frame| async {
-            let frame = frame.into_resp3();
-            responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
-            Ok((inner, server, counters, commands))
-          })
-          .and_then(|_| async { Ok(()) }),
-      ),
-      RedisStream::Tcp(stream) => Either::Right(
-        stream
-          .try_fold(memo, |(inner, server, counters, commands), frame| async {
-            let frame = frame.into_resp3();
-            responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
-            Ok((inner, server, counters, commands))
-          })
-          .and_then(|_| async { Ok(()) }),
-      ),
-    }
-    .fuse();
+    let stream_ft = stream
+      .try_fold(memo, |(inner, server, counters, commands), frame| async {
+        let frame = frame.into_resp3();
+        responses::process_centralized_frame(&inner, &server, &counters, &commands, frame).await?;
+        Ok((inner, server, counters, commands))
+      })
+      .and_then(|_| async { Ok(()) })
+      .fuse();
     pin_mut!(stream_ft);
 
     let close_inner = inner.clone();
@@ -923,13 +1075,32 @@ pub async fn connect_centralized(
     ref counters,
     ref server,
     ref connection_id,
+    ..
   } = connections
   {
-    let addr = protocol_utils::read_centralized_addr(&inner).await?;
     let uses_tls = protocol_utils::uses_tls(inner);
+    let unix_path = protocol_utils::read_unix_path(&inner.config);
+    let server = server.read().await.clone();
     client_utils::set_client_state(&inner.state, ClientState::Connecting);
 
-    let (sink, stream) = if uses_tls {
+    let (sink, stream) = if let Some(path) = unix_path {
+      _trace!(inner, "Connecting to Unix socket {}", server);
+      let socket = connection::create_authenticated_connection_unix(&path, inner).await?;
+      let socket = match connection::read_client_id(inner, socket).await {
+        Ok((id, socket)) => {
+          if let Some(id) = id {
+            connection_id.write().replace(id);
+          }
+          socket
+        }
+        Err((_, socket)) => socket,
+      };
+      let socket = cache::enable_tracking(inner, &server, socket).await?;
+
+      let (sink, stream) = socket.split();
+      (RedisSink::Unix(sink), RedisStream::Unix(stream))
+    } else if uses_tls {
+      let addr = protocol_utils::read_centralized_addr(&inner).await?;
       let domain = protocol_utils::read_centralized_domain(&inner.config)?;
       _trace!(inner, "Connecting to {} with domain {}", addr, domain);
       let socket = connection::create_authenticated_connection_tls(&addr, &domain, inner).await?;
@@ -942,10 +1113,12 @@ pub async fn connect_centralized(
         }
         Err((_, socket)) => socket,
       };
+      let socket = cache::enable_tracking(inner, &server, socket).await?;
 
       let (sink, stream) = socket.split();
       (RedisSink::Tls(sink), RedisStream::Tls(stream))
     } else {
+      let addr = protocol_utils::read_centralized_addr(&inner).await?;
       _trace!(inner, "Connecting to {}", addr);
       let socket = connection::create_authenticated_connection(&addr, inner).await?;
       let socket = match connection::read_client_id(inner, socket).await {
@@ -957,6 +1130,7 @@ pub async fn connect_centralized(
         }
         Err((_, socket)) => socket,
       };
+      let socket = cache::enable_tracking(inner, &server, socket).await?;
 
       let (sink, stream) = socket.split();
       (RedisSink::Tcp(sink), RedisStream::Tcp(stream))
@@ -967,7 +1141,6 @@ pub async fn connect_centralized(
     let tx = get_or_create_close_tx(inner, close_tx);
     _debug!(inner, "Set centralized connection closed sender.");
     let _ = client_utils::set_locked_async(&writer, Some(sink)).await;
-    let server = server.read().await.clone();
 
     spawn_centralized_listener(inner, &server, connections, tx.subscribe(), commands, counters, stream);
     client_utils::set_client_state(&inner.state, ClientState::Connected);
@@ -1062,6 +1235,7 @@ pub fn check_mset_cluster_keys(multiplexer: &Multiplexer, args: &Vec<RedisValue>
 }
 
 async fn create_cluster_change(
+  inner: &Arc<RedisClientInner>,
   cluster_state: &ClusterKeyCache,
   writers: &Arc<AsyncRwLock<BTreeMap<Arc<String>, RedisSink>>>,
 ) -> ClusterChange {
@@ -1070,6 +1244,9 @@ async fn create_cluster_change(
   for server in cluster_state.unique_main_nodes().into_iter() {
     new_servers.insert(server);
   }
+  for server in read_replica_nodes(inner, cluster_state).into_iter() {
+    new_servers.insert(server);
+  }
   {
     for server in writers.write().await.keys() {
       old_servers.insert(server.clone());
@@ -1131,15 +1308,16 @@ async fn add_server(
   connection_ids: &Arc<RwLock<BTreeMap<Arc<String>, i64>>>,
   close_tx: &Arc<RwLock<Option<CloseTx>>>,
   server: &Arc<String>,
+  replica: bool,
 ) -> Result<(), RedisError> {
   _debug!(inner, "Adding new clustered connection to {}", server);
   let uses_tls = protocol_utils::uses_tls(inner);
-  let (sink, stream) = create_cluster_connection(inner, connection_ids, server, uses_tls).await?;
+  let (sink, stream) = create_cluster_connection(inner, connection_ids, server, uses_tls, replica).await?;
   let tx = get_or_create_close_tx(inner, close_tx);
 
   insert_locked_map_mutex(commands, server.clone(), VecDeque::new());
   insert_locked_map_async(writers, server.clone(), sink).await;
-  insert_locked_map(counters, server.clone(), Counters::new(&inner.cmd_buffer_len));
+  insert_locked_map(counters, server.clone(), Counters::new(inner));
   spawn_clustered_listener(inner, connections, commands, counters, tx.subscribe(), &server, stream);
   Ok(())
 }
@@ -1317,7 +1495,8 @@ pub async fn sync_cluster(
       *old_cache = state.clone();
       state
     };
-    let changes = create_cluster_change(&cluster_state, &writers).await;
+    let changes = create_cluster_change(inner, &cluster_state, &writers).await;
+    let replica_nodes: BTreeSet<Arc<String>> = read_replica_nodes(inner, &cluster_state).into_iter().collect();
     _debug!(inner, "Changing cluster connections: {:?}", changes);
     broadcast_cluster_changes(inner, &changes);
 
@@ -1325,7 +1504,8 @@ pub async fn sync_cluster(
       remove_server(inner, counters, writers, commands, connection_ids, &removed_server).await?;
     }
     for new_server in changes.add.into_iter() {
-      add_server(
+      let replica = replica_nodes.contains(&new_server);
+      let result = add_server(
         inner,
         connections,
         counters,
@@ -1334,8 +1514,17 @@ pub async fn sync_cluster(
         connection_ids,
         close_tx,
         &new_server,
+        replica,
       )
-      .await?;
+      .await;
+
+      if let Err(e) = result {
+        if replica {
+          _warn!(inner, "Failed to connect to replica {}: {:?}", new_server, e);
+        } else {
+          return Err(e);
+        }
+      }
     }
 
     _debug!(inner, "Finish synchronizing cluster connections.");
@@ -1365,6 +1554,9 @@ mod tests {
       attempted: 0,
       sent: Instant::now(),
       resp_tx: Arc::new(RwLock::new(None)),
+      pipelined: false,
+      primary_only: false,
+      use_replica: false,
       #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
       traces: CommandTraces::default(),
     };
@@ -1384,18 +1576,21 @@ mod tests {
         end: 1,
         server: server_a.clone(),
         id: Arc::new("a1".into()),
+        replicas: Vec::new(),
       }),
       Arc::new(SlotRange {
         start: 1,
         end: 2,
         server: server_b.clone(),
         id: Arc::new("b1".into()),
+        replicas: Vec::new(),
       }),
       Arc::new(SlotRange {
         start: 2,
         end: 3,
         server: server_c.clone(),
         id: Arc::new("c1".into()),
+        replicas: Vec::new(),
       }),
     ];
     let cache = Arc::new(RwLock::new(cache.into()));
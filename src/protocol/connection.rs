@@ -9,32 +9,43 @@ use crate::protocol::utils::{frame_into_string, pretty_error};
 use crate::types::{ClientState, InfoKind, Resolve};
 use crate::utils as client_utils;
 use futures::sink::SinkExt;
-use futures::stream::{SplitSink, SplitStream, StreamExt};
+use futures::stream::{SplitSink, SplitStream, Stream, StreamExt};
 use redis_protocol::resp2::types::Frame as Resp2Frame;
 use redis_protocol::resp3::types::{Frame as Resp3Frame, RespVersion};
 use semver::Version;
 use std::net::SocketAddr;
+use std::path::Path;
+use std::pin::Pin;
 use std::str;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
 use tokio_util::codec::Framed;
 
-#[cfg(feature = "enable-tls")]
+#[cfg(feature = "credential-provider")]
+pub use crate::modules::credentials::read_credentials;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use crate::protocol::tls;
 #[cfg(feature = "monitor")]
 use crate::types::ServerConfig;
-#[cfg(feature = "enable-tls")]
-use tokio_native_tls::TlsStream;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use crate::protocol::tls::TlsStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
 
 /// The contents of a simplestring OK response.
 pub const OK: &'static str = "OK";
 
 pub type FramedTcp = Framed<TcpStream, RedisCodec>;
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub type FramedTls = Framed<TlsStream<TcpStream>, RedisCodec>;
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 pub type FramedTls = FramedTcp;
+#[cfg(unix)]
+pub type FramedUnix = Framed<UnixStream, RedisCodec>;
+#[cfg(not(unix))]
+pub type FramedUnix = FramedTcp;
 
 pub type TcpRedisReader = SplitStream<FramedTcp>;
 pub type TcpRedisWriter = SplitSink<FramedTcp, ProtocolFrame>;
@@ -42,19 +53,37 @@ pub type TcpRedisWriter = SplitSink<FramedTcp, ProtocolFrame>;
 pub type TlsRedisReader = SplitStream<FramedTls>;
 pub type TlsRedisWriter = SplitSink<FramedTls, ProtocolFrame>;
 
+pub type UnixRedisReader = SplitStream<FramedUnix>;
+pub type UnixRedisWriter = SplitSink<FramedUnix, ProtocolFrame>;
+
 pub enum RedisStream {
   Tls(TlsRedisReader),
   Tcp(TcpRedisReader),
+  Unix(UnixRedisReader),
+}
+
+impl Stream for RedisStream {
+  type Item = Result<ProtocolFrame, RedisError>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    match self.get_mut() {
+      RedisStream::Tls(ref mut inner) => inner.poll_next_unpin(cx),
+      RedisStream::Tcp(ref mut inner) => inner.poll_next_unpin(cx),
+      RedisStream::Unix(ref mut inner) => inner.poll_next_unpin(cx),
+    }
+  }
 }
 
 pub enum RedisSink {
   Tls(TlsRedisWriter),
   Tcp(TcpRedisWriter),
+  Unix(UnixRedisWriter),
 }
 
 pub enum RedisTransport {
   Tls(FramedTls),
   Tcp(FramedTcp),
+  Unix(FramedUnix),
 }
 
 pub fn null_frame(is_resp3: bool) -> ProtocolFrame {
@@ -88,6 +117,10 @@ pub fn split_transport(transport: RedisTransport) -> (RedisSink, RedisStream) {
       let (sink, stream) = framed.split();
       (RedisSink::Tls(sink), RedisStream::Tls(stream))
     }
+    RedisTransport::Unix(framed) => {
+      let (sink, stream) = framed.split();
+      (RedisSink::Unix(sink), RedisStream::Unix(stream))
+    }
   }
 }
 
@@ -158,6 +191,13 @@ pub async fn transport_request_response(
       };
       Ok((frame, RedisTransport::Tls(transport)))
     }
+    RedisTransport::Unix(transport) => {
+      let (frame, transport) = match request_response_safe(transport, request, is_resp3).await {
+        Ok(result) => result,
+        Err((e, _)) => return Err(e),
+      };
+      Ok((frame, RedisTransport::Unix(transport)))
+    }
   }
 }
 
@@ -258,6 +298,26 @@ where
   Ok((id, transport))
 }
 
+/// Send `READONLY` on a connection to a cluster replica node so that it can serve read-only commands.
+pub async fn send_readonly<T>(
+  inner: &Arc<RedisClientInner>,
+  transport: Framed<T, RedisCodec>,
+) -> Result<Framed<T, RedisCodec>, RedisError>
+where
+  T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+  _trace!(inner, "Sending READONLY to replica node.");
+  let command = RedisCommand::new(RedisCommandKind::Readonly, vec![], None);
+  let (result, transport) = request_response(transport, &command, inner.is_resp3()).await?;
+  let response = result.into_resp3();
+
+  if let Some(error) = protocol_utils::frame_to_error(&response) {
+    Err(error)
+  } else {
+    Ok(transport)
+  }
+}
+
 pub async fn select_database<T>(
   inner: &Arc<RedisClientInner>,
   transport: Framed<T, RedisCodec>,
@@ -282,7 +342,16 @@ where
   }
 }
 
-#[cfg(feature = "enable-tls")]
+/// Read the credentials for a new connection from the client's config.
+#[cfg(not(feature = "credential-provider"))]
+pub async fn read_credentials(
+  inner: &Arc<RedisClientInner>,
+) -> Result<(Option<String>, Option<String>), RedisError> {
+  let guard = inner.config.read();
+  Ok((guard.username.clone(), guard.password.clone()))
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub async fn create_authenticated_connection_tls(
   addr: &SocketAddr,
   domain: &str,
@@ -291,12 +360,10 @@ pub async fn create_authenticated_connection_tls(
   let server = format!("{}:{}", addr.ip().to_string(), addr.port());
   let codec = RedisCodec::new(inner, server);
   let client_name = inner.client_name();
-  let password = inner.config.read().password.clone();
-  let username = inner.config.read().username.clone();
+  let (username, password) = read_credentials(inner).await?;
 
   let socket = TcpStream::connect(addr).await?;
-  let tls_stream = tls::create_tls_connector(&inner.config)?;
-  let socket = tls_stream.connect(domain, socket).await?;
+  let socket = tls::connect(&inner.config, domain, socket).await?;
   let framed = switch_protocols(inner, Framed::new(socket, codec)).await?;
   let framed = authenticate(framed, &client_name, username, password, inner.is_resp3()).await?;
   let framed = select_database(inner, framed).await?;
@@ -305,7 +372,7 @@ pub async fn create_authenticated_connection_tls(
   Ok(framed)
 }
 
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 pub(crate) async fn create_authenticated_connection_tls(
   addr: &SocketAddr,
   _domain: &str,
@@ -321,8 +388,7 @@ pub async fn create_authenticated_connection(
   let server = format!("{}:{}", addr.ip().to_string(), addr.port());
   let codec = RedisCodec::new(inner, server);
   let client_name = inner.client_name();
-  let password = inner.config.read().password.clone();
-  let username = inner.config.read().username.clone();
+  let (username, password) = read_credentials(inner).await?;
 
   let socket = TcpStream::connect(addr).await?;
   let framed = switch_protocols(inner, Framed::new(socket, codec)).await?;
@@ -333,8 +399,42 @@ pub async fn create_authenticated_connection(
   Ok(framed)
 }
 
+#[cfg(unix)]
+pub async fn create_authenticated_connection_unix(
+  path: &Path,
+  inner: &Arc<RedisClientInner>,
+) -> Result<FramedUnix, RedisError> {
+  let codec = RedisCodec::new(inner, client_utils::unix_server_name(path));
+  let client_name = inner.client_name();
+  let (username, password) = read_credentials(inner).await?;
+
+  let socket = UnixStream::connect(path).await?;
+  let framed = switch_protocols(inner, Framed::new(socket, codec)).await?;
+  let framed = authenticate(framed, &client_name, username, password, inner.is_resp3()).await?;
+  let framed = select_database(inner, framed).await?;
+
+  client_utils::set_client_state(&inner.state, ClientState::Connected);
+  Ok(framed)
+}
+
+#[cfg(not(unix))]
+pub async fn create_authenticated_connection_unix(
+  _path: &Path,
+  _inner: &Arc<RedisClientInner>,
+) -> Result<FramedUnix, RedisError> {
+  Err(RedisError::new(
+    RedisErrorKind::Config,
+    "Unix sockets are not supported on this platform.",
+  ))
+}
+
 #[cfg(feature = "monitor")]
 pub async fn create_centralized_connection(inner: &Arc<RedisClientInner>) -> Result<RedisTransport, RedisError> {
+  if let Some(path) = protocol_utils::read_unix_path(&inner.config) {
+    let framed = create_authenticated_connection_unix(&path, inner).await?;
+    return Ok(RedisTransport::Unix(framed));
+  }
+
   let (host, port) = match inner.config.read().server {
     ServerConfig::Centralized { ref host, ref port } => (host.clone(), *port),
     _ => return Err(RedisError::new(RedisErrorKind::Config, "Expected centralized config.")),
@@ -536,6 +636,10 @@ pub async fn read_redis_version(inner: &Arc<RedisClientInner>) -> Result<Version
       RedisErrorKind::ProtocolError,
       "Failed to read server version from any cluster node.",
     ))
+  } else if let Some(path) = protocol_utils::read_unix_path(&inner.config) {
+    let transport = create_authenticated_connection_unix(&path, inner).await?;
+    let (version, _) = read_server_version(inner, transport).await?;
+    Ok(version)
   } else {
     let addr = protocol_utils::read_centralized_addr(&inner).await?;
 
@@ -583,6 +687,7 @@ pub async fn write_command(
     match sink {
       RedisSink::Tcp(ref mut inner) => inner.send(frame).await?,
       RedisSink::Tls(ref mut inner) => inner.send(frame).await?,
+      RedisSink::Unix(ref mut inner) => inner.send(frame).await?,
     };
     counters.reset_feed_count();
   } else {
@@ -591,6 +696,7 @@ pub async fn write_command(
     match sink {
       RedisSink::Tcp(ref mut inner) => inner.feed(frame).await?,
       RedisSink::Tls(ref mut inner) => inner.feed(frame).await?,
+      RedisSink::Unix(ref mut inner) => inner.feed(frame).await?,
     };
     counters.incr_feed_count();
   };
@@ -1,19 +1,106 @@
 use std::fmt;
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use crate::error::{RedisError, RedisErrorKind};
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use crate::types::RedisConfig;
 #[cfg(feature = "enable-tls")]
-use native_tls::{Certificate, Protocol, TlsConnector as NativeTlsConnector};
-#[cfg(feature = "enable-tls")]
+use native_tls::{Certificate, Identity, Protocol, TlsConnector as NativeTlsConnector};
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use parking_lot::RwLock;
-#[cfg(feature = "enable-tls")]
+#[cfg(feature = "enable-rustls")]
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+#[cfg(feature = "enable-rustls")]
+use rustls::{CertificateError, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+#[cfg(feature = "enable-rustls")]
+use rustls_pemfile::Item;
+#[cfg(feature = "enable-rustls")]
+use std::convert::TryFrom;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 use std::env;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use std::fs;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use std::io;
+#[cfg(feature = "enable-rustls")]
+use std::io::BufReader;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use std::path::Path;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use std::pin::Pin;
+#[cfg(feature = "enable-rustls")]
+use std::sync::Arc;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use std::task::{Context, Poll};
+#[cfg(feature = "enable-rustls")]
+use std::time::SystemTime;
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+use tokio::net::TcpStream;
 #[cfg(feature = "enable-tls")]
 use tokio_native_tls::TlsConnector;
+#[cfg(feature = "enable-rustls")]
+use tokio_rustls::TlsConnector as RustlsConnector;
 
-#[cfg(feature = "enable-tls")]
+/// The TLS stream type used by the client, wrapping a stream from the [TlsBackend] chosen in the [TlsConfig].
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+pub enum TlsStream<S> {
+  #[cfg(feature = "enable-tls")]
+  NativeTls(tokio_native_tls::TlsStream<S>),
+  #[cfg(feature = "enable-rustls")]
+  Rustls(Box<tokio_rustls::client::TlsStream<S>>),
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+impl<S> AsyncRead for TlsStream<S>
+where
+  S: AsyncRead + AsyncWrite + Unpin,
+{
+  fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+    match self.get_mut() {
+      #[cfg(feature = "enable-tls")]
+      TlsStream::NativeTls(ref mut stream) => Pin::new(stream).poll_read(cx, buf),
+      #[cfg(feature = "enable-rustls")]
+      TlsStream::Rustls(ref mut stream) => Pin::new(stream).poll_read(cx, buf),
+    }
+  }
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+impl<S> AsyncWrite for TlsStream<S>
+where
+  S: AsyncRead + AsyncWrite + Unpin,
+{
+  fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    match self.get_mut() {
+      #[cfg(feature = "enable-tls")]
+      TlsStream::NativeTls(ref mut stream) => Pin::new(stream).poll_write(cx, buf),
+      #[cfg(feature = "enable-rustls")]
+      TlsStream::Rustls(ref mut stream) => Pin::new(stream).poll_write(cx, buf),
+    }
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    match self.get_mut() {
+      #[cfg(feature = "enable-tls")]
+      TlsStream::NativeTls(ref mut stream) => Pin::new(stream).poll_flush(cx),
+      #[cfg(feature = "enable-rustls")]
+      TlsStream::Rustls(ref mut stream) => Pin::new(stream).poll_flush(cx),
+    }
+  }
+
+  fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    match self.get_mut() {
+      #[cfg(feature = "enable-tls")]
+      TlsStream::NativeTls(ref mut stream) => Pin::new(stream).poll_shutdown(cx),
+      #[cfg(feature = "enable-rustls")]
+      TlsStream::Rustls(ref mut stream) => Pin::new(stream).poll_shutdown(cx),
+    }
+  }
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub fn should_disable_cert_verification() -> bool {
   match env::var_os("FRED_DISABLE_CERT_VERIFICATION") {
     Some(s) => match s.into_string() {
@@ -27,7 +114,7 @@ pub fn should_disable_cert_verification() -> bool {
   }
 }
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub fn should_disable_host_verification() -> bool {
   match env::var_os("FRED_DISABLE_HOST_VERIFICATION") {
     Some(s) => match s.into_string() {
@@ -41,28 +128,176 @@ pub fn should_disable_host_verification() -> bool {
   }
 }
 
+/// A client certificate and private key used to authenticate the client with mutual TLS.
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+#[derive(Clone)]
+pub enum TlsIdentity {
+  /// A PEM encoded certificate chain and a PEM encoded private key.
+  ///
+  /// The key must be PKCS#8 when using `native-tls`. When using `rustls` PKCS#1 (RSA) and SEC1 (EC) keys can also be
+  /// used.
+  Pem { cert_chain: Vec<u8>, key: Vec<u8> },
+  /// A DER encoded PKCS#12 archive and the password used to decrypt it.
+  ///
+  /// This is only supported with `native-tls`.
+  Pkcs12 { der: Vec<u8>, password: String },
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+impl TlsIdentity {
+  /// Read a PEM encoded certificate chain and private key from the provided files.
+  pub fn from_pem_files<C, K>(cert_chain: C, key: K) -> Result<Self, RedisError>
+  where
+    C: AsRef<Path>,
+    K: AsRef<Path>,
+  {
+    Ok(TlsIdentity::Pem {
+      cert_chain: fs::read(cert_chain)?,
+      key: fs::read(key)?,
+    })
+  }
+
+  /// Read a PKCS#12 archive from the provided file.
+  pub fn from_pkcs12_file<P, S>(path: P, password: S) -> Result<Self, RedisError>
+  where
+    P: AsRef<Path>,
+    S: Into<String>,
+  {
+    Ok(TlsIdentity::Pkcs12 {
+      der: fs::read(path)?,
+      password: password.into(),
+    })
+  }
+}
+
+/// The TLS implementation used to create connections.
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TlsBackend {
+  /// Use `native-tls`, which relies on the platform's TLS library.
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
+  NativeTls,
+  /// Use `rustls`.
+  #[cfg(feature = "enable-rustls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-rustls")))]
+  Rustls,
+}
+
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+impl Default for TlsBackend {
+  #[cfg(feature = "enable-tls")]
+  fn default() -> Self {
+    TlsBackend::NativeTls
+  }
+
+  #[cfg(not(feature = "enable-tls"))]
+  fn default() -> Self {
+    TlsBackend::Rustls
+  }
+}
+
 /// Configuration for Tls Connections
 ///
-/// See <https://docs.rs/tokio-native-tls/0.3.0/tokio_native_tls/native_tls/struct.TlsConnectorBuilder.html> for more information.
-#[cfg(feature = "enable-tls")]
+/// The TLS settings are read each time a connection is created, so new values can be provided with
+/// [update_tls_config](crate::interfaces::ClientLike::update_tls_config) to rotate certificates without recreating
+/// the client. Existing connections continue to use the previous values until they reconnect.
+///
+/// Fields that only apply to one TLS implementation are only available when the associated feature is enabled. When
+/// both `enable-tls` and `enable-rustls` are enabled the `backend` field selects the implementation, and connections
+/// will fail if the config sets any fields that only apply to the other implementation.
+///
+/// See <https://docs.rs/tokio-native-tls/0.3.0/tokio_native_tls/native_tls/struct.TlsConnectorBuilder.html> or
+/// <https://docs.rs/rustls/0.21/rustls/client/struct.ClientConfig.html> for more information.
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 #[derive(Clone)]
 pub struct TlsConfig {
+  /// The TLS implementation used to create connections.
+  ///
+  /// Default: `NativeTls` if `enable-tls` is enabled, otherwise `Rustls`
+  pub backend: TlsBackend,
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
   pub root_certs: Option<Vec<Certificate>>,
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
   pub min_protocol_version: Option<Protocol>,
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
   pub max_protocol_version: Option<Protocol>,
+  #[cfg(feature = "enable-tls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-tls")))]
   pub disable_built_in_roots: bool,
   pub use_sni: bool,
+  /// An optional client certificate and private key used for mutual TLS.
+  ///
+  /// Default: `None`
+  pub identity: Option<TlsIdentity>,
+  /// Whether or not to accept invalid server certificates. This is equivalent to setting
+  /// `FRED_DISABLE_CERT_VERIFICATION`.
+  ///
+  /// **This should only be used for testing.**
+  ///
+  /// Default: `false`
+  pub danger_accept_invalid_certs: bool,
+  /// Whether or not to accept server certificates that are not valid for the server's host name. This is equivalent
+  /// to setting `FRED_DISABLE_HOST_VERIFICATION`.
+  ///
+  /// **This should only be used for testing.**
+  ///
+  /// Default: `false`
+  pub danger_accept_invalid_hostnames: bool,
+  /// Root certificates used to verify the server, in addition to the `webpki_roots`.
+  ///
+  /// Default: `None`
+  #[cfg(feature = "enable-rustls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-rustls")))]
+  pub root_store: Option<RootCertStore>,
+  /// Whether or not to trust the Mozilla root certificates from the `webpki-roots` crate.
+  ///
+  /// Default: `true`
+  #[cfg(feature = "enable-rustls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-rustls")))]
+  pub webpki_roots: bool,
+  /// The ALPN protocols to offer during the handshake.
+  ///
+  /// Default: empty
+  #[cfg(feature = "enable-rustls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-rustls")))]
+  pub alpn_protocols: Vec<Vec<u8>>,
+  /// A custom verifier for server certificates. If provided the root certificates are not used.
+  ///
+  /// Default: `None`
+  #[cfg(feature = "enable-rustls")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "enable-rustls")))]
+  pub cert_verifier: Option<Arc<dyn ServerCertVerifier>>,
 }
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 impl Default for TlsConfig {
   fn default() -> Self {
     TlsConfig {
+      backend: TlsBackend::default(),
+      #[cfg(feature = "enable-tls")]
       root_certs: None,
+      #[cfg(feature = "enable-tls")]
       min_protocol_version: None,
+      #[cfg(feature = "enable-tls")]
       max_protocol_version: None,
+      #[cfg(feature = "enable-tls")]
       disable_built_in_roots: false,
       use_sni: true,
+      identity: None,
+      danger_accept_invalid_certs: false,
+      danger_accept_invalid_hostnames: false,
+      #[cfg(feature = "enable-rustls")]
+      root_store: None,
+      #[cfg(feature = "enable-rustls")]
+      webpki_roots: true,
+      #[cfg(feature = "enable-rustls")]
+      alpn_protocols: Vec::new(),
+      #[cfg(feature = "enable-rustls")]
+      cert_verifier: None,
     }
   }
 }
@@ -70,11 +305,11 @@ impl Default for TlsConfig {
 /// Configuration for Tls Connections
 ///
 /// See https://docs.rs/tokio-native-tls/0.3.0/tokio_native_tls/native_tls/struct.TlsConnectorBuilder.html for more information.
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 #[derive(Clone)]
 pub struct TlsConfig;
 
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 impl Default for TlsConfig {
   fn default() -> Self {
     TlsConfig
@@ -96,38 +331,362 @@ impl fmt::Debug for TlsConfig {
 }
 
 #[cfg(feature = "enable-tls")]
-pub fn create_tls_connector(config: &RwLock<RedisConfig>) -> Result<TlsConnector, RedisError> {
+fn create_native_tls_connector(config: &TlsConfig) -> Result<TlsConnector, RedisError> {
   let mut builder = NativeTlsConnector::builder();
 
-  if should_disable_cert_verification() {
+  if config.danger_accept_invalid_certs || should_disable_cert_verification() {
     builder.danger_accept_invalid_certs(true);
   }
-  if should_disable_host_verification() {
+  if config.danger_accept_invalid_hostnames || should_disable_host_verification() {
     builder.danger_accept_invalid_hostnames(true);
   }
-
-  if let Some(ref config) = config.read().tls {
-    if let Some(ref root_certs) = config.root_certs {
-      for cert in root_certs.iter() {
-        builder.add_root_certificate(cert.clone());
-      }
-    }
-    if !config.use_sni {
-      builder.use_sni(false);
-    }
-    if config.disable_built_in_roots {
-      builder.disable_built_in_roots(true);
-    }
-    if let Some(ref protocol) = config.min_protocol_version {
-      builder.min_protocol_version(Some(protocol.clone()));
-    }
-    if let Some(ref protocol) = config.max_protocol_version {
-      builder.max_protocol_version(Some(protocol.clone()));
+  if let Some(ref identity) = config.identity {
+    let identity = match identity {
+      TlsIdentity::Pem { cert_chain, key } => Identity::from_pkcs8(cert_chain, key)?,
+      TlsIdentity::Pkcs12 { der, password } => Identity::from_pkcs12(der, password)?,
+    };
+    builder.identity(identity);
+  }
+  if let Some(ref root_certs) = config.root_certs {
+    for cert in root_certs.iter() {
+      builder.add_root_certificate(cert.clone());
     }
   }
+  if !config.use_sni {
+    builder.use_sni(false);
+  }
+  if config.disable_built_in_roots {
+    builder.disable_built_in_roots(true);
+  }
+  if let Some(ref protocol) = config.min_protocol_version {
+    builder.min_protocol_version(Some(protocol.clone()));
+  }
+  if let Some(ref protocol) = config.max_protocol_version {
+    builder.max_protocol_version(Some(protocol.clone()));
+  }
 
   builder
     .build()
     .map(|t| TlsConnector::from(t))
     .map_err(|e| RedisError::new(RedisErrorKind::Tls, format!("{:?}", e)))
 }
+
+#[cfg(feature = "enable-tls")]
+async fn connect_native_tls(
+  config: &TlsConfig,
+  domain: &str,
+  socket: TcpStream,
+) -> Result<TlsStream<TcpStream>, RedisError> {
+  let connector = create_native_tls_connector(config)?;
+  let stream = connector.connect(domain, socket).await?;
+
+  Ok(TlsStream::NativeTls(stream))
+}
+
+/// A verifier that accepts any server certificate.
+#[cfg(feature = "enable-rustls")]
+struct NoCertVerification;
+
+#[cfg(feature = "enable-rustls")]
+impl ServerCertVerifier for NoCertVerification {
+  fn verify_server_cert(
+    &self,
+    _: &rustls::Certificate,
+    _: &[rustls::Certificate],
+    _: &ServerName,
+    _: &mut dyn Iterator<Item = &[u8]>,
+    _: &[u8],
+    _: SystemTime,
+  ) -> Result<ServerCertVerified, rustls::Error> {
+    Ok(ServerCertVerified::assertion())
+  }
+}
+
+/// A verifier that accepts server certificates that are valid for a different host name.
+#[cfg(feature = "enable-rustls")]
+struct NoHostVerification(Arc<dyn ServerCertVerifier>);
+
+#[cfg(feature = "enable-rustls")]
+impl ServerCertVerifier for NoHostVerification {
+  fn verify_server_cert(
+    &self,
+    end_entity: &rustls::Certificate,
+    intermediates: &[rustls::Certificate],
+    server_name: &ServerName,
+    scts: &mut dyn Iterator<Item = &[u8]>,
+    ocsp_response: &[u8],
+    now: SystemTime,
+  ) -> Result<ServerCertVerified, rustls::Error> {
+    match self
+      .0
+      .verify_server_cert(end_entity, intermediates, server_name, scts, ocsp_response, now)
+    {
+      Err(rustls::Error::InvalidCertificate(CertificateError::NotValidForName)) => Ok(ServerCertVerified::assertion()),
+      result => result,
+    }
+  }
+}
+
+#[cfg(feature = "enable-rustls")]
+fn create_root_store(config: &TlsConfig) -> RootCertStore {
+  let mut root_store = config.root_store.clone().unwrap_or_else(|| RootCertStore::empty());
+
+  if config.webpki_roots {
+    root_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+      OwnedTrustAnchor::from_subject_spki_name_constraints(anchor.subject, anchor.spki, anchor.name_constraints)
+    }));
+  }
+  root_store
+}
+
+#[cfg(feature = "enable-rustls")]
+fn parse_identity(identity: &TlsIdentity) -> Result<(Vec<rustls::Certificate>, rustls::PrivateKey), RedisError> {
+  let (cert_chain, key) = match identity {
+    TlsIdentity::Pem { cert_chain, key } => (cert_chain, key),
+    TlsIdentity::Pkcs12 { .. } => {
+      return Err(RedisError::new(
+        RedisErrorKind::Tls,
+        "PKCS#12 identities are not supported with rustls.",
+      ))
+    }
+  };
+
+  let certs = rustls_pemfile::certs(&mut BufReader::new(cert_chain.as_slice()))?;
+  if certs.is_empty() {
+    return Err(RedisError::new(RedisErrorKind::Tls, "Missing client certificate."));
+  }
+
+  let mut reader = BufReader::new(key.as_slice());
+  while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+    match item {
+      Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => {
+        let certs = certs.into_iter().map(rustls::Certificate).collect();
+        return Ok((certs, rustls::PrivateKey(key)));
+      }
+      _ => continue,
+    }
+  }
+
+  Err(RedisError::new(RedisErrorKind::Tls, "Missing client private key."))
+}
+
+#[cfg(feature = "enable-rustls")]
+fn create_rustls_connector(tls_config: &TlsConfig) -> Result<RustlsConnector, RedisError> {
+  let root_store = create_root_store(tls_config);
+
+  let verifier: Arc<dyn ServerCertVerifier> = if tls_config.danger_accept_invalid_certs
+    || should_disable_cert_verification()
+  {
+    Arc::new(NoCertVerification)
+  } else if let Some(ref verifier) = tls_config.cert_verifier {
+    verifier.clone()
+  } else if root_store.is_empty() {
+    return Err(RedisError::new(
+      RedisErrorKind::Tls,
+      "TLS config must provide root certificates or a certificate verifier.",
+    ));
+  } else {
+    Arc::new(WebPkiVerifier::new(root_store.clone(), None))
+  };
+  let verifier: Arc<dyn ServerCertVerifier> = if tls_config.danger_accept_invalid_hostnames
+    || should_disable_host_verification()
+  {
+    Arc::new(NoHostVerification(verifier))
+  } else {
+    verifier
+  };
+
+  let builder = ClientConfig::builder()
+    .with_safe_defaults()
+    .with_root_certificates(root_store);
+  let mut client_config = if let Some(ref identity) = tls_config.identity {
+    let (cert_chain, key) = parse_identity(identity)?;
+    builder
+      .with_client_auth_cert(cert_chain, key)
+      .map_err(|e| RedisError::new(RedisErrorKind::Tls, format!("Invalid client identity: {:?}", e)))?
+  } else {
+    builder.with_no_client_auth()
+  };
+  client_config.dangerous().set_certificate_verifier(verifier);
+  client_config.alpn_protocols = tls_config.alpn_protocols.clone();
+  client_config.enable_sni = tls_config.use_sni;
+
+  Ok(RustlsConnector::from(Arc::new(client_config)))
+}
+
+#[cfg(feature = "enable-rustls")]
+async fn connect_rustls(
+  config: &TlsConfig,
+  domain: &str,
+  socket: TcpStream,
+) -> Result<TlsStream<TcpStream>, RedisError> {
+  let connector = create_rustls_connector(config)?;
+  let server_name = ServerName::try_from(domain)
+    .map_err(|e| RedisError::new(RedisErrorKind::Tls, format!("Invalid server name {}: {:?}", domain, e)))?;
+
+  connector
+    .connect(server_name, socket)
+    .await
+    .map(|stream| TlsStream::Rustls(Box::new(stream)))
+    .map_err(|e| RedisError::new(RedisErrorKind::Tls, format!("{:?}", e)))
+}
+
+/// Check that the config does not set any fields that only apply to the TLS implementation that was not selected.
+#[cfg(all(feature = "enable-tls", feature = "enable-rustls"))]
+fn check_backend_options(config: &TlsConfig) -> Result<(), RedisError> {
+  let uses_native_tls_options = config.root_certs.is_some()
+    || config.min_protocol_version.is_some()
+    || config.max_protocol_version.is_some()
+    || config.disable_built_in_roots;
+  let uses_rustls_options =
+    config.root_store.is_some() || config.cert_verifier.is_some() || !config.alpn_protocols.is_empty();
+
+  match config.backend {
+    TlsBackend::NativeTls if uses_rustls_options => Err(RedisError::new(
+      RedisErrorKind::Tls,
+      "TLS config sets rustls options with the native-tls backend.",
+    )),
+    TlsBackend::Rustls if uses_native_tls_options => Err(RedisError::new(
+      RedisErrorKind::Tls,
+      "TLS config sets native-tls options with the rustls backend.",
+    )),
+    _ => Ok(()),
+  }
+}
+
+#[cfg(all(
+  any(feature = "enable-tls", feature = "enable-rustls"),
+  not(all(feature = "enable-tls", feature = "enable-rustls"))
+))]
+fn check_backend_options(_: &TlsConfig) -> Result<(), RedisError> {
+  Ok(())
+}
+
+/// Create a TLS connection with the [TlsBackend] chosen in the client's TLS config.
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
+pub async fn connect(
+  config: &RwLock<RedisConfig>,
+  domain: &str,
+  socket: TcpStream,
+) -> Result<TlsStream<TcpStream>, RedisError> {
+  let tls_config = config.read().tls.clone().unwrap_or_default();
+  check_backend_options(&tls_config)?;
+
+  match tls_config.backend {
+    #[cfg(feature = "enable-tls")]
+    TlsBackend::NativeTls => connect_native_tls(&tls_config, domain, socket).await,
+    #[cfg(feature = "enable-rustls")]
+    TlsBackend::Rustls => connect_rustls(&tls_config, domain, socket).await,
+  }
+}
+
+#[cfg(all(test, any(feature = "enable-tls", feature = "enable-rustls")))]
+mod tests {
+  use super::*;
+  use crate::clients::RedisClient;
+  use crate::interfaces::ClientLike;
+
+  #[test]
+  fn should_update_tls_config() {
+    let client = RedisClient::new(RedisConfig::default());
+    assert!(!client.client_config().uses_tls());
+
+    client.update_tls_config(TlsConfig::default());
+    assert!(client.client_config().uses_tls());
+    client.update_tls_config(TlsConfig {
+      use_sni: false,
+      ..TlsConfig::default()
+    });
+    assert!(client.client_config().uses_tls());
+    assert!(!client.client_config().tls.unwrap().use_sni);
+  }
+
+  #[test]
+  #[cfg(feature = "enable-tls")]
+  fn should_error_with_invalid_native_identity() {
+    let config = TlsConfig {
+      identity: Some(TlsIdentity::Pem {
+        cert_chain: b"not a certificate".to_vec(),
+        key: b"not a key".to_vec(),
+      }),
+      ..TlsConfig::default()
+    };
+
+    assert!(create_native_tls_connector(&config).is_err());
+  }
+
+  #[test]
+  #[cfg(feature = "enable-rustls")]
+  fn should_create_rustls_connector_with_webpki_roots() {
+    let config = TlsConfig {
+      alpn_protocols: vec![b"redis".to_vec()],
+      ..TlsConfig::default()
+    };
+
+    assert!(create_rustls_connector(&config).is_ok());
+  }
+
+  #[test]
+  #[cfg(feature = "enable-rustls")]
+  fn should_error_without_rustls_roots() {
+    let config = TlsConfig {
+      webpki_roots: false,
+      ..TlsConfig::default()
+    };
+
+    assert!(create_rustls_connector(&config).is_err());
+  }
+
+  #[test]
+  #[cfg(feature = "enable-rustls")]
+  fn should_create_rustls_connector_without_roots_when_accepting_invalid_certs() {
+    let config = TlsConfig {
+      webpki_roots: false,
+      danger_accept_invalid_certs: true,
+      ..TlsConfig::default()
+    };
+
+    assert!(create_rustls_connector(&config).is_ok());
+  }
+
+  #[test]
+  #[cfg(feature = "enable-rustls")]
+  fn should_error_with_invalid_rustls_identity() {
+    let pkcs12 = TlsIdentity::Pkcs12 {
+      der: vec![],
+      password: "".into(),
+    };
+    assert!(parse_identity(&pkcs12).is_err());
+
+    let pem = TlsIdentity::Pem {
+      cert_chain: b"not a certificate".to_vec(),
+      key: b"not a key".to_vec(),
+    };
+    assert!(parse_identity(&pem).is_err());
+  }
+
+  #[test]
+  #[cfg(all(feature = "enable-tls", feature = "enable-rustls"))]
+  fn should_error_with_options_for_other_backend() {
+    let config = TlsConfig {
+      backend: TlsBackend::NativeTls,
+      alpn_protocols: vec![b"redis".to_vec()],
+      ..TlsConfig::default()
+    };
+    assert!(check_backend_options(&config).is_err());
+
+    let config = TlsConfig {
+      backend: TlsBackend::Rustls,
+      disable_built_in_roots: true,
+      ..TlsConfig::default()
+    };
+    assert!(check_backend_options(&config).is_err());
+
+    let config = TlsConfig {
+      backend: TlsBackend::Rustls,
+      alpn_protocols: vec![b"redis".to_vec()],
+      ..TlsConfig::default()
+    };
+    assert!(check_backend_options(&config).is_ok());
+  }
+}
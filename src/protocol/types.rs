@@ -285,6 +285,7 @@ pub enum RedisCommandKind {
   BgSave,
   BitCount,
   BitField,
+  BitFieldRO,
   BitOp,
   BitPos,
   BlPop,
@@ -299,6 +300,7 @@ pub enum RedisCommandKind {
   ClientList,
   ClientGetName,
   ClientGetRedir,
+  ClientTracking,
   ClientPause,
   ClientUnpause,
   ClientUnblock,
@@ -344,6 +346,8 @@ pub enum RedisCommandKind {
   Expire,
   ExpireAt,
   Failover,
+  Fcall(CustomKeySlot),
+  FcallRO(CustomKeySlot),
   FlushAll,
   FlushDB,
   GeoAdd,
@@ -455,12 +459,15 @@ pub enum RedisCommandKind {
   Smove,
   Sort,
   Spop,
+  Spublish,
   Srandmember,
   Srem,
+  Ssubscribe,
   Strlen,
   Subscribe,
   Sunion,
   Sunionstore,
+  Sunsubscribe,
   Swapdb,
   Sync,
   Time,
@@ -526,6 +533,13 @@ pub enum RedisCommandKind {
   ScriptExists,
   ScriptFlush,
   ScriptKill,
+  FunctionDelete,
+  FunctionDump,
+  FunctionFlush,
+  FunctionList,
+  FunctionLoad,
+  FunctionRestore,
+  FunctionStats,
   Scan(KeyScanInner),
   Sscan(ValueScanInner),
   Hscan(ValueScanInner),
@@ -538,6 +552,10 @@ pub enum RedisCommandKind {
   _ScriptFlushCluster(AllNodesResponse),
   _ScriptLoadCluster(AllNodesResponse),
   _ScriptKillCluster(AllNodesResponse),
+  _FunctionLoadCluster(AllNodesResponse),
+  _FunctionDeleteCluster(AllNodesResponse),
+  _FunctionFlushCluster(AllNodesResponse),
+  _FunctionRestoreCluster(AllNodesResponse),
   _Custom(CustomCommand),
 }
 
@@ -716,6 +734,7 @@ impl RedisCommandKind {
       RedisCommandKind::BgSave => "BGSAVE",
       RedisCommandKind::BitCount => "BITCOUNT",
       RedisCommandKind::BitField => "BITFIELD",
+      RedisCommandKind::BitFieldRO => "BITFIELD_RO",
       RedisCommandKind::BitOp => "BITOP",
       RedisCommandKind::BitPos => "BITPOS",
       RedisCommandKind::BlPop => "BLPOP",
@@ -729,6 +748,7 @@ impl RedisCommandKind {
       RedisCommandKind::ClientKill => "CLIENT KILL",
       RedisCommandKind::ClientList => "CLIENT LIST",
       RedisCommandKind::ClientGetRedir => "CLIENT GETREDIR",
+      RedisCommandKind::ClientTracking => "CLIENT TRACKING",
       RedisCommandKind::ClientGetName => "CLIENT GETNAME",
       RedisCommandKind::ClientPause => "CLIENT PAUSE",
       RedisCommandKind::ClientUnpause => "CLIENT UNPAUSE",
@@ -775,6 +795,8 @@ impl RedisCommandKind {
       RedisCommandKind::Expire => "EXPIRE",
       RedisCommandKind::ExpireAt => "EXPIREAT",
       RedisCommandKind::Failover => "FAILOVER",
+      RedisCommandKind::Fcall(_) => "FCALL",
+      RedisCommandKind::FcallRO(_) => "FCALL_RO",
       RedisCommandKind::FlushAll => "FLUSHALL",
       RedisCommandKind::FlushDB => "FLUSHDB",
       RedisCommandKind::GeoAdd => "GEOADD",
@@ -886,12 +908,15 @@ impl RedisCommandKind {
       RedisCommandKind::Smove => "SMOVE",
       RedisCommandKind::Sort => "SORT",
       RedisCommandKind::Spop => "SPOP",
+      RedisCommandKind::Spublish => "SPUBLISH",
       RedisCommandKind::Srandmember => "SRANDMEMBER",
       RedisCommandKind::Srem => "SREM",
+      RedisCommandKind::Ssubscribe => "SSUBSCRIBE",
       RedisCommandKind::Strlen => "STRLEN",
       RedisCommandKind::Subscribe => "SUBSCRIBE",
       RedisCommandKind::Sunion => "SUNION",
       RedisCommandKind::Sunionstore => "SUNIONSTORE",
+      RedisCommandKind::Sunsubscribe => "SUNSUBSCRIBE",
       RedisCommandKind::Swapdb => "SWAPDB",
       RedisCommandKind::Sync => "SYNC",
       RedisCommandKind::Time => "TIME",
@@ -961,6 +986,13 @@ impl RedisCommandKind {
       RedisCommandKind::ScriptFlush => "SCRIPT FLUSH",
       RedisCommandKind::ScriptKill => "SCRIPT KILL",
       RedisCommandKind::ScriptLoad => "SCRIPT LOAD",
+      RedisCommandKind::FunctionDelete => "FUNCTION DELETE",
+      RedisCommandKind::FunctionDump => "FUNCTION DUMP",
+      RedisCommandKind::FunctionFlush => "FUNCTION FLUSH",
+      RedisCommandKind::FunctionList => "FUNCTION LIST",
+      RedisCommandKind::FunctionLoad => "FUNCTION LOAD",
+      RedisCommandKind::FunctionRestore => "FUNCTION RESTORE",
+      RedisCommandKind::FunctionStats => "FUNCTION STATS",
       RedisCommandKind::_Close => "CLOSE",
       RedisCommandKind::_Split(_) => "SPLIT",
       RedisCommandKind::_AuthAllCluster(_) => "AUTH ALL CLUSTER",
@@ -969,6 +1001,10 @@ impl RedisCommandKind {
       RedisCommandKind::_ScriptFlushCluster(_) => "SCRIPT FLUSH CLUSTER",
       RedisCommandKind::_ScriptLoadCluster(_) => "SCRIPT LOAD CLUSTER",
       RedisCommandKind::_ScriptKillCluster(_) => "SCRIPT Kill CLUSTER",
+      RedisCommandKind::_FunctionLoadCluster(_) => "FUNCTION LOAD CLUSTER",
+      RedisCommandKind::_FunctionDeleteCluster(_) => "FUNCTION DELETE CLUSTER",
+      RedisCommandKind::_FunctionFlushCluster(_) => "FUNCTION FLUSH CLUSTER",
+      RedisCommandKind::_FunctionRestoreCluster(_) => "FUNCTION RESTORE CLUSTER",
       RedisCommandKind::_Custom(ref kind) => &kind.cmd,
     }
   }
@@ -994,6 +1030,7 @@ impl RedisCommandKind {
       RedisCommandKind::BgSave => "BGSAVE",
       RedisCommandKind::BitCount => "BITCOUNT",
       RedisCommandKind::BitField => "BITFIELD",
+      RedisCommandKind::BitFieldRO => "BITFIELD_RO",
       RedisCommandKind::BitOp => "BITOP",
       RedisCommandKind::BitPos => "BITPOS",
       RedisCommandKind::BlPop => "BLPOP",
@@ -1008,6 +1045,7 @@ impl RedisCommandKind {
       RedisCommandKind::ClientList => "CLIENT",
       RedisCommandKind::ClientGetName => "CLIENT",
       RedisCommandKind::ClientGetRedir => "CLIENT",
+      RedisCommandKind::ClientTracking => "CLIENT",
       RedisCommandKind::ClientPause => "CLIENT",
       RedisCommandKind::ClientUnpause => "CLIENT",
       RedisCommandKind::ClientUnblock => "CLIENT",
@@ -1053,6 +1091,8 @@ impl RedisCommandKind {
       RedisCommandKind::Expire => "EXPIRE",
       RedisCommandKind::ExpireAt => "EXPIREAT",
       RedisCommandKind::Failover => "FAILOVER",
+      RedisCommandKind::Fcall(_) => "FCALL",
+      RedisCommandKind::FcallRO(_) => "FCALL_RO",
       RedisCommandKind::FlushAll => "FLUSHALL",
       RedisCommandKind::_FlushAllCluster(_) => "FLUSHALL",
       RedisCommandKind::FlushDB => "FLUSHDB",
@@ -1165,12 +1205,15 @@ impl RedisCommandKind {
       RedisCommandKind::Smove => "SMOVE",
       RedisCommandKind::Sort => "SORT",
       RedisCommandKind::Spop => "SPOP",
+      RedisCommandKind::Spublish => "SPUBLISH",
       RedisCommandKind::Srandmember => "SRANDMEMBER",
       RedisCommandKind::Srem => "SREM",
+      RedisCommandKind::Ssubscribe => "SSUBSCRIBE",
       RedisCommandKind::Strlen => "STRLEN",
       RedisCommandKind::Subscribe => "SUBSCRIBE",
       RedisCommandKind::Sunion => "SUNION",
       RedisCommandKind::Sunionstore => "SUNIONSTORE",
+      RedisCommandKind::Sunsubscribe => "SUNSUBSCRIBE",
       RedisCommandKind::Swapdb => "SWAPDB",
       RedisCommandKind::Sync => "SYNC",
       RedisCommandKind::Time => "TIME",
@@ -1239,6 +1282,17 @@ impl RedisCommandKind {
       RedisCommandKind::_ScriptFlushCluster(_) => "SCRIPT",
       RedisCommandKind::_ScriptLoadCluster(_) => "SCRIPT",
       RedisCommandKind::_ScriptKillCluster(_) => "SCRIPT",
+      RedisCommandKind::FunctionDelete => "FUNCTION",
+      RedisCommandKind::FunctionDump => "FUNCTION",
+      RedisCommandKind::FunctionFlush => "FUNCTION",
+      RedisCommandKind::FunctionList => "FUNCTION",
+      RedisCommandKind::FunctionLoad => "FUNCTION",
+      RedisCommandKind::FunctionRestore => "FUNCTION",
+      RedisCommandKind::FunctionStats => "FUNCTION",
+      RedisCommandKind::_FunctionLoadCluster(_) => "FUNCTION",
+      RedisCommandKind::_FunctionDeleteCluster(_) => "FUNCTION",
+      RedisCommandKind::_FunctionFlushCluster(_) => "FUNCTION",
+      RedisCommandKind::_FunctionRestoreCluster(_) => "FUNCTION",
       RedisCommandKind::Scan(_) => "SCAN",
       RedisCommandKind::Sscan(_) => "SSCAN",
       RedisCommandKind::Hscan(_) => "HSCAN",
@@ -1265,6 +1319,17 @@ impl RedisCommandKind {
       RedisCommandKind::_ScriptFlushCluster(_) => "FLUSH",
       RedisCommandKind::_ScriptLoadCluster(_) => "LOAD",
       RedisCommandKind::_ScriptKillCluster(_) => "KILL",
+      RedisCommandKind::FunctionDelete => "DELETE",
+      RedisCommandKind::FunctionDump => "DUMP",
+      RedisCommandKind::FunctionFlush => "FLUSH",
+      RedisCommandKind::FunctionList => "LIST",
+      RedisCommandKind::FunctionLoad => "LOAD",
+      RedisCommandKind::FunctionRestore => "RESTORE",
+      RedisCommandKind::FunctionStats => "STATS",
+      RedisCommandKind::_FunctionLoadCluster(_) => "LOAD",
+      RedisCommandKind::_FunctionDeleteCluster(_) => "DELETE",
+      RedisCommandKind::_FunctionFlushCluster(_) => "FLUSH",
+      RedisCommandKind::_FunctionRestoreCluster(_) => "RESTORE",
       RedisCommandKind::AclLoad => "LOAD",
       RedisCommandKind::AclSave => "SAVE",
       RedisCommandKind::AclList => "LIST",
@@ -1303,6 +1368,7 @@ impl RedisCommandKind {
       RedisCommandKind::ClientKill => "KILL",
       RedisCommandKind::ClientList => "LIST",
       RedisCommandKind::ClientGetRedir => "GETREDIR",
+      RedisCommandKind::ClientTracking => "TRACKING",
       RedisCommandKind::ClientGetName => "GETNAME",
       RedisCommandKind::ClientPause => "PAUSE",
       RedisCommandKind::ClientUnpause => "UNPAUSE",
@@ -1396,6 +1462,7 @@ impl RedisCommandKind {
     match *self {
       RedisCommandKind::ClientGetName
       | RedisCommandKind::ClientGetRedir
+      | RedisCommandKind::ClientTracking
       | RedisCommandKind::ClientInfo
       | RedisCommandKind::ClientID
       | RedisCommandKind::ClientKill
@@ -1479,6 +1546,8 @@ impl RedisCommandKind {
       RedisCommandKind::_Custom(ref kind) => kind.hash_slot.clone(),
       RedisCommandKind::EvalSha(ref slot) => slot.key_slot.clone(),
       RedisCommandKind::Eval(ref slot) => slot.key_slot.clone(),
+      RedisCommandKind::Fcall(ref slot) => slot.key_slot.clone(),
+      RedisCommandKind::FcallRO(ref slot) => slot.key_slot.clone(),
       RedisCommandKind::Xread((_, ref slot)) => slot.clone(),
       RedisCommandKind::Xreadgroup((_, ref slot)) => slot.clone(),
       _ => None,
@@ -1492,6 +1561,10 @@ impl RedisCommandKind {
       | RedisCommandKind::_ScriptFlushCluster(_)
       | RedisCommandKind::_ScriptKillCluster(_)
       | RedisCommandKind::_HelloAllCluster(_)
+      | RedisCommandKind::_FunctionLoadCluster(_)
+      | RedisCommandKind::_FunctionDeleteCluster(_)
+      | RedisCommandKind::_FunctionFlushCluster(_)
+      | RedisCommandKind::_FunctionRestoreCluster(_)
       | RedisCommandKind::_ScriptLoadCluster(_) => true,
       _ => false,
     }
@@ -1512,6 +1585,10 @@ impl RedisCommandKind {
       RedisCommandKind::_ScriptFlushCluster(ref inner) => Some(inner),
       RedisCommandKind::_ScriptLoadCluster(ref inner) => Some(inner),
       RedisCommandKind::_ScriptKillCluster(ref inner) => Some(inner),
+      RedisCommandKind::_FunctionLoadCluster(ref inner) => Some(inner),
+      RedisCommandKind::_FunctionDeleteCluster(ref inner) => Some(inner),
+      RedisCommandKind::_FunctionFlushCluster(ref inner) => Some(inner),
+      RedisCommandKind::_FunctionRestoreCluster(ref inner) => Some(inner),
       _ => None,
     }
   }
@@ -1526,13 +1603,87 @@ impl RedisCommandKind {
       RedisCommandKind::_ScriptFlushCluster(ref inner) => Some(RedisCommandKind::_ScriptFlushCluster(inner.clone())),
       RedisCommandKind::_ScriptLoadCluster(ref inner) => Some(RedisCommandKind::_ScriptLoadCluster(inner.clone())),
       RedisCommandKind::_ScriptKillCluster(ref inner) => Some(RedisCommandKind::_ScriptKillCluster(inner.clone())),
+      RedisCommandKind::_FunctionLoadCluster(ref inner) => {
+        Some(RedisCommandKind::_FunctionLoadCluster(inner.clone()))
+      }
+      RedisCommandKind::_FunctionDeleteCluster(ref inner) => {
+        Some(RedisCommandKind::_FunctionDeleteCluster(inner.clone()))
+      }
+      RedisCommandKind::_FunctionFlushCluster(ref inner) => {
+        Some(RedisCommandKind::_FunctionFlushCluster(inner.clone()))
+      }
+      RedisCommandKind::_FunctionRestoreCluster(ref inner) => {
+        Some(RedisCommandKind::_FunctionRestoreCluster(inner.clone()))
+      }
       _ => None,
     }
   }
 
+  /// Whether or not the command only reads data, and can therefore be sent to a replica node.
+  ///
+  /// Blocking commands and scanning commands are always sent to the primary node.
   pub fn is_read(&self) -> bool {
-    // TODO finish this and use for sending reads to replicas
     match *self {
+      RedisCommandKind::BitCount
+      | RedisCommandKind::BitFieldRO
+      | RedisCommandKind::BitPos
+      | RedisCommandKind::Dump
+      | RedisCommandKind::Exists
+      | RedisCommandKind::FcallRO(_)
+      | RedisCommandKind::GeoHash
+      | RedisCommandKind::GeoPos
+      | RedisCommandKind::GeoDist
+      | RedisCommandKind::GeoSearch
+      | RedisCommandKind::Get
+      | RedisCommandKind::GetBit
+      | RedisCommandKind::GetRange
+      | RedisCommandKind::HExists
+      | RedisCommandKind::HGet
+      | RedisCommandKind::HGetAll
+      | RedisCommandKind::HKeys
+      | RedisCommandKind::HLen
+      | RedisCommandKind::HMGet
+      | RedisCommandKind::HStrLen
+      | RedisCommandKind::HVals
+      | RedisCommandKind::HRandField
+      | RedisCommandKind::LIndex
+      | RedisCommandKind::LLen
+      | RedisCommandKind::LPos
+      | RedisCommandKind::LRange
+      | RedisCommandKind::Mget
+      | RedisCommandKind::Pttl
+      | RedisCommandKind::Scard
+      | RedisCommandKind::Sdiff
+      | RedisCommandKind::Sinter
+      | RedisCommandKind::Sismember
+      | RedisCommandKind::Smembers
+      | RedisCommandKind::Smismember
+      | RedisCommandKind::Srandmember
+      | RedisCommandKind::Strlen
+      | RedisCommandKind::Sunion
+      | RedisCommandKind::Ttl
+      | RedisCommandKind::Type
+      | RedisCommandKind::Xlen
+      | RedisCommandKind::Xrange
+      | RedisCommandKind::Xrevrange
+      | RedisCommandKind::Zcard
+      | RedisCommandKind::Zcount
+      | RedisCommandKind::Zdiff
+      | RedisCommandKind::Zinter
+      | RedisCommandKind::Zlexcount
+      | RedisCommandKind::Zmscore
+      | RedisCommandKind::Zrandmember
+      | RedisCommandKind::Zrange
+      | RedisCommandKind::Zrangebylex
+      | RedisCommandKind::Zrangebyscore
+      | RedisCommandKind::Zrank
+      | RedisCommandKind::Zrevrange
+      | RedisCommandKind::Zrevrangebylex
+      | RedisCommandKind::Zrevrangebyscore
+      | RedisCommandKind::Zrevrank
+      | RedisCommandKind::Zscore
+      | RedisCommandKind::Zunion => true,
+      RedisCommandKind::Xread((ref blocking, _)) => !*blocking,
       _ => false,
     }
   }
@@ -1553,6 +1704,12 @@ pub struct RedisCommand {
   pub sent: Instant,
   /// Sender for notifying the command processing loop that the command received a response.
   pub resp_tx: Arc<RwLock<Option<OneshotSender<()>>>>,
+  /// Whether or not the command was sent by a `Pipeline`, in which case it will be pipelined regardless of the client's config.
+  pub pipelined: bool,
+  /// Whether or not the command must be sent to a primary node, even if it could be sent to a replica.
+  pub primary_only: bool,
+  /// Whether or not the command was sent by a `Replicas` client, in which case it will be sent to a replica if possible.
+  pub use_replica: bool,
   #[cfg(any(feature = "full-tracing", feature = "partial-tracing"))]
   pub traces: CommandTraces,
 }
@@ -1580,6 +1737,9 @@ impl RedisCommand {
       attempted: 0,
       sent: Instant::now(),
       resp_tx: Arc::new(RwLock::new(None)),
+      pipelined: false,
+      primary_only: false,
+      use_replica: false,
     }
   }
 
@@ -1592,6 +1752,9 @@ impl RedisCommand {
       attempted: 0,
       sent: Instant::now(),
       resp_tx: Arc::new(RwLock::new(None)),
+      pipelined: false,
+      primary_only: false,
+      use_replica: false,
     }
   }
 
@@ -1604,6 +1767,9 @@ impl RedisCommand {
       args: self.args.clone(),
       sent: self.sent.clone(),
       resp_tx: self.resp_tx.clone(),
+      pipelined: self.pipelined,
+      primary_only: self.primary_only,
+      use_replica: self.use_replica,
       traces: CommandTraces::default(),
     }
   }
@@ -1617,6 +1783,9 @@ impl RedisCommand {
       args: self.args.clone(),
       sent: self.sent.clone(),
       resp_tx: self.resp_tx.clone(),
+      pipelined: self.pipelined,
+      primary_only: self.primary_only,
+      use_replica: self.use_replica,
     }
   }
 
@@ -1692,6 +1861,14 @@ impl RedisCommand {
     if self.no_cluster() || has_custom_key_location {
       return None;
     }
+    if let RedisCommandKind::BitOp = self.kind {
+      // the destination key follows the operation
+      return match self.args.get(1) {
+        Some(RedisValue::String(ref s)) => Some(s.as_bytes()),
+        Some(RedisValue::Bytes(ref b)) => Some(b),
+        _ => None,
+      };
+    }
 
     match self.args.first() {
       Some(RedisValue::String(ref s)) => Some(s.as_bytes()),
@@ -1718,41 +1895,6 @@ impl RedisCommand {
   }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ReplicaNodes {
-  servers: Vec<Arc<String>>,
-  next: usize,
-}
-
-impl ReplicaNodes {
-  // TODO remove when replica support is added
-  #![allow(dead_code)]
-
-  pub fn new(servers: Vec<Arc<String>>) -> ReplicaNodes {
-    ReplicaNodes { servers, next: 0 }
-  }
-
-  pub fn add(&mut self, server: Arc<String>) {
-    self.servers.push(server);
-  }
-
-  pub fn clear(&mut self) {
-    self.servers.clear();
-    self.next = 0;
-  }
-
-  pub fn next(&mut self) -> Option<Arc<String>> {
-    if self.servers.len() == 0 {
-      return None;
-    }
-
-    let last = self.next;
-    self.next = (self.next + 1) % self.servers.len();
-
-    self.servers.get(last).cloned()
-  }
-}
-
 /// A slot range and associated cluster node information from the CLUSTER NODES command.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SlotRange {
@@ -1760,8 +1902,8 @@ pub struct SlotRange {
   pub end: u16,
   pub server: Arc<String>,
   pub id: Arc<String>,
-  // TODO cache replicas for each primary and round-robin reads to the replicas + primary, and only send writes to the primary
-  //pub replicas: Option<ReplicaNodes>,
+  /// The replica nodes of the primary node that owns the slot range.
+  pub replicas: Vec<Arc<String>>,
 }
 
 /// The cached view of the cluster used by the client to route commands to the correct cluster nodes.
@@ -1811,6 +1953,19 @@ impl ClusterKeyCache {
     out.into_iter().collect()
   }
 
+  /// Read the set of unique replica nodes in the cluster.
+  pub fn unique_replica_nodes(&self) -> Vec<Arc<String>> {
+    let mut out = BTreeSet::new();
+
+    for slot in self.data.iter() {
+      for replica in slot.replicas.iter() {
+        out.insert(replica.clone());
+      }
+    }
+
+    out.into_iter().collect()
+  }
+
   /// Clear the cached state of the cluster.
   pub fn clear(&mut self) {
     self.data.clear();
@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::convert::TryInto;
 use std::net::SocketAddr;
 use std::ops::Deref;
+use std::path::PathBuf;
 use std::str;
 use std::sync::Arc;
 
@@ -28,7 +29,7 @@ macro_rules! parse_or_zero(
   }
 );
 
-#[cfg(feature = "enable-tls")]
+#[cfg(any(feature = "enable-tls", feature = "enable-rustls"))]
 pub fn uses_tls(inner: &Arc<RedisClientInner>) -> bool {
   inner.config.read().tls.is_some()
 }
@@ -41,7 +42,7 @@ pub fn is_null(frame: &Resp3Frame) -> bool {
   }
 }
 
-#[cfg(not(feature = "enable-tls"))]
+#[cfg(not(any(feature = "enable-tls", feature = "enable-rustls")))]
 pub fn uses_tls(_: &Arc<RedisClientInner>) -> bool {
   false
 }
@@ -94,6 +95,14 @@ pub fn read_centralized_domain(config: &RwLock<RedisConfig>) -> Result<String, R
   }
 }
 
+/// Read the path to the Unix socket, if the client connects via a Unix socket.
+pub fn read_unix_path(config: &RwLock<RedisConfig>) -> Option<PathBuf> {
+  match config.read().server {
+    ServerConfig::Unix { ref path } => Some(path.clone()),
+    _ => None,
+  }
+}
+
 pub async fn read_centralized_addr(inner: &Arc<RedisClientInner>) -> Result<SocketAddr, RedisError> {
   let (host, port) = match inner.config.read().server {
     ServerConfig::Centralized { ref host, ref port, .. } => (host.clone(), *port),
@@ -147,8 +156,32 @@ pub fn binary_search(slots: &Vec<Arc<SlotRange>>, slot: u16) -> Option<Arc<SlotR
   None
 }
 
+/// Read the healthy replica nodes from the output of CLUSTER NODES, keyed by the ID of the associated primary node.
+fn parse_cluster_replicas(status: &str) -> HashMap<&str, Vec<Arc<String>>> {
+  let mut out: HashMap<&str, Vec<Arc<String>>> = HashMap::new();
+
+  for line in status.lines() {
+    let parts: Vec<&str> = line.split(" ").collect();
+    if parts.len() < 8 || !parts[2].contains("slave") {
+      continue;
+    }
+    if parts[2].contains("fail") || parts[2].contains("noaddr") || parts[2].contains("handshake") {
+      continue;
+    }
+    if parts[7] != "connected" || parts[3] == "-" {
+      continue;
+    }
+
+    let server = Arc::new(remove_cport_suffix(parts[1].to_owned()));
+    out.entry(parts[3]).or_insert(Vec::new()).push(server);
+  }
+
+  out
+}
+
 pub fn parse_cluster_nodes(status: &str) -> Result<HashMap<Arc<String>, Vec<SlotRange>>, RedisError> {
   let mut out: HashMap<Arc<String>, Vec<SlotRange>> = HashMap::new();
+  let replicas = parse_cluster_replicas(status);
 
   // build out the slot ranges for the primary nodes
   for line in status.lines() {
@@ -165,6 +198,7 @@ pub fn parse_cluster_nodes(status: &str) -> Result<HashMap<Arc<String>, Vec<Slot
 
     if parts[2].contains("master") {
       let mut slots: Vec<SlotRange> = Vec::new();
+      let replicas = replicas.get(parts[0]).cloned().unwrap_or(Vec::new());
 
       let server = Arc::new(remove_cport_suffix(parts[1].to_owned()));
       for slot in parts[8..].iter() {
@@ -178,6 +212,7 @@ pub fn parse_cluster_nodes(status: &str) -> Result<HashMap<Arc<String>, Vec<Slot
             end: inner_parts[0].parse::<u16>()?,
             server: server.clone(),
             id: id.clone(),
+            replicas: replicas.clone(),
           });
         } else if inner_parts.len() == 2 {
           // looking at a slot range
@@ -187,6 +222,7 @@ pub fn parse_cluster_nodes(status: &str) -> Result<HashMap<Arc<String>, Vec<Slot
             end: inner_parts[1].parse::<u16>()?,
             server: server.clone(),
             id: id.clone(),
+            replicas: replicas.clone(),
           });
         } else if inner_parts.len() == 3 {
           // looking at a migrating slot
@@ -203,7 +239,6 @@ pub fn parse_cluster_nodes(status: &str) -> Result<HashMap<Arc<String>, Vec<Slot
     }
   }
 
-  //add_replica_nodes(&mut out, &status)?;
   out.shrink_to_fit();
   Ok(out)
 }
@@ -267,6 +302,30 @@ pub fn frame_to_pubsub(frame: Resp3Frame) -> Result<(String, RedisValue), RedisE
   }
 }
 
+/// Convert a sharded `["smessage", channel, message]` frame to a `(channel, message)` tuple.
+pub fn frame_to_sharded_pubsub(frame: Resp3Frame) -> Result<(String, RedisValue), RedisError> {
+  let mut data = match frame {
+    Resp3Frame::Array { data, .. } | Resp3Frame::Push { data, .. } => data,
+    _ => {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Invalid sharded pubsub message frame.",
+      ))
+    }
+  };
+  if data.len() != 3 {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid sharded pubsub message frame.",
+    ));
+  }
+
+  // length checked above
+  let message = frame_to_single_result(data.pop().unwrap())?;
+  let channel = frame_into_string(data.pop().unwrap())?;
+  Ok((channel, message))
+}
+
 /// Attempt to parse a RESP3 frame as a pubsub message in the RESP2 format.
 ///
 /// This can be useful in cases where the codec layer automatically upgrades to RESP3,
@@ -627,6 +686,14 @@ pub fn flatten_frame(frame: Resp3Frame) -> Resp3Frame {
   }
 }
 
+/// Whether or not the frame is a `QUEUED` response, such as from a command in a transaction or pipeline.
+pub fn is_queued(frame: &Resp3Frame) -> bool {
+  match frame {
+    Resp3Frame::SimpleString { ref data, .. } => data == QUEUED,
+    _ => false,
+  }
+}
+
 /// Convert a frame to a nested RedisMap.
 pub fn frame_to_map(frame: Resp3Frame) -> Result<RedisMap, RedisError> {
   match frame {
@@ -1094,6 +1161,182 @@ pub fn parse_slowlog_entries(frames: Vec<Resp3Frame>) -> Result<Vec<SlowlogEntry
   Ok(out)
 }
 
+fn parse_function(frames: &Vec<Resp3Frame>) -> Result<Function, RedisError> {
+  if frames.len() % 2 != 0 {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid function. Expected an even number of frames.",
+    ));
+  }
+
+  let mut function = Function::default();
+  for chunk in frames.chunks(2) {
+    let key = match chunk[0].as_str() {
+      Some(s) => s,
+      None => continue,
+    };
+
+    match key.as_ref() {
+      "name" => function.name = chunk[1].as_str().map(|s| s.to_owned()).unwrap_or_default(),
+      "description" => function.description = chunk[1].as_str().map(|s| s.to_owned()),
+      "flags" => function.flags = frames_to_strings(&chunk[1])?,
+      _ => {}
+    };
+  }
+
+  Ok(function)
+}
+
+fn parse_function_library(frames: &Vec<Resp3Frame>) -> Result<FunctionLibrary, RedisError> {
+  if frames.len() % 2 != 0 {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid function library. Expected an even number of frames.",
+    ));
+  }
+
+  let mut library = FunctionLibrary::default();
+  for chunk in frames.chunks(2) {
+    let key = match chunk[0].as_str() {
+      Some(s) => s,
+      None => continue,
+    };
+
+    match key.as_ref() {
+      "library_name" => library.name = chunk[1].as_str().map(|s| s.to_owned()).unwrap_or_default(),
+      "engine" => library.engine = chunk[1].as_str().map(|s| s.to_owned()).unwrap_or_default(),
+      "library_code" => library.code = chunk[1].as_str().map(|s| s.to_owned()),
+      "functions" => {
+        if let Resp3Frame::Array { ref data, .. } = chunk[1] {
+          for frame in data.iter() {
+            if let Resp3Frame::Array { ref data, .. } = frame {
+              library.functions.push(parse_function(data)?);
+            } else {
+              return Err(RedisError::new(
+                RedisErrorKind::ProtocolError,
+                "Expected array of function fields.",
+              ));
+            }
+          }
+        } else {
+          return Err(RedisError::new(
+            RedisErrorKind::ProtocolError,
+            "Expected array of functions.",
+          ));
+        }
+      }
+      _ => {}
+    };
+  }
+
+  Ok(library)
+}
+
+/// Parse the response to `FUNCTION LIST`, after any maps or sets are converted to arrays.
+pub fn parse_function_libraries(frames: Vec<Resp3Frame>) -> Result<Vec<FunctionLibrary>, RedisError> {
+  let mut out = Vec::with_capacity(frames.len());
+
+  for frame in frames.into_iter() {
+    if let Resp3Frame::Array { ref data, .. } = frame {
+      out.push(parse_function_library(data)?);
+    } else {
+      return Err(RedisError::new(
+        RedisErrorKind::ProtocolError,
+        "Expected array of library fields.",
+      ));
+    }
+  }
+
+  Ok(out)
+}
+
+fn parse_running_function(frames: &Vec<Resp3Frame>) -> Result<RunningFunction, RedisError> {
+  if frames.len() % 2 != 0 {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid running script. Expected an even number of frames.",
+    ));
+  }
+
+  let mut running = RunningFunction::default();
+  for chunk in frames.chunks(2) {
+    let key = match chunk[0].as_str() {
+      Some(s) => s,
+      None => continue,
+    };
+
+    match key.as_ref() {
+      "name" => running.name = chunk[1].as_str().map(|s| s.to_owned()).unwrap_or_default(),
+      "command" => running.command = frames_to_strings(&chunk[1])?,
+      "duration_ms" => running.duration_ms = parse_u64(&chunk[1]),
+      _ => {}
+    };
+  }
+
+  Ok(running)
+}
+
+/// Parse the response to `FUNCTION STATS`, after any maps or sets are converted to arrays.
+pub fn parse_function_stats(frames: &Vec<Resp3Frame>) -> Result<FunctionStats, RedisError> {
+  if frames.len() % 2 != 0 {
+    return Err(RedisError::new(
+      RedisErrorKind::ProtocolError,
+      "Invalid FUNCTION STATS response. Result must have an even number of frames.",
+    ));
+  }
+
+  let mut out = FunctionStats::default();
+  for chunk in frames.chunks(2) {
+    let key = match chunk[0].as_str() {
+      Some(s) => s,
+      None => continue,
+    };
+
+    match key.as_ref() {
+      "running_script" => {
+        if let Resp3Frame::Array { ref data, .. } = chunk[1] {
+          out.running_script = Some(parse_running_function(data)?);
+        }
+      }
+      "engines" => {
+        let engines = match chunk[1] {
+          Resp3Frame::Array { ref data, .. } => data,
+          _ => continue,
+        };
+
+        for engine in engines.chunks(2) {
+          let name = match engine[0].as_str() {
+            Some(s) => s.to_owned(),
+            None => continue,
+          };
+          let fields = match engine.get(1) {
+            Some(Resp3Frame::Array { ref data, .. }) => data,
+            _ => continue,
+          };
+
+          let mut stats = FunctionEngineStats::default();
+          for field in fields.chunks(2) {
+            if field.len() < 2 {
+              continue;
+            }
+
+            match field[0].as_str() {
+              Some("libraries_count") => stats.libraries_count = parse_u64(&field[1]),
+              Some("functions_count") => stats.functions_count = parse_u64(&field[1]),
+              _ => {}
+            };
+          }
+
+          out.engines.insert(name, stats);
+        }
+      }
+      _ => {}
+    };
+  }
+
+  Ok(out)
+}
+
 fn parse_cluster_info_line(info: &mut ClusterInfo, line: &str) -> Result<(), RedisError> {
   let parts: Vec<&str> = line.split(":").collect();
   if parts.len() != 2 {
@@ -1372,7 +1615,12 @@ pub fn value_to_zset_result(value: RedisValue) -> Result<Vec<(RedisValue, f64)>,
   }
 }
 
-#[cfg(any(feature = "blocking-encoding", feature = "partial-tracing", feature = "full-tracing"))]
+#[cfg(any(
+  feature = "blocking-encoding",
+  feature = "partial-tracing",
+  feature = "full-tracing",
+  feature = "metrics"
+))]
 fn i64_size(i: i64) -> usize {
   if i < 0 {
     1 + redis_protocol::digits_in_number((i * -1) as usize)
@@ -1381,7 +1629,12 @@ fn i64_size(i: i64) -> usize {
   }
 }
 
-#[cfg(any(feature = "blocking-encoding", feature = "partial-tracing", feature = "full-tracing"))]
+#[cfg(any(
+  feature = "blocking-encoding",
+  feature = "partial-tracing",
+  feature = "full-tracing",
+  feature = "metrics"
+))]
 pub fn arg_size(value: &RedisValue) -> usize {
   match value {
     // use the RESP2 size
@@ -1403,7 +1656,12 @@ pub fn arg_size(value: &RedisValue) -> usize {
   }
 }
 
-#[cfg(any(feature = "blocking-encoding", feature = "partial-tracing", feature = "full-tracing"))]
+#[cfg(any(
+  feature = "blocking-encoding",
+  feature = "partial-tracing",
+  feature = "full-tracing",
+  feature = "metrics"
+))]
 pub fn resp2_frame_size(frame: &Resp2Frame) -> usize {
   match frame {
     Resp2Frame::Integer(ref i) => i64_size(*i),
@@ -1415,12 +1673,22 @@ pub fn resp2_frame_size(frame: &Resp2Frame) -> usize {
   }
 }
 
-#[cfg(any(feature = "blocking-encoding", feature = "partial-tracing", feature = "full-tracing"))]
+#[cfg(any(
+  feature = "blocking-encoding",
+  feature = "partial-tracing",
+  feature = "full-tracing",
+  feature = "metrics"
+))]
 pub fn resp3_frame_size(frame: &Resp3Frame) -> usize {
   frame.encode_len().unwrap_or(0)
 }
 
-#[cfg(any(feature = "blocking-encoding", feature = "partial-tracing", feature = "full-tracing"))]
+#[cfg(any(
+  feature = "blocking-encoding",
+  feature = "partial-tracing",
+  feature = "full-tracing",
+  feature = "metrics"
+))]
 pub fn frame_size(frame: &ProtocolFrame) -> usize {
   match frame {
     ProtocolFrame::Resp3(f) => resp3_frame_size(f),
@@ -1428,7 +1696,12 @@ pub fn frame_size(frame: &ProtocolFrame) -> usize {
   }
 }
 
-#[cfg(any(feature = "blocking-encoding", feature = "partial-tracing", feature = "full-tracing"))]
+#[cfg(any(
+  feature = "blocking-encoding",
+  feature = "partial-tracing",
+  feature = "full-tracing",
+  feature = "metrics"
+))]
 pub fn args_size(args: &Vec<RedisValue>) -> usize {
   args.iter().fold(0, |c, arg| c + arg_size(arg))
 }
@@ -1960,24 +2233,28 @@ b8553a4fae8ae99fca716d423b14875ebb10fefe quux.use2.cache.amazonaws.com:6379@1122
           end: 1696,
           server: Arc::new("foo.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("2edc9a62355eacff9376c4e09643e2c932b0356a".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 8195,
           end: 8245,
           server: Arc::new("foo.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("2edc9a62355eacff9376c4e09643e2c932b0356a".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 8247,
           end: 8423,
           server: Arc::new("foo.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("2edc9a62355eacff9376c4e09643e2c932b0356a".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 10923,
           end: 12287,
           server: Arc::new("foo.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("2edc9a62355eacff9376c4e09643e2c932b0356a".into()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -1989,36 +2266,42 @@ b8553a4fae8ae99fca716d423b14875ebb10fefe quux.use2.cache.amazonaws.com:6379@1122
           end: 1241,
           server: Arc::new("bar.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("db2fd89f83daa5fe49110ef760794f9ccee07d06".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 8152,
           end: 8194,
           server: Arc::new("bar.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("db2fd89f83daa5fe49110ef760794f9ccee07d06".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 8424,
           end: 8439,
           server: Arc::new("bar.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("db2fd89f83daa5fe49110ef760794f9ccee07d06".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 9203,
           end: 10112,
           server: Arc::new("bar.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("db2fd89f83daa5fe49110ef760794f9ccee07d06".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 12288,
           end: 12346,
           server: Arc::new("bar.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("db2fd89f83daa5fe49110ef760794f9ccee07d06".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 12576,
           end: 12685,
           server: Arc::new("bar.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("db2fd89f83daa5fe49110ef760794f9ccee07d06".into()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -2030,36 +2313,42 @@ b8553a4fae8ae99fca716d423b14875ebb10fefe quux.use2.cache.amazonaws.com:6379@1122
           end: 1697,
           server: Arc::new("baz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("d9aeabb1525e5656c98545a0ed42c8c99bbacae1".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 1815,
           end: 2291,
           server: Arc::new("baz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("d9aeabb1525e5656c98545a0ed42c8c99bbacae1".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 3657,
           end: 4089,
           server: Arc::new("baz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("d9aeabb1525e5656c98545a0ed42c8c99bbacae1".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 5861,
           end: 6770,
           server: Arc::new("baz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("d9aeabb1525e5656c98545a0ed42c8c99bbacae1".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 7531,
           end: 7713,
           server: Arc::new("baz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("d9aeabb1525e5656c98545a0ed42c8c99bbacae1".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 13154,
           end: 13197,
           server: Arc::new("baz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("d9aeabb1525e5656c98545a0ed42c8c99bbacae1".into()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -2071,24 +2360,28 @@ b8553a4fae8ae99fca716d423b14875ebb10fefe quux.use2.cache.amazonaws.com:6379@1122
           end: 8125,
           server: Arc::new("wibble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("5671f02def98d0279224f717aba0f95874e5fb89".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 12427,
           end: 12427,
           server: Arc::new("wibble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("5671f02def98d0279224f717aba0f95874e5fb89".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 13198,
           end: 13760,
           server: Arc::new("wibble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("5671f02def98d0279224f717aba0f95874e5fb89".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 15126,
           end: 16383,
           server: Arc::new("wibble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("5671f02def98d0279224f717aba0f95874e5fb89".into()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -2100,30 +2393,35 @@ b8553a4fae8ae99fca716d423b14875ebb10fefe quux.use2.cache.amazonaws.com:6379@1122
           end: 5860,
           server: Arc::new("wobble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("0b1923e386f6f6f3adc1b6deb250ef08f937e9b5".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 6771,
           end: 7382,
           server: Arc::new("wobble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("0b1923e386f6f6f3adc1b6deb250ef08f937e9b5".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 8133,
           end: 8151,
           server: Arc::new("wobble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("0b1923e386f6f6f3adc1b6deb250ef08f937e9b5".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 10113,
           end: 10922,
           server: Arc::new("wobble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("0b1923e386f6f6f3adc1b6deb250ef08f937e9b5".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 12686,
           end: 12893,
           server: Arc::new("wobble.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("0b1923e386f6f6f3adc1b6deb250ef08f937e9b5".into()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -2135,30 +2433,35 @@ b8553a4fae8ae99fca716d423b14875ebb10fefe quux.use2.cache.amazonaws.com:6379@1122
           end: 3656,
           server: Arc::new("qux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("1c5d99e3d6fca2090d0903d61d4e51594f6dcc05".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 7383,
           end: 7530,
           server: Arc::new("qux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("1c5d99e3d6fca2090d0903d61d4e51594f6dcc05".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 8896,
           end: 9202,
           server: Arc::new("qux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("1c5d99e3d6fca2090d0903d61d4e51594f6dcc05".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 12347,
           end: 12426,
           server: Arc::new("qux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("1c5d99e3d6fca2090d0903d61d4e51594f6dcc05".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 12428,
           end: 12575,
           server: Arc::new("qux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("1c5d99e3d6fca2090d0903d61d4e51594f6dcc05".into()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -2170,24 +2473,28 @@ b8553a4fae8ae99fca716d423b14875ebb10fefe quux.use2.cache.amazonaws.com:6379@1122
           end: 8246,
           server: Arc::new("quux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("b8553a4fae8ae99fca716d423b14875ebb10fefe".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 8440,
           end: 8895,
           server: Arc::new("quux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("b8553a4fae8ae99fca716d423b14875ebb10fefe".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 12919,
           end: 13144,
           server: Arc::new("quux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("b8553a4fae8ae99fca716d423b14875ebb10fefe".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 13761,
           end: 15125,
           server: Arc::new("quux.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("b8553a4fae8ae99fca716d423b14875ebb10fefe".into()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -2199,42 +2506,49 @@ b8553a4fae8ae99fca716d423b14875ebb10fefe quux.use2.cache.amazonaws.com:6379@1122
           end: 331,
           server: Arc::new("quuz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("4a58ba550f37208c9a9909986ce808cdb058e31f".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 1698,
           end: 1814,
           server: Arc::new("quuz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("4a58ba550f37208c9a9909986ce808cdb058e31f".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 4090,
           end: 5461,
           server: Arc::new("quuz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("4a58ba550f37208c9a9909986ce808cdb058e31f".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 7714,
           end: 7899,
           server: Arc::new("quuz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("4a58ba550f37208c9a9909986ce808cdb058e31f".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 8126,
           end: 8132,
           server: Arc::new("quuz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("4a58ba550f37208c9a9909986ce808cdb058e31f".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 12894,
           end: 12918,
           server: Arc::new("quuz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("4a58ba550f37208c9a9909986ce808cdb058e31f".into()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 13145,
           end: 13153,
           server: Arc::new("quuz.use2.cache.amazonaws.com:6379".into()),
           id: Arc::new("4a58ba550f37208c9a9909986ce808cdb058e31f".into()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -2273,6 +2587,7 @@ e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 127.0.0.1:30001 myself,master - 0 0 1 c
         end: 10922,
         server: Arc::new("127.0.0.1:30002".to_owned()),
         id: Arc::new("67ed2db8d677e59ec4a4cefb06858cf2a1a89fa1".to_owned()),
+        replicas: vec![Arc::new("127.0.0.1:30005".to_owned())],
       }],
     );
     expected.insert(
@@ -2282,6 +2597,7 @@ e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 127.0.0.1:30001 myself,master - 0 0 1 c
         end: 16383,
         server: Arc::new("127.0.0.1:30003".to_owned()),
         id: Arc::new("292f8b365bb7edb5e285caf0b7e6ddc7265d2f4f".to_owned()),
+        replicas: vec![Arc::new("127.0.0.1:30006".to_owned())],
       }],
     );
     expected.insert(
@@ -2291,6 +2607,7 @@ e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 127.0.0.1:30001 myself,master - 0 0 1 c
         end: 5460,
         server: Arc::new("127.0.0.1:30001".to_owned()),
         id: Arc::new("e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca".to_owned()),
+        replicas: vec![Arc::new("127.0.0.1:30004".to_owned())],
       }],
     );
 
@@ -2316,6 +2633,7 @@ b4fa5337b58e02673f961e22c9557e81dda4b559 bar.cache.amazonaws.com:6379@1122 mysel
         end: 10922,
         server: Arc::new("foo.cache.amazonaws.com:6379".to_owned()),
         id: Arc::new("eec2b077ee95c590279115aac13e7eefdce61dba".to_owned()),
+        replicas: Vec::new(),
       }],
     );
     expected.insert(
@@ -2325,6 +2643,7 @@ b4fa5337b58e02673f961e22c9557e81dda4b559 bar.cache.amazonaws.com:6379@1122 mysel
         end: 5461,
         server: Arc::new("bar.cache.amazonaws.com:6379".to_owned()),
         id: Arc::new("b4fa5337b58e02673f961e22c9557e81dda4b559".to_owned()),
+        replicas: Vec::new(),
       }],
     );
     expected.insert(
@@ -2334,6 +2653,7 @@ b4fa5337b58e02673f961e22c9557e81dda4b559 bar.cache.amazonaws.com:6379@1122 mysel
         end: 16383,
         server: Arc::new("baz.cache.amazonaws.com:6379".to_owned()),
         id: Arc::new("29d37b842d1bb097ba491be8f1cb00648620d4bd".to_owned()),
+        replicas: Vec::new(),
       }],
     );
 
@@ -2358,6 +2678,7 @@ b4fa5337b58e02673f961e22c9557e81dda4b559 bar.cache.amazonaws.com:6379@1122 mysel
         end: 10921,
         server: Arc::new("foo.cache.amazonaws.com:6379".to_owned()),
         id: Arc::new("eec2b077ee95c590279115aac13e7eefdce61dba".to_owned()),
+        replicas: Vec::new(),
       }],
     );
     expected.insert(
@@ -2368,12 +2689,14 @@ b4fa5337b58e02673f961e22c9557e81dda4b559 bar.cache.amazonaws.com:6379@1122 mysel
           end: 5461,
           server: Arc::new("bar.cache.amazonaws.com:6379".to_owned()),
           id: Arc::new("b4fa5337b58e02673f961e22c9557e81dda4b559".to_owned()),
+          replicas: Vec::new(),
         },
         SlotRange {
           start: 10922,
           end: 10922,
           server: Arc::new("bar.cache.amazonaws.com:6379".to_owned()),
           id: Arc::new("b4fa5337b58e02673f961e22c9557e81dda4b559".to_owned()),
+          replicas: Vec::new(),
         },
       ],
     );
@@ -2384,6 +2707,7 @@ b4fa5337b58e02673f961e22c9557e81dda4b559 bar.cache.amazonaws.com:6379@1122 mysel
         end: 16383,
         server: Arc::new("baz.cache.amazonaws.com:6379".to_owned()),
         id: Arc::new("29d37b842d1bb097ba491be8f1cb00648620d4bd".to_owned()),
+        replicas: Vec::new(),
       }],
     );
 
@@ -2393,4 +2717,159 @@ b4fa5337b58e02673f961e22c9557e81dda4b559 bar.cache.amazonaws.com:6379@1122 mysel
     };
     assert_eq!(actual, expected);
   }
+
+  #[test]
+  fn should_parse_function_list() {
+    /*
+    127.0.0.1:6379> function list withcode
+    1) 1) "library_name"
+       2) "mylib"
+       3) "engine"
+       4) "LUA"
+       5) "functions"
+       6) 1) 1) "name"
+             2) "myfunc"
+             3) "description"
+             4) (nil)
+             5) "flags"
+             6) 1) "no-writes"
+       7) "library_code"
+       8) "#!lua name=mylib\n..."
+    */
+
+    let code = "#!lua name=mylib\nredis.register_function{function_name='myfunc', callback=function() return 1 \
+                end, flags={'no-writes'}}";
+    let input = vec![Resp3Frame::Array {
+      data: vec![
+        str_to_bs("library_name"),
+        str_to_bs("mylib"),
+        str_to_bs("engine"),
+        str_to_bs("LUA"),
+        str_to_bs("functions"),
+        Resp3Frame::Array {
+          data: vec![Resp3Frame::Array {
+            data: vec![
+              str_to_bs("name"),
+              str_to_bs("myfunc"),
+              str_to_bs("description"),
+              Resp3Frame::Null,
+              str_to_bs("flags"),
+              Resp3Frame::Array {
+                data: vec![str_to_bs("no-writes")],
+                attributes: None,
+              },
+            ],
+            attributes: None,
+          }],
+          attributes: None,
+        },
+        str_to_bs("library_code"),
+        str_to_bs(code),
+      ],
+      attributes: None,
+    }];
+    let actual = parse_function_libraries(input).unwrap();
+
+    let expected = vec![FunctionLibrary {
+      name: "mylib".into(),
+      engine: "LUA".into(),
+      functions: vec![Function {
+        name: "myfunc".into(),
+        description: None,
+        flags: string_vec(vec!["no-writes"]),
+      }],
+      code: Some(code.to_owned()),
+    }];
+    assert_eq!(actual, expected);
+    assert!(actual[0].functions[0].is_read_only());
+  }
+
+  #[test]
+  fn should_parse_function_stats() {
+    /*
+    127.0.0.1:6379> function stats
+    1) "running_script"
+    2) 1) "name"
+       2) "myfunc"
+       3) "command"
+       4) 1) "fcall"
+          2) "myfunc"
+          3) "0"
+       5) "duration_ms"
+       6) (integer) 1502
+    3) "engines"
+    4) 1) "LUA"
+       2) 1) "libraries_count"
+          2) (integer) 1
+          3) "functions_count"
+          4) (integer) 2
+    */
+
+    let input = vec![
+      str_to_bs("running_script"),
+      Resp3Frame::Array {
+        data: vec![
+          str_to_bs("name"),
+          str_to_bs("myfunc"),
+          str_to_bs("command"),
+          Resp3Frame::Array {
+            data: vec![str_to_bs("fcall"), str_to_bs("myfunc"), str_to_bs("0")],
+            attributes: None,
+          },
+          str_to_bs("duration_ms"),
+          int_to_f(1502),
+        ],
+        attributes: None,
+      },
+      str_to_bs("engines"),
+      Resp3Frame::Array {
+        data: vec![
+          str_to_bs("LUA"),
+          Resp3Frame::Array {
+            data: vec![
+              str_to_bs("libraries_count"),
+              int_to_f(1),
+              str_to_bs("functions_count"),
+              int_to_f(2),
+            ],
+            attributes: None,
+          },
+        ],
+        attributes: None,
+      },
+    ];
+    let actual = parse_function_stats(&input).unwrap();
+
+    let mut engines = HashMap::new();
+    engines.insert("LUA".to_owned(), FunctionEngineStats {
+      libraries_count: 1,
+      functions_count: 2,
+    });
+    let expected = FunctionStats {
+      running_script: Some(RunningFunction {
+        name: "myfunc".into(),
+        command: string_vec(vec!["fcall", "myfunc", "0"]),
+        duration_ms: 1502,
+      }),
+      engines,
+    };
+    assert_eq!(actual, expected);
+  }
+
+  #[test]
+  fn should_parse_sharded_pubsub_message() {
+    let frame = Resp3Frame::Push {
+      data: vec![str_to_bs("smessage"), str_to_bs("foo"), str_to_bs("bar")],
+      attributes: None,
+    };
+    let (channel, message) = frame_to_sharded_pubsub(frame).unwrap();
+    assert_eq!(channel, "foo");
+    assert_eq!(message, RedisValue::String("bar".into()));
+
+    let frame = Resp3Frame::Array {
+      data: vec![str_to_bs("smessage"), str_to_bs("foo")],
+      attributes: None,
+    };
+    assert!(frame_to_sharded_pubsub(frame).is_err());
+  }
 }
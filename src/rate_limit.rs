@@ -0,0 +1,439 @@
+use crate::error::{RedisError, RedisErrorKind};
+use crate::interfaces::{KeysInterface, LuaInterface};
+use crate::types::{RedisKey, RedisValue};
+use crate::utils;
+use bytes::Bytes;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Count requests in fixed windows that start with the first request in each window.
+///
+/// Arguments: limit, window, cost
+static FIXED_WINDOW_SCRIPT: &'static str = r#"
+local limit = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local cost = tonumber(ARGV[3])
+local current = tonumber(redis.call('get', KEYS[1]) or '0')
+local ttl = redis.call('pttl', KEYS[1])
+if ttl < 0 then
+  ttl = window
+end
+
+if current + cost > limit then
+  return {0, math.max(0, limit - current), ttl, ttl}
+end
+
+current = redis.call('incrby', KEYS[1], cost)
+if redis.call('pttl', KEYS[1]) < 0 then
+  redis.call('pexpire', KEYS[1], window)
+end
+return {1, math.max(0, limit - current), 0, ttl}
+"#;
+
+/// Store a timestamp for each request in a sorted set and count the requests within the window.
+///
+/// Arguments: limit, window, cost, unique request ID
+static SLIDING_LOG_SCRIPT: &'static str = lua_with_now_ms!(r#"
+local limit = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local cost = tonumber(ARGV[3])
+
+redis.call('zremrangebyscore', KEYS[1], '-inf', now - window)
+local count = redis.call('zcard', KEYS[1])
+
+if count + cost > limit then
+  local retry = -1
+  if cost <= limit then
+    local idx = count + cost - limit - 1
+    local oldest = redis.call('zrange', KEYS[1], idx, idx, 'WITHSCORES')
+    retry = math.max(1, tonumber(oldest[2]) + window - now)
+  end
+  local newest = redis.call('zrange', KEYS[1], -1, -1, 'WITHSCORES')
+  local reset = math.max(0, tonumber(newest[2] or now) + window - now)
+  return {0, math.max(0, limit - count), retry, reset}
+end
+
+for i = 1, cost do
+  redis.call('zadd', KEYS[1], now, ARGV[4] .. ':' .. i)
+end
+redis.call('pexpire', KEYS[1], window)
+return {1, limit - count - cost, 0, window}
+"#);
+
+/// Store the number of tokens and the last refill time in a hash.
+///
+/// Arguments: capacity, refill amount, refill period, cost
+static TOKEN_BUCKET_SCRIPT: &'static str = lua_with_now_ms!(r#"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2]) / tonumber(ARGV[3])
+local cost = tonumber(ARGV[4])
+
+local bucket = redis.call('hmget', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(bucket[1]) or capacity
+local last = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - last) * rate)
+
+local allowed = 0
+local retry = -1
+if tokens >= cost then
+  tokens = tokens - cost
+  allowed = 1
+  retry = 0
+elseif cost <= capacity then
+  retry = math.ceil((cost - tokens) / rate)
+end
+
+local reset = math.ceil((capacity - tokens) / rate)
+redis.call('hmset', KEYS[1], 'tokens', tostring(tokens), 'ts', tostring(now))
+redis.call('pexpire', KEYS[1], math.max(1, reset))
+return {allowed, math.floor(tokens), retry, reset}
+"#);
+
+/// Store the theoretical arrival time of the next request.
+///
+/// Arguments: burst, rate, period, cost
+static GCRA_SCRIPT: &'static str = lua_with_now_ms!(r#"
+local burst = tonumber(ARGV[1])
+local emission = tonumber(ARGV[3]) / tonumber(ARGV[2])
+local cost = tonumber(ARGV[4])
+local increment = emission * cost
+local tolerance = emission * burst
+
+local tat = math.max(tonumber(redis.call('get', KEYS[1]) or now), now)
+local new_tat = tat + increment
+local diff = now - (new_tat - tolerance)
+
+if diff < 0 then
+  local retry = -1
+  if increment <= tolerance then
+    retry = math.ceil(-diff)
+  end
+  local remaining = math.max(0, math.floor((now - (tat - tolerance)) / emission))
+  return {0, remaining, retry, math.ceil(tat - now)}
+end
+
+local reset = math.ceil(new_tat - now)
+redis.call('set', KEYS[1], tostring(new_tat), 'PX', math.max(1, reset))
+return {1, math.floor(diff / emission), 0, reset}
+"#);
+
+/// The algorithm and parameters used by a [RateLimiter](crate::rate_limit::RateLimiter).
+///
+/// All durations are in milliseconds. Each algorithm uses the server's clock so that limits are consistent across
+/// callers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RateLimit {
+  /// Allow `limit` requests in each window of `window_ms`, where the window starts with the first request.
+  ///
+  /// This uses a single counter, but allows up to twice the limit across the boundary of two windows.
+  FixedWindow { limit: u64, window_ms: u64 },
+  /// Allow `limit` requests in any window of `window_ms`.
+  ///
+  /// This stores each request in a sorted set so it is precise, but uses memory proportional to the limit.
+  SlidingLog { limit: u64, window_ms: u64 },
+  /// Allow bursts of up to `capacity` requests, refilling `refill` tokens every `period_ms`.
+  TokenBucket { capacity: u64, refill: u64, period_ms: u64 },
+  /// Allow `rate` requests every `period_ms` with bursts of up to `burst` requests, using the
+  /// [generic cell rate algorithm](https://en.wikipedia.org/wiki/Generic_cell_rate_algorithm).
+  ///
+  /// This has the same behavior as a token bucket but stores a single timestamp.
+  Gcra { burst: u64, rate: u64, period_ms: u64 },
+}
+
+impl RateLimit {
+  fn script(&self) -> &'static str {
+    match *self {
+      RateLimit::FixedWindow { .. } => FIXED_WINDOW_SCRIPT,
+      RateLimit::SlidingLog { .. } => SLIDING_LOG_SCRIPT,
+      RateLimit::TokenBucket { .. } => TOKEN_BUCKET_SCRIPT,
+      RateLimit::Gcra { .. } => GCRA_SCRIPT,
+    }
+  }
+
+  fn args(&self, cost: u64) -> Vec<RedisValue> {
+    let values = match *self {
+      RateLimit::FixedWindow { limit, window_ms } => vec![limit, window_ms, cost],
+      RateLimit::SlidingLog { limit, window_ms } => vec![limit, window_ms, cost],
+      RateLimit::TokenBucket {
+        capacity,
+        refill,
+        period_ms,
+      } => vec![capacity, refill, period_ms, cost],
+      RateLimit::Gcra { burst, rate, period_ms } => vec![burst, rate, period_ms, cost],
+    };
+
+    let mut args: Vec<RedisValue> = values.into_iter().map(|v| RedisValue::Integer(v as i64)).collect();
+    if let RateLimit::SlidingLog { .. } = *self {
+      args.push(utils::random_string(16).into());
+    }
+    args
+  }
+
+  fn validate(&self) -> Result<(), RedisError> {
+    let valid = match *self {
+      RateLimit::FixedWindow { limit, window_ms } => limit > 0 && window_ms > 0,
+      RateLimit::SlidingLog { limit, window_ms } => limit > 0 && window_ms > 0,
+      RateLimit::TokenBucket {
+        capacity,
+        refill,
+        period_ms,
+      } => capacity > 0 && refill > 0 && period_ms > 0,
+      RateLimit::Gcra { burst, rate, period_ms } => burst > 0 && rate > 0 && period_ms > 0,
+    };
+
+    if valid {
+      Ok(())
+    } else {
+      Err(RedisError::new(
+        RedisErrorKind::Config,
+        "Rate limit parameters must be greater than zero.",
+      ))
+    }
+  }
+}
+
+/// The result of a rate limit check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateLimitResult {
+  /// Whether or not the request is allowed.
+  pub allowed: bool,
+  /// The number of requests that can be made immediately after this one.
+  pub remaining: u64,
+  /// The amount of time to wait before retrying a request that was not allowed.
+  ///
+  /// This is `None` if the request was allowed or if the request can never be allowed because its cost is larger than
+  /// the limit.
+  pub retry_after: Option<Duration>,
+  /// The amount of time until the limiter is fully reset if no more requests are made.
+  pub reset_after: Duration,
+}
+
+impl RateLimitResult {
+  fn from_values(values: Vec<i64>) -> Result<Self, RedisError> {
+    if values.len() != 4 {
+      return Err(RedisError::new_parse("Invalid rate limit response."));
+    }
+
+    let retry_after = if values[0] == 0 && values[2] >= 0 {
+      Some(Duration::from_millis(values[2] as u64))
+    } else {
+      None
+    };
+
+    Ok(RateLimitResult {
+      allowed: values[0] == 1,
+      remaining: values[1].max(0) as u64,
+      retry_after,
+      reset_after: Duration::from_millis(values[3].max(0) as u64),
+    })
+  }
+}
+
+/// A rate limiter backed by a Lua script.
+///
+/// Each identifier is stored in a key with the format `<prefix>:{<id>}`. The hash tag ensures that limiters with
+/// different prefixes for the same identifier use the same cluster hash slot.
+///
+/// The script is loaded with `SCRIPT LOAD` (on every primary node in a cluster) before the first request and then
+/// called with `EVALSHA`. If the server returns a `NOSCRIPT` error, such as after a `SCRIPT FLUSH` or a failover,
+/// the script is loaded again and the request is retried once. Callers can use [load_script](Self::load_script) to
+/// load the script ahead of time.
+///
+/// ```rust no_run
+/// # use fred::prelude::*;
+/// # use fred::rate_limit::{RateLimit, RateLimiter};
+/// # async fn example(client: RedisClient) -> Result<(), RedisError> {
+/// // allow 100 requests per minute with bursts of up to 10 requests
+/// let limit = RateLimit::Gcra { burst: 10, rate: 100, period_ms: 60_000 };
+/// let limiter = RateLimiter::new(client, "api", limit)?;
+///
+/// let result = limiter.check("user:1").await?;
+/// if !result.allowed {
+///   println!("Retry after {:?}", result.retry_after);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RateLimiter<C>
+where
+  C: KeysInterface + LuaInterface + Clone,
+{
+  client: C,
+  prefix: Arc<String>,
+  limit: RateLimit,
+  hash: Arc<String>,
+  loaded: Arc<AtomicBool>,
+}
+
+impl<C> fmt::Debug for RateLimiter<C>
+where
+  C: KeysInterface + LuaInterface + Clone,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("RateLimiter")
+      .field("prefix", &self.prefix)
+      .field("limit", &self.limit)
+      .finish()
+  }
+}
+
+impl<C> RateLimiter<C>
+where
+  C: KeysInterface + LuaInterface + Clone,
+{
+  /// Create a new rate limiter that stores keys with the provided prefix.
+  pub fn new<S>(client: C, prefix: S, limit: RateLimit) -> Result<Self, RedisError>
+  where
+    S: Into<String>,
+  {
+    let _ = limit.validate()?;
+    let hash = crate::util::sha1_hash(limit.script());
+
+    Ok(RateLimiter {
+      client,
+      prefix: Arc::new(prefix.into()),
+      limit,
+      hash: Arc::new(hash),
+      loaded: Arc::new(AtomicBool::new(false)),
+    })
+  }
+
+  /// Read the rate limit parameters.
+  pub fn limit(&self) -> &RateLimit {
+    &self.limit
+  }
+
+  /// Read the key used to store the state for the provided identifier.
+  pub fn key<K>(&self, id: K) -> RedisKey
+  where
+    K: Into<RedisKey>,
+  {
+    let id = id.into();
+    let mut key = Vec::with_capacity(self.prefix.len() + id.as_bytes().len() + 3);
+    key.extend_from_slice(self.prefix.as_bytes());
+    key.extend_from_slice(b":{");
+    key.extend_from_slice(id.as_bytes());
+    key.push(b'}');
+
+    Bytes::from(key).into()
+  }
+
+  /// Load the script on the server, or on every primary node in a cluster.
+  ///
+  /// This is not required since the script is loaded automatically before the first request, but can be used to
+  /// avoid the extra round trip on the first request.
+  pub async fn load_script(&self) -> Result<(), RedisError> {
+    let hash = if utils::is_clustered(&self.client.inner().config) {
+      self.client.script_load_cluster(self.limit.script()).await?
+    } else {
+      self.client.script_load(self.limit.script()).await?
+    };
+
+    if hash != *self.hash {
+      warn!("Unexpected rate limit script hash: {} != {}", hash, self.hash);
+    }
+    self.loaded.store(true, Ordering::SeqCst);
+    Ok(())
+  }
+
+  /// Call the script with `EVALSHA`, loading it first if needed and again if the server returns `NOSCRIPT`.
+  async fn evalsha(&self, key: RedisKey, args: Vec<RedisValue>) -> Result<Vec<i64>, RedisError> {
+    if !self.loaded.load(Ordering::SeqCst) {
+      self.load_script().await?;
+    }
+
+    match self.client.evalsha(self.hash.as_str(), key.clone(), args.clone()).await {
+      Err(e) if e.details().starts_with("NOSCRIPT") => {
+        self.loaded.store(false, Ordering::SeqCst);
+        self.load_script().await?;
+        self.client.evalsha(self.hash.as_str(), key, args).await
+      }
+      result => result,
+    }
+  }
+
+  /// Check whether a request for the identifier is allowed, counting it against the limit if so.
+  pub async fn check<K>(&self, id: K) -> Result<RateLimitResult, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    self.check_n(id, 1).await
+  }
+
+  /// Check whether a request with the provided cost is allowed, counting it against the limit if so.
+  pub async fn check_n<K>(&self, id: K, cost: u64) -> Result<RateLimitResult, RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    let key = self.key(id);
+    let args = self.limit.args(cost);
+
+    let values = self.evalsha(key, args).await?;
+    RateLimitResult::from_values(values)
+  }
+
+  /// Reset the limiter for the provided identifier.
+  pub async fn reset<K>(&self, id: K) -> Result<(), RedisError>
+  where
+    K: Into<RedisKey>,
+  {
+    self.client.del(self.key(id)).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_rate_limit_results() {
+    let allowed = RateLimitResult::from_values(vec![1, 9, 0, 1000]).unwrap();
+    assert_eq!(allowed, RateLimitResult {
+      allowed: true,
+      remaining: 9,
+      retry_after: None,
+      reset_after: Duration::from_millis(1000),
+    });
+
+    let denied = RateLimitResult::from_values(vec![0, 0, 250, 1000]).unwrap();
+    assert!(!denied.allowed);
+    assert_eq!(denied.retry_after, Some(Duration::from_millis(250)));
+
+    let never = RateLimitResult::from_values(vec![0, 0, -1, 1000]).unwrap();
+    assert_eq!(never.retry_after, None);
+    assert!(RateLimitResult::from_values(vec![1, 2]).is_err());
+  }
+
+  #[test]
+  fn should_validate_rate_limits() {
+    assert!(RateLimit::FixedWindow {
+      limit: 0,
+      window_ms: 1000
+    }
+    .validate()
+    .is_err());
+    assert!(RateLimit::Gcra {
+      burst: 1,
+      rate: 10,
+      period_ms: 1000
+    }
+    .validate()
+    .is_ok());
+  }
+
+  #[test]
+  fn should_add_request_id_to_sliding_log_args() {
+    let limit = RateLimit::SlidingLog {
+      limit: 10,
+      window_ms: 1000,
+    };
+    let args = limit.args(2);
+
+    assert_eq!(args.len(), 4);
+    assert_eq!(args[2], RedisValue::Integer(2));
+    assert!(args[3].is_string());
+  }
+}
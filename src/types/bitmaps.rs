@@ -0,0 +1,308 @@
+use crate::types::RedisValue;
+use crate::utils;
+use bytes_utils::Str;
+
+/// The unit used by the range arguments of the `BITCOUNT` and `BITPOS` commands.
+///
+/// <https://redis.io/commands/bitcount>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BitUnit {
+  Byte,
+  Bit,
+}
+
+impl BitUnit {
+  pub(crate) fn to_str(&self) -> Str {
+    utils::static_str(match *self {
+      BitUnit::Byte => "BYTE",
+      BitUnit::Bit => "BIT",
+    })
+  }
+}
+
+/// The bitwise operation to perform with the `BITOP` command.
+///
+/// <https://redis.io/commands/bitop>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BitOperation {
+  And,
+  Or,
+  Xor,
+  Not,
+}
+
+impl BitOperation {
+  pub(crate) fn to_str(&self) -> Str {
+    utils::static_str(match *self {
+      BitOperation::And => "AND",
+      BitOperation::Or => "OR",
+      BitOperation::Xor => "XOR",
+      BitOperation::Not => "NOT",
+    })
+  }
+}
+
+/// The integer encoding used by a `BITFIELD` operation.
+///
+/// Signed integers can use up to 64 bits and unsigned integers can use up to 63 bits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BitfieldEncoding {
+  /// A signed integer with the provided number of bits, such as `i5`.
+  Signed(u8),
+  /// An unsigned integer with the provided number of bits, such as `u8`.
+  Unsigned(u8),
+}
+
+impl BitfieldEncoding {
+  pub(crate) fn to_str(&self) -> Str {
+    match *self {
+      BitfieldEncoding::Signed(bits) => format!("i{}", bits).into(),
+      BitfieldEncoding::Unsigned(bits) => format!("u{}", bits).into(),
+    }
+  }
+}
+
+/// The offset of the integer modified or read by a `BITFIELD` operation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BitfieldOffset {
+  /// An offset in bits.
+  Bits(u32),
+  /// An offset that is multiplied by the width of the encoding, such as `#2`.
+  Width(u32),
+}
+
+impl BitfieldOffset {
+  pub(crate) fn to_value(&self) -> RedisValue {
+    match *self {
+      BitfieldOffset::Bits(offset) => offset.into(),
+      BitfieldOffset::Width(offset) => format!("#{}", offset).into(),
+    }
+  }
+}
+
+impl From<u32> for BitfieldOffset {
+  fn from(offset: u32) -> Self {
+    BitfieldOffset::Bits(offset)
+  }
+}
+
+/// The behavior of `BITFIELD` `SET` and `INCRBY` operations when an integer overflows.
+///
+/// <https://redis.io/commands/bitfield>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BitfieldOverflow {
+  Wrap,
+  Sat,
+  Fail,
+}
+
+impl BitfieldOverflow {
+  pub(crate) fn to_str(&self) -> Str {
+    utils::static_str(match *self {
+      BitfieldOverflow::Wrap => "WRAP",
+      BitfieldOverflow::Sat => "SAT",
+      BitfieldOverflow::Fail => "FAIL",
+    })
+  }
+}
+
+/// A single operation inside a `BITFIELD` command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BitfieldSubcommand {
+  Get {
+    encoding: BitfieldEncoding,
+    offset: BitfieldOffset,
+  },
+  Set {
+    encoding: BitfieldEncoding,
+    offset: BitfieldOffset,
+    value: i64,
+  },
+  IncrBy {
+    encoding: BitfieldEncoding,
+    offset: BitfieldOffset,
+    increment: i64,
+  },
+  Overflow(BitfieldOverflow),
+}
+
+impl BitfieldSubcommand {
+  /// Whether or not the operation can be used with `BITFIELD_RO`.
+  pub fn is_read_only(&self) -> bool {
+    match *self {
+      BitfieldSubcommand::Get { .. } => true,
+      _ => false,
+    }
+  }
+}
+
+/// A builder for the operations sent with the `BITFIELD` and `BITFIELD_RO` commands.
+///
+/// Operations are sent in the order they are added, and `OVERFLOW` only applies to the `SET` and `INCRBY` operations
+/// that follow it.
+///
+/// ```rust
+/// # use fred::types::{BitfieldEncoding, BitfieldOffset, BitfieldOp, BitfieldOverflow};
+/// let ops = BitfieldOp::new()
+///   .get(BitfieldEncoding::Signed(5), 100)
+///   .overflow(BitfieldOverflow::Sat)
+///   .incrby(BitfieldEncoding::Unsigned(8), BitfieldOffset::Width(2), 10);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BitfieldOp {
+  inner: Vec<BitfieldSubcommand>,
+}
+
+impl BitfieldOp {
+  /// Create a new empty set of operations.
+  pub fn new() -> Self {
+    BitfieldOp { inner: Vec::new() }
+  }
+
+  /// Read the integer at `offset`.
+  pub fn get<O>(mut self, encoding: BitfieldEncoding, offset: O) -> Self
+  where
+    O: Into<BitfieldOffset>,
+  {
+    self.inner.push(BitfieldSubcommand::Get {
+      encoding,
+      offset: offset.into(),
+    });
+    self
+  }
+
+  /// Set the integer at `offset`, returning the old value.
+  pub fn set<O>(mut self, encoding: BitfieldEncoding, offset: O, value: i64) -> Self
+  where
+    O: Into<BitfieldOffset>,
+  {
+    self.inner.push(BitfieldSubcommand::Set {
+      encoding,
+      offset: offset.into(),
+      value,
+    });
+    self
+  }
+
+  /// Increment the integer at `offset`, returning the new value.
+  pub fn incrby<O>(mut self, encoding: BitfieldEncoding, offset: O, increment: i64) -> Self
+  where
+    O: Into<BitfieldOffset>,
+  {
+    self.inner.push(BitfieldSubcommand::IncrBy {
+      encoding,
+      offset: offset.into(),
+      increment,
+    });
+    self
+  }
+
+  /// Change the overflow behavior of the following `SET` and `INCRBY` operations.
+  pub fn overflow(mut self, overflow: BitfieldOverflow) -> Self {
+    self.inner.push(BitfieldSubcommand::Overflow(overflow));
+    self
+  }
+
+  /// Read the number of operations.
+  pub fn len(&self) -> usize {
+    self.inner.len()
+  }
+
+  /// Whether or not any operations have been added.
+  pub fn is_empty(&self) -> bool {
+    self.inner.is_empty()
+  }
+
+  /// Whether or not every operation can be used with `BITFIELD_RO`.
+  pub fn is_read_only(&self) -> bool {
+    self.inner.iter().all(|op| op.is_read_only())
+  }
+
+  pub fn inner(self) -> Vec<BitfieldSubcommand> {
+    self.inner
+  }
+
+  pub(crate) fn into_args(self) -> Vec<RedisValue> {
+    let mut args = Vec::with_capacity(self.inner.len() * 4);
+
+    for op in self.inner.into_iter() {
+      match op {
+        BitfieldSubcommand::Get { encoding, offset } => {
+          args.push(static_val!("GET"));
+          args.push(encoding.to_str().into());
+          args.push(offset.to_value());
+        }
+        BitfieldSubcommand::Set {
+          encoding,
+          offset,
+          value,
+        } => {
+          args.push(static_val!("SET"));
+          args.push(encoding.to_str().into());
+          args.push(offset.to_value());
+          args.push(value.into());
+        }
+        BitfieldSubcommand::IncrBy {
+          encoding,
+          offset,
+          increment,
+        } => {
+          args.push(static_val!("INCRBY"));
+          args.push(encoding.to_str().into());
+          args.push(offset.to_value());
+          args.push(increment.into());
+        }
+        BitfieldSubcommand::Overflow(overflow) => {
+          args.push(static_val!("OVERFLOW"));
+          args.push(overflow.to_str().into());
+        }
+      }
+    }
+
+    args
+  }
+}
+
+impl From<BitfieldSubcommand> for BitfieldOp {
+  fn from(op: BitfieldSubcommand) -> Self {
+    BitfieldOp { inner: vec![op] }
+  }
+}
+
+impl From<Vec<BitfieldSubcommand>> for BitfieldOp {
+  fn from(ops: Vec<BitfieldSubcommand>) -> Self {
+    BitfieldOp { inner: ops }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_build_bitfield_args() {
+    let ops = BitfieldOp::new()
+      .get(BitfieldEncoding::Signed(5), 100)
+      .overflow(BitfieldOverflow::Fail)
+      .set(BitfieldEncoding::Unsigned(8), BitfieldOffset::Width(2), 255)
+      .incrby(BitfieldEncoding::Signed(64), 0, -1);
+    assert!(!ops.is_read_only());
+
+    let expected: Vec<RedisValue> = vec![
+      "GET".into(),
+      "i5".into(),
+      100.into(),
+      "OVERFLOW".into(),
+      "FAIL".into(),
+      "SET".into(),
+      "u8".into(),
+      "#2".into(),
+      255.into(),
+      "INCRBY".into(),
+      "i64".into(),
+      0.into(),
+      (-1).into(),
+    ];
+    assert_eq!(ops.into_args(), expected);
+  }
+}
@@ -0,0 +1,126 @@
+use crate::error::{RedisError, RedisErrorKind};
+use crate::utils;
+use bytes_utils::Str;
+use std::collections::HashMap;
+
+/// A function inside a library loaded with `FUNCTION LOAD`.
+///
+/// <https://redis.io/commands/function-list>
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Function {
+  pub name: String,
+  pub description: Option<String>,
+  /// Flags such as `no-writes` or `allow-stale` that were provided when the function was registered.
+  pub flags: Vec<String>,
+}
+
+impl Function {
+  /// Whether or not the function was registered with the `no-writes` flag, and can therefore be called with
+  /// `FCALL_RO`.
+  pub fn is_read_only(&self) -> bool {
+    self.flags.iter().any(|flag| flag == "no-writes")
+  }
+}
+
+/// A library returned by the `FUNCTION LIST` command.
+///
+/// <https://redis.io/commands/function-list>
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FunctionLibrary {
+  pub name: String,
+  pub engine: String,
+  pub functions: Vec<Function>,
+  /// The source code of the library, if `WITHCODE` was provided.
+  pub code: Option<String>,
+}
+
+/// A function that is currently running, as reported by the `FUNCTION STATS` command.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RunningFunction {
+  pub name: String,
+  pub command: Vec<String>,
+  pub duration_ms: u64,
+}
+
+/// Per-engine counters reported by the `FUNCTION STATS` command.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FunctionEngineStats {
+  pub libraries_count: u64,
+  pub functions_count: u64,
+}
+
+/// The response to the `FUNCTION STATS` command.
+///
+/// <https://redis.io/commands/function-stats>
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FunctionStats {
+  pub running_script: Option<RunningFunction>,
+  pub engines: HashMap<String, FunctionEngineStats>,
+}
+
+/// The policy used when restoring libraries with `FUNCTION RESTORE`.
+///
+/// <https://redis.io/commands/function-restore>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FunctionRestorePolicy {
+  /// Append the restored libraries to the existing libraries, failing on collisions. This is the server default.
+  Append,
+  /// Append the restored libraries to the existing libraries, replacing any libraries with the same name.
+  Replace,
+  /// Delete all existing libraries before restoring the payload.
+  Flush,
+}
+
+impl FunctionRestorePolicy {
+  pub(crate) fn to_str(&self) -> Str {
+    utils::static_str(match *self {
+      FunctionRestorePolicy::Append => "APPEND",
+      FunctionRestorePolicy::Replace => "REPLACE",
+      FunctionRestorePolicy::Flush => "FLUSH",
+    })
+  }
+}
+
+/// Read the library name from the `#!<engine> name=<name>` header at the start of the library code.
+pub(crate) fn parse_library_name(code: &str) -> Result<String, RedisError> {
+  let header = code.lines().next().unwrap_or("");
+  if !header.starts_with("#!") {
+    return Err(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "Missing library metadata header.",
+    ));
+  }
+
+  header
+    .split_whitespace()
+    .skip(1)
+    .find_map(|part| {
+      if part.starts_with("name=") {
+        Some(part[5 ..].to_owned())
+      } else {
+        None
+      }
+    })
+    .filter(|name| !name.is_empty())
+    .ok_or(RedisError::new(
+      RedisErrorKind::InvalidArgument,
+      "Missing library name in metadata header.",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_library_name() {
+    let code = "#!lua name=mylib\nredis.register_function('foo', function() return 1 end)";
+    assert_eq!(parse_library_name(code).unwrap(), "mylib");
+
+    let code = "#!lua engine=foo name=other\nreturn 1";
+    assert_eq!(parse_library_name(code).unwrap(), "other");
+
+    assert!(parse_library_name("redis.register_function('foo', function() return 1 end)").is_err());
+    assert!(parse_library_name("#!lua\nreturn 1").is_err());
+  }
+}
@@ -1,3 +1,4 @@
+use crate::types::RedisValue;
 use crate::utils;
 use bytes_utils::Str;
 use std::collections::HashMap;
@@ -31,6 +32,32 @@ pub struct KeyspaceEvent {
   pub key: String,
 }
 
+/// The kind of subscription that produced a message on the publish-subscribe interface.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum MessageKind {
+  /// A message on a channel from `SUBSCRIBE`.
+  Message,
+  /// A message on a channel that matched a `PSUBSCRIBE` pattern.
+  PMessage,
+  /// A message on a sharded channel from `SSUBSCRIBE`.
+  SMessage,
+}
+
+/// A message on the publish-subscribe interface.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Message {
+  pub channel: String,
+  pub value: RedisValue,
+  pub kind: MessageKind,
+}
+
+impl Message {
+  /// Whether or not the message was published to a sharded channel with `SPUBLISH`.
+  pub fn is_sharded(&self) -> bool {
+    self.kind == MessageKind::SMessage
+  }
+}
+
 /// Aggregate options for the [zinterstore](https://redis.io/commands/zinterstore) (and related) commands.
 pub enum AggregateOptions {
   Sum,
@@ -4,9 +4,11 @@ use tokio::task::JoinHandle;
 
 mod acl;
 mod args;
+mod bitmaps;
 mod client;
 mod cluster;
 mod config;
+mod functions;
 mod geo;
 mod lists;
 mod misc;
@@ -17,9 +19,11 @@ mod streams;
 
 pub use acl::*;
 pub use args::*;
+pub use bitmaps::*;
 pub use client::*;
 pub use cluster::*;
 pub use config::*;
+pub use functions::*;
 pub use geo::*;
 pub use lists::*;
 pub use misc::*;
@@ -30,12 +34,28 @@ pub use streams::*;
 
 #[cfg(feature = "metrics")]
 #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
-pub use crate::modules::metrics::Stats;
+pub use crate::modules::metrics::{HistogramStats, Stats};
+
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub use crate::modules::metrics::MetricsExporter;
+#[cfg(feature = "prometheus-exporter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prometheus-exporter")))]
+pub use crate::modules::metrics::PrometheusExporter;
+#[cfg(feature = "opentelemetry-exporter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry-exporter")))]
+pub use crate::modules::metrics::OpenTelemetryExporter;
+#[cfg(feature = "credential-provider")]
+#[cfg_attr(docsrs, doc(cfg(feature = "credential-provider")))]
+pub use crate::modules::credentials::CredentialProvider;
 
 pub(crate) static QUEUED: &'static str = "QUEUED";
 pub(crate) static NIL: &'static str = "nil";
 
 pub use crate::modules::response::{FromRedis, FromRedisKey};
+#[cfg(feature = "serde-value")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde-value")))]
+pub use crate::modules::serde_value::{from_redis_value, to_redis_map, to_redis_value, RedisValueSerializer, Serde};
 pub use crate::protocol::types::ClusterKeyCache;
 pub use redis_protocol::resp3::types::{Frame, RespVersion};
 
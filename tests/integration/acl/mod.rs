@@ -4,6 +4,13 @@ use fred::error::RedisError;
 use fred::interfaces::*;
 use fred::types::{AclUserFlag, RedisConfig};
 
+#[cfg(feature = "credential-provider")]
+use fred::types::{AclCommandPattern, AclKeyPattern, AclRule, CredentialProvider};
+#[cfg(feature = "credential-provider")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "credential-provider")]
+use std::time::Duration;
+
 // the docker image we use for sentinel tests doesn't allow for configuring users, just passwords,
 // so for the tests here we just use an empty username so it uses the `default` user
 #[cfg(feature = "sentinel-tests")]
@@ -46,6 +53,76 @@ pub async fn should_auth_as_test_user_via_config(_: RedisClient, mut config: Red
   Ok(())
 }
 
+#[cfg(feature = "credential-provider")]
+#[derive(Debug)]
+struct RotatingCredentialProvider {
+  credentials: Mutex<(String, String)>,
+}
+
+#[cfg(feature = "credential-provider")]
+impl RotatingCredentialProvider {
+  fn rotate(&self, username: &str, password: &str) {
+    *self.credentials.lock().unwrap() = (username.to_owned(), password.to_owned());
+  }
+}
+
+#[cfg(feature = "credential-provider")]
+#[async_trait::async_trait]
+impl CredentialProvider for RotatingCredentialProvider {
+  async fn fetch(&self) -> Result<(Option<String>, String), RedisError> {
+    let (username, password) = self.credentials.lock().unwrap().clone();
+    Ok((Some(username), password))
+  }
+
+  fn refresh_interval(&self) -> Option<Duration> {
+    Some(Duration::from_millis(200))
+  }
+}
+
+#[cfg(feature = "credential-provider")]
+fn full_access_rules(password: &str) -> Vec<AclRule> {
+  vec![
+    AclRule::Reset,
+    AclRule::On,
+    AclRule::AddPassword(password.to_owned()),
+    AclRule::AddKeys(AclKeyPattern::AllKeys),
+    AclRule::AddCommands(AclCommandPattern::AllCommands),
+  ]
+}
+
+// note: currently this only works against the centralized server since the ACL changes are only sent to one node
+#[cfg(feature = "credential-provider")]
+pub async fn should_reauth_with_rotated_credentials(
+  client: RedisClient,
+  mut config: RedisConfig,
+) -> Result<(), RedisError> {
+  let _ = client.acl_setuser("fred-rotate-1", full_access_rules("foo")).await?;
+  let _ = client.acl_setuser("fred-rotate-2", full_access_rules("bar")).await?;
+
+  let provider = Arc::new(RotatingCredentialProvider {
+    credentials: Mutex::new(("fred-rotate-1".into(), "foo".into())),
+  });
+  config.username = None;
+  config.password = None;
+  config.credential_provider = Some(provider.clone());
+  let rotating = RedisClient::new(config);
+  let _ = rotating.connect(None);
+  let _ = rotating.wait_for_connect().await?;
+  assert_eq!(rotating.acl_whoami().await?, "fred-rotate-1");
+
+  // rotate the second user's password on the server, then check that the refresh task authenticates with it since
+  // failed refreshes are only logged
+  let rules = vec![AclRule::RemovePassword("bar".into()), AclRule::AddPassword("baz".into())];
+  let _ = client.acl_setuser("fred-rotate-2", rules).await?;
+  provider.rotate("fred-rotate-2", "baz");
+  tokio::time::sleep(Duration::from_millis(600)).await;
+  assert_eq!(rotating.acl_whoami().await?, "fred-rotate-2");
+
+  let _ = rotating.quit().await?;
+  let _: u32 = client.acl_deluser(vec!["fred-rotate-1", "fred-rotate-2"]).await?;
+  Ok(())
+}
+
 pub async fn should_run_acl_getuser(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   let user = client.acl_getuser("default").await?.unwrap();
   assert!(user.flags.contains(&AclUserFlag::On));
@@ -0,0 +1,90 @@
+use fred::prelude::*;
+use fred::types::{BitOperation, BitUnit, BitfieldEncoding, BitfieldOffset, BitfieldOp, BitfieldOverflow};
+
+pub async fn should_setbit_and_getbit(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let result: u8 = client.setbit("foo", 7, 1).await?;
+  assert_eq!(result, 0);
+  let result: u8 = client.setbit("foo", 7, 0).await?;
+  assert_eq!(result, 1);
+  let result: u8 = client.getbit("foo", 7).await?;
+  assert_eq!(result, 0);
+  let result: u8 = client.getbit("foo", 100).await?;
+  assert_eq!(result, 0);
+
+  Ok(())
+}
+
+pub async fn should_bitcount_with_ranges(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let _: () = client.set("foo", "foobar", None, None, false).await?;
+  let result: i64 = client.bitcount("foo", None, None).await?;
+  assert_eq!(result, 26);
+  let result: i64 = client.bitcount("foo", Some((1, 1)), None).await?;
+  assert_eq!(result, 6);
+  let result: i64 = client.bitcount("foo", Some((5, 30)), Some(BitUnit::Bit)).await?;
+  assert_eq!(result, 17);
+
+  Ok(())
+}
+
+pub async fn should_bitpos_with_ranges(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let _: () = client.set("foo", &[0xff_u8, 0xf0, 0x00][..], None, None, false).await?;
+  let result: i64 = client.bitpos("foo", 0, None, None, None).await?;
+  assert_eq!(result, 12);
+  let result: i64 = client.bitpos("foo", 1, Some(2), Some(-1), Some(BitUnit::Byte)).await?;
+  assert_eq!(result, -1);
+  let result: i64 = client.bitpos("foo", 1, Some(7), Some(15), Some(BitUnit::Bit)).await?;
+  assert_eq!(result, 7);
+
+  Ok(())
+}
+
+pub async fn should_bitop_keys(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo{1}");
+  check_null!(client, "bar{1}");
+  check_null!(client, "baz{1}");
+
+  let _: () = client.set("foo{1}", "abc", None, None, false).await?;
+  let _: () = client.set("bar{1}", "abc", None, None, false).await?;
+  let len: i64 = client
+    .bitop(BitOperation::Xor, "baz{1}", vec!["foo{1}", "bar{1}"])
+    .await?;
+  assert_eq!(len, 3);
+  let result: i64 = client.bitcount("baz{1}", None, None).await?;
+  assert_eq!(result, 0);
+
+  let _: i64 = client.bitop(BitOperation::Not, "baz{1}", "foo{1}").await?;
+  let result: i64 = client.bitcount("baz{1}", None, None).await?;
+  assert_eq!(result, 24 - 10);
+
+  Ok(())
+}
+
+pub async fn should_bitfield_with_overflow(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+
+  let ops = BitfieldOp::new()
+    .set(BitfieldEncoding::Signed(5), 0, 15)
+    .get(BitfieldEncoding::Signed(5), 0)
+    .overflow(BitfieldOverflow::Sat)
+    .incrby(BitfieldEncoding::Signed(5), 0, 100)
+    .overflow(BitfieldOverflow::Fail)
+    .incrby(BitfieldEncoding::Unsigned(8), BitfieldOffset::Width(1), 300);
+  let result: Vec<Option<i64>> = client.bitfield("foo", ops).await?;
+  assert_eq!(result, vec![Some(0), Some(15), Some(15), None]);
+
+  let ops = BitfieldOp::new().get(BitfieldEncoding::Signed(5), 0);
+  let result: Vec<i64> = client.bitfield_ro("foo", ops).await?;
+  assert_eq!(result, vec![15]);
+
+  let ops = BitfieldOp::new().incrby(BitfieldEncoding::Unsigned(8), 0, 1);
+  let result: Result<Vec<i64>, RedisError> = client.bitfield_ro("foo", ops).await;
+  assert!(result.is_err());
+
+  Ok(())
+}
@@ -21,6 +21,8 @@ mod keys {
   centralized_test!(keys, should_msetnx_values);
   centralized_test!(keys, should_copy_values);
   centralized_test!(keys, should_get_keys_from_pool_in_a_stream);
+  #[cfg(feature = "codec-msgpack")]
+  centralized_test!(keys, should_set_and_get_values_with_codec);
 }
 
 mod multi {
@@ -33,17 +35,36 @@ mod other {
 
   #[cfg(all(not(feature = "chaos-monkey"), feature = "metrics"))]
   centralized_test!(other, should_track_size_stats);
+  #[cfg(feature = "metrics")]
+  centralized_test!(other, should_export_metrics);
+  #[cfg(feature = "metrics")]
+  centralized_test!(other, should_track_command_histograms);
 
   centralized_test!(other, should_automatically_unblock);
   centralized_test!(other, should_manually_unblock);
   centralized_test!(other, should_error_when_blocked);
   centralized_test!(other, should_smoke_test_from_redis_impl);
   centralized_test!(other, should_safely_change_protocols_repeatedly);
+  centralized_test!(other, should_pipeline_all);
+  centralized_test!(other, should_pipeline_try_all);
+  centralized_test!(other, should_pipeline_convert_responses);
+  centralized_test!(other, should_invalidate_client_side_cache);
+  #[cfg(unix)]
+  centralized_test!(other, should_connect_over_unix_socket);
+  #[cfg(feature = "sentinel-tests")]
+  centralized_test!(other, should_read_from_sentinel_replicas);
 }
 
 mod pool {
   centralized_test!(pool, should_connect_and_ping_static_pool_single_conn);
   centralized_test!(pool, should_connect_and_ping_static_pool_two_conn);
+  centralized_test!(pool, should_send_commands_to_least_loaded_client);
+  centralized_test!(pool, should_skip_clients_in_a_transaction);
+  centralized_test!(pool, should_apply_connection_state_to_every_client);
+  centralized_test!(pool, should_resize_elastic_pool);
+  centralized_test!(pool, should_reconnect_unhealthy_clients);
+  centralized_test!(pool, should_skip_health_checks_in_a_transaction);
+  centralized_test!(pool, should_scale_up_when_queue_exceeds_threshold);
   #[cfg(feature = "fd-tests")]
   centralized_test!(pool, should_connect_and_ping_static_pool_many_conn);
   #[cfg(feature = "fd-tests")]
@@ -65,6 +86,8 @@ mod hashes {
   centralized_test!(hashes, should_get_random_field);
   centralized_test!(hashes, should_get_strlen);
   centralized_test!(hashes, should_get_values);
+  #[cfg(feature = "serde-value")]
+  centralized_test!(hashes, should_hset_and_hgetall_with_serde);
 }
 
 #[cfg(not(feature = "chaos-monkey"))]
@@ -72,6 +95,17 @@ mod pubsub {
 
   centralized_test!(pubsub, should_publish_and_recv_messages);
   centralized_test!(pubsub, should_psubscribe_and_recv_messages);
+  #[cfg(feature = "keyspace-notifications")]
+  centralized_test!(pubsub, should_receive_keyspace_notifications);
+}
+
+mod bitmaps {
+
+  centralized_test!(bitmaps, should_setbit_and_getbit);
+  centralized_test!(bitmaps, should_bitcount_with_ranges);
+  centralized_test!(bitmaps, should_bitpos_with_ranges);
+  centralized_test!(bitmaps, should_bitop_keys);
+  centralized_test!(bitmaps, should_bitfield_with_overflow);
 }
 
 mod hyperloglog {
@@ -145,6 +179,16 @@ pub mod lua {
   centralized_test!(lua, should_evalsha_get_script);
 }
 
+pub mod functions {
+
+  centralized_test!(functions, should_load_and_list_library);
+  centralized_test!(functions, should_fcall_echo_function);
+  centralized_test!(functions, should_fcall_ro_get_function);
+  centralized_test!(functions, should_delete_library);
+  centralized_test!(functions, should_dump_and_restore_libraries);
+  centralized_test!(functions, should_read_function_stats);
+}
+
 pub mod sorted_sets {
 
   centralized_test!(sorted_sets, should_bzpopmin);
@@ -220,6 +264,8 @@ pub mod geo {
 pub mod acl {
   centralized_test!(acl, should_auth_as_test_user);
   centralized_test!(acl, should_auth_as_test_user_via_config);
+  #[cfg(feature = "credential-provider")]
+  centralized_test!(acl, should_reauth_with_rotated_credentials);
   centralized_test!(acl, should_run_acl_getuser);
 }
 
@@ -261,4 +307,64 @@ mod streams {
   centralized_test!(streams, should_xclaim_multiple_ids);
   centralized_test!(streams, should_xclaim_with_justid);
   centralized_test!(streams, should_xautoclaim_default);
+  #[cfg(feature = "stream-consumer")]
+  centralized_test!(streams, should_consume_and_ack_with_stream_consumer);
+  #[cfg(feature = "stream-consumer")]
+  centralized_test!(streams, should_move_messages_to_dead_letter_stream);
+  #[cfg(feature = "stream-consumer")]
+  centralized_test!(streams, should_run_stream_consumer);
+  #[cfg(feature = "stream-consumer")]
+  centralized_test!(streams, should_return_error_from_stream_consumer);
+  #[cfg(feature = "stream-consumer")]
+  centralized_test!(streams, should_resume_stream_consumer_after_reconnect);
+}
+
+#[cfg(feature = "redlock")]
+mod lock {
+  centralized_test!(lock, should_acquire_and_release_lock);
+  centralized_test!(lock, should_extend_lock);
+  centralized_test!(lock, should_renew_lock_automatically);
+  centralized_test!(lock, should_notify_when_lock_is_lost);
+  centralized_test!(lock, should_release_lock_on_drop);
+  centralized_test!(lock, should_acquire_lock_with_quorum);
+  centralized_test!(lock, should_acquire_lock_with_failed_instance);
+}
+
+#[cfg(feature = "rate-limit")]
+mod rate_limit {
+  centralized_test!(rate_limit, should_limit_with_fixed_window);
+  centralized_test!(rate_limit, should_limit_with_sliding_log);
+  centralized_test!(rate_limit, should_limit_with_token_bucket);
+  centralized_test!(rate_limit, should_limit_with_gcra);
+  centralized_test!(rate_limit, should_allow_requests_after_window);
+  centralized_test!(rate_limit, should_reject_requests_larger_than_limit);
+  centralized_test!(rate_limit, should_reload_flushed_script);
+}
+
+#[cfg(feature = "delayed-queue")]
+mod delayed_queue {
+  centralized_test!(delayed_queue, should_schedule_and_claim_due_jobs);
+  centralized_test!(delayed_queue, should_nack_and_reclaim_jobs);
+  centralized_test!(delayed_queue, should_redeliver_after_visibility_timeout);
+  centralized_test!(delayed_queue, should_reject_stale_ack_after_redelivery);
+  centralized_test!(delayed_queue, should_stream_delayed_jobs);
+  centralized_test!(delayed_queue, should_reject_scheduling_claimed_jobs);
+  centralized_test!(delayed_queue, should_dead_letter_after_max_attempts);
+  centralized_test!(delayed_queue, should_listen_for_expired_keys);
+}
+
+#[cfg(feature = "tls-tests")]
+mod tls {
+  #[cfg(feature = "enable-tls")]
+  centralized_test!(tls, should_connect_with_native_tls);
+  #[cfg(feature = "enable-rustls")]
+  centralized_test!(tls, should_connect_with_rustls);
+  #[cfg(feature = "enable-tls")]
+  centralized_test!(tls, should_connect_with_native_tls_client_cert);
+  #[cfg(feature = "enable-rustls")]
+  centralized_test!(tls, should_connect_with_rustls_client_cert);
+  #[cfg(feature = "enable-tls")]
+  centralized_test!(tls, should_require_native_tls_client_cert);
+  #[cfg(feature = "enable-rustls")]
+  centralized_test!(tls, should_require_rustls_client_cert);
 }
@@ -21,6 +21,8 @@ mod keys {
   cluster_test!(keys, should_msetnx_values);
   cluster_test!(keys, should_copy_values);
   cluster_test!(keys, should_get_keys_from_pool_in_a_stream);
+  #[cfg(feature = "codec-msgpack")]
+  cluster_test!(keys, should_set_and_get_values_with_codec);
 }
 
 mod multi {
@@ -35,6 +37,10 @@ mod other {
 
   #[cfg(all(not(feature = "chaos-monkey"), feature = "metrics"))]
   cluster_test!(other, should_track_size_stats);
+  #[cfg(feature = "metrics")]
+  cluster_test!(other, should_export_metrics);
+  #[cfg(feature = "metrics")]
+  cluster_test!(other, should_track_command_histograms);
 
   cluster_test!(other, should_split_clustered_connection);
   cluster_test!(other, should_run_flushall_cluster);
@@ -42,11 +48,22 @@ mod other {
   cluster_test!(other, should_manually_unblock);
   cluster_test!(other, should_error_when_blocked);
   cluster_test!(other, should_safely_change_protocols_repeatedly);
+  cluster_test!(other, should_pipeline_all);
+  cluster_test!(other, should_pipeline_try_all);
+  cluster_test!(other, should_pipeline_convert_responses);
+  cluster_test!(other, should_invalidate_client_side_cache);
+  cluster_test!(other, should_read_from_replicas);
 }
 
 mod pool {
   cluster_test!(pool, should_connect_and_ping_static_pool_single_conn);
   cluster_test!(pool, should_connect_and_ping_static_pool_two_conn);
+  cluster_test!(pool, should_send_commands_to_least_loaded_client);
+  cluster_test!(pool, should_skip_clients_in_a_transaction);
+  cluster_test!(pool, should_resize_elastic_pool);
+  cluster_test!(pool, should_reconnect_unhealthy_clients);
+  cluster_test!(pool, should_skip_health_checks_in_a_transaction);
+  cluster_test!(pool, should_scale_up_when_queue_exceeds_threshold);
   #[cfg(feature = "fd-tests")]
   cluster_test!(pool, should_connect_and_ping_static_pool_many_conn);
   #[cfg(feature = "fd-tests")]
@@ -68,6 +85,8 @@ mod hashes {
   cluster_test!(hashes, should_get_random_field);
   cluster_test!(hashes, should_get_strlen);
   cluster_test!(hashes, should_get_values);
+  #[cfg(feature = "serde-value")]
+  cluster_test!(hashes, should_hset_and_hgetall_with_serde);
 }
 
 #[cfg(not(feature = "chaos-monkey"))]
@@ -75,6 +94,20 @@ mod pubsub {
 
   cluster_test!(pubsub, should_publish_and_recv_messages);
   cluster_test!(pubsub, should_psubscribe_and_recv_messages);
+  #[cfg(feature = "keyspace-notifications")]
+  cluster_test!(pubsub, should_receive_keyspace_notifications);
+  cluster_test!(pubsub, should_ssubscribe_and_recv_sharded_messages);
+  #[cfg(feature = "subscriber-client")]
+  cluster_test!(pubsub, should_resubscribe_sharded_channels);
+}
+
+mod bitmaps {
+
+  cluster_test!(bitmaps, should_setbit_and_getbit);
+  cluster_test!(bitmaps, should_bitcount_with_ranges);
+  cluster_test!(bitmaps, should_bitpos_with_ranges);
+  cluster_test!(bitmaps, should_bitop_keys);
+  cluster_test!(bitmaps, should_bitfield_with_overflow);
 }
 
 mod hyperloglog {
@@ -149,6 +182,16 @@ pub mod lua {
   cluster_test!(lua, should_evalsha_get_script);
 }
 
+pub mod functions {
+
+  cluster_test!(functions, should_load_and_list_library);
+  cluster_test!(functions, should_fcall_echo_function);
+  cluster_test!(functions, should_fcall_ro_get_function);
+  cluster_test!(functions, should_delete_library);
+  cluster_test!(functions, should_dump_and_restore_libraries);
+  cluster_test!(functions, should_read_function_stats);
+}
+
 pub mod sorted_sets {
 
   #[cfg(not(feature = "chaos-monkey"))]
@@ -265,4 +308,44 @@ mod streams {
   cluster_test!(streams, should_xclaim_multiple_ids);
   cluster_test!(streams, should_xclaim_with_justid);
   cluster_test!(streams, should_xautoclaim_default);
+  #[cfg(feature = "stream-consumer")]
+  cluster_test!(streams, should_consume_and_ack_with_stream_consumer);
+  #[cfg(feature = "stream-consumer")]
+  cluster_test!(streams, should_move_messages_to_dead_letter_stream);
+  #[cfg(feature = "stream-consumer")]
+  cluster_test!(streams, should_run_stream_consumer);
+  #[cfg(feature = "stream-consumer")]
+  cluster_test!(streams, should_return_error_from_stream_consumer);
+}
+
+#[cfg(feature = "redlock")]
+mod lock {
+  cluster_test!(lock, should_acquire_and_release_lock);
+  cluster_test!(lock, should_extend_lock);
+  cluster_test!(lock, should_renew_lock_automatically);
+  cluster_test!(lock, should_notify_when_lock_is_lost);
+  cluster_test!(lock, should_release_lock_on_drop);
+}
+
+#[cfg(feature = "rate-limit")]
+mod rate_limit {
+  cluster_test!(rate_limit, should_limit_with_fixed_window);
+  cluster_test!(rate_limit, should_limit_with_sliding_log);
+  cluster_test!(rate_limit, should_limit_with_token_bucket);
+  cluster_test!(rate_limit, should_limit_with_gcra);
+  cluster_test!(rate_limit, should_allow_requests_after_window);
+  cluster_test!(rate_limit, should_reject_requests_larger_than_limit);
+  cluster_test!(rate_limit, should_reload_flushed_script);
+}
+
+#[cfg(feature = "delayed-queue")]
+mod delayed_queue {
+  cluster_test!(delayed_queue, should_schedule_and_claim_due_jobs);
+  cluster_test!(delayed_queue, should_nack_and_reclaim_jobs);
+  cluster_test!(delayed_queue, should_redeliver_after_visibility_timeout);
+  cluster_test!(delayed_queue, should_reject_stale_ack_after_redelivery);
+  cluster_test!(delayed_queue, should_stream_delayed_jobs);
+  cluster_test!(delayed_queue, should_reject_scheduling_claimed_jobs);
+  cluster_test!(delayed_queue, should_dead_letter_after_max_attempts);
+  cluster_test!(delayed_queue, should_listen_for_expired_keys);
 }
@@ -0,0 +1,238 @@
+use fred::clients::{KeyspaceConfig, RedisClient};
+use fred::delayed_queue::{DelayedQueue, DelayedQueueConfig, ExpirationListener};
+use fred::error::{RedisError, RedisErrorKind};
+use fred::interfaces::*;
+use fred::types::{Expiration, RedisConfig};
+use futures::StreamExt;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+fn queue(client: &RedisClient, visibility_timeout_ms: u64) -> DelayedQueue<RedisClient> {
+  let config = DelayedQueueConfig {
+    visibility_timeout_ms,
+    batch_size: 10,
+    poll_interval_ms: 50,
+    max_attempts: None,
+  };
+
+  DelayedQueue::new(client.clone(), "jobs", config)
+}
+
+async fn clear(client: &RedisClient) -> Result<(), RedisError> {
+  client
+    .del(vec![
+      "{jobs}:queue",
+      "{jobs}:processing",
+      "{jobs}:jobs",
+      "{jobs}:attempts",
+      "{jobs}:dead",
+    ])
+    .await
+}
+
+pub async fn should_schedule_and_claim_due_jobs(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = clear(&client).await?;
+  let queue = queue(&client, 10_000);
+
+  let first = queue.schedule("foo", Duration::from_millis(0)).await?;
+  let _ = queue.schedule("bar", Duration::from_secs(60)).await?;
+  assert_eq!(queue.len().await?, 2);
+
+  let jobs = queue.claim(10).await?;
+  assert_eq!(jobs.len(), 1);
+  assert_eq!(jobs[0].id, first);
+  assert_eq!(jobs[0].payload.as_str().unwrap(), "foo");
+  assert_eq!(jobs[0].attempts, 1);
+  assert_eq!(queue.len().await?, 1);
+  assert_eq!(queue.processing_len().await?, 1);
+
+  assert!(jobs[0].ack().await?);
+  assert!(!jobs[0].ack().await?);
+  assert_eq!(queue.processing_len().await?, 0);
+  assert!(queue.claim(10).await?.is_empty());
+
+  let _ = clear(&client).await?;
+  Ok(())
+}
+
+pub async fn should_nack_and_reclaim_jobs(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = clear(&client).await?;
+  let queue = queue(&client, 10_000);
+
+  let _ = queue.schedule_with_id("a", "foo", Duration::from_millis(0)).await?;
+  let jobs = queue.claim(10).await?;
+  assert!(jobs[0].nack(Duration::from_millis(0)).await?);
+  assert_eq!(queue.processing_len().await?, 0);
+
+  let jobs = queue.claim(10).await?;
+  assert_eq!(jobs.len(), 1);
+  assert_eq!(jobs[0].id, "a");
+  assert_eq!(jobs[0].attempts, 2);
+  assert!(queue.cancel("a").await?);
+  assert!(!queue.cancel("a").await?);
+
+  let _ = clear(&client).await?;
+  Ok(())
+}
+
+pub async fn should_redeliver_after_visibility_timeout(
+  client: RedisClient,
+  _: RedisConfig,
+) -> Result<(), RedisError> {
+  let _ = clear(&client).await?;
+  let queue = queue(&client, 100);
+
+  let _ = queue.schedule_with_id("a", "foo", Duration::from_millis(0)).await?;
+  let jobs = queue.claim(10).await?;
+  assert_eq!(jobs.len(), 1);
+  assert!(queue.claim(10).await?.is_empty());
+
+  sleep(Duration::from_millis(200)).await;
+  let jobs = queue.claim(10).await?;
+  assert_eq!(jobs.len(), 1);
+  assert_eq!(jobs[0].attempts, 2);
+  assert!(jobs[0].ack().await?);
+
+  let _ = clear(&client).await?;
+  Ok(())
+}
+
+pub async fn should_reject_stale_ack_after_redelivery(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = clear(&client).await?;
+  let queue = queue(&client, 100);
+
+  let _ = queue.schedule_with_id("a", "foo", Duration::from_millis(0)).await?;
+  let stale = queue.claim(10).await?.pop().expect("Missing job");
+  sleep(Duration::from_millis(200)).await;
+  let current = queue.claim(10).await?.pop().expect("Missing job");
+  assert_eq!(current.attempts, 2);
+
+  // the first consumer's visibility timeout expired, so it can no longer modify the job
+  assert!(!stale.ack().await?);
+  assert!(!stale.nack(Duration::from_millis(0)).await?);
+  assert_eq!(queue.processing_len().await?, 1);
+  assert_eq!(queue.len().await?, 0);
+
+  assert!(current.ack().await?);
+  assert_eq!(queue.processing_len().await?, 0);
+
+  let _ = clear(&client).await?;
+  Ok(())
+}
+
+pub async fn should_stream_delayed_jobs(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = clear(&client).await?;
+  let queue = queue(&client, 10_000);
+
+  let _ = queue.schedule_with_id("a", 1, Duration::from_millis(200)).await?;
+  let _ = queue.schedule_with_id("b", 2, Duration::from_millis(100)).await?;
+
+  let mut jobs = queue.jobs();
+  let mut ids = Vec::new();
+  while ids.len() < 2 {
+    let job = timeout(Duration::from_secs(5), jobs.next())
+      .await
+      .expect("Timed out waiting for jobs")
+      .expect("Missing job")?;
+
+    assert!(job.ack().await?);
+    ids.push(job.id);
+  }
+  drop(jobs);
+  assert_eq!(ids, vec!["b", "a"]);
+  // wait for the first stream to finish any claim that was in flight when it was dropped
+  sleep(Duration::from_millis(100)).await;
+
+  // dropping one stream should not affect other streams on the same queue
+  let _ = queue.schedule_with_id("c", 3, Duration::from_millis(0)).await?;
+  let mut jobs = queue.jobs();
+  let job = timeout(Duration::from_secs(5), jobs.next())
+    .await
+    .expect("Timed out waiting for jobs")
+    .expect("Missing job")?;
+  assert_eq!(job.id, "c");
+  assert!(job.ack().await?);
+
+  let _ = clear(&client).await?;
+  Ok(())
+}
+
+pub async fn should_reject_scheduling_claimed_jobs(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = clear(&client).await?;
+  let queue = queue(&client, 10_000);
+
+  let _ = queue.schedule_with_id("a", "foo", Duration::from_millis(0)).await?;
+  let job = queue.claim(10).await?.pop().expect("Missing job");
+
+  let error = queue
+    .schedule_with_id("a", "bar", Duration::from_millis(0))
+    .await
+    .expect_err("Scheduled a claimed job");
+  assert_eq!(*error.kind(), RedisErrorKind::InvalidArgument);
+  assert_eq!(queue.len().await?, 0);
+
+  // the claimed job is unchanged, so the consumer's ack still applies to it
+  assert!(job.ack().await?);
+  let _ = queue.schedule_with_id("a", "bar", Duration::from_millis(0)).await?;
+  assert_eq!(queue.len().await?, 1);
+
+  let _ = clear(&client).await?;
+  Ok(())
+}
+
+pub async fn should_dead_letter_after_max_attempts(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = clear(&client).await?;
+  let config = DelayedQueueConfig {
+    max_attempts: Some(2),
+    ..DelayedQueueConfig::default()
+  };
+  let queue = DelayedQueue::new(client.clone(), "jobs", config);
+
+  let _ = queue.schedule_with_id("a", "foo", Duration::from_millis(0)).await?;
+  for attempt in 1 .. 3 {
+    let job = queue.claim(10).await?.pop().expect("Missing job");
+    assert_eq!(job.attempts, attempt);
+    assert!(job.nack(Duration::from_millis(0)).await?);
+  }
+
+  assert!(queue.claim(10).await?.is_empty());
+  assert_eq!(queue.len().await?, 0);
+  assert_eq!(queue.dead_letter_len().await?, 1);
+
+  assert!(queue.retry_dead_letter("a", Duration::from_millis(0)).await?);
+  assert!(!queue.retry_dead_letter("a", Duration::from_millis(0)).await?);
+  let job = queue.claim(10).await?.pop().expect("Missing job");
+  assert_eq!(job.attempts, 1);
+  assert_eq!(job.payload.as_str().unwrap(), "foo");
+  assert!(job.ack().await?);
+  assert_eq!(queue.dead_letter_len().await?, 0);
+
+  let _ = clear(&client).await?;
+  Ok(())
+}
+
+pub async fn should_listen_for_expired_keys(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let config = KeyspaceConfig {
+    notify_flags: Some("KEA".into()),
+    databases: vec![0],
+    key_pattern: "{expire}:*".into(),
+    ..KeyspaceConfig::default()
+  };
+  let listener = ExpirationListener::new(&client, config);
+  let mut expired = listener.on_expired();
+  let _ = listener.connect().await?;
+
+  let _: () = client.set("{expire}:foo", "bar", Some(Expiration::PX(100)), None, false).await?;
+  let _: () = client.set("{expire}:bar", "baz", None, None, false).await?;
+  let _: () = client.del("{expire}:bar").await?;
+
+  let event = timeout(Duration::from_secs(5), expired.next())
+    .await
+    .map_err(|_| RedisError::new(RedisErrorKind::Timeout, "Timed out waiting for expired keys."))?
+    .expect("Missing expired key");
+  assert_eq!(event.key, "{expire}:foo");
+  assert_eq!(event.db, 0);
+
+  let _ = listener.quit().await?;
+  Ok(())
+}
@@ -0,0 +1,121 @@
+use bytes::Bytes;
+use fred::prelude::*;
+use fred::types::FunctionRestorePolicy;
+
+static ECHO_LIBRARY: &'static str = "#!lua name=echolib
+redis.register_function('echo_keys', function(keys, args) return {keys[1], keys[2], args[1], args[2]} end)
+local function echo_get(keys, args)
+  return redis.call('get', keys[1])
+end
+redis.register_function{function_name='echo_get', callback=echo_get, flags={'no-writes'}}";
+
+pub async fn load_library(client: &RedisClient, code: &str) -> Result<String, RedisError> {
+  if client.is_clustered() {
+    client.function_load_cluster(true, code).await
+  } else {
+    client.function_load(true, code).await
+  }
+}
+
+pub async fn flush_functions(client: &RedisClient) -> Result<(), RedisError> {
+  if client.is_clustered() {
+    client.function_flush_cluster(false).await
+  } else {
+    client.function_flush(false).await
+  }
+}
+
+pub async fn should_load_and_list_library(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = flush_functions(&client).await?;
+  let name = load_library(&client, ECHO_LIBRARY).await?;
+  assert_eq!(name, "echolib");
+
+  let libraries = client.function_list(Some("echo*"), true).await?;
+  assert_eq!(libraries.len(), 1);
+  assert_eq!(libraries[0].name, "echolib");
+  assert_eq!(libraries[0].engine, "LUA");
+  assert_eq!(libraries[0].code.as_deref(), Some(ECHO_LIBRARY));
+  assert_eq!(libraries[0].functions.len(), 2);
+
+  let echo_get = libraries[0].functions.iter().find(|f| f.name == "echo_get").unwrap();
+  assert!(echo_get.is_read_only());
+
+  let _ = flush_functions(&client).await?;
+  Ok(())
+}
+
+pub async fn should_fcall_echo_function(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = load_library(&client, ECHO_LIBRARY).await?;
+
+  let result: Vec<String> = client
+    .fcall("echo_keys", vec!["a{1}", "b{1}"], vec!["c{1}", "d{1}"])
+    .await?;
+  assert_eq!(result, vec!["a{1}", "b{1}", "c{1}", "d{1}"]);
+
+  let _ = flush_functions(&client).await?;
+  Ok(())
+}
+
+pub async fn should_fcall_ro_get_function(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = load_library(&client, ECHO_LIBRARY).await?;
+
+  let result: Option<String> = client.fcall_ro("echo_get", vec!["foo"], None).await?;
+  assert!(result.is_none());
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let result: String = client.fcall_ro("echo_get", vec!["foo"], None).await?;
+  assert_eq!(result, "bar");
+
+  let _ = flush_functions(&client).await?;
+  Ok(())
+}
+
+pub async fn should_delete_library(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = load_library(&client, ECHO_LIBRARY).await?;
+
+  if client.is_clustered() {
+    let _ = client.function_delete_cluster("echolib").await?;
+  } else {
+    let _ = client.function_delete("echolib").await?;
+  }
+  let libraries = client.function_list(Some("echolib"), false).await?;
+  assert!(libraries.is_empty());
+
+  Ok(())
+}
+
+pub async fn should_dump_and_restore_libraries(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = load_library(&client, ECHO_LIBRARY).await?;
+  let payload: Bytes = client.function_dump().await?;
+  let _ = flush_functions(&client).await?;
+
+  if client.is_clustered() {
+    let _ = client
+      .function_restore_cluster(payload, Some(FunctionRestorePolicy::Replace))
+      .await?;
+  } else {
+    let _ = client
+      .function_restore(payload, Some(FunctionRestorePolicy::Replace))
+      .await?;
+  }
+  let libraries = client.function_list(None::<&str>, false).await?;
+  assert_eq!(libraries.len(), 1);
+  assert_eq!(libraries[0].name, "echolib");
+
+  let _ = flush_functions(&client).await?;
+  Ok(())
+}
+
+pub async fn should_read_function_stats(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = flush_functions(&client).await?;
+  let _ = load_library(&client, ECHO_LIBRARY).await?;
+
+  let stats = client.function_stats().await?;
+  assert!(stats.running_script.is_none());
+  let lua = stats.engines.get("LUA").unwrap();
+  assert_eq!(lua.libraries_count, 1);
+  assert_eq!(lua.functions_count, 2);
+
+  let _ = flush_functions(&client).await?;
+  Ok(())
+}
@@ -218,3 +218,54 @@ pub async fn should_get_values(client: RedisClient, _: RedisConfig) -> Result<()
 
   Ok(())
 }
+
+#[cfg(feature = "serde-value")]
+pub async fn should_hset_and_hgetall_with_serde(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  use fred::types::{to_redis_map, Serde};
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+  struct Location {
+    city: String,
+  }
+
+  #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+  struct User {
+    name: String,
+    #[serde(rename = "user_age")]
+    age: u32,
+    active: bool,
+    nickname: Option<String>,
+    #[serde(skip)]
+    password: String,
+    #[serde(flatten)]
+    location: Location,
+  }
+
+  check_null!(client, "foo");
+  let user = User {
+    name: "alice".into(),
+    age: 42,
+    active: true,
+    nickname: None,
+    password: "hunter2".into(),
+    location: Location { city: "Berlin".into() },
+  };
+
+  let result: i64 = client.hset("foo", to_redis_map(&user)?).await?;
+  assert_eq!(result, 4);
+  let age: u32 = client.hget("foo", "user_age").await?;
+  assert_eq!(age, 42);
+
+  let Serde(actual): Serde<User> = client.hgetall("foo").await?;
+  assert_eq!(actual, User {
+    password: String::new(),
+    ..user
+  });
+
+  let _: () = client.hmset("bar", Serde(Location { city: "Paris".into() })).await?;
+  let Serde(location): Serde<Location> = client.hgetall("bar").await?;
+  assert_eq!(location.city, "Paris");
+
+  Ok(())
+}
@@ -321,3 +321,39 @@ pub async fn should_get_keys_from_pool_in_a_stream(
 
   Ok(())
 }
+
+#[cfg(feature = "codec-msgpack")]
+pub async fn should_set_and_get_values_with_codec(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  use fred::codec::MsgPackCodec;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct User {
+    name: String,
+    scores: Vec<u32>,
+  }
+
+  check_null!(client, "{foo}.1");
+  check_null!(client, "{foo}.2");
+  let codec_client = client.with_codec(MsgPackCodec);
+  let user = User {
+    name: "alice".into(),
+    scores: vec![1, 2, 3],
+  };
+
+  assert!(codec_client.set("{foo}.1", &user, None, None).await?);
+  let actual: Option<User> = codec_client.get("{foo}.1").await?;
+  assert_eq!(actual, Some(user));
+  let missing: Option<User> = codec_client.get("{foo}.2").await?;
+  assert!(missing.is_none());
+
+  let raw: Vec<u8> = client.get("{foo}.1").await?;
+  assert_eq!(codec_client.decode::<User>(RedisValue::Bytes(raw.into()))?.unwrap().name, "alice");
+
+  let values: Vec<Option<User>> = codec_client.mget(vec!["{foo}.1", "{foo}.2"]).await?;
+  assert_eq!(values.len(), 2);
+  assert!(values[0].is_some());
+  assert!(values[1].is_none());
+
+  Ok(())
+}
@@ -0,0 +1,178 @@
+use fred::clients::RedisClient;
+use fred::error::RedisError;
+use fred::interfaces::*;
+use fred::lock::{LockConfig, Redlock};
+use fred::types::{RedisConfig, RedisValue};
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+fn redlock(client: &RedisClient, ttl_ms: u64, auto_renew: bool) -> Result<Redlock<RedisClient>, RedisError> {
+  let config = LockConfig {
+    ttl_ms,
+    retry_count: 0,
+    auto_renew,
+    ..LockConfig::default()
+  };
+
+  Redlock::new(vec![client.clone()], config)
+}
+
+/// Create clients connected to separate databases on the same server, which act as independent lock instances.
+async fn instances(config: &RedisConfig, count: u8) -> Result<Vec<RedisClient>, RedisError> {
+  let mut clients = Vec::with_capacity(count as usize);
+  for database in 0 .. count {
+    let client = RedisClient::new(RedisConfig {
+      database: Some(database),
+      ..config.clone()
+    });
+    let _ = client.connect(None);
+    let _ = client.wait_for_connect().await?;
+    clients.push(client);
+  }
+
+  Ok(clients)
+}
+
+fn multi_redlock(instances: Vec<RedisClient>) -> Result<Redlock<RedisClient>, RedisError> {
+  let config = LockConfig {
+    ttl_ms: 10_000,
+    retry_count: 0,
+    auto_renew: false,
+    instance_timeout_ms: 100,
+    ..LockConfig::default()
+  };
+
+  Redlock::new(instances, config)
+}
+
+pub async fn should_acquire_and_release_lock(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let redlock = redlock(&client, 10_000, false)?;
+
+  let lock = redlock.lock("foo").await?.expect("Failed to acquire lock");
+  let token: String = client.get("foo").await?;
+  assert_eq!(token, lock.token());
+  assert!(!lock.is_lost());
+  assert!(redlock.lock("foo").await?.is_none());
+
+  assert!(lock.release().await?);
+  check_null!(client, "foo");
+  let lock = redlock.lock("foo").await?.expect("Failed to reacquire lock");
+  assert!(lock.release().await?);
+
+  Ok(())
+}
+
+pub async fn should_extend_lock(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let redlock = redlock(&client, 1_000, false)?;
+
+  let lock = redlock.lock("foo").await?.expect("Failed to acquire lock");
+  assert!(lock.extend(10_000).await?);
+  let ttl: i64 = client.pttl("foo").await?;
+  assert!(ttl > 1_000);
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  assert!(!lock.extend(10_000).await?);
+  assert!(!lock.release().await?);
+
+  Ok(())
+}
+
+pub async fn should_renew_lock_automatically(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let redlock = redlock(&client, 300, true)?;
+
+  let lock = redlock.lock("foo").await?.expect("Failed to acquire lock");
+  sleep(Duration::from_millis(1_000)).await;
+  assert!(!lock.is_lost());
+  let token: String = client.get("foo").await?;
+  assert_eq!(token, lock.token());
+
+  assert!(lock.release().await?);
+  Ok(())
+}
+
+pub async fn should_notify_when_lock_is_lost(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let redlock = redlock(&client, 300, true)?;
+
+  let lock = redlock.lock("foo").await?.expect("Failed to acquire lock");
+  let _: () = client.del("foo").await?;
+  timeout(Duration::from_secs(1), lock.on_lost())
+    .await
+    .expect("Failed to notify lost lock");
+  assert!(lock.is_lost());
+
+  Ok(())
+}
+
+pub async fn should_release_lock_on_drop(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  check_null!(client, "foo");
+  let redlock = redlock(&client, 10_000, true)?;
+
+  let lock = redlock.lock("foo").await?.expect("Failed to acquire lock");
+  drop(lock);
+  sleep(Duration::from_millis(100)).await;
+  check_null!(client, "foo");
+
+  Ok(())
+}
+
+pub async fn should_acquire_lock_with_quorum(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let clients = instances(&config, 3).await?;
+  let redlock = multi_redlock(clients.clone())?;
+  assert_eq!(redlock.quorum(), 2);
+
+  // the lock should be acquired while a minority of the instances are held by someone else
+  let _: () = clients[2].set("foo", "bar", None, None, false).await?;
+  let lock = redlock.lock("foo").await?.expect("Failed to acquire lock");
+  for client in clients[0 .. 2].iter() {
+    let token: String = client.get("foo").await?;
+    assert_eq!(token, lock.token());
+  }
+  let token: String = clients[2].get("foo").await?;
+  assert_eq!(token, "bar");
+  assert!(lock.release().await?);
+  let (first, second) = (&clients[0], &clients[1]);
+  check_null!(first, "foo");
+  check_null!(second, "foo");
+
+  // the lock should not be acquired when a majority of the instances are held, and partial locks are cleaned up
+  let _: () = clients[1].set("foo", "bar", None, None, false).await?;
+  assert!(redlock.lock("foo").await?.is_none());
+  check_null!(first, "foo");
+
+  for client in clients.iter() {
+    let _ = client.quit().await;
+  }
+  Ok(())
+}
+
+pub async fn should_acquire_lock_with_failed_instance(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let mut clients = instances(&config, 2).await?;
+  // this client is never connected, so commands sent to it will time out
+  let failed = RedisClient::new(RedisConfig {
+    database: Some(2),
+    ..config.clone()
+  });
+  clients.push(failed.clone());
+  let redlock = multi_redlock(clients.clone())?;
+
+  let lock = redlock.lock("foo").await?.expect("Failed to acquire lock");
+  let token: String = clients[0].get("foo").await?;
+  assert_eq!(token, lock.token());
+  assert!(lock.extend(10_000).await?);
+  assert!(lock.release().await?);
+  let first = &clients[0];
+  check_null!(first, "foo");
+
+  // an error should be returned when every instance fails
+  let redlock = multi_redlock(vec![failed])?;
+  assert!(redlock.lock("foo").await.is_err());
+
+  for client in clients[0 .. 2].iter() {
+    let _ = client.quit().await;
+  }
+  Ok(())
+}
@@ -2,25 +2,35 @@
 pub mod utils;
 
 mod acl;
+mod bitmaps;
 mod client;
 mod cluster;
+#[cfg(feature = "delayed-queue")]
+mod delayed_queue;
+mod functions;
 mod geo;
 mod hashes;
 mod hyperloglog;
 mod keys;
 mod lists;
+#[cfg(feature = "redlock")]
+mod lock;
 mod lua;
 mod memory;
 mod multi;
 mod other;
 mod pool;
 mod pubsub;
+#[cfg(feature = "rate-limit")]
+mod rate_limit;
 mod scanning;
 mod server;
 mod sets;
 mod slowlog;
 mod sorted_sets;
 mod streams;
+#[cfg(feature = "tls-tests")]
+mod tls;
 
 pub mod centralized;
 pub mod clustered;
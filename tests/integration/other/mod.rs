@@ -2,7 +2,10 @@ use fred::clients::RedisClient;
 use fred::error::{RedisError, RedisErrorKind};
 use fred::interfaces::*;
 use fred::prelude::{Blocking, RedisValue};
-use fred::types::{ClientUnblockFlag, RedisConfig, RedisKey, RedisMap, ServerConfig};
+use fred::types::{
+  CacheConfig, ClientUnblockFlag, InfoKind, RedisConfig, RedisKey, RedisMap, ReplicaConfig, ReplicaPolicy,
+  ServerConfig,
+};
 use parking_lot::RwLock;
 use redis_protocol::resp3::types::RespVersion;
 use std::collections::HashMap;
@@ -13,6 +16,16 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 
+#[cfg(unix)]
+use crate::integration::utils::read_env_var;
+
+#[cfg(feature = "metrics")]
+use fred::types::{CustomCommand, MetricsExporter};
+#[cfg(feature = "metrics")]
+use fred::util::redis_keyslot;
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 fn hash_to_btree(vals: &RedisMap) -> BTreeMap<RedisKey, u16> {
   vals
     .iter()
@@ -24,6 +37,60 @@ fn array_to_set<T: Ord>(vals: Vec<T>) -> BTreeSet<T> {
   vals.into_iter().collect()
 }
 
+/// Read the number of `GET` calls from the `INFO commandstats` output.
+fn read_get_calls(info: &str) -> u64 {
+  info
+    .lines()
+    .find(|line| line.starts_with("cmdstat_get:"))
+    .and_then(|line| line["cmdstat_get:calls=".len() ..].split(',').next())
+    .and_then(|calls| calls.parse::<u64>().ok())
+    .unwrap_or(0)
+}
+
+/// Connect to each replica listed in the `INFO replication` output from `primary`.
+async fn connect_to_replicas(primary: &RedisClient, config: &RedisConfig) -> Result<Vec<RedisClient>, RedisError> {
+  let info: String = primary.info(Some(InfoKind::Replication)).await?;
+  let mut replicas = Vec::new();
+
+  for line in info.lines().filter(|line| line.starts_with("slave") && line.contains("ip=")) {
+    let fields: HashMap<&str, &str> = line[line.find(':').unwrap() + 1 ..]
+      .split(',')
+      .filter_map(|field| field.split_once('='))
+      .collect();
+
+    let mut config = config.clone();
+    config.replica = None;
+    config.server = ServerConfig::Centralized {
+      host: fields["ip"].to_owned(),
+      port: fields["port"].parse().unwrap(),
+    };
+    let replica = RedisClient::new(config);
+    let _ = replica.connect(None);
+    let _ = replica.wait_for_connect().await?;
+    replicas.push(replica);
+  }
+
+  Ok(replicas)
+}
+
+/// Reset the command stats on each client's server.
+async fn reset_stats(clients: &[RedisClient]) -> Result<(), RedisError> {
+  for client in clients.iter() {
+    let _ = client.config_resetstat().await?;
+  }
+  Ok(())
+}
+
+/// Read the total number of `GET` calls on each client's server.
+async fn count_get_calls(clients: &[RedisClient]) -> Result<u64, RedisError> {
+  let mut calls = 0;
+  for client in clients.iter() {
+    let info: String = client.info(Some(InfoKind::CommandStats)).await?;
+    calls += read_get_calls(&info);
+  }
+  Ok(calls)
+}
+
 pub async fn should_smoke_test_from_redis_impl(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   let nested_values: RedisMap = vec![("a", 1), ("b", 2)].try_into()?;
   let _ = client.set("foo", "123", None, None, false).await?;
@@ -163,6 +230,139 @@ pub async fn should_track_size_stats(client: RedisClient, _config: RedisConfig)
   Ok(())
 }
 
+#[cfg(feature = "metrics")]
+pub async fn should_track_command_histograms(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _ = client.take_command_latency_histograms();
+  let _ = client.take_server_latency_histograms();
+  let _ = client.take_command_res_size_histograms();
+
+  for _ in 0 .. 10 {
+    let _: () = client.set("foo", "bar", None, None, false).await?;
+  }
+  let _: String = client.get("foo").await?;
+
+  let latency = client.take_command_latency_histograms();
+  let set = latency.get("SET").unwrap();
+  assert_eq!(set.samples, 10);
+  assert!(set.p50 <= set.p99 && set.p99 <= set.max);
+  assert_eq!(latency.get("GET").unwrap().samples, 1);
+  assert!(client.read_command_latency_histograms().is_empty());
+
+  let servers = client.take_server_latency_histograms();
+  assert_eq!(servers.values().map(|stats| stats.samples).sum::<u64>(), 11);
+  let res_size = client.take_command_res_size_histograms();
+  assert!(res_size.get("GET").unwrap().max > 0);
+
+  Ok(())
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default)]
+struct CountingExporter {
+  latencies: AtomicUsize,
+  errors: AtomicUsize,
+  reconnects: AtomicUsize,
+  backpressure: AtomicUsize,
+  in_flight: AtomicUsize,
+  max_in_flight: AtomicUsize,
+  redirects: AtomicUsize,
+}
+
+#[cfg(feature = "metrics")]
+impl MetricsExporter for CountingExporter {
+  fn record_latency(&self, _: &str, _: &str, _: &str, _: Duration) {
+    self.latencies.fetch_add(1, Ordering::SeqCst);
+  }
+
+  fn record_error(&self, _: &str, _: &RedisErrorKind) {
+    self.errors.fetch_add(1, Ordering::SeqCst);
+  }
+
+  fn record_reconnect(&self, _: &str) {
+    self.reconnects.fetch_add(1, Ordering::SeqCst);
+  }
+
+  fn record_backpressure(&self, _: &str, _: &str) {
+    self.backpressure.fetch_add(1, Ordering::SeqCst);
+  }
+
+  fn record_in_flight(&self, _: &str, _: &str, count: usize) {
+    self.in_flight.fetch_add(1, Ordering::SeqCst);
+    self.max_in_flight.fetch_max(count, Ordering::SeqCst);
+  }
+
+  fn record_redirect(&self, _: &str, _: &str, _: &str) {
+    self.redirects.fetch_add(1, Ordering::SeqCst);
+  }
+}
+
+#[cfg(feature = "metrics")]
+pub async fn should_export_metrics(_: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  let exporter = Arc::new(CountingExporter::default());
+  config.metrics_exporter = Some(exporter.clone());
+  config.performance.backpressure.max_in_flight_commands = 1;
+  config.performance.backpressure.min_sleep_duration_ms = 1;
+  let client = RedisClient::new(config.clone());
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+  assert!(exporter.reconnects.load(Ordering::SeqCst) >= 1);
+  let latencies = exporter.latencies.load(Ordering::SeqCst);
+  let in_flight = exporter.in_flight.load(Ordering::SeqCst);
+
+  let _: () = client.set("foo{1}", "bar", None, None, false).await?;
+  let _: String = client.get("foo{1}").await?;
+  assert_eq!(exporter.latencies.load(Ordering::SeqCst), latencies + 2);
+  assert!(exporter.in_flight.load(Ordering::SeqCst) >= in_flight + 4);
+  assert!(exporter.max_in_flight.load(Ordering::SeqCst) >= 1);
+
+  let result: Result<i64, RedisError> = client.incr("foo{1}").await;
+  assert!(result.is_err());
+  assert_eq!(exporter.errors.load(Ordering::SeqCst), 1);
+
+  if config.performance.pipeline {
+    let commands: Vec<_> = (0 .. 100).map(|_| client.incr::<i64, _>("bar{1}")).collect();
+    let _ = futures::future::try_join_all(commands).await?;
+    assert!(exporter.backpressure.load(Ordering::SeqCst) > 0);
+  }
+
+  if config.server.is_clustered() {
+    let state = client.cached_cluster_state().unwrap();
+    let owner = state.get_server(redis_keyslot(b"foo{1}")).unwrap().server.clone();
+    let other = state.slots().iter().find(|range| range.server != owner).unwrap().start;
+
+    // send the command to the wrong node to force a MOVED redirection
+    let cmd = CustomCommand::new_static("GET", Some(other), false);
+    let _ = tokio::time::timeout(Duration::from_secs(5), client.custom::<RedisValue, _>(cmd, vec!["foo{1}"])).await;
+    assert!(exporter.redirects.load(Ordering::SeqCst) > 0);
+  }
+
+  let _ = client.quit().await?;
+  Ok(())
+}
+
+/// Connect to the Unix socket opened by `tests/scripts/install_redis_centralized.sh`.
+#[cfg(unix)]
+pub async fn should_connect_over_unix_socket(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let path = read_env_var("FRED_REDIS_UNIX_SOCK").unwrap_or("/tmp/fred_redis.sock".into());
+  let mut unix_config = RedisConfig::from_url(&format!("redis+unix://{}", path))?;
+  unix_config.username = config.username.clone();
+  unix_config.password = config.password.clone();
+  unix_config.version = config.version.clone();
+  assert!(unix_config.server.is_unix());
+
+  let client = RedisClient::new(unix_config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+  let _ = client.ping().await?;
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let value: String = client.get("foo").await?;
+  assert_eq!(value, "bar");
+
+  let _ = client.quit().await?;
+  Ok(())
+}
+
 pub async fn should_run_flushall_cluster(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
   let count: i64 = 200;
 
@@ -213,3 +413,183 @@ pub async fn should_safely_change_protocols_repeatedly(
   let _ = jh.await?;
   Ok(())
 }
+
+pub async fn should_pipeline_all(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let pipeline = client.pipeline();
+  let _: () = pipeline.incr("foo").await?;
+  let _: () = pipeline.incr("foo").await?;
+  let _: () = pipeline.hset("bar", ("a", 1)).await?;
+  assert_eq!(pipeline.len(), 3);
+
+  let (first, second, hset): (i64, i64, i64) = pipeline.all().await?;
+  assert_eq!((first, second, hset), (1, 2, 1));
+  assert!(pipeline.is_empty());
+
+  let foo: i64 = client.get("foo").await?;
+  assert_eq!(foo, 2);
+  Ok(())
+}
+
+pub async fn should_pipeline_try_all(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+
+  let pipeline = client.pipeline();
+  let _: () = pipeline.incr("baz").await?;
+  let _: () = pipeline.hgetall("foo").await?;
+  let _: () = pipeline.get("foo").await?;
+
+  let results = pipeline.try_all::<RedisValue>().await?;
+  assert_eq!(results.len(), 3);
+  assert_eq!(results[0].as_ref().unwrap().as_i64(), Some(1));
+  assert!(results[1].is_err());
+  assert_eq!(results[2].as_ref().unwrap().as_str().unwrap(), "bar");
+  Ok(())
+}
+
+pub async fn should_pipeline_convert_responses(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let _: () = client.hset("foo", ("a", 1)).await?;
+  let _: () = client.xadd("bar", false, None, "1-0", ("a", 1)).await?;
+  let expected_map: RedisValue = client.hgetall("foo").await?;
+  let expected_range: RedisValue = client.xrange("bar", "-", "+", None).await?;
+
+  let pipeline = client.pipeline();
+  let _: () = pipeline.hgetall("foo").await?;
+  let _: () = pipeline.xrange("bar", "-", "+", None).await?;
+  assert!(pipeline.memory_stats().await.is_err());
+  assert_eq!(pipeline.len(), 2);
+
+  let (map, range): (RedisValue, RedisValue) = pipeline.all().await?;
+  assert!(map.is_map());
+  assert_eq!(map, expected_map);
+  assert_eq!(range, expected_range);
+  Ok(())
+}
+
+pub async fn should_invalidate_client_side_cache(
+  client: RedisClient,
+  mut config: RedisConfig,
+) -> Result<(), RedisError> {
+  config.cache = Some(CacheConfig::default());
+  let cached = RedisClient::new(config);
+  let _ = cached.connect(None);
+  let _ = cached.wait_for_connect().await?;
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let _: () = client.hset("baz", ("a", 1)).await?;
+  let foo: String = cached.get("foo").await?;
+  assert_eq!(foo, "bar");
+  let a: i64 = cached.hget("baz", "a").await?;
+  assert_eq!(a, 1);
+  assert_eq!(cached.cache_hits(), 0);
+
+  // the second reads should be served from the local cache
+  let foo: String = cached.get("foo").await?;
+  assert_eq!(foo, "bar");
+  let a: i64 = cached.hget("baz", "a").await?;
+  assert_eq!(a, 1);
+  assert_eq!(cached.cache_hits(), 2);
+
+  let _: () = client.set("foo", "wibble", None, None, false).await?;
+  let _: () = client.hset("baz", ("a", 2)).await?;
+  // wait for the invalidation messages
+  sleep(Duration::from_millis(100)).await;
+
+  let foo: String = cached.get("foo").await?;
+  assert_eq!(foo, "wibble");
+  let a: i64 = cached.hget("baz", "a").await?;
+  assert_eq!(a, 2);
+  // the invalidated values were read from the server
+  assert_eq!(cached.cache_hits(), 2);
+
+  let _ = cached.quit().await;
+  Ok(())
+}
+
+pub async fn should_read_from_replicas(client: RedisClient, mut config: RedisConfig) -> Result<(), RedisError> {
+  let primaries = client.split_cluster().await?;
+  let mut replica_nodes = Vec::new();
+  for primary in primaries.iter() {
+    let _ = primary.connect(None);
+    let _ = primary.wait_for_connect().await?;
+    replica_nodes.extend(connect_to_replicas(primary, &config).await?);
+  }
+
+  config.replica = Some(ReplicaConfig {
+    policy: ReplicaPolicy::RoundRobin,
+    ..Default::default()
+  });
+  let replicas = RedisClient::new(config);
+  let _ = replicas.connect(None);
+  let _ = replicas.wait_for_connect().await?;
+
+  for idx in 0..50 {
+    let _: () = replicas.set(format!("foo-{}", idx), idx, None, None, false).await?;
+  }
+  // wait for the writes to replicate
+  sleep(Duration::from_millis(100)).await;
+  reset_stats(&primaries).await?;
+  reset_stats(&replica_nodes).await?;
+
+  for idx in 0..50 {
+    let value: i64 = replicas.get(format!("foo-{}", idx)).await?;
+    assert_eq!(value, idx);
+  }
+  // every read should have been served by a replica
+  assert_eq!(count_get_calls(&primaries).await?, 0);
+  assert_eq!(count_get_calls(&replica_nodes).await?, 50);
+
+  for client in primaries.iter().chain(replica_nodes.iter()).chain(Some(&replicas)) {
+    let _ = client.quit().await;
+  }
+  Ok(())
+}
+
+pub async fn should_read_from_sentinel_replicas(client: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let replica_client = |route_by_default| {
+    let mut config = config.clone();
+    config.replica = Some(ReplicaConfig {
+      policy: ReplicaPolicy::RoundRobin,
+      route_by_default,
+    });
+    RedisClient::new(config)
+  };
+  let (routed, per_call) = (replica_client(true), replica_client(false));
+  for client in [&routed, &per_call].iter() {
+    let _ = client.connect(None);
+    let _ = client.wait_for_connect().await?;
+  }
+
+  for idx in 0..50 {
+    let _: () = routed.set(format!("foo-{}", idx), idx, None, None, false).await?;
+  }
+  // wait for the writes to replicate
+  sleep(Duration::from_millis(100)).await;
+  let primary = vec![client.clone()];
+  let replica_nodes = connect_to_replicas(&client, &config).await?;
+  reset_stats(&primary).await?;
+  reset_stats(&replica_nodes).await?;
+
+  for idx in 0..50 {
+    let value: i64 = routed.get(format!("foo-{}", idx)).await?;
+    assert_eq!(value, idx);
+    let value: i64 = per_call.replicas().get(format!("foo-{}", idx)).await?;
+    assert_eq!(value, idx);
+  }
+  // every read should have been served by a replica
+  assert_eq!(count_get_calls(&primary).await?, 0);
+  assert_eq!(count_get_calls(&replica_nodes).await?, 100);
+  for replica in replica_nodes.iter() {
+    let _ = replica.quit().await;
+  }
+
+  // read-only commands in a transaction should be sent to the primary node with the rest of the transaction
+  let trx = routed.multi(true).await?;
+  let _: () = trx.incr("foo-0").await?;
+  let _: () = trx.get("foo-0").await?;
+  let (_, value): (i64, i64) = trx.exec().await?;
+  assert_eq!(value, 1);
+
+  let _ = routed.quit().await;
+  let _ = per_call.quit().await;
+  Ok(())
+}
@@ -2,9 +2,7 @@ use fred::clients::RedisClient;
 use fred::error::RedisError;
 use fred::interfaces::*;
 use fred::pool::RedisPool;
-use fred::types::RedisConfig;
-
-#[cfg(feature = "fd-tests")]
+use fred::types::{PoolConfig, RedisConfig};
 use std::time::Duration;
 
 async fn create_and_ping_pool(config: &RedisConfig, count: usize) -> Result<(), RedisError> {
@@ -35,6 +33,189 @@ pub async fn should_connect_and_ping_static_pool_two_conn(
   create_and_ping_pool(&config, 2).await
 }
 
+pub async fn should_send_commands_to_least_loaded_client(
+  _: RedisClient,
+  config: RedisConfig,
+) -> Result<(), RedisError> {
+  let pool = RedisPool::new(config, 2)?;
+  let _ = pool.connect(None);
+  let _ = pool.wait_for_connect().await?;
+  let (blocked, idle) = (pool.clients()[0].clone(), pool.clients()[1].clone());
+
+  // park a blocking command on the first client so it always has one in-flight command
+  let blocked_task = tokio::spawn(async move {
+    let result: Option<(String, i64)> = blocked.blpop("pool-blpop", 5.0).await?;
+    Ok::<_, RedisError>(result)
+  });
+  tokio::time::sleep(Duration::from_millis(100)).await;
+
+  let _: () = pool.set("foo", 1, None, None, false).await?;
+  for _ in 0 .. 10 {
+    assert_eq!(pool.least_loaded().id(), idle.id());
+    let _: i64 = pool.incr("foo").await?;
+    assert_eq!(pool.last().id(), idle.id());
+  }
+  let value: i64 = pool.get("foo").await?;
+  assert_eq!(value, 11);
+
+  let _: i64 = idle.lpush("pool-blpop", 1).await?;
+  assert_eq!(blocked_task.await??, Some(("pool-blpop".into(), 1)));
+  let _: () = pool.del("foo").await?;
+  let _ = pool.quit_pool().await;
+  Ok(())
+}
+
+pub async fn should_skip_clients_in_a_transaction(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let pool = RedisPool::new(config, 2)?;
+  let _ = pool.connect(None);
+  let _ = pool.wait_for_connect().await?;
+
+  let client = pool.with_affinity("foo");
+  let _ = client.watch("foo").await?;
+  let trx = client.multi(true).await?;
+  for _ in 0 .. 10 {
+    assert_ne!(pool.least_loaded().id(), client.id());
+    let _ = pool.ping().await?;
+  }
+
+  let _: () = trx.incr("foo").await?;
+  let results: Vec<i64> = trx.exec().await?;
+  assert_eq!(results, vec![1]);
+
+  let _: () = pool.del("foo").await?;
+  let _ = pool.quit_pool().await;
+  Ok(())
+}
+
+pub async fn should_resize_elastic_pool(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let pool_config = PoolConfig {
+    min_size: 1,
+    max_size: 4,
+    ..Default::default()
+  };
+  let pool = RedisPool::new_elastic(config, pool_config)?;
+  let _ = pool.connect(None);
+  let _ = pool.wait_for_connect().await?;
+  assert_eq!(pool.size(), 1);
+
+  let _ = pool.resize(4).await?;
+  assert_eq!(pool.size(), 4);
+  for client in pool.clients().iter() {
+    assert!(client.is_connected());
+    let _ = client.ping().await?;
+  }
+
+  let removed = pool.clients()[3].clone();
+  let _ = pool.resize(2).await?;
+  assert_eq!(pool.size(), 2);
+  assert!(!removed.is_connected());
+  let _ = pool.ping().await?;
+
+  assert!(pool.resize(0).await.is_err());
+  assert!(pool.resize(5).await.is_err());
+
+  let _ = pool.quit_pool().await;
+  Ok(())
+}
+
+pub async fn should_reconnect_unhealthy_clients(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let pool_config = PoolConfig {
+    min_size: 2,
+    max_size: 2,
+    health_check_interval_ms: 100,
+    ..Default::default()
+  };
+  let pool = RedisPool::new_elastic(config, pool_config)?;
+  let _ = pool.connect(None);
+  let _ = pool.wait_for_connect().await?;
+  pool.start_health_checks();
+
+  // without a reconnect policy the client will stay disconnected until the health check replaces the connection
+  let client = pool.clients()[0].clone();
+  let _ = client.quit().await?;
+  tokio::time::sleep(Duration::from_millis(1000)).await;
+  assert!(client.is_connected());
+  let _ = client.ping().await?;
+
+  let _ = pool.quit_pool().await;
+  Ok(())
+}
+
+pub async fn should_skip_health_checks_in_a_transaction(
+  _: RedisClient,
+  config: RedisConfig,
+) -> Result<(), RedisError> {
+  let pool_config = PoolConfig {
+    min_size: 2,
+    max_size: 2,
+    health_check_interval_ms: 100,
+    ..Default::default()
+  };
+  let pool = RedisPool::new_elastic(config, pool_config)?;
+  let _ = pool.connect(None);
+  let _ = pool.wait_for_connect().await?;
+  pool.start_health_checks();
+
+  // a health check PING would otherwise be queued into the transaction and change the EXEC result
+  let trx = pool.clients()[0].multi(true).await?;
+  let _: () = trx.incr("foo").await?;
+  tokio::time::sleep(Duration::from_millis(500)).await;
+  let results: Vec<i64> = trx.exec().await?;
+  assert_eq!(results, vec![1]);
+
+  let _: () = pool.del("foo").await?;
+  let _ = pool.quit_pool().await;
+  Ok(())
+}
+
+pub async fn should_scale_up_when_queue_exceeds_threshold(
+  _: RedisClient,
+  config: RedisConfig,
+) -> Result<(), RedisError> {
+  let pool_config = PoolConfig {
+    min_size: 1,
+    max_size: 2,
+    scale_up_threshold: Some(1),
+    ..Default::default()
+  };
+  let pool = RedisPool::new_elastic(config, pool_config)?;
+  let _ = pool.connect(None);
+  let _ = pool.wait_for_connect().await?;
+  assert_eq!(pool.size(), 1);
+
+  // commands sent after a blocking command wait in the client's queue until the blocking command finishes
+  let blocked = pool.clients()[0].clone();
+  let blocked_task = tokio::spawn(async move {
+    let result: Option<(String, i64)> = blocked.blpop("pool-scale", 5.0).await?;
+    Ok::<_, RedisError>(result)
+  });
+  tokio::time::sleep(Duration::from_millis(100)).await;
+  let mut queued = Vec::new();
+  for _ in 0 .. 3 {
+    let pool = pool.clone();
+    queued.push(tokio::spawn(async move { pool.ping().await }));
+  }
+  tokio::time::sleep(Duration::from_millis(100)).await;
+  assert_eq!(pool.size(), 1);
+
+  // selecting a client with more than `scale_up_threshold` queued commands adds another client to the pool
+  let trigger = pool.ping();
+  assert_eq!(pool.size(), 2);
+  let added = pool.clients()[1].clone();
+  let _ = added.wait_for_connect().await?;
+  let _ = added.ping().await?;
+
+  let _: i64 = added.lpush("pool-scale", 1).await?;
+  assert_eq!(blocked_task.await??, Some(("pool-scale".into(), 1)));
+  for task in queued.into_iter() {
+    let _ = task.await??;
+  }
+  let _ = trigger.await?;
+
+  let _ = pool.quit_pool().await;
+  Ok(())
+}
+
 // this may require increasing the number of allowed file descriptors
 #[cfg(feature = "fd-tests")]
 pub async fn should_connect_and_ping_static_pool_many_conn(
@@ -61,3 +242,27 @@ pub async fn should_connect_and_ping_static_pool_repeatedly(
 
   Ok(())
 }
+
+pub async fn should_apply_connection_state_to_every_client(
+  _: RedisClient,
+  config: RedisConfig,
+) -> Result<(), RedisError> {
+  let pool = RedisPool::new(config, 3)?;
+  let _ = pool.connect(None);
+  let _ = pool.wait_for_connect().await?;
+
+  let _ = pool.client_setname("fred-pool").await?;
+  let _ = pool.select(1).await?;
+  let _: () = pool.clients()[0].set("foo", "bar", None, None, false).await?;
+  for client in pool.clients().iter() {
+    let name: String = client.client_getname().await?;
+    assert_eq!(name, "fred-pool");
+    let value: String = client.get("foo").await?;
+    assert_eq!(value, "bar");
+  }
+
+  let _: () = pool.del("foo").await?;
+  let _ = pool.select(0).await?;
+  let _ = pool.quit_pool().await;
+  Ok(())
+}
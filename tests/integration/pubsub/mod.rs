@@ -3,6 +3,7 @@ use futures::StreamExt;
 use std::time::Duration;
 use tokio::time::sleep;
 use fred::interfaces::PubsubInterface;
+use fred::types::MessageKind;
 
 const CHANNEL1: &'static str = "foo";
 const CHANNEL2: &'static str = "bar";
@@ -101,3 +102,157 @@ pub async fn should_psubscribe_and_recv_messages(client: RedisClient, _: RedisCo
 
   Ok(())
 }
+
+pub async fn should_ssubscribe_and_recv_sharded_messages(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let subscriber_client = client.clone_new();
+  let policy = client.client_reconnect_policy();
+  let _ = subscriber_client.connect(policy);
+  let _ = subscriber_client.wait_for_connect().await?;
+  let _ = subscriber_client.ssubscribe(CHANNEL1).await?;
+
+  let subscriber_jh = tokio::spawn(async move {
+    let mut message_stream = subscriber_client.on_pubsub_message();
+
+    let mut count = 0;
+    while count < NUM_MESSAGES {
+      if let Some(message) = message_stream.next().await {
+        assert_eq!(message.kind, MessageKind::SMessage);
+        assert!(message.is_sharded());
+        let (channel, message): (String, String) = (message.channel, message.value.convert().unwrap());
+
+        assert_eq!(CHANNEL1, channel);
+        if ASSERT_COUNT {
+          assert_eq!(format!("{}-{}", FAKE_MESSAGE, count), message);
+        }
+        count += 1;
+      }
+    }
+
+    let _ = subscriber_client.sunsubscribe(CHANNEL1).await?;
+    Ok::<_, RedisError>(())
+  });
+
+  for idx in 0..NUM_MESSAGES + EXTRA_MESSAGES {
+    // https://redis.io/commands/spublish#return-value
+    let _: () = client.spublish(CHANNEL1, format!("{}-{}", FAKE_MESSAGE, idx)).await?;
+
+    sleep(Duration::from_millis(50)).await;
+  }
+  let _ = subscriber_jh.await?;
+
+  Ok(())
+}
+
+/// Kill the pubsub connections on each server, forcing subscribers to reconnect and subscribe again.
+#[cfg(feature = "subscriber-client")]
+async fn kill_pubsub_connections(client: &RedisClient) -> Result<(), RedisError> {
+  use fred::types::{ClientKillFilter, ClientKillType};
+
+  let clients = if client.is_clustered() {
+    client.split_cluster().await?
+  } else {
+    vec![client.clone_new()]
+  };
+  for client in clients.into_iter() {
+    let _ = client.connect(None);
+    let _ = client.wait_for_connect().await?;
+    let _: RedisValue = client
+      .client_kill(vec![ClientKillFilter::Type(ClientKillType::Pubsub)])
+      .await?;
+    let _ = client.quit().await;
+  }
+
+  Ok(())
+}
+
+#[cfg(feature = "subscriber-client")]
+pub async fn should_resubscribe_sharded_channels(client: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  use fred::bytes_utils::Str;
+  use fred::clients::SubscriberClient;
+  use std::collections::BTreeSet;
+  use tokio::time::timeout;
+
+  let subscriber = SubscriberClient::new(config);
+  let _ = subscriber.connect(client.client_reconnect_policy());
+  let _ = subscriber.wait_for_connect().await?;
+  let _manage_jh = subscriber.manage_subscriptions();
+
+  let _ = subscriber.ssubscribe(CHANNEL1).await?;
+  let _ = subscriber.ssubscribe(CHANNEL2).await?;
+  let _ = subscriber.sunsubscribe(CHANNEL2).await?;
+  let expected: BTreeSet<Str> = vec![CHANNEL1.into()].into_iter().collect();
+  assert_eq!(subscriber.tracked_sharded_channels(), expected);
+
+  let mut message_stream = subscriber.on_pubsub_message();
+  kill_pubsub_connections(&client).await?;
+  let _ = subscriber.wait_for_connect().await?;
+  // the sharded channel should still be tracked after reconnecting
+  assert_eq!(subscriber.tracked_sharded_channels(), expected);
+
+  let publisher = client.clone();
+  let publisher_jh = tokio::spawn(async move {
+    for idx in 0 .. NUM_MESSAGES {
+      let _: () = publisher.spublish(CHANNEL1, format!("{}-{}", FAKE_MESSAGE, idx)).await?;
+      sleep(Duration::from_millis(50)).await;
+    }
+    Ok::<_, RedisError>(())
+  });
+
+  let message = timeout(Duration::from_secs(5), message_stream.next())
+    .await
+    .expect("Timed out waiting for sharded message")
+    .expect("Missing sharded message");
+  assert_eq!(message.kind, MessageKind::SMessage);
+  assert_eq!(message.channel, CHANNEL1);
+  let _ = publisher_jh.await?;
+
+  let _ = subscriber.unsubscribe_all().await?;
+  assert!(subscriber.tracked_sharded_channels().is_empty());
+  let _ = subscriber.quit().await;
+  Ok(())
+}
+
+#[cfg(feature = "keyspace-notifications")]
+pub async fn should_receive_keyspace_notifications(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  use fred::clients::{KeyspaceConfig, KeyspaceEventKind, KeyspaceNotifications};
+  use tokio::time::timeout;
+
+  let config = KeyspaceConfig {
+    notify_flags: Some("KEA".into()),
+    databases: vec![0],
+    key_pattern: "{foo}:*".into(),
+    ..KeyspaceConfig::default()
+  };
+  let notifications = KeyspaceNotifications::new(&client, config);
+  let mut events = notifications.on_event();
+  let _ = notifications.connect().await?;
+
+  // connecting again should close the old clients so events are not duplicated
+  let old_clients = notifications.clients();
+  let _ = notifications.connect().await?;
+  assert!(old_clients.iter().all(|client| !client.is_connected()));
+
+  let _: () = client.set("{foo}:bar", "baz", None, None, false).await?;
+  let _: () = client.lpush("{foo}:list", "baz").await?;
+  let _: () = client.del(vec!["{foo}:bar", "{foo}:list"]).await?;
+
+  let mut received = Vec::with_capacity(4);
+  while received.len() < 4 {
+    let event = timeout(Duration::from_secs(5), events.next())
+      .await
+      .map_err(|_| RedisError::new(RedisErrorKind::Timeout, "Timed out waiting for keyspace events."))?
+      .expect("Missing keyspace event");
+
+    assert_eq!(event.db, 0);
+    received.push((event.key, event.kind));
+  }
+  assert_eq!(received, vec![
+    ("{foo}:bar".to_owned(), KeyspaceEventKind::Set),
+    ("{foo}:list".to_owned(), KeyspaceEventKind::LPush),
+    ("{foo}:bar".to_owned(), KeyspaceEventKind::Del),
+    ("{foo}:list".to_owned(), KeyspaceEventKind::Del),
+  ]);
+
+  let _ = notifications.quit().await?;
+  Ok(())
+}
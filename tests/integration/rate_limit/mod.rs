@@ -0,0 +1,113 @@
+use super::lua::flush_scripts;
+use fred::clients::RedisClient;
+use fred::error::RedisError;
+use fred::interfaces::*;
+use fred::rate_limit::{RateLimit, RateLimiter};
+use fred::types::RedisConfig;
+use std::time::Duration;
+use tokio::time::sleep;
+
+async fn should_limit_requests(client: RedisClient, limit: RateLimit, max: u64) -> Result<(), RedisError> {
+  let limiter = RateLimiter::new(client.clone(), "rate", limit)?;
+  let _ = limiter.reset("foo").await?;
+
+  for i in 0 .. max {
+    let result = limiter.check("foo").await?;
+    assert!(result.allowed);
+    assert_eq!(result.remaining, max - i - 1);
+  }
+  let result = limiter.check("foo").await?;
+  assert!(!result.allowed);
+  assert_eq!(result.remaining, 0);
+  assert!(result.retry_after.is_some());
+
+  let _ = limiter.reset("foo").await?;
+  assert!(limiter.check("foo").await?.allowed);
+  let _ = limiter.reset("foo").await?;
+  Ok(())
+}
+
+pub async fn should_limit_with_fixed_window(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let limit = RateLimit::FixedWindow {
+    limit: 5,
+    window_ms: 10_000,
+  };
+  should_limit_requests(client, limit, 5).await
+}
+
+pub async fn should_limit_with_sliding_log(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let limit = RateLimit::SlidingLog {
+    limit: 5,
+    window_ms: 10_000,
+  };
+  should_limit_requests(client, limit, 5).await
+}
+
+pub async fn should_limit_with_token_bucket(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let limit = RateLimit::TokenBucket {
+    capacity: 5,
+    refill: 1,
+    period_ms: 10_000,
+  };
+  should_limit_requests(client, limit, 5).await
+}
+
+pub async fn should_limit_with_gcra(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let limit = RateLimit::Gcra {
+    burst: 5,
+    rate: 1,
+    period_ms: 10_000,
+  };
+  should_limit_requests(client, limit, 5).await
+}
+
+pub async fn should_allow_requests_after_window(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let limit = RateLimit::SlidingLog {
+    limit: 2,
+    window_ms: 500,
+  };
+  let limiter = RateLimiter::new(client, "rate", limit)?;
+  let _ = limiter.reset("foo").await?;
+
+  assert!(limiter.check_n("foo", 2).await?.allowed);
+  let result = limiter.check("foo").await?;
+  assert!(!result.allowed);
+
+  sleep(result.retry_after.unwrap() + Duration::from_millis(50)).await;
+  assert!(limiter.check("foo").await?.allowed);
+  let _ = limiter.reset("foo").await?;
+  Ok(())
+}
+
+pub async fn should_reject_requests_larger_than_limit(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let limit = RateLimit::TokenBucket {
+    capacity: 2,
+    refill: 1,
+    period_ms: 1_000,
+  };
+  let limiter = RateLimiter::new(client, "rate", limit)?;
+  let _ = limiter.reset("foo").await?;
+
+  let result = limiter.check_n("foo", 3).await?;
+  assert!(!result.allowed);
+  assert_eq!(result.retry_after, None);
+  assert_eq!(result.remaining, 2);
+  let _ = limiter.reset("foo").await?;
+  Ok(())
+}
+
+pub async fn should_reload_flushed_script(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  let limit = RateLimit::Gcra {
+    burst: 2,
+    rate: 1,
+    period_ms: 1_000,
+  };
+  let limiter = RateLimiter::new(client.clone(), "rate", limit)?;
+  let _ = limiter.reset("foo").await?;
+  let _ = limiter.load_script().await?;
+
+  let _ = flush_scripts(&client).await?;
+  assert!(limiter.check("foo").await?.allowed);
+  let _ = limiter.reset("foo").await?;
+  Ok(())
+}
@@ -718,3 +718,203 @@ pub async fn should_xautoclaim_default(client: RedisClient, _: RedisConfig) -> R
 
   Ok(())
 }
+
+#[cfg(feature = "stream-consumer")]
+pub async fn should_consume_and_ack_with_stream_consumer(
+  client: RedisClient,
+  _: RedisConfig,
+) -> Result<(), RedisError> {
+  use fred::clients::{StreamConsumer, StreamConsumerConfig};
+  use futures::StreamExt;
+
+  check_null!(client, "foo{1}");
+  let _ = create_fake_group_and_stream(&client, "foo{1}").await?;
+  let (ids, _) = add_stream_entries(&client, "foo{1}", 3).await?;
+
+  let consumer_client = client.clone_new();
+  let _ = consumer_client.connect(None);
+  let _ = consumer_client.wait_for_connect().await?;
+  let mut config = StreamConsumerConfig::new("group1", "consumer1");
+  config.claim_idle_ms = None;
+  let consumer = StreamConsumer::new(&consumer_client, "foo{1}", config);
+
+  let messages: Vec<_> = consumer.messages::<usize>().take(3).collect().await;
+  let mut actual_ids = Vec::with_capacity(3);
+  for (idx, message) in messages.into_iter().enumerate() {
+    let message = message?;
+    assert_eq!(message.fields.get("count"), Some(&idx));
+    actual_ids.push(message.id);
+  }
+  assert_eq!(actual_ids, ids);
+
+  let acked = consumer.ack(actual_ids).await?;
+  assert_eq!(acked, 3);
+  let (count, _, _, _): (u64, Option<String>, Option<String>, Option<Vec<(String, u64)>>) =
+    client.xpending("foo{1}", "group1", ()).await?;
+  assert_eq!(count, 0);
+
+  consumer.stop();
+  let _ = consumer_client.quit().await;
+  Ok(())
+}
+
+#[cfg(feature = "stream-consumer")]
+pub async fn should_move_messages_to_dead_letter_stream(
+  client: RedisClient,
+  _: RedisConfig,
+) -> Result<(), RedisError> {
+  use fred::clients::{StreamConsumer, StreamConsumerConfig};
+
+  check_null!(client, "foo{1}");
+  check_null!(client, "bar{1}");
+  let _ = create_fake_group_and_stream(&client, "foo{1}").await?;
+  let _ = add_stream_entries(&client, "foo{1}", 5).await?;
+  // deliver each message once without acknowledging it
+  let _: XReadResponse<String, String, String, usize> = client
+    .xreadgroup_map("group1", "consumer1", None, None, false, "foo{1}", ">")
+    .await?;
+
+  let mut config = StreamConsumerConfig::new("group1", "consumer2");
+  config.claim_idle_ms = Some(0);
+  config.max_deliveries = Some(1);
+  config.dead_letter_key = Some("bar{1}".into());
+  // the pending entries should be read in several pages
+  config.batch_size = 2;
+  let consumer = StreamConsumer::new(&client, "foo{1}", config);
+
+  let claimed = consumer.claim_pending::<usize>().await?;
+  assert!(claimed.is_empty());
+  let dead_letters: usize = client.xlen("bar{1}").await?;
+  assert_eq!(dead_letters, 5);
+  let (count, _, _, _): (u64, Option<String>, Option<String>, Option<Vec<(String, u64)>>) =
+    client.xpending("foo{1}", "group1", ()).await?;
+  assert_eq!(count, 0);
+
+  Ok(())
+}
+
+#[cfg(feature = "stream-consumer")]
+pub async fn should_run_stream_consumer(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  use fred::clients::{StreamConsumer, StreamConsumerConfig, StreamMessage};
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use std::sync::Arc;
+  use tokio::time::timeout;
+
+  check_null!(client, "foo{1}");
+  let _ = create_fake_group_and_stream(&client, "foo{1}").await?;
+  let (ids, _) = add_stream_entries(&client, "foo{1}", 3).await?;
+
+  let consumer_client = client.clone_new();
+  let _ = consumer_client.connect(None);
+  let _ = consumer_client.wait_for_connect().await?;
+  let mut config = StreamConsumerConfig::new("group1", "consumer1");
+  config.claim_idle_ms = None;
+  config.concurrency = 2;
+  config.block_ms = 100;
+  let consumer = StreamConsumer::new(&consumer_client, "foo{1}", config);
+
+  let processed = Arc::new(AtomicUsize::new(0));
+  let failed_id = ids[1].clone();
+  let handler = |message: StreamMessage<usize>| {
+    let (consumer, processed, failed_id) = (consumer.clone(), processed.clone(), failed_id.clone());
+
+    async move {
+      // stop after the first 3 messages, and again after the retried and new messages
+      let count = processed.fetch_add(1, Ordering::SeqCst) + 1;
+      if count == 3 || count == 5 {
+        consumer.stop();
+      }
+
+      if message.id == failed_id {
+        Err(RedisError::new(RedisErrorKind::Unknown, "Failed to process message."))
+      } else {
+        Ok(())
+      }
+    }
+  };
+  let _ = timeout(Duration::from_secs(5), consumer.run(handler))
+    .await
+    .expect("Timed out running stream consumer")?;
+  assert_eq!(processed.load(Ordering::SeqCst), 3);
+
+  // only the message that failed should still be pending
+  let (count, first, _, _): (u64, Option<String>, Option<String>, Option<Vec<(String, u64)>>) =
+    client.xpending("foo{1}", "group1", ()).await?;
+  assert_eq!(count, 1);
+  assert_eq!(first, Some(ids[1].clone()));
+
+  // the consumer can run again after it was stopped, reading the failed message again before the new message
+  let _ = add_stream_entries(&client, "foo{1}", 1).await?;
+  let _ = timeout(Duration::from_secs(5), consumer.run(handler))
+    .await
+    .expect("Timed out running stream consumer again")?;
+  assert_eq!(processed.load(Ordering::SeqCst), 5);
+
+  let _ = consumer_client.quit().await;
+  Ok(())
+}
+
+#[cfg(feature = "stream-consumer")]
+pub async fn should_return_error_from_stream_consumer(client: RedisClient, _: RedisConfig) -> Result<(), RedisError> {
+  use fred::clients::{StreamConsumer, StreamConsumerConfig, StreamMessage};
+  use tokio::time::timeout;
+
+  // the consumer group cannot be created on a key that isn't a stream
+  let _: () = client.set("foo{1}", "bar", None, None, false).await?;
+  let consumer = StreamConsumer::new(&client, "foo{1}", StreamConsumerConfig::new("group1", "consumer1"));
+
+  let result = timeout(
+    Duration::from_secs(5),
+    consumer.run(|_: StreamMessage<usize>| async move { Ok(()) }),
+  )
+  .await
+  .expect("Timed out running stream consumer");
+  assert!(result.is_err());
+  Ok(())
+}
+
+#[cfg(feature = "stream-consumer")]
+pub async fn should_resume_stream_consumer_after_reconnect(
+  client: RedisClient,
+  _: RedisConfig,
+) -> Result<(), RedisError> {
+  use fred::clients::{StreamConsumer, StreamConsumerConfig};
+  use fred::types::ClientKillFilter;
+  use futures::StreamExt;
+  use tokio::time::timeout;
+
+  check_null!(client, "foo{1}");
+  let _ = create_fake_group_and_stream(&client, "foo{1}").await?;
+  let (ids, _) = add_stream_entries(&client, "foo{1}", 1).await?;
+
+  let consumer_client = client.clone_new();
+  let _ = consumer_client.connect(client.client_reconnect_policy());
+  let _ = consumer_client.wait_for_connect().await?;
+  let connection_id: i64 = consumer_client.client_id().await?;
+  let mut config = StreamConsumerConfig::new("group1", "consumer1");
+  config.claim_idle_ms = None;
+  config.block_ms = 100;
+  let consumer = StreamConsumer::new(&consumer_client, "foo{1}", config);
+
+  let mut messages = consumer.messages::<usize>();
+  let message = timeout(Duration::from_secs(5), messages.next())
+    .await
+    .expect("Timed out reading message")
+    .expect("Missing message")?;
+  assert_eq!(message.id, ids[0]);
+
+  // close the connection without acknowledging the message, which should be delivered again after reconnecting
+  let _: RedisValue = client
+    .client_kill(vec![ClientKillFilter::ID(connection_id.to_string())])
+    .await?;
+  let message = timeout(Duration::from_secs(5), messages.next())
+    .await
+    .expect("Timed out reading message after reconnecting")
+    .expect("Missing message after reconnecting")?;
+  assert_eq!(message.id, ids[0]);
+
+  assert_eq!(consumer.ack(message.id).await?, 1);
+  consumer.stop();
+  let _ = consumer_client.quit().await;
+  Ok(())
+}
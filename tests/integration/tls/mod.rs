@@ -0,0 +1,166 @@
+use crate::integration::utils::read_env_var;
+use fred::clients::RedisClient;
+use fred::error::{RedisError, RedisErrorKind};
+use fred::interfaces::*;
+use fred::types::{ReconnectPolicy, RedisConfig, ServerConfig, TlsBackend, TlsConfig, TlsIdentity};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+fn read_tls_server() -> ServerConfig {
+  let host = read_env_var("FRED_REDIS_TLS_HOST").unwrap_or("127.0.0.1".into());
+  let port = read_env_var("FRED_REDIS_TLS_PORT")
+    .and_then(|s| s.parse::<u16>().ok())
+    .unwrap_or(6380);
+
+  ServerConfig::Centralized { host, port }
+}
+
+/// Read the server that requires clients to authenticate with a certificate.
+fn read_mtls_server() -> ServerConfig {
+  let host = read_env_var("FRED_REDIS_TLS_HOST").unwrap_or("127.0.0.1".into());
+  let port = read_env_var("FRED_REDIS_MTLS_PORT")
+    .and_then(|s| s.parse::<u16>().ok())
+    .unwrap_or(6381);
+
+  ServerConfig::Centralized { host, port }
+}
+
+/// Read the path to a file in the folder containing the certificates created by `tests/scripts/install_redis_tls.sh`.
+fn cert_path(name: &str) -> PathBuf {
+  let mut path = PathBuf::from(read_env_var("FRED_REDIS_TLS_CERTS").unwrap_or("tests/tmp/tls".into()));
+  path.push(name);
+  path
+}
+
+fn read_cert_file(name: &str) -> Vec<u8> {
+  let path = cert_path(name);
+  fs::read(&path).unwrap_or_else(|e| panic!("Failed to read {:?}: {:?}", path, e))
+}
+
+fn read_client_identity() -> TlsIdentity {
+  TlsIdentity::from_pem_files(cert_path("client.crt"), cert_path("client.key"))
+    .expect("Failed to read client identity")
+}
+
+#[cfg(feature = "enable-tls")]
+fn native_tls_config() -> TlsConfig {
+  use fred::native_tls::Certificate;
+
+  TlsConfig {
+    backend: TlsBackend::NativeTls,
+    root_certs: Some(vec![Certificate::from_pem(&read_cert_file("ca.crt")).expect("Invalid CA certificate")]),
+    ..TlsConfig::default()
+  }
+}
+
+#[cfg(feature = "enable-rustls")]
+fn rustls_config() -> TlsConfig {
+  use fred::rustls::{Certificate, RootCertStore};
+
+  let mut root_store = RootCertStore::empty();
+  root_store
+    .add(&Certificate(read_cert_file("ca.der")))
+    .expect("Invalid CA certificate");
+
+  TlsConfig {
+    backend: TlsBackend::Rustls,
+    root_store: Some(root_store),
+    webpki_roots: false,
+    ..TlsConfig::default()
+  }
+}
+
+async fn should_set_and_get_over_tls(
+  config: RedisConfig,
+  server: ServerConfig,
+  tls: TlsConfig,
+) -> Result<(), RedisError> {
+  let config = RedisConfig {
+    server,
+    tls: Some(tls),
+    ..config
+  };
+  let client = RedisClient::new(config);
+  let _ = client.connect(None);
+  let _ = client.wait_for_connect().await?;
+
+  let _: () = client.set("foo", "bar", None, None, false).await?;
+  let value: String = client.get("foo").await?;
+  assert_eq!(value, "bar");
+  let _: () = client.del("foo").await?;
+
+  let _ = client.quit().await;
+  Ok(())
+}
+
+/// Check that the server rejects the client without a certificate, then add a certificate with `update_tls_config`
+/// while the client is trying to reconnect.
+async fn should_require_client_cert(config: RedisConfig, tls: TlsConfig) -> Result<(), RedisError> {
+  let config = RedisConfig {
+    server: read_mtls_server(),
+    tls: Some(tls.clone()),
+    // retry the initial connection with the reconnect policy
+    fail_fast: false,
+    ..config
+  };
+  let client = RedisClient::new(config);
+  let _ = client.connect(Some(ReconnectPolicy::new_constant(0, 100)));
+
+  sleep(Duration::from_millis(500)).await;
+  assert!(!client.is_connected());
+
+  client.update_tls_config(TlsConfig {
+    identity: Some(read_client_identity()),
+    ..tls
+  });
+  let _ = timeout(Duration::from_secs(5), client.wait_for_connect())
+    .await
+    .map_err(|_| RedisError::new(RedisErrorKind::Timeout, "Timed out waiting to reconnect."))??;
+  let _: () = client.ping().await?;
+
+  let _ = client.quit().await;
+  Ok(())
+}
+
+#[cfg(feature = "enable-tls")]
+pub async fn should_connect_with_native_tls(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  should_set_and_get_over_tls(config, read_tls_server(), native_tls_config()).await
+}
+
+#[cfg(feature = "enable-rustls")]
+pub async fn should_connect_with_rustls(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  should_set_and_get_over_tls(config, read_tls_server(), rustls_config()).await
+}
+
+#[cfg(feature = "enable-tls")]
+pub async fn should_connect_with_native_tls_client_cert(
+  _: RedisClient,
+  config: RedisConfig,
+) -> Result<(), RedisError> {
+  let tls = TlsConfig {
+    identity: Some(read_client_identity()),
+    ..native_tls_config()
+  };
+  should_set_and_get_over_tls(config, read_mtls_server(), tls).await
+}
+
+#[cfg(feature = "enable-rustls")]
+pub async fn should_connect_with_rustls_client_cert(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  let tls = TlsConfig {
+    identity: Some(read_client_identity()),
+    ..rustls_config()
+  };
+  should_set_and_get_over_tls(config, read_mtls_server(), tls).await
+}
+
+#[cfg(feature = "enable-tls")]
+pub async fn should_require_native_tls_client_cert(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  should_require_client_cert(config, native_tls_config()).await
+}
+
+#[cfg(feature = "enable-rustls")]
+pub async fn should_require_rustls_client_cert(_: RedisClient, config: RedisConfig) -> Result<(), RedisError> {
+  should_require_client_cert(config, rustls_config()).await
+}
@@ -3,4 +3,5 @@
 ./tests/runners/default-features.sh
 ./tests/runners/no-features.sh
 ./tests/runners/all-features.sh
-./tests/runners/sentinel-features.sh
\ No newline at end of file
+./tests/runners/sentinel-features.sh
+./tests/runners/tls-features.sh
\ No newline at end of file
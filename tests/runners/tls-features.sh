@@ -0,0 +1,19 @@
+#!/bin/bash
+
+declare -a arr=("REDIS_VERSION" "FRED_REDIS_TLS_HOST" "FRED_REDIS_TLS_PORT" "FRED_REDIS_MTLS_PORT")
+
+for env in "${arr[@]}"
+do
+  if [ -z "$env" ]; then
+    echo "$env must be set. Run `source tests/environ` if needed."
+    exit 1
+  fi
+done
+
+FEATURES="network-logs enable-tls enable-rustls tls-tests"
+
+if [ -z "$FRED_CI_NEXTEST" ]; then
+  cargo test --release --test lib --features "$FEATURES" -- --test-threads=1 tls "$@"
+else
+  cargo nextest run --release --test lib --features "$FEATURES" --test-threads=1 tls "$@"
+fi
@@ -19,4 +19,5 @@ fi
 rm -rf ./tests/tmp/redis*
 ./tests/scripts/install_redis_centralized.sh
 ./tests/scripts/install_redis_clustered.sh
+./tests/scripts/install_redis_tls.sh
 ./tests/scripts/docker-install-redis-sentinel.sh
\ No newline at end of file
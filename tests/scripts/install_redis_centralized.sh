@@ -18,6 +18,7 @@ do
 done
 
 ROOT=$PWD
+[[ -z "${FRED_REDIS_UNIX_SOCK}" ]] && UNIX_SOCK='/tmp/fred_redis.sock' || UNIX_SOCK="${FRED_REDIS_UNIX_SOCK}"
 [[ -z "${JOBS}" ]] && PARALLEL_JOBS='2' || PARALLEL_JOBS="${JOBS}"
 
 # Returns 0 if not installed, 1 otherwise.
@@ -75,10 +76,10 @@ function start_server {
 
   if [ -f "./redis_centralized.conf" ]; then
     echo "Starting server with config file..."
-    nohup ./src/redis-server ./redis_centralized.conf > ./centralized_server.log 2>&1 &
+    nohup ./src/redis-server ./redis_centralized.conf --unixsocket "$UNIX_SOCK" > ./centralized_server.log 2>&1 &
   else
     echo "Starting server without config file..."
-    nohup ./src/redis-server > ./centralized_server.log 2>&1 &
+    nohup ./src/redis-server --unixsocket "$UNIX_SOCK" > ./centralized_server.log 2>&1 &
   fi
   echo $! > ./redis_server.pid
   echo "Redis server PID is `cat redis_server.pid`"
@@ -0,0 +1,108 @@
+#!/bin/bash
+
+function check_root_dir {
+  if [ ! -d "./tests/tmp" ]; then
+    echo "Must be in application root for redis installation scripts to work."
+    exit 1
+  fi
+}
+
+declare -a arr=("REDIS_VERSION" "FRED_REDIS_TLS_PORT" "FRED_REDIS_MTLS_PORT")
+
+for env in "${arr[@]}"
+do
+  if [ -z "$env" ]; then
+    echo "$env must be set. Run `source tests/environ` if needed."
+    exit 1
+  fi
+done
+
+ROOT=$PWD
+CERTS=$ROOT/tests/tmp/tls
+[[ -z "${JOBS}" ]] && PARALLEL_JOBS='2' || PARALLEL_JOBS="${JOBS}"
+
+# Returns 0 if not installed, 1 otherwise.
+function check_redis {
+  if [ -d "$ROOT/tests/tmp/redis_tls_$REDIS_VERSION/redis-$REDIS_VERSION" ]; then
+    echo "Skipping redis install."
+    return 1
+  else
+    echo "Redis install not found."
+    return 0
+  fi
+}
+
+function install_redis {
+  echo "Installing redis with TLS support..."
+  pushd $ROOT > /dev/null
+  cd tests/tmp
+  curl -O http://download.redis.io/releases/redis-$REDIS_VERSION.tar.gz
+  mkdir redis_tls_$REDIS_VERSION
+  tar xf redis-$REDIS_VERSION.tar.gz -C redis_tls_$REDIS_VERSION
+  rm redis-$REDIS_VERSION.tar.gz
+  cd redis_tls_$REDIS_VERSION/redis-$REDIS_VERSION
+  make BUILD_TLS=yes -j"${PARALLEL_JOBS}"
+  popd > /dev/null
+}
+
+function generate_certs {
+  echo "Generating TLS certificates..."
+  rm -rf $CERTS
+  mkdir -p $CERTS
+  pushd $CERTS > /dev/null
+
+  openssl genpkey -algorithm RSA -out ca.key
+  openssl req -x509 -new -nodes -sha256 -key ca.key -days 3650 -subj "/O=fred/CN=fred test CA" -out ca.crt
+  openssl x509 -in ca.crt -outform der -out ca.der
+
+  openssl genpkey -algorithm RSA -out server.key
+  openssl req -new -sha256 -key server.key -subj "/O=fred/CN=localhost" -out server.csr
+  echo "subjectAltName=DNS:localhost,IP:127.0.0.1" > server.ext
+  openssl x509 -req -sha256 -in server.csr -CA ca.crt -CAkey ca.key -CAcreateserial -days 3650 \
+    -extfile server.ext -out server.crt
+
+  # the client key is written as PKCS#8 so it can be used with native-tls
+  openssl genpkey -algorithm RSA -out client.key
+  openssl req -new -sha256 -key client.key -subj "/O=fred/CN=fred test client" -out client.csr
+  echo "extendedKeyUsage=clientAuth" > client.ext
+  openssl x509 -req -sha256 -in client.csr -CA ca.crt -CAkey ca.key -CAcreateserial -days 3650 \
+    -extfile client.ext -out client.crt
+
+  popd > /dev/null
+}
+
+function start_server {
+  pushd $ROOT > /dev/null
+  cd $ROOT/tests/tmp/redis_tls_$REDIS_VERSION/redis-$REDIS_VERSION
+
+  for pid_file in ./redis_server.pid ./redis_mtls_server.pid
+  do
+    if [ -f "$pid_file" ]; then
+      echo "Found running redis server. Stopping..."
+      kill -9 `cat $pid_file`
+    fi
+  done
+
+  echo "Starting TLS server on port $FRED_REDIS_TLS_PORT..."
+  nohup ./src/redis-server --port 0 --tls-port $FRED_REDIS_TLS_PORT --tls-cert-file $CERTS/server.crt \
+    --tls-key-file $CERTS/server.key --tls-ca-cert-file $CERTS/ca.crt --tls-auth-clients no > ./tls_server.log 2>&1 &
+  echo $! > ./redis_server.pid
+  echo "Redis server PID is `cat redis_server.pid`"
+
+  echo "Starting TLS server that requires client certificates on port $FRED_REDIS_MTLS_PORT..."
+  nohup ./src/redis-server --port 0 --tls-port $FRED_REDIS_MTLS_PORT --tls-cert-file $CERTS/server.crt \
+    --tls-key-file $CERTS/server.key --tls-ca-cert-file $CERTS/ca.crt --tls-auth-clients yes > ./mtls_server.log 2>&1 &
+  echo $! > ./redis_mtls_server.pid
+  echo "Redis server PID is `cat redis_mtls_server.pid`"
+  popd > /dev/null
+}
+
+check_root_dir
+check_redis
+if [[ "$?" -eq 0 ]]; then
+  install_redis
+fi
+generate_certs
+start_server
+
+echo "Finished installing TLS redis server."